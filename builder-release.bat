@@ -43,6 +43,8 @@ move padamostft.dll                  plugins
 move padamoeusoroot.dll              plugins
 move padamoplaintext.dll             plugins
 move padamotransform.dll             plugins
+move padamoscripting.dll             plugins
+move padamonpy.dll                   plugins
 
 move /Y padamo-neuraltrigger plugins\padamo-neuraltrigger
 
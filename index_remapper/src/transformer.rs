@@ -4,6 +4,7 @@ use std::fmt::Debug;
 use nom::multi::separated_list1;
 use nom::{IResult, Parser};
 use padamo_api::lazy_array_operations::ArrayND;
+use padamo_api::lazy_array_operations::ndim_array::ArrayView;
 use crate::errors::ReindexError;
 use crate::expression::parse_expression;
 use crate::utils::{parse_comma_sep, parse_semicolon_sep, parse_shape_array};
@@ -109,13 +110,18 @@ impl<T:Clone+Debug+abi_stable::StableAbi> BakedTransformer<T>{
     }
 
     pub fn apply(&self, src:&ArrayND<T>)->Result<ArrayND<T>,crate::errors::ReindexError>{
-        if src.form_compatible(&self.source_shape){
+        self.apply_view(&src.view())
+    }
+
+    /// Same as `apply` for view, e.g. frame of signal
+    pub fn apply_view(&self, src:&ArrayView<'_,T>)->Result<ArrayND<T>,crate::errors::ReindexError>{
+        if src.shape()==self.source_shape.as_slice(){
             let mut res = ArrayND::new(self.target_shape.clone(), self.fill_value.clone());
             // println!("{:?}", self.mapping);
 
             for (target_index, source_index) in &self.mapping{
                 // println!("Applying {:?} -> {:?}", source_index, target_index);
-                if let Some(got) = src.try_get(source_index){
+                if let Some(got) = src.get(source_index){
                     res.set(target_index, got.clone());
                 }
             }
@@ -123,7 +129,7 @@ impl<T:Clone+Debug+abi_stable::StableAbi> BakedTransformer<T>{
             Ok(res)
         }
         else{
-            Err(ReindexError::IncompatibleShapes(src.shape.clone().into_vec(), self.source_shape.clone()))
+            Err(ReindexError::IncompatibleShapes(src.shape().to_vec(), self.source_shape.clone()))
         }
     }
 }
@@ -2,6 +2,8 @@ use proc_macro::TokenStream;
 use quote::{quote, format_ident};
 use syn::DeriveInput;
 
+mod node_derive;
+
 #[proc_macro_attribute]
 pub fn impl_content(_args:TokenStream,item:TokenStream)->TokenStream{
     let item2: proc_macro2::TokenStream = item.clone().into();
@@ -146,3 +148,25 @@ pub fn impl_content(_args:TokenStream,item:TokenStream)->TokenStream{
         quote!{compile_error!("impl_content only works with enums")}.into()
     }
 }
+
+/// Implements `NodePorts` for struct with one field per port. Port name is field name unless set with `#[port(name = "...")]`.
+#[proc_macro_derive(NodePorts, attributes(port))]
+pub fn derive_node_ports(item:TokenStream)->TokenStream{
+    let parsed = syn::parse_macro_input!(item as DeriveInput);
+    node_derive::derive_node_ports(parsed).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Implements `NodeConstants` for struct with one field per constant. Default value is set with `#[constant(default = ...)]`.
+#[proc_macro_derive(NodeConstants, attributes(constant))]
+pub fn derive_node_constants(item:TokenStream)->TokenStream{
+    let parsed = syn::parse_macro_input!(item as DeriveInput);
+    node_derive::derive_node_constants(parsed).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Implements `CalculationNode` for type implementing `TypedCalculationNode`.
+/// Node is described with `#[node(name = "...", identifier = "...", category = [...], old_identifier = "...", primary, reads_environment)]`.
+#[proc_macro_derive(CalculationNode, attributes(node))]
+pub fn derive_calculation_node(item:TokenStream)->TokenStream{
+    let parsed = syn::parse_macro_input!(item as DeriveInput);
+    node_derive::derive_calculation_node(parsed).unwrap_or_else(syn::Error::into_compile_error).into()
+}
@@ -0,0 +1,260 @@
+//! Derives for typed calculation nodes. Generated code refers to `::padamo_api`, so they are used through its reexports.
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Expr, LitStr};
+
+/// Name, display name and default value of field from `#[port(...)]` or `#[constant(...)]` attribute
+struct FieldSpec{
+    ident:syn::Ident,
+    ty:syn::Type,
+    name:String,
+    display:Option<String>,
+    default:Option<Expr>,
+}
+
+fn named_fields(input:&DeriveInput, attr_name:&str)->syn::Result<Vec<FieldSpec>>{
+    let data = match &input.data {
+        syn::Data::Struct(s)=>s,
+        _=>return Err(syn::Error::new_spanned(&input.ident, "Only structs with named fields are supported")),
+    };
+    let fields = match &data.fields {
+        syn::Fields::Named(f)=>f.named.iter().collect(),
+        syn::Fields::Unit=>Vec::new(),
+        syn::Fields::Unnamed(f)=>return Err(syn::Error::new_spanned(f, "Only structs with named fields are supported")),
+    };
+    let mut res = Vec::with_capacity(fields.len());
+    for field in fields{
+        let ident = field.ident.clone().expect("Fields are named");
+        let mut spec = FieldSpec { name: ident.to_string(), ident, ty: field.ty.clone(), display: None, default: None };
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident(attr_name)){
+            attr.parse_nested_meta(|meta|{
+                if meta.path.is_ident("name"){
+                    spec.name = meta.value()?.parse::<LitStr>()?.value();
+                }
+                else if meta.path.is_ident("display"){
+                    spec.display = Some(meta.value()?.parse::<LitStr>()?.value());
+                }
+                else if meta.path.is_ident("default") && attr_name=="constant"{
+                    spec.default = Some(meta.value()?.parse::<Expr>()?);
+                }
+                else{
+                    return Err(meta.error("Unknown attribute key"));
+                }
+                Ok(())
+            })?;
+        }
+        res.push(spec);
+    }
+    Ok(res)
+}
+
+pub fn derive_node_ports(input:DeriveInput)->syn::Result<TokenStream>{
+    let fields = named_fields(&input, "port")?;
+    let name = &input.ident;
+
+    let mut names = std::collections::HashSet::new();
+    for f in fields.iter(){
+        if !names.insert(f.name.clone()){
+            return Err(syn::Error::new_spanned(&f.ident, format!("Port \"{}\" is declared twice", f.name)));
+        }
+    }
+
+    let ports = fields.iter().map(|f|{
+        let FieldSpec { ty, name, display, .. } = f;
+        let display = display.clone().unwrap_or(name.clone());
+        quote!{
+            ::padamo_api::prelude::CalculationIO::new_named(#name, #display, <#ty as ::padamo_api::calculation_nodes::typed_node::PortValue>::CONTENT_TYPE)
+        }
+    });
+    let extract = fields.iter().map(|f|{
+        let FieldSpec { ident, name, .. } = f;
+        quote!{
+            #ident: ::padamo_api::calculation_nodes::typed_node::request_port(inputs, #name)?,
+        }
+    });
+    let store = fields.iter().map(|f|{
+        let FieldSpec { ident, name, .. } = f;
+        quote!{
+            outputs.set_value(#name, ::padamo_api::calculation_nodes::typed_node::PortValue::into_content(self.#ident))?;
+        }
+    });
+
+    Ok(quote!{
+        impl ::padamo_api::calculation_nodes::typed_node::NodePorts for #name{
+            fn ports()->::abi_stable::std_types::RVec<::padamo_api::prelude::CalculationIO>{
+                #[allow(unused_mut)]
+                let mut res = ::abi_stable::std_types::RVec::new();
+                #(res.push(#ports);)*
+                res
+            }
+
+            fn extract(inputs:&::padamo_api::prelude::ContentContainer)->Result<Self,::padamo_api::prelude::ExecutionError>{
+                Ok(Self{
+                    #(#extract)*
+                })
+            }
+
+            fn store(self, outputs:&mut ::padamo_api::prelude::IOData)->Result<(),::padamo_api::prelude::ExecutionError>{
+                #(#store)*
+                Ok(())
+            }
+        }
+    })
+}
+
+pub fn derive_node_constants(input:DeriveInput)->syn::Result<TokenStream>{
+    let fields = named_fields(&input, "constant")?;
+    let name = &input.ident;
+
+    let constants = fields.iter().map(|f|{
+        let FieldSpec { ty, name, display, default, .. } = f;
+        let display = display.clone().unwrap_or(name.clone());
+        let default = match default {
+            Some(expr)=>quote!{::std::convert::Into::<#ty>::into(#expr)},
+            None=>quote!{<#ty as ::std::default::Default>::default()},
+        };
+        quote!{
+            ::padamo_api::prelude::CalculationConstant::new_named(#name, #display, ::padamo_api::calculation_nodes::typed_node::ConstantValue::into_constant(#default))
+        }
+    });
+    let extract = fields.iter().map(|f|{
+        let FieldSpec { ident, name, .. } = f;
+        quote!{
+            #ident: ::padamo_api::calculation_nodes::typed_node::request_constant(constants, #name)?,
+        }
+    });
+
+    Ok(quote!{
+        impl ::padamo_api::calculation_nodes::typed_node::NodeConstants for #name{
+            fn constants()->::abi_stable::std_types::RVec<::padamo_api::prelude::CalculationConstant>{
+                #[allow(unused_mut)]
+                let mut res = ::abi_stable::std_types::RVec::new();
+                #(res.push(#constants);)*
+                res
+            }
+
+            fn extract(constants:&::padamo_api::prelude::ConstantContentContainer)->Result<Self,::padamo_api::prelude::ExecutionError>{
+                Ok(Self{
+                    #(#extract)*
+                })
+            }
+        }
+    })
+}
+
+/// Category is either list of strings or path to function returning `RVec<RString>`
+fn category_tokens(expr:&Expr)->syn::Result<TokenStream>{
+    match expr {
+        Expr::Array(arr)=>{
+            let items = arr.elems.iter();
+            Ok(quote!{
+                {
+                    #[allow(unused_mut)]
+                    let mut res = ::abi_stable::std_types::RVec::new();
+                    #(res.push(::abi_stable::std_types::RString::from(#items));)*
+                    res
+                }
+            })
+        },
+        Expr::Path(p)=>Ok(quote!{#p()}),
+        _=>Err(syn::Error::new_spanned(expr, "Category must be array of strings or path to function")),
+    }
+}
+
+pub fn derive_calculation_node(input:DeriveInput)->syn::Result<TokenStream>{
+    let name = &input.ident;
+    let mut node_name:Option<LitStr> = None;
+    let mut identifier:Option<LitStr> = None;
+    let mut old_identifier:Option<LitStr> = None;
+    let mut category:Option<TokenStream> = None;
+    let mut primary = false;
+    let mut reads_environment = false;
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("node")){
+        attr.parse_nested_meta(|meta|{
+            if meta.path.is_ident("name"){
+                node_name = Some(meta.value()?.parse()?);
+            }
+            else if meta.path.is_ident("identifier"){
+                identifier = Some(meta.value()?.parse()?);
+            }
+            else if meta.path.is_ident("old_identifier"){
+                old_identifier = Some(meta.value()?.parse()?);
+            }
+            else if meta.path.is_ident("category"){
+                category = Some(category_tokens(&meta.value()?.parse()?)?);
+            }
+            else if meta.path.is_ident("primary"){
+                primary = true;
+            }
+            else if meta.path.is_ident("reads_environment"){
+                reads_environment = true;
+            }
+            else{
+                return Err(meta.error("Unknown node attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    let node_name = node_name.ok_or_else(|| syn::Error::new_spanned(name, "Node name is required: #[node(name = \"...\")]"))?;
+    let identifier = identifier.ok_or_else(|| syn::Error::new_spanned(name, "Node identifier is required: #[node(identifier = \"...\")]"))?;
+
+    let category = category.map(|c| quote!{
+        fn category(&self)->::abi_stable::std_types::RVec<::abi_stable::std_types::RString>{
+            #c
+        }
+    });
+    let old_identifier = old_identifier.map(|x| quote!{
+        fn old_identifier(&self)->::abi_stable::std_types::ROption<::abi_stable::std_types::RString>{
+            ::abi_stable::std_types::ROption::RSome(#x.into())
+        }
+    });
+    let primary = primary.then(|| quote!{
+        fn is_primary(&self)->bool{
+            true
+        }
+    });
+    let reads_environment = reads_environment.then(|| quote!{
+        fn reads_environment(&self)->bool{
+            true
+        }
+    });
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote!{
+        impl #impl_generics ::padamo_api::prelude::CalculationNode for #name #ty_generics #where_clause{
+            fn name(&self)->::abi_stable::std_types::RString{
+                #node_name.into()
+            }
+
+            #category
+
+            fn identifier(&self)->::abi_stable::std_types::RString{
+                #identifier.into()
+            }
+
+            #old_identifier
+
+            #primary
+
+            #reads_environment
+
+            fn inputs(&self)->::abi_stable::std_types::RVec<::padamo_api::prelude::CalculationIO>{
+                <<Self as ::padamo_api::calculation_nodes::typed_node::TypedCalculationNode>::Inputs as ::padamo_api::calculation_nodes::typed_node::NodePorts>::ports()
+            }
+
+            fn outputs(&self)->::abi_stable::std_types::RVec<::padamo_api::prelude::CalculationIO>{
+                <<Self as ::padamo_api::calculation_nodes::typed_node::TypedCalculationNode>::Outputs as ::padamo_api::calculation_nodes::typed_node::NodePorts>::ports()
+            }
+
+            fn constants(&self)->::abi_stable::std_types::RVec<::padamo_api::prelude::CalculationConstant>{
+                <<Self as ::padamo_api::calculation_nodes::typed_node::TypedCalculationNode>::Constants as ::padamo_api::calculation_nodes::typed_node::NodeConstants>::constants()
+            }
+
+            fn calculate(&self, args: ::padamo_api::prelude::CalculationNodeArguments)->::abi_stable::std_types::RResult<(),::padamo_api::prelude::ExecutionError>{
+                ::padamo_api::calculation_nodes::typed_node::calculate_typed(self, args)
+            }
+        }
+    })
+}
@@ -9,6 +9,8 @@ use padamo_api_macros_internal::impl_content;
 use abi_stable::std_types::RHashMap;
 use super::node::CalculationConstant;
 use crate::lazy_array_operations::{LazyDetectorSignal, LazyTriSignal, LazyTimeSignal};
+use crate::lazy_array_operations::typed::TypedDetectorSignal;
+use crate::linalg::Mat4;
 
 #[repr(C)]
 #[derive(StableAbi,Clone,Debug)]
@@ -21,7 +23,9 @@ pub enum Content{
     Function(DoubleFunctionOperatorBox),
     DetectorSignal(LazyDetectorSignal),
     DetectorFullData(LazyTriSignal),
-    DetectorTime(LazyTimeSignal)
+    DetectorTime(LazyTimeSignal),
+    Matrix(Mat4),
+    TypedSignal(TypedDetectorSignal),
 }
 
 
@@ -77,6 +81,8 @@ impl ContentType{
             ContentType::DetectorSignal => Color { r: 1.0, g: 0.33333, b: 0.0, a: 1.0 },
             ContentType::DetectorFullData => Color { r: 0.3333333, g: 0.5, b: 0.0, a: 1.0 },
             ContentType::DetectorTime => Color { r: 0.34, g: 0.39, b: 0.69, a: 1.0 },
+            ContentType::Matrix => Color { r: 0.6, g: 0.0, b: 0.8, a: 1.0 },
+            ContentType::TypedSignal => Color { r: 0.8, g: 0.5, b: 0.2, a: 1.0 },
             //ContentType::Array => iced::Color { r: 1.0, g: 1./3., b: 0.0, a: 1.0 },
         }
     }
@@ -1,8 +1,19 @@
-use abi_stable::std_types::RString;
+use abi_stable::std_types::{RString, RVec};
 use abi_stable::StableAbi;
 use std::error::Error;
 use std::fmt::Display;
 
+/// Node of graph where error happened
+#[repr(C)]
+#[derive(StableAbi,Debug,Clone)]
+pub struct NodeErrorContext{
+    pub node_index:usize,
+    pub identifier:RString,
+    pub name:RString,
+    /// Error messages from outer graph to innermost failed node. Nested graphs (subgraphs) add their own entries.
+    pub causes:RVec<RString>,
+}
+
 #[repr(C)]
 #[derive(StableAbi,Debug,Clone)]
 pub enum ExecutionError{
@@ -12,7 +23,10 @@ pub enum ExecutionError{
     UnfilledOutputs,
     MissingPort(RString),
     CycleError,
-    OtherError(RString)
+    OtherError(RString),
+    NodeError(NodeErrorContext),
+    /// Execution was stopped by user
+    Cancelled,
 }
 
 impl Display for ExecutionError{
@@ -25,6 +39,11 @@ impl Display for ExecutionError{
             Self::MissingPort(p)=>write!(f,"Missing port {}", p),
             Self::CycleError=>write!(f,"Cycle connection detected"),
             Self::OtherError(s)=>write!(f, "Error: {}", s),
+            Self::Cancelled=>write!(f, "Cancelled"),
+            Self::NodeError(c)=>{
+                let causes:Vec<&str> = c.causes.iter().map(|x| x.as_str()).collect();
+                write!(f, "Node #{} \"{}\" ({}): {}", c.node_index, c.name, c.identifier, causes.join(": "))
+            }
         }
     }
 }
@@ -41,4 +60,33 @@ impl ExecutionError{
         let v = format!("{}",err);
         Self::OtherError(v.into())
     }
+
+    /// Attaches node context to error. Context of error raised inside nested graph becomes a cause.
+    /// Cancellation is not an error of node so it is left as is.
+    pub fn in_node(self, node_index:usize, identifier:&str, name:&str)->Self{
+        let causes = match self{
+            Self::Cancelled=>return Self::Cancelled,
+            Self::NodeError(inner)=>{
+                let mut causes = RVec::with_capacity(inner.causes.len()+1);
+                causes.push(format!("node #{} \"{}\"", inner.node_index, inner.name).into());
+                causes.extend(inner.causes);
+                causes
+            }
+            other=>abi_stable::rvec![other.to_string().into()]
+        };
+        Self::NodeError(NodeErrorContext { node_index, identifier:identifier.into(), name:name.into(), causes })
+    }
+
+    pub fn is_cancelled(&self)->bool{
+        matches!(self, Self::Cancelled)
+    }
+
+    pub fn node_context(&self)->Option<&NodeErrorContext>{
+        if let Self::NodeError(c) = self{
+            Some(c)
+        }
+        else{
+            None
+        }
+    }
 }
@@ -0,0 +1,120 @@
+//! Fingerprints of graph nodes.
+//! They are used as keys of persistent caches, so they must not depend on Rust version, platform or order of nodes in graph.
+
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::ConstantContent;
+
+const FNV_OFFSET:u64 = 0xcbf29ce484222325;
+const FNV_PRIME:u64 = 0x100000001b3;
+
+/// Files up to this size are identified by content, larger ones by modification time
+const MAX_HASHED_FILE:u64 = 1<<20;
+
+/// 64-bit FNV-1a hash. All values are written in fixed little endian form.
+#[derive(Clone,Debug)]
+pub struct StableHasher(u64);
+
+impl StableHasher{
+    pub fn new()->Self{
+        Self(FNV_OFFSET)
+    }
+
+    pub fn write_bytes(&mut self, bytes:&[u8]){
+        for b in bytes{
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    pub fn write_u64(&mut self, value:u64){
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    /// Length is written first, so consecutive strings cannot be confused
+    pub fn write_str(&mut self, value:&str){
+        self.write_u64(value.len() as u64);
+        self.write_bytes(value.as_bytes());
+    }
+
+    /// String constants naming existing files also contribute identity of the file, so changed file gives new fingerprint.
+    pub fn write_constant(&mut self, value:&ConstantContent){
+        match value {
+            ConstantContent::Integer(x)=>{self.write_bytes(&[0]); self.write_bytes(&x.to_le_bytes());},
+            ConstantContent::Float(x)=>{self.write_bytes(&[1]); self.write_u64(x.to_bits());},
+            ConstantContent::Boolean(x)=>{self.write_bytes(&[2, *x as u8]);},
+            ConstantContent::String(x)=>{
+                self.write_bytes(&[3]);
+                self.write_str(x);
+                self.write_file_identity(Path::new(x.as_str()));
+            },
+        }
+    }
+
+    /// Size and content of small file (e.g. script) or size and modification time of large one. Nothing is written if path is not a file.
+    pub fn write_file_identity(&mut self, path:&Path){
+        let Ok(meta) = std::fs::metadata(path) else {return;};
+        if !meta.is_file(){
+            return;
+        }
+        self.write_u64(meta.len());
+        if meta.len()<=MAX_HASHED_FILE{
+            if let Ok(content) = std::fs::read(path){
+                self.write_bytes(&content);
+                return;
+            }
+        }
+        if let Some(modified) = meta.modified().ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()){
+            self.write_u64(modified.as_secs());
+            self.write_u64(modified.subsec_nanos() as u64);
+        }
+    }
+
+    pub fn finish(&self)->u64{
+        self.0
+    }
+}
+
+impl Default for StableHasher{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::StableHasher;
+    use crate::ConstantContent;
+
+    #[test]
+    fn test_stable_values(){
+        // Reference values of FNV-1a
+        let h = StableHasher::new();
+        assert_eq!(h.finish(), 0xcbf29ce484222325);
+        let mut h = StableHasher::new();
+        h.write_bytes(b"a");
+        assert_eq!(h.finish(), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn test_file_identity(){
+        let dir = std::env::temp_dir().join(format!("padamo_fingerprint_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.bin");
+        std::fs::write(&path, [1u8,2,3]).unwrap();
+        let value = ConstantContent::String(path.to_str().unwrap().into());
+        let mut h1 = StableHasher::new();
+        h1.write_constant(&value);
+        std::fs::write(&path, [1u8,2,4]).unwrap();
+        let mut h2 = StableHasher::new();
+        h2.write_constant(&value);
+        assert_ne!(h1.finish(), h2.finish());
+        // Rewriting the same content keeps fingerprint
+        std::fs::write(&path, [1u8,2,3]).unwrap();
+        let mut h3 = StableHasher::new();
+        h3.write_constant(&value);
+        assert_eq!(h1.finish(), h3.finish());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -12,22 +12,22 @@ use abi_stable::std_types::RVec;
 #[derive(Clone,Debug)]
 pub struct FullReaderNode;
 
-fn read_full<T,U>(arg:LazyArrayOperationBox<U>)->LazyArrayOperationBox<T>
+fn read_full<T,U>(arg:LazyArrayOperationBox<U>)->Result<LazyArrayOperationBox<T>,ExecutionError>
 where
     T:Clone+Debug+Send+Sync,
     U:Clone+Debug+Send+Sync+LazyArrayOperation<T>+'static
 {
     let len = arg.length();
-    let full = arg.request_range(0,len);
-    make_lao_box(full)
+    let full = arg.try_request_range(0,len).into_result()?;
+    Ok(make_lao_box(full))
 }
 
 impl FullReaderNode{
     fn calculate(&self,args:CalculationNodeArguments,) -> Result<(),ExecutionError>{
         let mut signal = args.inputs.request_detectorfulldata("Signal")?;
         //signal.0 = make_lao_box()
-        signal.0 = read_full(signal.0);
-        signal.1 = read_full(signal.1);
+        signal.0 = read_full(signal.0)?;
+        signal.1 = read_full(signal.1)?;
         args.outputs.set_value("Signal", signal.into())
     }
 }
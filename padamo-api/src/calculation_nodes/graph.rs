@@ -1,16 +1,21 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use padamo_detectors::loaded_detectors_storage::DetectorEntry;
 use abi_stable::StableAbi;
-use abi_stable::std_types::{RString, RVec};
+use abi_stable::std_types::{ROption, RString, RVec};
 use topo_sort::{TopoSort,SortResults};
 
+use crate::function_operator::DoubleFunctionOperatorBox;
+use crate::lazy_array_operations::LazyArrayOperationBox;
 use crate::rng::RandomState;
 use crate::{CalculationNodeArguments, ConstantContent};
 
 use super::content::{Content, ContentContainer, ConstantContentContainer};
 use super::errors::ExecutionError;
+use super::fingerprint::StableHasher;
 use super::node::CalculationNodeObject;
 use super::node::IOData;
+use super::progress::ProgressToken;
 
 use abi_stable::std_types::RHashMap;
 
@@ -25,11 +30,23 @@ pub struct CalculationSequenceStorage{
     pub nodes:Vec<CalculationNodeObject>,
     pub nets:HashMap<PortKey,Content>,
     pub environment:ContentContainer,
+    /// Cancellation and progress of execution. Clone it to stop execution from another thread.
+    pub progress:ProgressToken,
+    /// Outputs of non-primary nodes from previous execution by node fingerprint. Survives graph rebuild.
+    cache:HashMap<u64,HashMap<RString,Content>>,
+    /// Fingerprints of nodes being executed
+    fingerprints:HashMap<usize,u64>,
+    /// Mixed into fingerprints of all nodes. Subgraph sets it to fingerprint of its node so inner nodes depend on subgraph inputs.
+    pub fingerprint_salt:u64,
+    /// Environment variables written by nodes in previous executions. They are results of graph, not its inputs.
+    written_environment:HashSet<RString>,
+    /// Nodes reading environment and nodes depending on them. Their outputs are not described by fingerprints.
+    volatile:HashSet<usize>,
 }
 
 impl CalculationSequenceStorage{
     pub fn new()->Self{
-        Self { nodes:Vec::new(), nets: HashMap::new() , environment:ContentContainer::new()}
+        Self { nodes:Vec::new(), nets: HashMap::new() , environment:ContentContainer::new(), progress:ProgressToken::new(), cache:HashMap::new(), fingerprints:HashMap::new(), fingerprint_salt:0, written_environment:HashSet::new(), volatile:HashSet::new()}
     }
 
     pub fn push_node(&mut self,node:CalculationNodeObject){
@@ -47,7 +64,18 @@ impl CalculationSequenceStorage{
         }
     }
 
-    pub fn execute_node(&mut self, i:usize,random_state:&mut RandomState, detectors:&RVec<DetectorEntry>)->Result<(),ExecutionError>{
+    fn node_error(&self, i:usize, err:ExecutionError)->ExecutionError{
+        let calculator = &self.nodes[i].calculator;
+        err.in_node(i, calculator.identifier().as_str(), calculator.name().as_str())
+    }
+
+    /// Runs node `i` using given environment. Node outputs are returned instead of being written to nets, so several nodes can run at once.
+    /// Errors are wrapped with context of node.
+    fn run_node(&self, i:usize,random_state:&mut RandomState, environment:&mut ContentContainer, detectors:&RVec<DetectorEntry>)->Result<HashMap<RString,Content>,ExecutionError>{
+        self.run_node_unwrapped(i, random_state, environment, detectors).map_err(|e| self.node_error(i, e))
+    }
+
+    fn run_node_unwrapped(&self, i:usize,random_state:&mut RandomState, environment:&mut ContentContainer, detectors:&RVec<DetectorEntry>)->Result<HashMap<RString,Content>,ExecutionError>{
         let node = &self.nodes[i];
         let mut inputs:RHashMap<RString, Content> = RHashMap::new();
         let mut input_mapping:HashMap<_, _> = node.get_connections().into_result()?.into();
@@ -86,14 +114,21 @@ impl CalculationSequenceStorage{
             inputs,
             outputs:&mut outputs,
             constants:consts,
-            environment:&mut self.environment,
+            environment,
             rng: random_state,
-            detectors
+            detectors,
+            progress: &self.progress,
+            fingerprint: self.fingerprints.get(&i).copied().unwrap_or_default(),
         };
 
+        self.progress.check()?;
         node.calculator.calculate(args).into_result()?;
-        let mut explicit_outputs:HashMap<RString,Content> = outputs.clarify()?.into();
-        for (port,value) in explicit_outputs.drain(){
+        let explicit_outputs:HashMap<RString,Content> = outputs.clarify()?.into();
+        Ok(explicit_outputs)
+    }
+
+    fn store_outputs(&mut self, i:usize, mut outputs:HashMap<RString,Content>){
+        for (port,value) in outputs.drain(){
             let key = PortKey{
                index:i,
                port_name:port.into()
@@ -101,17 +136,189 @@ impl CalculationSequenceStorage{
             //println!("INSERT NET {:?} = {:?}", key, value);
             self.nets.insert(key, value);
         }
+    }
 
-        //let outputs_defs = node.calculator.outputs();
-
+    pub fn execute_node(&mut self, i:usize,random_state:&mut RandomState, detectors:&RVec<DetectorEntry>)->Result<(),ExecutionError>{
+        let mut environment = std::mem::replace(&mut self.environment, ContentContainer::new());
+        let res = self.run_node(i, random_state, &mut environment, detectors);
+        self.environment = environment;
+        self.store_outputs(i, res?);
         Ok(())
     }
 
+    /// Runs nodes of one level concurrently. Each node gets its own copy of environment,
+    /// changes made by nodes are merged back in order of node indices afterwards.
+    fn execute_level(&mut self, level:&[usize], random_state:&RandomState, detectors:&RVec<DetectorEntry>)->Result<(),ExecutionError>{
+        if level.len()==1{
+            let i = level[0];
+            let mut state2 = random_state.separate(i as u64);
+            return self.execute_node(i, &mut state2, detectors);
+        }
+
+        let workers = std::thread::available_parallelism().map(|x| x.get()).unwrap_or(1).min(level.len());
+        let next_task = AtomicUsize::new(0);
+        let this = &*self;
+        let mut results:Vec<(usize,Result<HashMap<RString,Content>,ExecutionError>,ContentContainer,EnvFingerprints)> = std::thread::scope(|s|{
+            let handles:Vec<_> = (0..workers).map(|_| s.spawn(||{
+                let mut done = Vec::new();
+                loop{
+                    let task = next_task.fetch_add(1, Ordering::Relaxed);
+                    let Some(&i) = level.get(task) else {break;};
+                    let mut state2 = random_state.separate(i as u64);
+                    let mut environment = this.environment.clone();
+                    let before = fingerprint_environment(&environment);
+                    let res = this.run_node(i, &mut state2, &mut environment, detectors);
+                    done.push((i,res,environment,before));
+                }
+                done
+            })).collect();
+            handles.into_iter().flat_map(|h| h.join().expect("Graph worker thread panicked")).collect()
+        });
+        results.sort_by_key(|x| x.0);
+
+        let mut first_error = None;
+        for (i,res,mut environment,before) in results.drain(..){
+            for (key,_) in before.iter(){
+                if !environment.0.contains_key(key){
+                    self.environment.0.remove(key);
+                }
+            }
+            for (key,value) in environment.0.drain().map(|x| x.into_tuple()){
+                let changed = before.get(&key).map(|fp| *fp!=fingerprint_content(&value)).unwrap_or(true);
+                if changed{
+                    self.environment.0.insert(key, value);
+                }
+            }
+            match res{
+                Ok(outputs)=>self.store_outputs(i, outputs),
+                Err(e)=>{
+                    if first_error.is_none(){
+                        first_error = Some(e);
+                    }
+                }
+            }
+        }
+        if let Some(e) = first_error{
+            Err(e)
+        }
+        else{
+            Ok(())
+        }
+    }
+
     pub fn clear_graph(&mut self){
         self.nets.clear();
         self.nodes.clear();
     }
 
+    /// Forget outputs of previous execution so next one recomputes every node.
+    pub fn clear_cache(&mut self){
+        self.cache.clear();
+        self.written_environment.clear();
+    }
+
+    /// Environment variables written by nodes in executions so far
+    pub(crate) fn written_environment(&self)->&HashSet<RString>{
+        &self.written_environment
+    }
+
+    /// Nodes executed every time: primary ones output data somewhere else, others depend on environment which is not tracked by fingerprints.
+    fn is_uncached(&self, i:usize)->bool{
+        self.nodes[i].calculator.is_primary() || self.volatile.contains(&i)
+    }
+
+    /// Fingerprint of node setup. Node with same fingerprint as in previous execution yields same outputs.
+    /// Index of node is not used, so fingerprints survive removal of unrelated nodes.
+    fn node_fingerprint(&self, i:usize, base:u64, fingerprints:&HashMap<usize,u64>)->Result<u64,ExecutionError>{
+        let node = &self.nodes[i];
+        let mut hasher = StableHasher::new();
+        hasher.write_u64(base);
+        hasher.write_str(&node.calculator.identifier());
+        hasher.write_str(&node.calculator.setup_description());
+
+        let mut constants:Vec<_> = node.constants.0.iter().map(|x| x.into_tuple()).collect();
+        constants.sort_by(|a,b| a.0.cmp(b.0));
+        for (key,value) in constants{
+            hasher.write_str(key);
+            hasher.write_constant(value);
+        }
+
+        let mut externals:Vec<_> = node.constants_external_flags.iter().map(|x| x.into_tuple()).collect();
+        externals.sort_by(|a,b| a.0.cmp(b.0));
+        for (key,value) in externals{
+            hasher.write_str(key);
+            hasher.write_bytes(&[*value as u8]);
+        }
+
+        let conns:HashMap<_, _> = node.get_connections().into_result()?.into();
+        let mut conns:Vec<_> = conns.into_iter().collect();
+        conns.sort_by(|a,b| a.0.cmp(&b.0));
+        for (port,src) in conns{
+            hasher.write_str(&port);
+            hasher.write_str(&src.port_name);
+            hasher.write_u64(fingerprints[&src.index]);
+        }
+        Ok(hasher.finish())
+    }
+
+    /// Fingerprints of all nodes in topological order.
+    /// Random state of node depends on its index, so identical nodes get different fingerprints by order of occurrence.
+    fn node_fingerprints(&self, sorted:&[usize], base:u64)->Result<HashMap<usize,u64>,ExecutionError>{
+        let mut fingerprints:HashMap<usize,u64> = HashMap::new();
+        let mut used = HashSet::new();
+        for i in sorted.iter(){
+            let mut fp = self.node_fingerprint(*i, base, &fingerprints)?;
+            let mut occurrence = 0;
+            while !used.insert(fp){
+                occurrence += 1;
+                let mut hasher = StableHasher::new();
+                hasher.write_u64(fp);
+                hasher.write_u64(occurrence);
+                fp = hasher.finish();
+            }
+            fingerprints.insert(*i, fp);
+        }
+        Ok(fingerprints)
+    }
+
+    /// Part of fingerprint shared by all nodes: seed, detectors and plain environment values set outside of graph.
+    fn base_fingerprint(&self, random_seed:u64, detectors:&RVec<DetectorEntry>)->u64{
+        let mut hasher = StableHasher::new();
+        hasher.write_u64(self.fingerprint_salt);
+        hasher.write_u64(random_seed);
+        // Serialized form is stable unlike Debug output
+        hasher.write_str(&serde_json::to_string(detectors).unwrap_or_default());
+        let mut env:Vec<_> = self.environment.0.iter().map(|x| x.into_tuple()).collect();
+        env.sort_by(|a,b| a.0.cmp(b.0));
+        for (key,value) in env{
+            // Provenance describes whole graph, so it would invalidate every node on any edit
+            if super::provenance::is_provenance_var(key) || self.written_environment.contains(key){
+                continue;
+            }
+            let plain = match value {
+                Content::Integer(x)=>Some(ConstantContent::Integer(*x)),
+                Content::Float(x)=>Some(ConstantContent::Float(*x)),
+                Content::Boolean(x)=>Some(ConstantContent::Boolean(*x)),
+                Content::String(x)=>Some(ConstantContent::String(x.clone())),
+                _=>None,
+            };
+            if let Some(v) = plain{
+                hasher.write_str(key);
+                hasher.write_constant(&v);
+            }
+        }
+        hasher.finish()
+    }
+
+    fn collect_outputs(&self, i:usize)->HashMap<RString,Content>{
+        self.nodes[i].calculator.outputs().iter()
+            .filter_map(|port| {
+                let key = PortKey{index:i, port_name:port.name.clone()};
+                self.nets.get(&key).map(|v| (port.name.clone(), v.clone()))
+            })
+            .collect()
+    }
+
     pub fn link_fromto(&mut self, start_i:usize, end_i:usize, output_port:&str, input_port:&str){
         self.nodes[end_i].connect_from(input_port, PortKey { port_name: output_port.into(), index: start_i });
     }
@@ -131,7 +338,7 @@ impl CalculationSequenceStorage{
 
         while let Some(i) = nodes_under_processing.pop_front(){
             let node = &self.nodes[i];
-            let conns:HashMap<_, _> = node.get_connections().into_result()?.into();
+            let conns:HashMap<_, _> = node.get_connections().into_result().map_err(|e| self.node_error(i, e))?.into();
             let deps:Vec<_> = conns.values().map(|x| x.index).collect();
             //if sorter.
             if let None = sorter.get(&i){
@@ -144,11 +351,35 @@ impl CalculationSequenceStorage{
         }
 
         if let SortResults::Full(sorted) = sorter.into_vec_nodes(){
+            // Nodes are grouped by depth: node can run once all nodes of previous levels are finished.
+            let mut depths:HashMap<usize,usize> = HashMap::new();
+            let mut levels:Vec<Vec<usize>> = Vec::new();
+            self.volatile.clear();
             for i in sorted.iter(){
-                //println!("Executing node {}", i);
-                let mut state2 = random_state.separate(*i as u64);
-                self.execute_node(*i,&mut state2, detectors)?;
+                let conns:HashMap<_, _> = self.nodes[*i].get_connections().into_result()?.into();
+                if self.nodes[*i].calculator.reads_environment() || conns.values().any(|x| self.volatile.contains(&x.index)){
+                    self.volatile.insert(*i);
+                }
+                let depth = conns.values().map(|x| depths[&x.index]+1).max().unwrap_or(0);
+                depths.insert(*i, depth);
+                if levels.len()<=depth{
+                    levels.resize(depth+1, Vec::new());
+                }
+                levels[depth].push(*i);
+            }
+
+            let base = self.base_fingerprint(random_seed, detectors);
+            self.fingerprints = self.node_fingerprints(&sorted, base)?;
+
+            // Cached nodes with unchanged fingerprint take outputs from previous execution.
+            let env_before = fingerprint_environment(&self.environment);
+            let res = self.execute_levels(&mut levels, &random_state, detectors);
+            for (key,value) in self.environment.0.iter().map(|x| x.into_tuple()){
+                if env_before.get(key).map(|fp| *fp!=fingerprint_content(value)).unwrap_or(true){
+                    self.written_environment.insert(key.clone());
+                }
             }
+            res?;
         }
         else{
             return Err(ExecutionError::CycleError);
@@ -156,4 +387,309 @@ impl CalculationSequenceStorage{
 
         Ok(())
     }
+
+    /// Runs levels of nodes in order. Outputs of cached nodes are stored for next execution.
+    fn execute_levels(&mut self, levels:&mut [Vec<usize>], random_state:&RandomState, detectors:&RVec<DetectorEntry>)->Result<(),ExecutionError>{
+        let mut new_cache = HashMap::new();
+        for level in levels.iter_mut(){
+            level.sort();
+            let mut to_run = Vec::with_capacity(level.len());
+            for i in level.iter(){
+                let cached = if self.is_uncached(*i) {None} else {self.cache.get(&self.fingerprints[i]).cloned()};
+                if let Some(outputs) = cached{
+                    self.store_outputs(*i, outputs);
+                }
+                else{
+                    to_run.push(*i);
+                }
+            }
+            let res = self.progress.check().and_then(|_| self.execute_level(&to_run, random_state, detectors));
+            for i in level.iter(){
+                if !self.is_uncached(*i){
+                    let outputs = self.collect_outputs(*i);
+                    if outputs.len()==self.nodes[*i].calculator.outputs().len(){
+                        new_cache.insert(self.fingerprints[i], outputs);
+                    }
+                }
+            }
+            if let Err(e) = res{
+                self.cache.extend(new_cache);
+                return Err(e);
+            }
+        }
+        self.cache = new_cache;
+        Ok(())
+    }
+}
+
+type EnvFingerprints = HashMap<RString,Vec<u8>>;
+
+/// Address of value boxed into trait object. Boxes are not changed in place, so new address means new value.
+/// Values of zero size may share address, they have no state to tell apart anyway.
+trait BoxedIdentity{
+    fn boxed_address(&self)->usize;
+}
+
+impl<T> BoxedIdentity for LazyArrayOperationBox<T>{
+    fn boxed_address(&self)->usize{
+        self.obj.sabi_as_rref().as_ptr() as usize
+    }
+}
+
+impl BoxedIdentity for DoubleFunctionOperatorBox{
+    fn boxed_address(&self)->usize{
+        self.obj.sabi_as_rref().as_ptr() as usize
+    }
+}
+
+fn boxed_bytes<T:BoxedIdentity>(value:&T)->[u8;std::mem::size_of::<usize>()]{
+    value.boxed_address().to_le_bytes()
+}
+
+/// Value of content for plain values and identity of boxed object otherwise.
+/// Nodes write environment by inserting new values, so changed identity means that value was written.
+fn fingerprint_content(content:&Content)->Vec<u8>{
+    let mut res = Vec::new();
+    match content {
+        Content::Integer(x)=>{res.push(0); res.extend(x.to_le_bytes());},
+        Content::Float(x)=>{res.push(1); res.extend(x.to_bits().to_le_bytes());},
+        Content::Boolean(x)=>{res.push(2); res.push(*x as u8);},
+        Content::String(x)=>{res.push(3); res.extend(x.as_bytes());},
+        Content::Function(x)=>{res.push(4); res.extend(boxed_bytes(x));},
+        Content::DetectorSignal(x)=>{res.push(5); res.extend(boxed_bytes(x));},
+        Content::DetectorFullData(x)=>{
+            res.push(6);
+            res.extend(boxed_bytes(&x.0));
+            res.extend(boxed_bytes(&x.1));
+            if let ROption::RSome(trig) = &x.2{
+                res.extend(boxed_bytes(trig));
+            }
+        },
+        Content::DetectorTime(x)=>{res.push(7); res.extend(boxed_bytes(x));},
+        Content::Matrix(x)=>{res.push(8); x.0.iter().flatten().for_each(|v| res.extend(v.to_bits().to_le_bytes()));},
+        Content::TypedSignal(x)=>{
+            res.push(9);
+            res.push(x.dtype() as u8);
+            crate::with_typed_signal!(x, op => res.extend(boxed_bytes(op)));
+        },
+    }
+    res
+}
+
+fn fingerprint_environment(environment:&ContentContainer)->EnvFingerprints{
+    environment.0.iter().map(|x| (x.0.clone(), fingerprint_content(x.1))).collect()
+}
+
+#[cfg(test)]
+mod tests{
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use abi_stable::std_types::{RResult, RString, RVec};
+    use crate::{constants, make_node_box, ports};
+    use crate::prelude::*;
+    use super::CalculationSequenceStorage;
+    use super::super::node::CalculationNodeObject;
+
+    /// Writes random value into environment variable
+    #[derive(Clone,Debug)]
+    struct RandomWriter(&'static str);
+
+    impl CalculationNode for RandomWriter{
+        fn name(&self,) -> RString {
+            "Random writer".into()
+        }
+
+        fn is_primary(&self,) -> bool {
+            true
+        }
+
+        fn inputs(&self,) -> RVec<CalculationIO> {
+            ports!(("Value", ContentType::Integer))
+        }
+
+        fn calculate(&self,args:CalculationNodeArguments) -> RResult<(),ExecutionError> {
+            let offset = args.inputs.request_integer("Value").unwrap();
+            let v = (args.rng.generate_new()>>2) as i64 + offset;
+            args.environment.0.insert(self.0.into(), Content::Integer(v));
+            RResult::ROk(())
+        }
+    }
+
+    #[derive(Clone,Debug)]
+    struct Source;
+
+    impl CalculationNode for Source{
+        fn name(&self,) -> RString {
+            "Source".into()
+        }
+
+        fn outputs(&self,) -> RVec<CalculationIO> {
+            ports!(("Value", ContentType::Integer))
+        }
+
+        fn constants(&self,) -> RVec<CalculationConstant> {
+            constants!(("value", 1))
+        }
+
+        fn calculate(&self,args:CalculationNodeArguments) -> RResult<(),ExecutionError> {
+            let v = args.constants.request_integer("value").unwrap();
+            args.outputs.set_value("Value", Content::Integer(v)).into()
+        }
+    }
+
+    static COUNTED_CALLS:AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Clone,Debug)]
+    struct CountedSource;
+
+    impl CalculationNode for CountedSource{
+        fn name(&self,) -> RString {
+            "Counted source".into()
+        }
+
+        fn outputs(&self,) -> RVec<CalculationIO> {
+            ports!(("Value", ContentType::Integer))
+        }
+
+        fn constants(&self,) -> RVec<CalculationConstant> {
+            constants!(("value", 1))
+        }
+
+        fn calculate(&self,args:CalculationNodeArguments) -> RResult<(),ExecutionError> {
+            COUNTED_CALLS.fetch_add(1, Ordering::SeqCst);
+            let v = args.constants.request_integer("value").unwrap();
+            args.outputs.set_value("Value", Content::Integer(v)).into()
+        }
+    }
+
+    #[test]
+    fn test_incremental_execution(){
+        let mut graph = CalculationSequenceStorage::new();
+        graph.push_node(CalculationNodeObject::new(make_node_box(CountedSource), None, None));
+        graph.push_node(CalculationNodeObject::new(make_node_box(RandomWriter("a")), None, None));
+        graph.link_fromto(0, 1, "Value", "Value");
+        let detectors = RVec::new();
+
+        graph.execute(0, &detectors).unwrap();
+        graph.execute(0, &detectors).unwrap();
+        assert_eq!(COUNTED_CALLS.load(Ordering::SeqCst), 1);
+
+        graph.edit_constants(0).0.insert("value".into(), ConstantContent::Integer(5));
+        graph.execute(0, &detectors).unwrap();
+        assert_eq!(COUNTED_CALLS.load(Ordering::SeqCst), 2);
+
+        graph.clear_cache();
+        graph.execute(0, &detectors).unwrap();
+        assert_eq!(COUNTED_CALLS.load(Ordering::SeqCst), 3);
+    }
+
+    static READER_CALLS:AtomicUsize = AtomicUsize::new(0);
+
+    /// Outputs environment variable "x"
+    #[derive(Clone,Debug)]
+    struct EnvReader;
+
+    impl CalculationNode for EnvReader{
+        fn name(&self,) -> RString {
+            "Environment reader".into()
+        }
+
+        fn reads_environment(&self,) -> bool {
+            true
+        }
+
+        fn outputs(&self,) -> RVec<CalculationIO> {
+            ports!(("Value", ContentType::Integer))
+        }
+
+        fn calculate(&self,args:CalculationNodeArguments) -> RResult<(),ExecutionError> {
+            READER_CALLS.fetch_add(1, Ordering::SeqCst);
+            let v = args.environment.request_integer("x").unwrap();
+            args.outputs.set_value("Value", Content::Integer(v)).into()
+        }
+    }
+
+    /// Passes integer as is
+    #[derive(Clone,Debug)]
+    struct Pass;
+
+    impl CalculationNode for Pass{
+        fn name(&self,) -> RString {
+            "Pass".into()
+        }
+
+        fn inputs(&self,) -> RVec<CalculationIO> {
+            ports!(("Value", ContentType::Integer))
+        }
+
+        fn outputs(&self,) -> RVec<CalculationIO> {
+            ports!(("Value", ContentType::Integer))
+        }
+
+        fn calculate(&self,args:CalculationNodeArguments) -> RResult<(),ExecutionError> {
+            let v = args.inputs.request_integer("Value").unwrap();
+            args.outputs.set_value("Value", Content::Integer(v)).into()
+        }
+    }
+
+    #[test]
+    fn test_environment_reader(){
+        let mut graph = CalculationSequenceStorage::new();
+        graph.push_node(CalculationNodeObject::new(make_node_box(EnvReader), None, None));
+        graph.push_node(CalculationNodeObject::new(make_node_box(Pass), None, None));
+        graph.push_node(CalculationNodeObject::new(make_node_box(RandomWriter("x")), None, None));
+        graph.link_fromto(0, 1, "Value", "Value");
+        graph.link_fromto(1, 2, "Value", "Value");
+        graph.environment.0.insert("x".into(), Content::Integer(0));
+        let detectors = RVec::new();
+
+        // Writer changes "x" on every execution, so neither reader nor node after it may take stale value from cache
+        graph.execute(0, &detectors).unwrap();
+        let first = graph.environment.request_integer("x").unwrap();
+        graph.execute(0, &detectors).unwrap();
+        assert_eq!(READER_CALLS.load(Ordering::SeqCst), 2);
+        assert_ne!(graph.environment.request_integer("x").unwrap(), first);
+    }
+
+    #[test]
+    fn test_parallel_branches(){
+        let mut graph = CalculationSequenceStorage::new();
+        graph.push_node(CalculationNodeObject::new(make_node_box(Source), None, None));
+        graph.push_node(CalculationNodeObject::new(make_node_box(RandomWriter("a")), None, None));
+        graph.push_node(CalculationNodeObject::new(make_node_box(RandomWriter("b")), None, None));
+        graph.push_node(CalculationNodeObject::new(make_node_box(RandomWriter("c")), None, None));
+        for i in 1..4{
+            graph.link_fromto(0, i, "Value", "Value");
+        }
+        graph.environment.0.insert("b".into(), Content::Integer(-1));
+        graph.environment.0.insert("untouched".into(), Content::Integer(-1));
+
+        let detectors = RVec::new();
+        graph.execute(42, &detectors).unwrap();
+
+        let state = RandomState::new(42);
+        for (i,key) in [(1,"a"),(2,"b"),(3,"c")]{
+            let expected = (state.separate(i).generate_new()>>2) as i64 + 1;
+            assert_eq!(graph.environment.request_integer(key).unwrap(), expected);
+        }
+        assert_eq!(graph.environment.request_integer("untouched").unwrap(), -1);
+    }
+
+    #[test]
+    fn test_error_context(){
+        let mut graph = CalculationSequenceStorage::new();
+        graph.push_node(CalculationNodeObject::new(make_node_box(Source), None, None));
+        graph.push_node(CalculationNodeObject::new(make_node_box(RandomWriter("a")), None, None));
+
+        let err = graph.execute(42, &RVec::new()).unwrap_err();
+        let context = err.node_context().unwrap();
+        assert_eq!(context.node_index, 1);
+        assert_eq!(context.name.as_str(), "Random writer");
+
+        // Context of inner graph is kept as cause
+        let err = err.in_node(5, "subgraph.test", "Test");
+        let context = err.node_context().unwrap();
+        assert_eq!(context.node_index, 5);
+        assert_eq!(context.causes.len(), 2);
+        assert_eq!(context.causes[0].as_str(), "node #1 \"Random writer\"");
+    }
 }
@@ -0,0 +1,134 @@
+//! Save format of graph editor. Application and headless runner read the same files.
+use std::collections::HashMap;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize,Deserialize};
+
+use super::content::ConstantContent;
+
+/// Position of node in editor
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Copy,Debug,Default)]
+pub struct SerdePoint{
+    pub x:f32,
+    pub y:f32
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug)]
+pub struct SerdeConnection{
+    pub node_index:usize,              //source node
+    pub port:String                    //output port
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug,PartialEq)]
+pub enum NodeConstantContent{
+    Boolean(bool),
+    Text(String),
+    Integer(i64),
+    Real(f64)
+}
+
+impl NodeConstantContent{
+    pub fn is_compatible(&self, other:&Self)->bool{
+        std::mem::discriminant(self)==std::mem::discriminant(other)
+    }
+}
+
+impl From<ConstantContent> for NodeConstantContent{
+    fn from(value: ConstantContent) -> Self {
+        match value {
+            ConstantContent::Integer(x)=>Self::Integer(x),
+            ConstantContent::Boolean(b)=>Self::Boolean(b),
+            ConstantContent::Float(f)=>Self::Real(f),
+            ConstantContent::String(s)=>Self::Text(s.into_string()),
+        }
+    }
+}
+
+impl From<NodeConstantContent> for ConstantContent{
+    fn from(value: NodeConstantContent) -> Self {
+        match value{
+            NodeConstantContent::Boolean(b)=>Self::Boolean(b),
+            NodeConstantContent::Real(f)=>Self::Float(f),
+            NodeConstantContent::Integer(i)=>Self::Integer(i),
+            NodeConstantContent::Text(s)=>Self::String(s.into()),
+        }
+    }
+}
+
+impl From<&str> for NodeConstantContent{
+    fn from(value: &str) -> Self {
+        Self::Text(value.into())
+    }
+}
+
+impl From<String> for NodeConstantContent{
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<f64> for NodeConstantContent{
+    fn from(value: f64) -> Self {
+        Self::Real(value)
+    }
+}
+
+impl From<i64> for NodeConstantContent{
+    fn from(value: i64) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<bool> for NodeConstantContent{
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+/// Text shown in editor field of constant
+impl From<NodeConstantContent> for String{
+    fn from(value: NodeConstantContent) -> Self {
+        match value {
+            NodeConstantContent::Boolean(x) => x.to_string(),
+            NodeConstantContent::Text(x) => x,
+            NodeConstantContent::Integer(x) => x.to_string(),
+            NodeConstantContent::Real(x) => x.to_string(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug)]
+pub struct SerializationEntry{
+    /// Not needed to run graph, so files written by hand may omit it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub position:SerdePoint,
+    pub identifier:String,
+    pub connections:HashMap<String,Option<SerdeConnection>>,
+    pub constants:HashMap<String,NodeConstantContent>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub constants_external_flags:HashMap<String,bool>,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug)]
+pub struct SerializedNodes(pub Vec<SerializationEntry>);
+
+#[cfg(all(test, feature = "serde"))]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_entry_without_editor_fields(){
+        let src = r#"[{"identifier":"a","connections":{"Value":{"node_index":1,"port":"Out"}},"constants":{"Scale":{"Real":2.0}}}]"#;
+        let nodes:SerializedNodes = serde_json::from_str(src).unwrap();
+        let entry = &nodes.0[0];
+        assert_eq!(entry.position.x, 0.0);
+        assert!(entry.constants_external_flags.is_empty());
+        let scale:ConstantContent = entry.constants["Scale"].clone().into();
+        assert!(matches!(scale, ConstantContent::Float(x) if x==2.0));
+    }
+}
@@ -74,4 +74,9 @@ impl CompiledGraph{
         //println!("{:?}",compute_graph.nodes);
     }
 
+    /// Reports all problems found in graph. See [`super::validation::validate_graph`].
+    pub fn validate(&self, node_registry:&HashMap<String,CalculationNodeBox>)->Vec<super::validation::Diagnostic>{
+        super::validation::validate_graph(self, node_registry)
+    }
+
 }
@@ -7,3 +7,12 @@ pub mod ad_hoc_output_node;
 pub mod ad_hoc_input_node;
 pub mod signal_time_node;
 pub mod full_reader;
+pub mod detector_muxer;
+pub mod viewer_nodes;
+pub mod subgraph;
+pub mod validation;
+pub mod progress;
+pub mod typed_node;
+pub mod provenance;
+pub mod fingerprint;
+pub mod graph_file;
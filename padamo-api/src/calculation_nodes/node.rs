@@ -5,6 +5,7 @@ use padamo_detectors::loaded_detectors_storage::DetectorEntry;
 use super::content::{Content, ContentContainer, ContentType, ConstantContent, ConstantContentContainer};
 use super::errors::ExecutionError;
 use super::graph::PortKey;
+use super::progress::ProgressToken;
 use crate::rng::RandomState;
 
 
@@ -14,6 +15,8 @@ pub struct CalculationIO{
     pub name: RString,
     pub display_name: RString,
     pub port_type: ContentType,
+    /// Input which may be left unconnected. Node finds it missing in its inputs then.
+    pub optional: bool,
 }
 
 impl CalculationIO{
@@ -21,7 +24,8 @@ impl CalculationIO{
         Self{
             name: name.into(),
             display_name: name.into(),
-            port_type
+            port_type,
+            optional: false,
         }
     }
 
@@ -29,9 +33,15 @@ impl CalculationIO{
         Self{
             name: name.into(),
             display_name: display_name.into(),
-            port_type
+            port_type,
+            optional: false,
         }
     }
+
+    pub fn optional(mut self)->Self{
+        self.optional = true;
+        self
+    }
 }
 
 impl Into<CalculationIO> for (&str,ContentType){
@@ -156,6 +166,11 @@ pub struct CalculationNodeArguments<'a>{
     pub environment:&'a mut ContentContainer,
     pub rng:&'a mut RandomState,
     pub detectors:&'a RVec<DetectorEntry>,
+    /// Cancellation and progress of current execution. Long running nodes should check it between chunks of work.
+    pub progress:&'a ProgressToken,
+    /// Hash of node setup: its constants, identity of files named by them and the whole graph upstream of it.
+    /// Same fingerprint means same outputs. It is stable between runs of application, so it can be used as key for persistent caches.
+    pub fingerprint:u64,
 }
 
 #[allow(non_local_definitions)]
@@ -168,7 +183,7 @@ pub mod traits{
     use super::CalculationNodeArguments;
     /// Trait for calculation node
     #[sabi_trait]
-    pub trait CalculationNode: Debug+Clone{
+    pub trait CalculationNode: Debug+Clone+Send+Sync{
 
         /// Name of node displayed in graph editor or node list
         fn name(&self)->RString;
@@ -191,6 +206,17 @@ pub mod traits{
             false
         }
 
+        /// If node outputs depend on environment. Such nodes are executed every time instead of taking outputs from cache.
+        fn reads_environment(&self)->bool{
+            false
+        }
+
+        /// Setup of node which is not stored in constants, e.g. code of script the node was made from.
+        /// It is part of node fingerprint, so cached outputs are dropped when it changes.
+        fn setup_description(&self)->RString{
+            RString::new()
+        }
+
         /// Input definitions of node
         fn inputs(&self)->RVec<CalculationIO>{
             ports!()
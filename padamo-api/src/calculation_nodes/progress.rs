@@ -0,0 +1,149 @@
+//! Cancellation and progress reporting of long running work.
+//! Token is shared between the one who started the work (e.g. GUI) and the work itself.
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use abi_stable::std_types::RArc;
+use abi_stable::StableAbi;
+
+use super::errors::ExecutionError;
+
+#[repr(C)]
+#[derive(StableAbi,Debug,Default)]
+struct ProgressState{
+    cancelled:AtomicBool,
+    done:AtomicUsize,
+    total:AtomicUsize,
+}
+
+/// Cancellation flag and progress counter. Clones refer to the same state.
+#[repr(C)]
+#[derive(StableAbi,Clone,Debug)]
+pub struct ProgressToken(RArc<ProgressState>);
+
+impl Default for ProgressToken{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressToken{
+    pub fn new()->Self{
+        Self(RArc::new(ProgressState::default()))
+    }
+
+    /// Requests work to stop. Work stops at the next check.
+    pub fn cancel(&self){
+        self.0.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self)->bool{
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns `ExecutionError::Cancelled` if stop was requested.
+    pub fn check(&self)->Result<(),ExecutionError>{
+        if self.is_cancelled(){
+            Err(ExecutionError::Cancelled)
+        }
+        else{
+            Ok(())
+        }
+    }
+
+    /// Clears cancellation flag and progress so token can be used for next run.
+    pub fn reset(&self){
+        self.0.cancelled.store(false, Ordering::Relaxed);
+        self.set_total(0);
+    }
+
+    /// Starts new stage of work consisting of `total` steps.
+    pub fn set_total(&self, total:usize){
+        self.0.done.store(0, Ordering::Relaxed);
+        self.0.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Adds `steps` to total of current stage. Works started in parallel or one after another share the same counts this way.
+    pub fn add_total(&self, steps:usize){
+        self.0.total.fetch_add(steps, Ordering::Relaxed);
+    }
+
+    pub fn set_done(&self, done:usize){
+        self.0.done.store(done, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, steps:usize){
+        self.0.done.fetch_add(steps, Ordering::Relaxed);
+    }
+
+    /// Done and total steps of current stage.
+    pub fn counts(&self)->(usize,usize){
+        (self.0.done.load(Ordering::Relaxed), self.0.total.load(Ordering::Relaxed))
+    }
+
+    /// Fraction of work done. `None` if work does not report progress.
+    pub fn fraction(&self)->Option<f32>{
+        let (done,total) = self.counts();
+        if total==0{
+            None
+        }
+        else{
+            Some((done.min(total) as f32)/(total as f32))
+        }
+    }
+}
+
+/// Extension of files being written
+pub const PARTIAL_EXTENSION:&str = "part";
+
+static NEXT_PARTIAL:AtomicUsize = AtomicUsize::new(0);
+
+/// Path of file being written until it is complete. Name is unique, so several writers of one file do not clash.
+pub fn partial_path(path:&Path)->PathBuf{
+    let mut name = path.file_name().map(|x| x.to_os_string()).unwrap_or_default();
+    name.push(format!(".{}_{}.{}", std::process::id(), NEXT_PARTIAL.fetch_add(1, Ordering::Relaxed), PARTIAL_EXTENSION));
+    path.with_file_name(name)
+}
+
+/// Runs `writer` on temporary file and moves it to `path` on success.
+/// On error (including cancellation) temporary file is removed, so no half-written file is left.
+pub fn write_file_atomically<F>(path:&Path, writer:F)->Result<(),ExecutionError>
+where
+    F:FnOnce(&Path)->Result<(),ExecutionError>
+{
+    let temp_path = partial_path(path);
+    let res = writer(&temp_path).and_then(|_| std::fs::rename(&temp_path, path).map_err(ExecutionError::from_error));
+    if res.is_err() && temp_path.exists(){
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_shared_state(){
+        let token = ProgressToken::new();
+        let other = token.clone();
+        assert_eq!(token.fraction(), None);
+        other.set_total(4);
+        other.advance(1);
+        assert_eq!(token.fraction(), Some(0.25));
+        assert!(token.check().is_ok());
+        token.cancel();
+        assert!(other.check().is_err());
+        other.reset();
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn test_added_totals(){
+        let token = ProgressToken::new();
+        token.add_total(2);
+        token.advance(2);
+        token.add_total(2);
+        assert_eq!(token.fraction(), Some(0.5));
+    }
+}
@@ -0,0 +1,119 @@
+//! Description of how data was produced. Application stores it in environment before execution,
+//! so writers can attach it to output files.
+use std::path::Path;
+
+use abi_stable::std_types::RString;
+
+use super::content::{ConstantContent, ConstantContentContainer, Content, ContentContainer};
+use super::graph::CalculationSequenceStorage;
+use super::node::CalculationNodeArguments;
+use super::viewer_nodes::viewer::VIEWER_FILENAME_VAR;
+
+/// Environment variables with this prefix do not affect node fingerprints
+pub const PROVENANCE_PREFIX:&str = "provenance_";
+/// Serialized graph
+pub const GRAPH_VAR:&str = "provenance_graph";
+/// Version of application running graph
+pub const VERSION_VAR:&str = "provenance_version";
+/// Files referenced by graph, one per line
+pub const SOURCES_VAR:&str = "provenance_sources";
+/// Seed entered for whole graph. Nodes get seeds derived from it.
+pub const SEED_VAR:&str = "provenance_seed";
+
+pub fn is_provenance_var(key:&str)->bool{
+    key.starts_with(PROVENANCE_PREFIX)
+}
+
+pub fn format_constant(value:&ConstantContent)->String{
+    match value {
+        ConstantContent::Integer(x)=>x.to_string(),
+        ConstantContent::Float(x)=>x.to_string(),
+        ConstantContent::Boolean(x)=>x.to_string(),
+        ConstantContent::String(x)=>x.to_string(),
+    }
+}
+
+impl CalculationSequenceStorage{
+    /// Existing files named by string constants of graph nodes or opened in viewer
+    pub fn referenced_files(&self)->Vec<String>{
+        let mut res:Vec<String> = Vec::new();
+        let constants = self.nodes.iter().flat_map(|node| node.constants.0.values());
+        let viewer_file = self.environment.0.get(VIEWER_FILENAME_VAR).and_then(|x| {
+            if let Content::String(s) = x {Some(s)} else {None}
+        });
+        let candidates = constants.filter_map(|x| {
+            if let ConstantContent::String(s) = x {Some(s)} else {None}
+        }).chain(viewer_file);
+        for candidate in candidates{
+            let candidate = candidate.as_str();
+            if !candidate.is_empty() && Path::new(candidate).is_file() && !res.iter().any(|x| x==candidate){
+                res.push(candidate.to_string());
+            }
+        }
+        res
+    }
+
+    /// Stores graph description in environment. Call it after graph is built.
+    pub fn set_provenance(&mut self, graph_json:&str, version:&str, seed:u64){
+        let sources = self.referenced_files().join("\n");
+        self.environment.0.insert(GRAPH_VAR.into(), Content::String(graph_json.into()));
+        self.environment.0.insert(VERSION_VAR.into(), Content::String(version.into()));
+        self.environment.0.insert(SOURCES_VAR.into(), Content::String(sources.into()));
+        // Stored bitwise, so seeds above i64::MAX survive round trip
+        self.environment.0.insert(SEED_VAR.into(), Content::Integer(seed as i64));
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct Provenance{
+    pub graph:Option<String>,
+    pub version:String,
+    pub seed:u64,
+    pub sources:Vec<String>,
+    /// Constants of node writing data as `name = value` lines
+    pub constants:String,
+}
+
+fn environment_string(environment:&ContentContainer, key:&str)->Option<RString>{
+    if let Some(Content::String(s)) = environment.0.get(key){
+        Some(s.clone())
+    }
+    else{
+        None
+    }
+}
+
+fn environment_seed(environment:&ContentContainer)->Option<u64>{
+    if let Some(Content::Integer(x)) = environment.0.get(SEED_VAR){
+        Some(*x as u64)
+    }
+    else{
+        None
+    }
+}
+
+fn format_constants(constants:&ConstantContentContainer)->String{
+    let mut lines:Vec<String> = constants.0.iter()
+        .map(|x| x.into_tuple())
+        .map(|(k,v)| format!("{} = {}", k, format_constant(v)))
+        .collect();
+    lines.sort();
+    lines.join("\n")
+}
+
+impl Provenance{
+    /// Collects provenance of node being executed. Application version falls back to API version if application did not set it.
+    /// Seed falls back to seed of node itself.
+    pub fn from_args(args:&CalculationNodeArguments)->Self{
+        let sources = environment_string(args.environment, SOURCES_VAR)
+            .map(|s| s.lines().filter(|x| !x.is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+        Self {
+            graph: environment_string(args.environment, GRAPH_VAR).map(|x| x.into_string()),
+            version: environment_string(args.environment, VERSION_VAR).map(|x| x.into_string()).unwrap_or(crate::plugin::API_VERSION.into()),
+            seed: environment_seed(args.environment).unwrap_or(args.rng.current_seed),
+            sources,
+            constants: format_constants(&args.constants),
+        }
+    }
+}
@@ -47,7 +47,9 @@ impl<T:CalculationNode, U:CalculationNode> SignalTimeEmbeddedMergingNode<T,U>{
             constants:consts.clone(),
             environment:env,
             rng,
-            detectors: args.detectors
+            detectors: args.detectors,
+            progress: args.progress,
+            fingerprint: args.fingerprint,
         };
 
         self.signal.calculate(args_signal).into_result()?;
@@ -60,7 +62,9 @@ impl<T:CalculationNode, U:CalculationNode> SignalTimeEmbeddedMergingNode<T,U>{
             constants:consts.clone(),
             environment:env,
             rng,
-            detectors: args.detectors
+            detectors: args.detectors,
+            progress: args.progress,
+            fingerprint: args.fingerprint,
         };
 
         self.time.calculate(args_time).into_result()?;
@@ -120,6 +124,10 @@ impl<T:CalculationNode, U:CalculationNode> CalculationNode for SignalTimeEmbedde
         self.legacy_id.clone()
     }
 
+    fn reads_environment(&self,) -> bool {
+        self.signal.reads_environment() || self.time.reads_environment()
+    }
+
     fn inputs(&self,) -> RVec<CalculationIO>{
         ports!(
             ("Filename", ContentType::String)
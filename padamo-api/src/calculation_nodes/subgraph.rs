@@ -0,0 +1,202 @@
+//! Subgraph (macro) nodes. Subgraph is a compiled graph wrapped into single node.
+//! Its inputs are taken from environment output nodes and its outputs from environment input nodes of core plugin.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use abi_stable::rvec;
+use abi_stable::std_types::{RString, RVec};
+#[cfg(feature = "serde")]
+use serde::{Serialize,Deserialize};
+
+use crate::prelude::*;
+use super::graph::CalculationSequenceStorage;
+use super::immediate::CompiledGraph;
+
+pub const SUBGRAPH_ID_PREFIX:&'static str = "subgraph.";
+const ENV_OUTPUT_PREFIX:&'static str = "padamocore.env_output.";
+const ENV_INPUT_PREFIX:&'static str = "padamocore.env_input.";
+
+/// Constant of inner node exposed as constant of subgraph node
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug)]
+pub struct PromotedConstant{
+    pub node_index:usize,
+    pub key:String,
+    pub name:String,
+    pub display_name:String,
+    pub default_value:ConstantContent,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug)]
+pub struct SubgraphDefinition{
+    pub name:String,
+    pub graph:CompiledGraph,
+    pub promoted_constants:Vec<PromotedConstant>,
+}
+
+impl SubgraphDefinition{
+    pub fn identifier(&self)->String{
+        format!("{}{}", SUBGRAPH_ID_PREFIX, self.name)
+    }
+}
+
+/// Collection of subgraphs stored in one file
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug,Default)]
+pub struct SubgraphLibrary(pub Vec<SubgraphDefinition>);
+
+impl SubgraphLibrary{
+    /// Adds subgraph replacing one with the same name
+    pub fn insert(&mut self, definition:SubgraphDefinition){
+        if let Some(entry) = self.0.iter_mut().find(|x| x.name==definition.name){
+            *entry = definition;
+        }
+        else{
+            self.0.push(definition);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl SubgraphLibrary{
+    /// Loads library. Missing file is treated as empty library.
+    pub fn load<T:AsRef<std::path::Path>>(path:T)->Result<Self,ExecutionError>{
+        if !path.as_ref().exists(){
+            return Ok(Self::default());
+        }
+        let s = std::fs::read_to_string(path).map_err(ExecutionError::from_error)?;
+        serde_json::from_str(&s).map_err(ExecutionError::from_error)
+    }
+
+    pub fn save<T:AsRef<std::path::Path>>(&self, path:T)->Result<(),ExecutionError>{
+        let s = serde_json::to_string_pretty(self).map_err(ExecutionError::from_error)?;
+        std::fs::write(path, s).map_err(ExecutionError::from_error)
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct SubgraphNode{
+    definition:Arc<SubgraphDefinition>,
+    registry:Arc<HashMap<String,CalculationNodeBox>>,
+    inputs:RVec<CalculationIO>,
+    outputs:RVec<CalculationIO>,
+    primary:bool,
+    /// Some inner node reads environment of outer graph
+    reads_environment:bool,
+}
+
+impl SubgraphNode{
+    /// Creates subgraph node. All inner nodes must be present in registry.
+    pub fn new(definition:SubgraphDefinition, registry:Arc<HashMap<String,CalculationNodeBox>>)->Result<Self,ExecutionError>{
+        let mut inputs = RVec::new();
+        let mut outputs = RVec::new();
+        let mut primary = false;
+        let mut reads_environment = false;
+        for (i,node) in definition.graph.nodes.iter().enumerate(){
+            let inner = registry.get(&node.identifier).ok_or(
+                ExecutionError::OtherError(format!("Subgraph {}: node #{} ({}) is not found", definition.name, i, node.identifier).into())
+            )?;
+            let is_input = node.identifier.starts_with(ENV_OUTPUT_PREFIX);
+            let is_output = node.identifier.starts_with(ENV_INPUT_PREFIX);
+            if is_input || is_output{
+                let key = match node.constants.0.get("Key"){
+                    Some(ConstantContent::String(s))=>s.clone(),
+                    _=>return Err(ExecutionError::ConstantMissing),
+                };
+                let port_type = if is_input {inner.outputs()[0].port_type} else {inner.inputs()[0].port_type};
+                let ports = if is_input {&mut inputs} else {&mut outputs};
+                if ports.iter().any(|x:&CalculationIO| x.name==key){
+                    return Err(ExecutionError::OtherError(format!("Subgraph {}: port {} is defined twice", definition.name, key).into()));
+                }
+                ports.push(CalculationIO::new(key.as_str(), port_type));
+            }
+            else{
+                primary |= inner.is_primary();
+                reads_environment |= inner.reads_environment();
+            }
+        }
+        for promoted in definition.promoted_constants.iter(){
+            if promoted.node_index>=definition.graph.nodes.len(){
+                return Err(ExecutionError::OtherError(format!("Subgraph {}: constant {} refers to missing node", definition.name, promoted.name).into()));
+            }
+        }
+        Ok(Self { definition:Arc::new(definition), registry, inputs, outputs, primary, reads_environment })
+    }
+
+    fn calculate(&self,args:CalculationNodeArguments) -> Result<(),ExecutionError>{
+        let mut storage = CalculationSequenceStorage::new();
+        // Environment of outer graph is visible inside. Inner nodes writing it make subgraph primary, their writes are merged back below.
+        storage.environment = args.environment.clone();
+        storage.progress = args.progress.clone();
+        storage.fingerprint_salt = args.fingerprint;
+        for port in self.inputs.iter(){
+            let value = args.inputs.request_type(&port.port_type, &port.name)?;
+            storage.environment.0.insert(port.name.clone(), value);
+        }
+
+        self.definition.graph.make_compute_graph(&mut storage, &self.registry);
+        for promoted in self.definition.promoted_constants.iter(){
+            let value = args.constants.0.get(promoted.name.as_str()).cloned().unwrap_or(promoted.default_value.clone());
+            storage.edit_constants(promoted.node_index).0.insert(promoted.key.clone().into(), value);
+        }
+
+        storage.execute(args.rng.generate_new(), args.detectors)?;
+
+        for port in self.outputs.iter(){
+            let value = storage.environment.0.get(&port.name).cloned().ok_or(ExecutionError::NotConnected(port.name.clone()))?;
+            args.outputs.set_value(&port.name, value)?;
+        }
+        // Ports of subgraph are passed through environment of inner graph only
+        let ports:Vec<&RString> = self.inputs.iter().chain(self.outputs.iter()).map(|x| &x.name).collect();
+        for key in storage.written_environment().iter(){
+            if ports.contains(&key){
+                continue;
+            }
+            if let Some(value) = storage.environment.0.get(key){
+                args.environment.0.insert(key.clone(), value.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CalculationNode for SubgraphNode{
+    fn name(&self,) -> RString {
+        self.definition.name.clone().into()
+    }
+
+    fn category(&self,) -> RVec<RString> {
+        rvec!["Subgraphs".into()]
+    }
+
+    fn identifier(&self,) -> RString {
+        self.definition.identifier().into()
+    }
+
+    fn is_primary(&self,) -> bool {
+        self.primary
+    }
+
+    fn reads_environment(&self,) -> bool {
+        self.reads_environment
+    }
+
+    fn inputs(&self,) -> RVec<CalculationIO> {
+        self.inputs.clone()
+    }
+
+    fn outputs(&self,) -> RVec<CalculationIO> {
+        self.outputs.clone()
+    }
+
+    fn constants(&self,) -> RVec<CalculationConstant> {
+        self.definition.promoted_constants.iter()
+            .map(|x| CalculationConstant::new_named(&x.name, &x.display_name, x.default_value.clone()))
+            .collect()
+    }
+
+    fn calculate(&self,args:CalculationNodeArguments) -> abi_stable::std_types::RResult<(),ExecutionError> {
+        self.calculate(args).into()
+    }
+}
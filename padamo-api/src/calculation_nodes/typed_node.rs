@@ -0,0 +1,263 @@
+//! Typed description of node ports and constants.
+//!
+//! Instead of requesting values by port names, node declares structs with one field per port or constant
+//! and derives [`NodePorts`] and [`NodeConstants`] for them. `#[derive(CalculationNode)]` on node itself generates
+//! [`CalculationNode`](super::node::CalculationNode) implementation from [`TypedCalculationNode`].
+//!
+//! ```ignore
+//! #[derive(NodePorts)]
+//! pub struct Inputs{
+//!     #[port(name = "Signal")]
+//!     signal:LazyTriSignal,
+//! }
+//!
+//! #[derive(NodeConstants)]
+//! pub struct Constants{
+//!     #[constant(default = 100)]
+//!     window:i64,
+//! }
+//!
+//! #[derive(Clone,Debug,CalculationNode)]
+//! #[node(name = "Example", identifier = "example.node", category = ["Data Processing"])]
+//! pub struct ExampleNode;
+//!
+//! impl TypedCalculationNode for ExampleNode{
+//!     type Inputs = Inputs;
+//!     type Outputs = Inputs;
+//!     type Constants = Constants;
+//!
+//!     fn run(&self, inputs:Inputs, constants:Constants, _args:&mut CalculationNodeArguments)->Result<Inputs,ExecutionError>{
+//!         Ok(inputs)
+//!     }
+//! }
+//! ```
+use abi_stable::std_types::{RResult, RString, RVec};
+
+use super::content::{ConstantContent, ConstantContentContainer, ConstantContentType, Content, ContentContainer, ContentType};
+use super::errors::ExecutionError;
+use super::node::{CalculationConstant, CalculationIO, CalculationNodeArguments, IOData};
+use crate::function_operator::DoubleFunctionOperatorBox;
+use crate::lazy_array_operations::typed::TypedDetectorSignal;
+use crate::lazy_array_operations::{LazyDetectorSignal, LazyTimeSignal, LazyTriSignal};
+use crate::linalg::Mat4;
+
+/// Type that can be passed through node port
+pub trait PortValue: Sized{
+    const CONTENT_TYPE:ContentType;
+
+    fn from_content(content:&Content)->Result<Self,ExecutionError>;
+
+    fn into_content(self)->Content;
+}
+
+macro_rules! impl_port_value {
+    ($t:ty, $variant:ident) => {
+        impl PortValue for $t{
+            const CONTENT_TYPE:ContentType = ContentType::$variant;
+
+            fn from_content(content:&Content)->Result<Self,ExecutionError>{
+                if let Content::$variant(x) = content{
+                    Ok(x.clone())
+                }
+                else{
+                    Err(ExecutionError::TypeError)
+                }
+            }
+
+            fn into_content(self)->Content{
+                Content::$variant(self)
+            }
+        }
+    };
+}
+
+impl_port_value!(i64, Integer);
+impl_port_value!(f64, Float);
+impl_port_value!(bool, Boolean);
+impl_port_value!(RString, String);
+impl_port_value!(DoubleFunctionOperatorBox, Function);
+impl_port_value!(LazyDetectorSignal, DetectorSignal);
+impl_port_value!(LazyTriSignal, DetectorFullData);
+impl_port_value!(LazyTimeSignal, DetectorTime);
+impl_port_value!(Mat4, Matrix);
+impl_port_value!(TypedDetectorSignal, TypedSignal);
+
+/// Type that can be used as node constant
+pub trait ConstantValue: Sized{
+    const CONSTANT_TYPE:ConstantContentType;
+
+    fn from_constant(content:&ConstantContent)->Result<Self,ExecutionError>;
+
+    fn into_constant(self)->ConstantContent;
+}
+
+macro_rules! impl_constant_value {
+    ($t:ty, $variant:ident) => {
+        impl ConstantValue for $t{
+            const CONSTANT_TYPE:ConstantContentType = ConstantContentType::$variant;
+
+            fn from_constant(content:&ConstantContent)->Result<Self,ExecutionError>{
+                if let ConstantContent::$variant(x) = content{
+                    Ok(x.clone())
+                }
+                else{
+                    Err(ExecutionError::TypeError)
+                }
+            }
+
+            fn into_constant(self)->ConstantContent{
+                ConstantContent::$variant(self)
+            }
+        }
+    };
+}
+
+impl_constant_value!(i64, Integer);
+impl_constant_value!(f64, Float);
+impl_constant_value!(bool, Boolean);
+impl_constant_value!(RString, String);
+
+/// Set of node inputs or outputs. Usually derived with `#[derive(NodePorts)]`.
+pub trait NodePorts: Sized{
+    fn ports()->RVec<CalculationIO>;
+
+    /// Takes values of ports from node inputs
+    fn extract(inputs:&ContentContainer)->Result<Self,ExecutionError>;
+
+    /// Writes values of ports to node outputs
+    fn store(self, outputs:&mut IOData)->Result<(),ExecutionError>;
+}
+
+impl NodePorts for (){
+    fn ports()->RVec<CalculationIO>{
+        RVec::new()
+    }
+
+    fn extract(_inputs:&ContentContainer)->Result<Self,ExecutionError>{
+        Ok(())
+    }
+
+    fn store(self, _outputs:&mut IOData)->Result<(),ExecutionError>{
+        Ok(())
+    }
+}
+
+/// Set of node constants. Usually derived with `#[derive(NodeConstants)]`.
+pub trait NodeConstants: Sized{
+    fn constants()->RVec<CalculationConstant>;
+
+    fn extract(constants:&ConstantContentContainer)->Result<Self,ExecutionError>;
+}
+
+impl NodeConstants for (){
+    fn constants()->RVec<CalculationConstant>{
+        RVec::new()
+    }
+
+    fn extract(_constants:&ConstantContentContainer)->Result<Self,ExecutionError>{
+        Ok(())
+    }
+}
+
+/// Helper for derived code: value of port `name`
+pub fn request_port<T:PortValue>(inputs:&ContentContainer, name:&str)->Result<T,ExecutionError>{
+    let content = inputs.0.get(name).ok_or_else(|| ExecutionError::NotConnected(name.into()))?;
+    T::from_content(content)
+}
+
+/// Helper for derived code: value of constant `name`
+pub fn request_constant<T:ConstantValue>(constants:&ConstantContentContainer, name:&str)->Result<T,ExecutionError>{
+    let content = constants.0.get(name).ok_or(ExecutionError::ConstantMissing)?;
+    T::from_constant(content)
+}
+
+/// Node with typed inputs, outputs and constants. `#[derive(CalculationNode)]` implements node trait on top of it.
+pub trait TypedCalculationNode{
+    type Inputs:NodePorts;
+    type Outputs:NodePorts;
+    type Constants:NodeConstants;
+
+    /// Main calculation. `args` gives access to environment, detectors, random state and progress.
+    fn run(&self, inputs:Self::Inputs, constants:Self::Constants, args:&mut CalculationNodeArguments)->Result<Self::Outputs,ExecutionError>;
+}
+
+fn run_typed<N:TypedCalculationNode>(node:&N, mut args:CalculationNodeArguments)->Result<(),ExecutionError>{
+    let inputs = N::Inputs::extract(&args.inputs)?;
+    let constants = N::Constants::extract(&args.constants)?;
+    let outputs = node.run(inputs, constants, &mut args)?;
+    outputs.store(args.outputs)
+}
+
+/// Extracts typed arguments, runs node and stores its outputs
+pub fn calculate_typed<N:TypedCalculationNode>(node:&N, args:CalculationNodeArguments)->RResult<(),ExecutionError>{
+    run_typed(node, args).into()
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::prelude::{CalculationNode, NodeConstants, NodePorts};
+
+    #[derive(NodePorts)]
+    struct Ports{
+        #[port(name = "Value", display = "Input value")]
+        value:f64,
+        flag:bool,
+    }
+
+    #[derive(NodeConstants)]
+    struct Constants{
+        #[constant(default = 5)]
+        count:i64,
+        label:RString,
+    }
+
+    #[derive(Clone,Debug,CalculationNode)]
+    #[node(name = "Test node", identifier = "test.node", category = ["Tests"])]
+    struct TestNode;
+
+    impl TypedCalculationNode for TestNode{
+        type Inputs = Ports;
+        type Outputs = Ports;
+        type Constants = Constants;
+
+        fn run(&self, inputs:Ports, _constants:Constants, _args:&mut CalculationNodeArguments)->Result<Ports,ExecutionError>{
+            Ok(inputs)
+        }
+    }
+
+    #[test]
+    fn test_derived_node(){
+        let node = TestNode;
+        assert_eq!(node.name().as_str(), "Test node");
+        assert_eq!(node.identifier().as_str(), "test.node");
+        assert_eq!(node.category().len(), 1);
+        assert_eq!(node.inputs().len(), 2);
+        assert_eq!(node.constants().len(), 2);
+    }
+
+    #[test]
+    fn test_derived_ports(){
+        let ports = Ports::ports();
+        assert_eq!(ports.len(), 2);
+        assert_eq!(ports[0].name.as_str(), "Value");
+        assert_eq!(ports[0].display_name.as_str(), "Input value");
+        assert_eq!(ports[1].port_type, ContentType::Boolean);
+
+        let mut inputs = ContentContainer::new();
+        inputs.0.insert("Value".into(), Content::Float(2.5));
+        assert!(matches!(Ports::extract(&inputs), Err(ExecutionError::NotConnected(_))));
+        inputs.0.insert("flag".into(), Content::Boolean(true));
+        let ports = Ports::extract(&inputs).unwrap();
+        assert_eq!(ports.value, 2.5);
+        assert!(ports.flag);
+    }
+
+    #[test]
+    fn test_derived_constants(){
+        let container = ConstantContentContainer::from_rvec(Constants::constants());
+        let constants = Constants::extract(&container).unwrap();
+        assert_eq!(constants.count, 5);
+        assert_eq!(constants.label.as_str(), "");
+    }
+}
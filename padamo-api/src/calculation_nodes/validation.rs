@@ -0,0 +1,251 @@
+//! Static checks of compiled graph. Unlike execution, validation reports all problems at once.
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Display;
+
+use crate::CalculationNodeBox;
+use super::content::{ConstantContent, ConstantContentType};
+use super::immediate::CompiledGraph;
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum DiagnosticKind{
+    UnknownNode,
+    NotConnected(String),
+    UnknownInput(String),
+    UnknownOutput(String),
+    MissingLinkTarget(usize),
+    MissingConstant(String),
+    WrongConstantType{key:String, expected:ConstantContentType, found:ConstantContentType},
+    Cycle,
+}
+
+impl Display for DiagnosticKind{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownNode=>write!(f, "Node is not found in loaded plugins"),
+            Self::NotConnected(p)=>write!(f, "Input {} is not connected", p),
+            Self::UnknownInput(p)=>write!(f, "Input {} does not exist", p),
+            Self::UnknownOutput(p)=>write!(f, "Output {} does not exist", p),
+            Self::MissingLinkTarget(i)=>write!(f, "Output is linked to missing node #{}", i),
+            Self::MissingConstant(k)=>write!(f, "Constant {} is missing", k),
+            Self::WrongConstantType { key, expected, found }=>write!(f, "Constant {} has type {:?}, expected {:?}", key, found, expected),
+            Self::Cycle=>write!(f, "Node is a part of cycle"),
+        }
+    }
+}
+
+/// Problem found in one node of graph
+#[derive(Clone,Debug,PartialEq)]
+pub struct Diagnostic{
+    pub node_index:usize,
+    pub identifier:String,
+    pub kind:DiagnosticKind,
+}
+
+impl Diagnostic{
+    /// Input port related to problem if any
+    pub fn input_port(&self)->Option<&str>{
+        match &self.kind{
+            DiagnosticKind::NotConnected(p)|DiagnosticKind::UnknownInput(p)=>Some(p.as_str()),
+            _=>None
+        }
+    }
+}
+
+impl Display for Diagnostic{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Node #{} ({}): {}", self.node_index, self.identifier, self.kind)
+    }
+}
+
+pub fn constant_type(value:&ConstantContent)->ConstantContentType{
+    match value {
+        ConstantContent::Integer(_)=>ConstantContentType::Integer,
+        ConstantContent::Float(_)=>ConstantContentType::Float,
+        ConstantContent::Boolean(_)=>ConstantContentType::Boolean,
+        ConstantContent::String(_)=>ConstantContentType::String,
+    }
+}
+
+/// Checks graph against node registry.
+/// Unconnected inputs and cycles are reported only for nodes that would be executed, i.e. primary nodes and their dependencies.
+pub fn validate_graph(graph:&CompiledGraph, registry:&HashMap<String,CalculationNodeBox>)->Vec<Diagnostic>{
+    let mut res = Vec::new();
+    let count = graph.nodes.len();
+    let mut push = |node_index:usize, kind:DiagnosticKind|{
+        res.push(Diagnostic { node_index, identifier:graph.nodes[node_index].identifier.clone(), kind });
+    };
+
+    // Connected inputs of each node
+    let mut links:Vec<HashMap<String,usize>> = vec![HashMap::new(); count];
+    for (i,node) in graph.nodes.iter().enumerate(){
+        let calculator = registry.get(&node.identifier);
+        if calculator.is_none(){
+            push(i, DiagnosticKind::UnknownNode);
+        }
+        if let Some(calculator) = calculator{
+            for def in calculator.constants().iter(){
+                let expected = constant_type(&def.default_value);
+                if let Some(value) = node.constants.0.get(def.name.as_str()){
+                    if !value.is_compatible(&expected){
+                        push(i, DiagnosticKind::WrongConstantType { key: def.name.clone().into(), expected, found: constant_type(value) });
+                    }
+                }
+                else{
+                    push(i, DiagnosticKind::MissingConstant(def.name.clone().into()));
+                }
+            }
+            let outputs = calculator.outputs();
+            for link in node.links.iter(){
+                if !outputs.iter().any(|x| x.name.as_str()==link.output){
+                    push(i, DiagnosticKind::UnknownOutput(link.output.clone()));
+                }
+            }
+        }
+        for link in node.links.iter(){
+            if link.target_index>=count{
+                push(i, DiagnosticKind::MissingLinkTarget(link.target_index));
+            }
+            else{
+                links[link.target_index].insert(link.target_input.clone(), i);
+            }
+        }
+    }
+
+    // Inputs of external constants are optional: executor uses constant value if they are not connected
+    let required_inputs = |i:usize|->Option<Vec<String>>{
+        let calculator = registry.get(&graph.nodes[i].identifier)?;
+        Some(calculator.inputs().iter().filter(|x| !x.optional).map(|x| x.name.clone().into()).collect())
+    };
+
+    let known_inputs = |i:usize|->Option<Vec<String>>{
+        let calculator = registry.get(&graph.nodes[i].identifier)?;
+        let mut inputs:Vec<String> = calculator.inputs().iter().map(|x| x.name.clone().into()).collect();
+        for (key,external) in graph.nodes[i].externals.iter().map(|x| x.into_tuple()){
+            if *external{
+                inputs.push(format!("constant_{}",key));
+            }
+        }
+        Some(inputs)
+    };
+
+    for i in 0..count{
+        if let Some(inputs) = known_inputs(i){
+            for port in links[i].keys(){
+                if !inputs.contains(port){
+                    push(i, DiagnosticKind::UnknownInput(port.clone()));
+                }
+            }
+        }
+    }
+
+    // Nodes to be executed
+    let mut active:HashSet<usize> = HashSet::new();
+    let mut queue:VecDeque<usize> = graph.nodes.iter().enumerate()
+        .filter(|(_,n)| registry.get(&n.identifier).map(|x| x.is_primary()).unwrap_or(false))
+        .map(|(i,_)| i)
+        .collect();
+    while let Some(i) = queue.pop_front(){
+        if active.insert(i){
+            queue.extend(links[i].values());
+        }
+    }
+    let mut active:Vec<usize> = active.into_iter().collect();
+    active.sort();
+
+    for i in active.iter(){
+        if let Some(inputs) = required_inputs(*i){
+            for port in inputs.iter(){
+                if !links[*i].contains_key(port){
+                    push(*i, DiagnosticKind::NotConnected(port.clone()));
+                }
+            }
+        }
+    }
+
+    // Node is in cycle if it can be reached from itself
+    for i in active.iter(){
+        let mut visited:HashSet<usize> = HashSet::new();
+        let mut stack:Vec<usize> = links[*i].values().copied().collect();
+        while let Some(j) = stack.pop(){
+            if j==*i{
+                push(*i, DiagnosticKind::Cycle);
+                break;
+            }
+            if visited.insert(j){
+                stack.extend(links[j].values());
+            }
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests{
+    use abi_stable::std_types::{RHashMap, RResult, RString, RVec};
+    use crate::prelude::*;
+    use crate::calculation_nodes::immediate::{CompiledNode, SmallLink};
+    use crate::{constants, ports};
+    use super::*;
+
+    #[derive(Clone,Debug)]
+    struct Pass;
+
+    impl CalculationNode for Pass{
+        fn name(&self,) -> RString {
+            "Pass".into()
+        }
+        fn is_primary(&self,) -> bool {
+            true
+        }
+        fn inputs(&self,) -> RVec<CalculationIO> {
+            ports!(
+                ("Value", ContentType::Integer),
+                CalculationIO::new("Extra", ContentType::Integer).optional()
+            )
+        }
+        fn outputs(&self,) -> RVec<CalculationIO> {
+            ports!(("Value", ContentType::Integer))
+        }
+        fn constants(&self,) -> RVec<CalculationConstant> {
+            constants!(("Scale", 1))
+        }
+        fn calculate(&self,_:CalculationNodeArguments) -> RResult<(),ExecutionError> {
+            RResult::ROk(())
+        }
+    }
+
+    fn node(identifier:&str, scale:ConstantContent)->CompiledNode{
+        let mut constants = ConstantContentContainer::new();
+        constants.0.insert("Scale".into(), scale);
+        CompiledNode::new(constants, RHashMap::new(), identifier.into())
+    }
+
+    #[test]
+    fn test_all_problems_reported(){
+        let mut registry = HashMap::new();
+        registry.insert("pass".to_string(), crate::make_node_box(Pass));
+
+        let mut graph = CompiledGraph::new();
+        graph.nodes.push(node("pass", ConstantContent::Integer(1)));
+        graph.nodes.push(node("pass", ConstantContent::Integer(1)));
+        graph.nodes.push(node("pass", ConstantContent::Float(1.0)));
+        graph.nodes.push(node("missing", ConstantContent::Integer(1)));
+        graph.nodes.push(node("pass", ConstantContent::Integer(1)));
+        graph.nodes[4].externals.insert("Scale".into(), true);
+        graph.nodes[2].links.push(SmallLink { output: "Value".into(), target_index: 4, target_input: "Value".into() });
+        graph.nodes[0].links.push(SmallLink { output: "Value".into(), target_index: 1, target_input: "Value".into() });
+        graph.nodes[1].links.push(SmallLink { output: "Value".into(), target_index: 0, target_input: "Value".into() });
+
+        let diagnostics = validate_graph(&graph, &registry);
+        let kinds:Vec<_> = diagnostics.iter().map(|x| (x.node_index, x.kind.clone())).collect();
+        assert!(kinds.contains(&(0, DiagnosticKind::Cycle)));
+        assert!(kinds.contains(&(1, DiagnosticKind::Cycle)));
+        assert!(kinds.contains(&(2, DiagnosticKind::NotConnected("Value".into()))));
+        assert!(kinds.contains(&(2, DiagnosticKind::WrongConstantType { key: "Scale".into(), expected: ConstantContentType::Integer, found: ConstantContentType::Float })));
+        assert!(kinds.contains(&(3, DiagnosticKind::UnknownNode)));
+        // Unconnected external constant falls back to its value, unconnected optional input is absent
+        assert!(!kinds.iter().any(|x| x.0==4));
+        assert_eq!(diagnostics.len(), 5);
+    }
+}
@@ -0,0 +1,37 @@
+//! Nodes which connect graph with the viewer. They only write to the environment so they work without GUI as well.
+use abi_stable::std_types::RVec;
+use padamo_detectors::loaded_detectors_storage::DetectorEntry;
+use crate::prelude::{CalculationNodeArguments, CalculationNodeBox};
+
+pub mod viewer;
+pub mod viewer_smart;
+
+/// All viewer nodes
+pub fn nodes()->RVec<CalculationNodeBox>{
+    let mut res = viewer::nodes();
+    res.extend(viewer_smart::nodes());
+    res
+}
+
+pub fn find_detector<'a>(args:&'a CalculationNodeArguments, detector_name:&'a str) -> Option<&'a DetectorEntry>{
+    let mut detector = None;
+    for det in args.detectors.iter(){
+        if det.get_friendly_name()==detector_name{
+            detector = Some(det);
+            break;
+        }
+    }
+    detector
+}
+
+pub fn find_primary_detector<'a>(args:&'a CalculationNodeArguments) -> Option<&'a DetectorEntry>{
+    // let mut detector = None;
+    // for det in args.detectors.iter(){
+    //     if det.get_friendly_name()==detector_name{
+    //         detector = Some(det);
+    //         break;
+    //     }
+    // }
+    // detector
+    args.detectors.get(0)
+}
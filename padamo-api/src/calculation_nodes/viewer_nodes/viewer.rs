@@ -1,8 +1,9 @@
-use padamo_api::{constants, prelude::*};
+use crate::{constants, nodes_vec, prelude::*};
 use abi_stable::{rvec, std_types::{ROption::RSome, RString}};
-use padamo_api::ports;
+use crate::ports;
+use crate::lazy_array_operations::typed::TypedDetectorSignal;
 use abi_stable::std_types::RVec;
-use crate::detector_muxer::{get_signal_var, get_transform_var, VIEWER_TEST_OBJECT_KEY};
+use crate::calculation_nodes::detector_muxer::{get_signal_var, get_transform_var, VIEWER_TEST_OBJECT_KEY};
 
 #[derive(Clone,Debug)]
 pub struct ViewerNode;
@@ -114,10 +115,12 @@ impl ViewerMaskNode{
         )?;
 
         let mask = detector.mask.clone();
+        let typed_mask = TypedDetectorSignal::Bool(make_lao_box(mask.clone()));
         let mask = make_lao_box(mask.cast::<f64>());
 
         // let mask= args.environment.request_detectorsignal(&get_mask_var(0))?;
         args.outputs.set_value("Alive pixels".into(), mask.into())?;
+        args.outputs.set_value("Alive pixels mask".into(), typed_mask.into())?;
         Ok(())
     }
 }
@@ -141,7 +144,8 @@ impl CalculationNode for ViewerMaskNode{
 
     fn outputs(&self,) -> RVec<CalculationIO>where {
         ports!(
-            ("Alive pixels", ContentType::DetectorSignal)
+            ("Alive pixels", ContentType::DetectorSignal),
+            ("Alive pixels mask", ContentType::TypedSignal)
         )
     }
 
@@ -212,10 +216,12 @@ impl AuxViewerMaskNode{
         )?;
 
         let mask = detector.mask.clone();
+        let typed_mask = TypedDetectorSignal::Bool(make_lao_box(mask.clone()));
         let mask = make_lao_box(mask.cast::<f64>());
 
         // let mask = args.environment.request_detectorsignal(&get_mask_var(detector_id))?;
         args.outputs.set_value("Alive pixels".into(), mask.into())?;
+        args.outputs.set_value("Alive pixels mask".into(), typed_mask.into())?;
         Ok(())
     }
 }
@@ -235,7 +241,8 @@ impl CalculationNode for AuxViewerMaskNode{
 
     fn outputs(&self,) -> RVec<CalculationIO>where {
         ports!(
-            ("Alive pixels", ContentType::DetectorSignal)
+            ("Alive pixels", ContentType::DetectorSignal),
+            ("Alive pixels mask", ContentType::TypedSignal)
         )
     }
 
@@ -257,7 +264,7 @@ pub struct DetectorTransformNode;
 
 impl DetectorTransformNode{
     fn calculate(&self,args:CalculationNodeArguments) -> Result<(),ExecutionError>{
-        let signal = args.inputs.request_detectorsignal("Transform")?;
+        let signal = args.inputs.request_matrix("Transform")?;
         let detector_id = args.constants.request_integer("detector")?;
         let detector_id:usize = detector_id.try_into().map_err(ExecutionError::from_error)?;
         args.environment.0.insert(get_transform_var(detector_id).into(),signal.into());
@@ -280,7 +287,7 @@ impl CalculationNode for DetectorTransformNode{
 
     fn inputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Transform", ContentType::DetectorSignal)
+            ("Transform", ContentType::Matrix)
         ]
     }
 
@@ -304,7 +311,7 @@ pub struct TestObjectTransformNode;
 
 impl TestObjectTransformNode{
     fn calculate(&self,args:CalculationNodeArguments) -> Result<(),ExecutionError>{
-        let signal = args.inputs.request_detectorsignal("Transform")?;
+        let signal = args.inputs.request_matrix("Transform")?;
         args.environment.0.insert(VIEWER_TEST_OBJECT_KEY.into(), signal.into());
         Ok(())
     }
@@ -325,7 +332,7 @@ impl CalculationNode for TestObjectTransformNode{
 
     fn inputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Transform", ContentType::DetectorSignal)
+            ("Transform", ContentType::Matrix)
         ]
     }
 
@@ -339,13 +346,14 @@ impl CalculationNode for TestObjectTransformNode{
 }
 
 
-pub fn register_nodes(nodes:&mut crate::nodes_interconnect::NodesRegistry){
-    nodes.register_node(LoadedFileNode).unwrap();
-    nodes.register_node(ViewerNode).unwrap();
-    nodes.register_node(AuxViewerNode).unwrap();
-    nodes.register_node(AuxViewerMaskNode).unwrap();
-    nodes.register_node(ViewerMaskNode).unwrap();
-
-    nodes.register_node(DetectorTransformNode).unwrap();
-    nodes.register_node(TestObjectTransformNode).unwrap();
+pub fn nodes()->RVec<CalculationNodeBox>{
+    nodes_vec![
+        LoadedFileNode,
+        ViewerNode,
+        AuxViewerNode,
+        AuxViewerMaskNode,
+        ViewerMaskNode,
+        DetectorTransformNode,
+        TestObjectTransformNode,
+    ]
 }
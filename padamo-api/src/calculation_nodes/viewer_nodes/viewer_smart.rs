@@ -1,8 +1,9 @@
-use padamo_api::{constants, prelude::*};
+use crate::{constants, nodes_vec, prelude::*};
 use abi_stable::{rvec, std_types::RString};
-use padamo_api::ports;
+use crate::ports;
+use crate::lazy_array_operations::typed::TypedDetectorSignal;
 use abi_stable::std_types::RVec;
-use crate::detector_muxer::{get_signal_var_by_name, get_transform_var_by_name};
+use crate::calculation_nodes::detector_muxer::{get_signal_var_by_name, get_transform_var_by_name};
 
 #[derive(Clone,Debug)]
 pub struct SmartViewNode;
@@ -38,17 +39,19 @@ impl SmartMaskNode{
         )?;
 
         let mask = detector.mask.clone();
+        let typed_mask = TypedDetectorSignal::Bool(make_lao_box(mask.clone()));
         let mask = make_lao_box(mask.cast::<f64>());
 
         // let mask= args.environment.request_detectorsignal(&get_mask_var_by_name(&detector))?;
         args.outputs.set_value("Alive pixels", mask.into())?;
+        args.outputs.set_value("Alive pixels mask", typed_mask.into())?;
         Ok(())
     }
 }
 
 impl SmartTransformNode{
     fn calculate(&self,args:CalculationNodeArguments) -> Result<(),ExecutionError>{
-        let signal = args.inputs.request_detectorsignal("Transform")?;
+        let signal = args.inputs.request_matrix("Transform")?;
         let detector_id = args.constants.request_string("detector")?;
         args.environment.0.insert(get_transform_var_by_name(&detector_id).into(),signal.into());
         Ok(())
@@ -104,7 +107,8 @@ impl CalculationNode for SmartMaskNode{
 
     fn outputs(&self,) -> RVec<CalculationIO>where {
         ports!(
-            ("Alive pixels", ContentType::DetectorSignal)
+            ("Alive pixels", ContentType::DetectorSignal),
+            ("Alive pixels mask", ContentType::TypedSignal)
         )
     }
 
@@ -134,7 +138,7 @@ impl CalculationNode for SmartTransformNode{
 
     fn inputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Transform", ContentType::DetectorSignal)
+            ("Transform", ContentType::Matrix)
         ]
     }
 
@@ -153,8 +157,10 @@ impl CalculationNode for SmartTransformNode{
     }
 }
 
-pub fn register_nodes(nodes:&mut crate::nodes_interconnect::NodesRegistry){
-    nodes.register_node(SmartViewNode).unwrap();
-    nodes.register_node(SmartMaskNode).unwrap();
-    nodes.register_node(SmartTransformNode).unwrap();
+pub fn nodes()->RVec<CalculationNodeBox>{
+    nodes_vec![
+        SmartViewNode,
+        SmartMaskNode,
+        SmartTransformNode,
+    ]
 }
@@ -1,12 +1,20 @@
 use std::fmt::Debug;
 use abi_stable::sabi_trait::prelude::TD_Opaque;
+use abi_stable::std_types::RResult;
+use crate::calculation_nodes::errors::ExecutionError;
 #[allow(non_local_definitions)]
 pub mod traits{
-    use abi_stable::{sabi_trait, std_types::RBox};
+    use abi_stable::{sabi_trait, std_types::{RBox, RResult}};
+    use crate::calculation_nodes::errors::ExecutionError;
 
     #[sabi_trait]
     pub trait DoubleFunctionOperator: Clone+Debug+Sync+Send{
         fn calculate(&self, x:f64)->f64;
+
+        /// Same as `calculate` for functions which can fail, e.g. scripted ones.
+        fn try_calculate(&self, x:f64)->RResult<f64,ExecutionError>{
+            RResult::ROk(self.calculate(x))
+        }
     }
 
 
@@ -70,6 +78,10 @@ impl<T:Fn(f64)->f64+Send+Sync+Clone> DoubleFunctionOperator for MapOperator<T>{
         let x1 = self.parameter.calculate(x);
         (self.func)(x1)
     }
+
+    fn try_calculate(&self,x:f64)->RResult<f64,ExecutionError>{
+        self.parameter.try_calculate(x).map(|x1| (self.func)(x1))
+    }
 }
 
 impl<T:Fn(f64)->f64+Send+Sync+Clone> DoubleFunctionOperator for InvMapOperator<T>{
@@ -77,6 +89,10 @@ impl<T:Fn(f64)->f64+Send+Sync+Clone> DoubleFunctionOperator for InvMapOperator<T
         let x1 = (self.func)(x);
         self.parameter.calculate(x1)
     }
+
+    fn try_calculate(&self,x:f64)->RResult<f64,ExecutionError>{
+        self.parameter.try_calculate((self.func)(x))
+    }
 }
 
 impl <T:Fn(f64,f64)->f64+Send+Sync+Clone> DoubleFunctionOperator for Map2Operator<T>{
@@ -85,6 +101,11 @@ impl <T:Fn(f64,f64)->f64+Send+Sync+Clone> DoubleFunctionOperator for Map2Operato
         let x2 = self.parameter2.calculate(x);
         (self.func)(x1,x2)
     }
+
+    fn try_calculate(&self,x:f64)->RResult<f64,ExecutionError>{
+        self.parameter1.try_calculate(x)
+            .and_then(|x1| self.parameter2.try_calculate(x).map(|x2| (self.func)(x1,x2)))
+    }
 }
 
 impl <T:Fn(f64)->f64+Send+Sync+Clone> DoubleFunctionOperator for WrappedDoubleFunction<T> {
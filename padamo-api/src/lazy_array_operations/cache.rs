@@ -1,22 +1,289 @@
+//! Chunk aligned LRU cache of lazy operations.
+//! Cached data is stored in chunks of fixed number of frames, so several distant ranges of one signal can stay in memory at once.
+//! All caches of module share one pool of chunks. Memory budget and statistics are shared by whole process, see [`share_cache`].
+//! Every plugin has its own pool, so budget keeps entry points of all pools and evicts least recently used chunk among them.
 use super::{LazyArrayOperation,LazyArrayOperationBox};
-use std::{fmt::Debug, sync::{Arc, Mutex}};
+use crate::calculation_nodes::errors::ExecutionError;
+use abi_stable::external_types::RMutex;
+use abi_stable::std_types::{RArc, ROption, RResult, RVec};
+use abi_stable::{sabi_extern_fn, StableAbi};
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Display};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
 
+/// Default number of frames in one cache chunk
+pub const CACHE_CHUNK:usize = 256;
 
-#[derive(Debug,Clone)]
-pub struct CacheContent<T>
-where
-    T:Cache
-{
-    pub cache:Option<T>,
-    pub last_bounds:(usize,usize)
+/// Budget used until application sets its own
+const DEFAULT_BUDGET:usize = 1<<30;
+
+/// Entry points to chunk pool of one module
+#[repr(C)]
+#[derive(StableAbi,Clone,Copy,Debug)]
+struct PoolHandle{
+    /// Access tick of oldest chunk of pool
+    oldest:extern "C" fn()->ROption<u64>,
+    /// Removes oldest chunk of pool. Returns false if pool is empty.
+    evict_oldest:extern "C" fn()->bool,
+}
+
+impl PoolHandle{
+    fn is_same(&self, other:&Self)->bool{
+        self.evict_oldest as usize==other.evict_oldest as usize
+    }
+}
+
+#[repr(C)]
+#[derive(StableAbi)]
+struct CacheBudgetState{
+    budget:AtomicUsize,
+    used:AtomicUsize,
+    hits:AtomicUsize,
+    misses:AtomicUsize,
+    evictions:AtomicUsize,
+    /// Clock of chunk accesses common for all pools
+    tick:AtomicU64,
+    pools:RMutex<RVec<PoolHandle>>,
+}
+
+/// Memory budget of caches in bytes together with usage statistics. Clones refer to the same state.
+#[repr(C)]
+#[derive(StableAbi,Clone)]
+pub struct CacheBudget(RArc<CacheBudgetState>);
+
+impl Debug for CacheBudget{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CacheBudget").field(&self.stats()).finish()
+    }
+}
+
+#[derive(Clone,Copy,Debug,Default,PartialEq)]
+pub struct CacheStats{
+    pub budget:usize,
+    pub used:usize,
+    pub hits:usize,
+    pub misses:usize,
+    pub evictions:usize,
+}
+
+impl CacheStats{
+    /// Fraction of chunk requests served from cache
+    pub fn hit_rate(&self)->Option<f64>{
+        let total = self.hits+self.misses;
+        if total==0{
+            None
+        }
+        else{
+            Some(self.hits as f64/total as f64)
+        }
+    }
 }
 
-impl<T:Cache> CacheContent<T>{
-    pub fn new()->Self{
-        Self{cache:None, last_bounds:(0,0)}
+impl Display for CacheStats{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const MB:f64 = 1024.0*1024.0;
+        write!(f, "Cache: {:.1}/{:.1} MB used, {} hits, {} misses, {} evictions",
+            self.used as f64/MB, self.budget as f64/MB, self.hits, self.misses, self.evictions)?;
+        if let Some(rate) = self.hit_rate(){
+            write!(f, " (hit rate {:.1}%)", rate*100.0)?;
+        }
+        Ok(())
     }
 }
 
+impl CacheBudget{
+    pub fn new(budget:usize)->Self{
+        Self(RArc::new(CacheBudgetState {
+            budget: AtomicUsize::new(budget),
+            used: AtomicUsize::new(0),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+            evictions: AtomicUsize::new(0),
+            tick: AtomicU64::new(0),
+            pools: RMutex::new(RVec::new()),
+        }))
+    }
+
+    /// Sets budget in bytes. Caches shrink on next insertion.
+    pub fn set_budget(&self, budget:usize){
+        self.0.budget.store(budget, Ordering::Relaxed);
+    }
+
+    pub fn budget(&self)->usize{
+        self.0.budget.load(Ordering::Relaxed)
+    }
+
+    pub fn stats(&self)->CacheStats{
+        CacheStats {
+            budget: self.budget(),
+            used: self.0.used.load(Ordering::Relaxed),
+            hits: self.0.hits.load(Ordering::Relaxed),
+            misses: self.0.misses.load(Ordering::Relaxed),
+            evictions: self.0.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset_stats(&self){
+        self.0.hits.store(0, Ordering::Relaxed);
+        self.0.misses.store(0, Ordering::Relaxed);
+        self.0.evictions.store(0, Ordering::Relaxed);
+    }
+
+    fn is_exceeded(&self)->bool{
+        self.0.used.load(Ordering::Relaxed)>self.budget()
+    }
+
+    fn next_tick(&self)->u64{
+        self.0.tick.fetch_add(1, Ordering::Relaxed)+1
+    }
+
+    fn register_pool(&self, handle:PoolHandle){
+        let mut pools = self.0.pools.lock();
+        if !pools.iter().any(|x| x.is_same(&handle)){
+            pools.push(handle);
+        }
+    }
+
+    fn unregister_pool(&self, handle:&PoolHandle){
+        self.0.pools.lock().retain(|x| !x.is_same(handle));
+    }
+
+    /// Evicts least recently used chunks of all pools until budget is met.
+    /// Must be called without lock of any pool held, as pools of other modules are locked to evict their chunks.
+    fn shrink(&self){
+        while self.is_exceeded(){
+            let pools = self.0.pools.lock().clone();
+            let oldest = pools.iter()
+                .filter_map(|x| Option::from((x.oldest)()).map(|tick:u64| (tick,*x)))
+                .min_by_key(|x| x.0);
+            let Some((_,pool)) = oldest else {break;};
+            if !(pool.evict_oldest)(){
+                break;
+            }
+        }
+    }
+}
+
+static BUDGET:LazyLock<RwLock<CacheBudget>> = LazyLock::new(||{
+    let budget = CacheBudget::new(DEFAULT_BUDGET);
+    budget.register_pool(POOL_HANDLE);
+    RwLock::new(budget)
+});
+static POOL:LazyLock<Mutex<CachePool>> = LazyLock::new(|| Mutex::new(CachePool::default()));
+static NEXT_CACHE_ID:AtomicU64 = AtomicU64::new(0);
+
+const POOL_HANDLE:PoolHandle = PoolHandle{oldest:pool_oldest, evict_oldest:pool_evict_oldest};
+
+#[sabi_extern_fn]
+fn pool_oldest()->ROption<u64>{
+    POOL.lock().unwrap().lru.keys().next().copied().into()
+}
+
+#[sabi_extern_fn]
+fn pool_evict_oldest()->bool{
+    let budget = cache_budget();
+    let mut pool = POOL.lock().unwrap();
+    let Some(oldest) = pool.lru.values().next().copied() else {return false;};
+    pool.remove(oldest, &budget);
+    budget.0.evictions.fetch_add(1, Ordering::Relaxed);
+    true
+}
+
+/// Budget used by caches of this module
+pub fn cache_budget()->CacheBudget{
+    BUDGET.read().unwrap().clone()
+}
+
+/// Makes caches of this module use `budget`.
+/// Each plugin links its own copy of this crate, so application passes its budget to every plugin through [`crate::PadamoModule`].
+#[sabi_extern_fn]
+pub fn share_cache(budget:CacheBudget){
+    let previous = {
+        let pool = POOL.lock().unwrap();
+        let mut current = BUDGET.write().unwrap();
+        // Chunks already stored are moved to new budget
+        current.0.used.fetch_sub(pool.used, Ordering::Relaxed);
+        budget.0.used.fetch_add(pool.used, Ordering::Relaxed);
+        std::mem::replace(&mut *current, budget.clone())
+    };
+    previous.unregister_pool(&POOL_HANDLE);
+    budget.register_pool(POOL_HANDLE);
+    budget.shrink();
+}
+
+type ChunkKey = (u64,usize);
+
+struct PoolEntry{
+    data:Arc<dyn Any+Send+Sync>,
+    size:usize,
+    tick:u64,
+}
+
+#[derive(Default)]
+struct CachePool{
+    entries:HashMap<ChunkKey,PoolEntry>,
+    /// Keys ordered by last access
+    lru:BTreeMap<u64,ChunkKey>,
+    used:usize,
+}
+
+impl CachePool{
+    fn get(&mut self, key:ChunkKey, budget:&CacheBudget)->Option<Arc<dyn Any+Send+Sync>>{
+        let tick = budget.next_tick();
+        let entry = self.entries.get_mut(&key)?;
+        self.lru.remove(&entry.tick);
+        entry.tick = tick;
+        self.lru.insert(tick, key);
+        Some(entry.data.clone())
+    }
+
+    fn remove(&mut self, key:ChunkKey, budget:&CacheBudget){
+        if let Some(entry) = self.entries.remove(&key){
+            self.lru.remove(&entry.tick);
+            self.used -= entry.size;
+            budget.0.used.fetch_sub(entry.size, Ordering::Relaxed);
+        }
+    }
+
+    /// Stores chunk. Budget is not enforced here, call [`CacheBudget::shrink`] after pool is unlocked.
+    fn insert(&mut self, key:ChunkKey, data:Arc<dyn Any+Send+Sync>, size:usize, budget:&CacheBudget){
+        if size>budget.budget(){
+            return;
+        }
+        self.remove(key, budget);
+        let tick = budget.next_tick();
+        self.entries.insert(key, PoolEntry { data, size, tick });
+        self.lru.insert(tick, key);
+        self.used += size;
+        budget.0.used.fetch_add(size, Ordering::Relaxed);
+    }
+
+    fn remove_cache(&mut self, id:u64, budget:&CacheBudget){
+        let keys:Vec<ChunkKey> = self.entries.keys().filter(|x| x.0==id).copied().collect();
+        for key in keys{
+            self.remove(key, budget);
+        }
+    }
+}
+
+/// Identifier of cache in pool. Chunks of cache are dropped with the last clone of cache.
+#[derive(Debug)]
+struct CacheId(u64);
+
+impl CacheId{
+    fn new()->Self{
+        Self(NEXT_CACHE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Drop for CacheId{
+    fn drop(&mut self) {
+        let budget = cache_budget();
+        POOL.lock().unwrap().remove_cache(self.0, &budget);
+    }
+}
 
 #[derive(Debug,Clone)]
 pub struct LazyArrayOperationLocalCache<T>
@@ -24,80 +291,111 @@ where
     T:Cache
 {
     src:LazyArrayOperationBox<T>,
-    cache:Arc<Mutex<CacheContent<T>>>,
-    // last_bounds:Arc<Mutex<(usize,usize)>>,
+    id:Arc<CacheId>,
+    chunk:usize,
 }
 
 impl<T:Cache> LazyArrayOperationLocalCache<T>{
     pub fn new(src:LazyArrayOperationBox<T>)->Self{
-        Self{src, cache:Arc::new(Mutex::new(CacheContent::new()))}
+        Self::with_chunk(src, CACHE_CHUNK)
     }
-}
 
-impl<T:Cache+Clone+Debug+Send+Sync> LazyArrayOperation<T> for LazyArrayOperationLocalCache<T>{
-    #[allow(clippy::let_and_return)]
-    fn length(&self,) -> usize where {
-        self.src.length()
+    pub fn with_chunk(src:LazyArrayOperationBox<T>, chunk:usize)->Self{
+        Self{src, id:Arc::new(CacheId::new()), chunk:chunk.max(1)}
     }
+}
 
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        (end-start)+self.src.calculate_overhead(start,end)
+impl<T:Cache+Clone+Debug+Send+Sync+'static> LazyArrayOperationLocalCache<T>{
+    fn chunk_bounds(&self, index:usize)->(usize,usize){
+        let start = index*self.chunk;
+        (start, (start+self.chunk).min(self.src.length()))
     }
 
-    #[allow(clippy::let_and_return)]
-    fn request_range(&self,start:usize,end:usize,) -> T where {
-        let mut cache_mut = self.cache.lock().unwrap();
-
-
-        //let mut last_bounds = self.last_bounds.borrow_mut();
-        if let Some(v) = cache_mut.cache.take(){
-            let (old_start,old_end) = cache_mut.last_bounds;
-            cache_mut.last_bounds = (start,end);
-            let mut res = v;
-            //println!("CACHE REQUEST {}-{} ({}-{})",start,end,old_start,old_end);
+    fn try_request(&self,start:usize,end:usize) -> Result<T,ExecutionError>{
+        if start>=end{
+            return self.src.try_request_range(start,end).into_result();
+        }
+        check_range(start, end, self.src.length())?;
+        let first = start/self.chunk;
+        let last = (end-1)/self.chunk;
+        let budget = cache_budget();
 
-            if start>=old_end || end<=old_start{
-                let data = self.src.request_range(start,end);
-                cache_mut.cache = Some(data.clone());
-                cache_mut.last_bounds = (start,end);
+        let mut chunks:Vec<Option<Arc<T>>> = {
+            let mut pool = POOL.lock().unwrap();
+            (first..=last).map(|i| pool.get((self.id.0,i), &budget).and_then(|x| x.downcast::<T>().ok())).collect()
+        };
+        let hits = chunks.iter().filter(|x| x.is_some()).count();
+        budget.0.hits.fetch_add(hits, Ordering::Relaxed);
+        budget.0.misses.fetch_add(chunks.len()-hits, Ordering::Relaxed);
 
-                data
+        // Every run of missing chunks is requested at once. Pool is not locked meanwhile as source may use cache too.
+        let mut i = 0;
+        while i<chunks.len(){
+            if chunks[i].is_some(){
+                i += 1;
+                continue;
             }
-            else{
-
-                if start>=old_start{
-                    //println!("CUTLEFT {}",start-old_start);
-                    res = res.cut_front(start-old_start);
-                }
-                else{
-                    //println!("ADDLEFT {}-{}",start,old_start);
-                    let part = self.src.request_range(start,old_start);
-                    res = res.prepend(part);
-                }
-
-                if end<=old_end{
-                    //println!("CUTRIGHT {}",old_end-end);
-                    res = res.cut_end(old_end-end);
+            let mut j = i;
+            while j<chunks.len() && chunks[j].is_none(){
+                j += 1;
+            }
+            let run_start = self.chunk_bounds(first+i).0;
+            let run_end = self.chunk_bounds(first+j-1).1;
+            let mut data = self.src.try_request_range(run_start,run_end).into_result()?;
+            let mut pieces = Vec::with_capacity(j-i);
+            for k in i..j{
+                let (chunk_start,chunk_end) = self.chunk_bounds(first+k);
+                if k+1<j{
+                    let (piece,rest) = data.split_front(chunk_end-chunk_start);
+                    pieces.push(piece);
+                    data = rest;
                 }
                 else{
-                    //println!("ADDRIGHT {}-{}",old_end,end);
-                    let part = self.src.request_range(old_end,end);
-                    res = res.append(part);
+                    pieces.push(data);
+                    break;
                 }
+            }
 
-                res
+            let mut pool = POOL.lock().unwrap();
+            for (k,piece) in (i..j).zip(pieces){
+                let size = piece.memory_size();
+                let piece = Arc::new(piece);
+                pool.insert((self.id.0,first+k), piece.clone(), size, &budget);
+                chunks[k] = Some(piece);
             }
+            drop(pool);
+            budget.shrink();
+            i = j;
         }
-        else {
-            let data = self.src.request_range(start,end);
-            cache_mut.cache = Some(data.clone());
-            cache_mut.last_bounds = (start,end);
-            //println!("CACHE REQUEST {}-{}",start,end);
-            data
+
+        let mut res = None;
+        for (k,chunk) in chunks.iter().flatten().enumerate(){
+            let (chunk_start,chunk_end) = self.chunk_bounds(first+k);
+            append_range(&mut res, chunk.as_ref(), start.max(chunk_start)-chunk_start, end.min(chunk_end)-chunk_start);
         }
+        Ok(res.expect("At least one chunk is requested"))
     }
 }
 
+impl<T:Cache+Clone+Debug+Send+Sync+'static> LazyArrayOperation<T> for LazyArrayOperationLocalCache<T>{
+    #[allow(clippy::let_and_return)]
+    fn length(&self,) -> usize where {
+        self.src.length()
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        (end-start)+self.src.calculate_overhead(start,end)
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn request_range(&self,start:usize,end:usize,) -> T where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<T,ExecutionError> where {
+        self.try_request(start,end).into()
+    }
+}
 
 
 pub trait Cache{
@@ -105,9 +403,39 @@ pub trait Cache{
     fn cut_end(self,count:usize)->Self;
     fn prepend(self,data:Self)->Self;
     fn append(self,data:Self)->Self;
+
+    /// Splits data into first `count` frames and the rest
+    fn split_front(self,count:usize)->(Self,Self) where Self:Sized;
+
+    /// Copy of frames `[start, end)`
+    fn copy_range(&self,start:usize,end:usize)->Self where Self:Sized;
+
+    /// Appends copy of frames `[start, end)` of `data`
+    fn extend_range(&mut self,data:&Self,start:usize,end:usize);
+
+    /// Approximate size of data in bytes
+    fn memory_size(&self)->usize;
 }
 
-impl<T> Cache for Vec<T>{
+/// Chunked caches cannot serve frames past the end of source
+pub(crate) fn check_range(start:usize, end:usize, length:usize)->Result<(),ExecutionError>{
+    if end>length{
+        Err(ExecutionError::OtherError(format!("Requested range {}..{} is out of length {}", start, end, length).into()))
+    }
+    else{
+        Ok(())
+    }
+}
+
+/// Copies frames `[start, end)` of chunk to the end of `res`. Chunks are never merged whole, so only requested frames are copied.
+pub(crate) fn append_range<T:Cache>(res:&mut Option<T>, chunk:&T, start:usize, end:usize){
+    match res {
+        Some(r)=>r.extend_range(chunk, start, end),
+        None=>*res = Some(chunk.copy_range(start, end)),
+    }
+}
+
+impl<T:Clone> Cache for Vec<T>{
     fn cut_front(self,count:usize)->Self {
         let mut res = self;
         res.drain(..count);
@@ -132,5 +460,82 @@ impl<T> Cache for Vec<T>{
         res.extend(data);
         res
     }
+
+    fn split_front(self,count:usize)->(Self,Self) {
+        let mut head = self;
+        let tail = head.split_off(count);
+        (head,tail)
+    }
+
+    fn copy_range(&self,start:usize,end:usize)->Self {
+        self[start..end].to_vec()
+    }
+
+    fn extend_range(&mut self,data:&Self,start:usize,end:usize) {
+        self.extend_from_slice(&data[start..end]);
+    }
+
+    fn memory_size(&self)->usize {
+        self.len()*std::mem::size_of::<T>()
+    }
 }
 
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::lazy_array_operations::testing::Counting;
+
+    #[test]
+    fn test_chunks_are_reused(){
+        let source = Counting::new(20);
+        let cache = LazyArrayOperationLocalCache::with_chunk(source.signal(), 4);
+
+        let data = cache.request_range(1, 6);
+        assert_eq!(data.flat_data.as_slice(), &[1.0,2.0,3.0,4.0,5.0]);
+        let data = cache.request_range(17, 20);
+        assert_eq!(data.flat_data.as_slice(), &[17.0,18.0,19.0]);
+        assert_eq!(source.calls(), 2);
+
+        // Both ranges stay in cache
+        let data = cache.request_range(2, 8);
+        assert_eq!(data.flat_data.as_slice(), &[2.0,3.0,4.0,5.0,6.0,7.0]);
+        cache.request_range(16, 18);
+        assert_eq!(source.calls(), 2);
+
+        // Only missing chunks are requested
+        let data = cache.request_range(6, 13);
+        assert_eq!(data.shape.as_slice(), &[7]);
+        assert_eq!(data.flat_data[6], 12.0);
+        assert_eq!(source.calls(), 3);
+    }
+
+    #[test]
+    fn test_range_past_end(){
+        let cache = LazyArrayOperationLocalCache::with_chunk(Counting::new(10).signal(), 4);
+        assert!(cache.try_request_range(8, 12).is_err());
+    }
+
+    /// Pool of another plugin: chunks of 50 bytes older than any chunk of this module
+    static OTHER_BUDGET:LazyLock<CacheBudget> = LazyLock::new(|| CacheBudget::new(60));
+    static OTHER_CHUNKS:AtomicUsize = AtomicUsize::new(3);
+
+    extern "C" fn other_oldest()->ROption<u64>{
+        if OTHER_CHUNKS.load(Ordering::SeqCst)>0 {ROption::RSome(0)} else {ROption::RNone}
+    }
+
+    extern "C" fn other_evict_oldest()->bool{
+        OTHER_CHUNKS.fetch_sub(1, Ordering::SeqCst);
+        OTHER_BUDGET.0.used.fetch_sub(50, Ordering::SeqCst);
+        true
+    }
+
+    #[test]
+    fn test_other_pools_are_evicted(){
+        let budget = &*OTHER_BUDGET;
+        budget.0.used.fetch_add(150, Ordering::SeqCst);
+        budget.register_pool(PoolHandle { oldest: other_oldest, evict_oldest: other_evict_oldest });
+        budget.shrink();
+        assert_eq!(OTHER_CHUNKS.load(Ordering::SeqCst), 1);
+        assert_eq!(budget.stats().used, 50);
+    }
+}
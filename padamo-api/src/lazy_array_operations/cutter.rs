@@ -1,6 +1,9 @@
 
 use std::fmt::Debug;
 
+use abi_stable::std_types::RResult;
+
+use crate::calculation_nodes::errors::ExecutionError;
 use super::LazyArrayOperationBox;
 use super::LazyArrayOperation;
 
@@ -70,4 +73,8 @@ where
     fn request_range(&self,start:usize,end:usize,) -> T{
         self.source.request_range(self.start+start,self.start+end)
     }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<T,ExecutionError>{
+        self.source.try_request_range(self.start+start,self.start+end)
+    }
 }
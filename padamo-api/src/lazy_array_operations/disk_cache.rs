@@ -0,0 +1,373 @@
+//! Cache of lazy operations stored on disk, so expensive results survive application restart.
+//! Each cached output gets its own directory named after fingerprint of node producing it (see [`crate::CalculationNodeArguments`]).
+//! Files of all caches share one root directory with common size limit. Least recently used files are removed first.
+use super::cache::{append_range, check_range, Cache};
+use super::{ArrayND, LazyArrayOperation, LazyArrayOperationBox};
+use crate::calculation_nodes::errors::ExecutionError;
+use crate::calculation_nodes::progress::{write_file_atomically, PARTIAL_EXTENSION};
+use crate::trigger_operations::sparse_event_storage::SparseTag;
+use crate::trigger_operations::SparseTagArray;
+use abi_stable::std_types::{RResult, RVec};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+
+/// Estimated size of cache roots. Root is scanned only when its estimate exceeds limit, not after every write.
+static USAGE:LazyLock<Mutex<HashMap<PathBuf,u64>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Data that can be stored in disk cache
+pub trait DiskChunk:Sized{
+    fn write_chunk(&self, writer:&mut dyn Write)->std::io::Result<()>;
+    fn read_chunk(reader:&mut dyn Read)->std::io::Result<Self>;
+}
+
+fn write_usize(writer:&mut dyn Write, value:usize)->std::io::Result<()>{
+    writer.write_all(&(value as u64).to_le_bytes())
+}
+
+fn read_usize(reader:&mut dyn Read)->std::io::Result<usize>{
+    let mut buf = [0u8;8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf) as usize)
+}
+
+impl DiskChunk for ArrayND<f64>{
+    fn write_chunk(&self, writer:&mut dyn Write)->std::io::Result<()> {
+        write_usize(writer, self.shape.len())?;
+        for dim in self.shape.iter(){
+            write_usize(writer, *dim)?;
+        }
+        for value in self.flat_data.iter(){
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn read_chunk(reader:&mut dyn Read)->std::io::Result<Self> {
+        let ndim = read_usize(reader)?;
+        let shape = (0..ndim).map(|_| read_usize(reader)).collect::<std::io::Result<RVec<usize>>>()?;
+        let size:usize = shape.iter().product();
+        let mut flat_data = RVec::with_capacity(size);
+        let mut buf = [0u8;8];
+        for _ in 0..size{
+            reader.read_exact(&mut buf)?;
+            flat_data.push(f64::from_le_bytes(buf));
+        }
+        Ok(ArrayND { flat_data, shape })
+    }
+}
+
+impl DiskChunk for SparseTagArray{
+    fn write_chunk(&self, writer:&mut dyn Write)->std::io::Result<()> {
+        write_usize(writer, self.tags.len())?;
+        for tag in self.tags.iter(){
+            write_usize(writer, tag.position)?;
+            write_usize(writer, tag.duration)?;
+            write_usize(writer, tag.tag.len())?;
+            writer.write_all(tag.tag.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn read_chunk(reader:&mut dyn Read)->std::io::Result<Self> {
+        let count = read_usize(reader)?;
+        let mut res = SparseTagArray::with_capacity(count);
+        for _ in 0..count{
+            let position = read_usize(reader)?;
+            let duration = read_usize(reader)?;
+            let mut tag = vec![0u8; read_usize(reader)?];
+            reader.read_exact(&mut tag)?;
+            let tag = String::from_utf8(tag).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            res.tags.push(SparseTag::new(tag.into(), position, duration));
+        }
+        Ok(res)
+    }
+}
+
+/// Total size of files in directory in bytes
+pub fn disk_usage(root:&Path)->u64{
+    list_files(root).iter().map(|x| x.1).sum()
+}
+
+/// Removes least recently used files of directory until their total size fits `limit`.
+pub fn shrink_dir(root:&Path, limit:u64){
+    let mut usage = USAGE.lock().unwrap();
+    usage.insert(root.to_path_buf(), shrink_files(root, limit));
+}
+
+/// Returns size of files left
+fn shrink_files(root:&Path, limit:u64)->u64{
+    let mut files = list_files(root);
+    let mut total:u64 = files.iter().map(|x| x.1).sum();
+    if total<=limit{
+        return total;
+    }
+    files.sort_by_key(|x| x.2);
+    for (path,size,_) in files{
+        if total<=limit{
+            break;
+        }
+        if std::fs::remove_file(&path).is_ok(){
+            total -= size;
+            if let Some(parent) = path.parent(){
+                // Fails if directory still has files
+                let _ = std::fs::remove_dir(parent);
+            }
+        }
+    }
+    total
+}
+
+fn list_files(root:&Path)->Vec<(PathBuf,u64,SystemTime)>{
+    let mut res = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop(){
+        let Ok(entries) = std::fs::read_dir(&dir) else {continue;};
+        for entry in entries.flatten(){
+            let Ok(meta) = entry.metadata() else {continue;};
+            if meta.is_dir(){
+                dirs.push(entry.path());
+            }
+            // Files being written belong to their writers
+            else if entry.path().extension().is_some_and(|x| x==PARTIAL_EXTENSION){
+                continue;
+            }
+            else{
+                res.push((entry.path(), meta.len(), meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)));
+            }
+        }
+    }
+    res
+}
+
+/// Directory of one cached output
+#[derive(Clone,Debug)]
+pub struct DiskCacheDir{
+    root:PathBuf,
+    dir:PathBuf,
+    limit:u64,
+}
+
+impl DiskCacheDir{
+    /// Directory `name` of output of node with given `fingerprint` inside `root`. Whole `root` is kept within `limit` bytes.
+    pub fn new(root:&Path, fingerprint:u64, name:&str, limit:u64)->Self{
+        let dir = root.join(format!("{:016x}",fingerprint)).join(name);
+        Self { root:root.to_path_buf(), dir, limit }
+    }
+
+    /// Reads file if it exists. Unreadable file is removed and treated as missing.
+    fn load<T:DiskChunk>(&self, file:&str)->Option<T>{
+        let path = self.dir.join(file);
+        let reader = std::fs::File::open(&path).ok()?;
+        match T::read_chunk(&mut BufReader::new(reader)){
+            Ok(v)=>{
+                // Modification time marks last use
+                if let Ok(f) = std::fs::File::options().write(true).open(&path){
+                    let _ = f.set_modified(SystemTime::now());
+                }
+                Some(v)
+            }
+            Err(_)=>{
+                let _ = std::fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// Writes file and keeps root within limit. Failure is reported to requester, so broken cache directory does not go unnoticed.
+    fn store<T:DiskChunk>(&self, file:&str, data:&T)->Result<(),ExecutionError>{
+        let path = self.dir.join(file);
+        let res = std::fs::create_dir_all(&self.dir).map_err(ExecutionError::from_error).and_then(|_|{
+            write_file_atomically(&path, |tmp|{
+                let mut writer = BufWriter::new(std::fs::File::create(tmp).map_err(ExecutionError::from_error)?);
+                data.write_chunk(&mut writer).map_err(ExecutionError::from_error)?;
+                writer.flush().map_err(ExecutionError::from_error)
+            })
+        });
+        match res {
+            Ok(())=>{
+                self.account(std::fs::metadata(&path).map(|x| x.len()).unwrap_or(0));
+                Ok(())
+            }
+            // Another thread stored the same file meanwhile
+            Err(_) if path.exists()=>Ok(()),
+            Err(e)=>Err(ExecutionError::OtherError(format!("Cannot write disk cache {}: {}", path.display(), e).into())),
+        }
+    }
+
+    /// Adds `written` bytes to usage estimate of root and shrinks root if estimate exceeds limit
+    fn account(&self, written:u64){
+        let mut usage = USAGE.lock().unwrap();
+        let used = match usage.entry(self.root.clone()) {
+            Entry::Occupied(e)=>{
+                let used = e.into_mut();
+                *used += written;
+                used
+            }
+            // Scan already counts written file
+            Entry::Vacant(e)=>e.insert(disk_usage(&self.root)),
+        };
+        if *used>self.limit{
+            *used = shrink_files(&self.root, self.limit);
+        }
+    }
+}
+
+/// Disk cache of data that can be split by frames. Data is stored in chunks of fixed length.
+#[derive(Clone,Debug)]
+pub struct LazyArrayOperationDiskCache<T>{
+    src:LazyArrayOperationBox<T>,
+    dir:DiskCacheDir,
+    chunk:usize,
+}
+
+impl<T:Cache+DiskChunk+Clone+Debug+'static> LazyArrayOperationDiskCache<T>{
+    pub fn new(src:LazyArrayOperationBox<T>, dir:DiskCacheDir, chunk:usize)->Self{
+        Self { src, dir, chunk:chunk.max(1) }
+    }
+
+    fn chunk_bounds(&self, index:usize)->(usize,usize){
+        let start = index*self.chunk;
+        (start, (start+self.chunk).min(self.src.length()))
+    }
+
+    fn chunk_file(&self, index:usize)->String{
+        format!("c{}_{}.bin", self.chunk, index)
+    }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<T,ExecutionError>{
+        if start>=end{
+            return self.src.try_request_range(start,end).into_result();
+        }
+        check_range(start, end, self.src.length())?;
+        let first = start/self.chunk;
+        let last = (end-1)/self.chunk;
+        let mut res:Option<T> = None;
+        for index in first..=last{
+            let file = self.chunk_file(index);
+            let (chunk_start,chunk_end) = self.chunk_bounds(index);
+            let data = if let Some(v) = self.dir.load(&file){
+                v
+            }
+            else{
+                let v = self.src.try_request_range(chunk_start,chunk_end).into_result()?;
+                self.dir.store(&file, &v)?;
+                v
+            };
+            append_range(&mut res, &data, start.max(chunk_start)-chunk_start, end.min(chunk_end)-chunk_start);
+        }
+        Ok(res.expect("At least one chunk is requested"))
+    }
+}
+
+impl<T:Cache+DiskChunk+Clone+Debug+Send+Sync+'static> LazyArrayOperation<T> for LazyArrayOperationDiskCache<T>{
+    fn length(&self,) -> usize where {
+        self.src.length()
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.src.calculate_overhead(start,end)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> T where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<T,ExecutionError> where {
+        self.try_request(start,end).into()
+    }
+}
+
+/// Disk cache of data which cannot be glued from parts, e.g. triggers, whose events depend on requested interval.
+/// Each requested interval is stored as is, so it only helps when the same intervals are requested again.
+#[derive(Clone,Debug)]
+pub struct LazyRangeDiskCache<T>{
+    src:LazyArrayOperationBox<T>,
+    dir:DiskCacheDir,
+}
+
+impl<T:DiskChunk+Clone+Debug+'static> LazyRangeDiskCache<T>{
+    pub fn new(src:LazyArrayOperationBox<T>, dir:DiskCacheDir)->Self{
+        Self { src, dir }
+    }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<T,ExecutionError>{
+        let file = format!("r{}_{}.bin", start, end);
+        if let Some(v) = self.dir.load(&file){
+            return Ok(v);
+        }
+        let v = self.src.try_request_range(start,end).into_result()?;
+        self.dir.store(&file, &v)?;
+        Ok(v)
+    }
+}
+
+impl<T:DiskChunk+Clone+Debug+Send+Sync+'static> LazyArrayOperation<T> for LazyRangeDiskCache<T>{
+    fn length(&self,) -> usize where {
+        self.src.length()
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.src.calculate_overhead(start,end)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> T where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<T,ExecutionError> where {
+        self.try_request(start,end).into()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::lazy_array_operations::testing::Counting;
+
+    #[test]
+    fn test_disk_cache_survives_restart(){
+        let root = std::env::temp_dir().join(format!("padamo_disk_cache_test_{}", std::process::id()));
+        let source = Counting::new(10);
+        let make = || LazyArrayOperationDiskCache::new(source.signal(), DiskCacheDir::new(&root, 1, "signal", u64::MAX), 4);
+
+        let data = make().request_range(2, 7);
+        assert_eq!(data.flat_data.as_slice(), &[2.0,3.0,4.0,5.0,6.0]);
+        assert_eq!(source.calls(), 2);
+
+        // New cache object reads chunks written by previous one
+        let data = make().request_range(3, 8);
+        assert_eq!(data.flat_data.as_slice(), &[3.0,4.0,5.0,6.0,7.0]);
+        assert_eq!(source.calls(), 2);
+
+        shrink_dir(&root, 0);
+        assert_eq!(disk_usage(&root), 0);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_concurrent_stores(){
+        let root = std::env::temp_dir().join(format!("padamo_disk_cache_concurrent_{}", std::process::id()));
+        let dir = DiskCacheDir::new(&root, 1, "signal", u64::MAX);
+        let data = ArrayND { flat_data: vec![1.0;1000].into(), shape: vec![1000].into() };
+        std::thread::scope(|s|{
+            for _ in 0..8{
+                s.spawn(|| dir.store("c.bin", &data).unwrap());
+            }
+        });
+        assert_eq!(list_files(&root).len(), 1);
+
+        // Root is shrunk once estimate exceeds limit
+        let size = disk_usage(&root);
+        let dir = DiskCacheDir::new(&root, 2, "signal", size*2);
+        for i in 0..4{
+            dir.store(&format!("c{}.bin", i), &data).unwrap();
+        }
+        assert!(disk_usage(&root)<=size*2);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -1,6 +1,7 @@
-use abi_stable::std_types::RVec;
+use abi_stable::std_types::{RResult, RVec};
 use std::fmt::Debug;
 
+use crate::calculation_nodes::errors::ExecutionError;
 use super::{LazyArrayOperation, LazyArrayOperationBox};
 
 
@@ -35,6 +36,20 @@ where
         Self { a, b ,split_index}
 
     }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<T,ExecutionError>{
+        if end<=self.split_index{
+            self.a.try_request_range(start,end).into_result()
+        }
+        else if start>= self.split_index{
+            self.b.try_request_range(start-self.split_index,end-self.split_index).into_result()
+        }
+        else{
+            let a = self.a.try_request_range(start,self.split_index).into_result()?;
+            let b = self.b.try_request_range(0,end-self.split_index).into_result()?;
+            Ok(a.merge(b))
+        }
+    }
 }
 
 
@@ -48,17 +63,11 @@ where
 
     #[allow(clippy::let_and_return)]
     fn request_range(&self,start:usize,end:usize,) -> T where {
-        if end<=self.split_index{
-            self.a.request_range(start,end)
-        }
-        else if start>= self.split_index{
-            self.b.request_range(start-self.split_index,end-self.split_index)
-        }
-        else{
-            let a = self.a.request_range(start,self.split_index);
-            let b = self.b.request_range(0,end-self.split_index);
-            a.merge(b)
-        }
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<T,ExecutionError> where {
+        self.try_request(start,end).into()
     }
 
     fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
@@ -4,23 +4,43 @@ use std::fmt::Debug;
 pub mod ndim_array;
 pub mod merge;
 pub mod cache;
+pub mod disk_cache;
+pub mod prefetch;
+pub mod parallel;
+pub mod typed;
 pub mod cutter;
+#[cfg(test)]
+mod testing;
 use abi_stable::sabi_trait::prelude::TD_Opaque;
 
 pub use ndim_array::ArrayND;
 use super::trigger_operations::SparseTagArray;
+use crate::calculation_nodes::errors::ExecutionError;
+use crate::calculation_nodes::progress::ProgressToken;
 
 #[allow(non_local_definitions)]
 pub mod traits{
+    use abi_stable::std_types::RResult;
+    use crate::calculation_nodes::errors::ExecutionError;
+
     #[abi_stable::sabi_trait]
     pub trait LazyArrayOperation<T>: Clone+Debug+Sync+Send
     {
         fn length(&self)->usize;
+
+        /// Get data in range [start, end). Panics if the data cannot be obtained.
         fn request_range(&self,start:usize, end:usize)->T;
+
         fn calculate_overhead(&self,start:usize, end:usize)->usize{
             end-start
         }
 
+        /// Fallible version of `request_range`.
+        /// Operations that read files or depend on other lazy operations should override it
+        /// so errors are propagated to the caller instead of panicking.
+        fn try_request_range(&self,start:usize, end:usize)->RResult<T,ExecutionError>{
+            RResult::ROk(self.request_range(start,end))
+        }
     }
 }
 pub use traits::{LazyArrayOperation,LazyArrayOperation_TO};
@@ -68,6 +88,47 @@ where
         Ok(make_lao_box(cutdata))
     }
 
+    /// Calculates next `depth` ranges of `chunk` elements in background while consumer processes current one.
+    /// Useful for consumers reading signal forward by ranges of same length.
+    pub fn prefetched(self, chunk:usize, depth:usize)->Self
+    where
+        T:Send+Sync
+    {
+        let end = self.length();
+        self.prefetched_until(chunk, depth, end)
+    }
+
+    /// Same as [`Self::prefetched`], but nothing past `end` is calculated in advance.
+    /// Consumer reading only part of signal should pass end of that part, so prefetched ranges match requested ones.
+    pub fn prefetched_until(self, chunk:usize, depth:usize, end:usize)->Self
+    where
+        T:Send+Sync
+    {
+        let prefetched = prefetch::LazyArrayOperationPrefetch::new(self, chunk, depth, end);
+        make_lao_box(prefetched)
+    }
+
+    /// Requests range [start, end) by chunks of at most `chunk` elements and passes each one with its start position to `consumer`.
+    /// Progress is reported to `progress` in elements and added to its total, so all consumers sharing token make one counter.
+    /// Cancellation is checked before each chunk.
+    pub fn try_for_each_chunk<F>(&self, start:usize, end:usize, chunk:usize, progress:&ProgressToken, mut consumer:F)->Result<(),ExecutionError>
+    where
+        F:FnMut(usize,T)->Result<(),ExecutionError>
+    {
+        let chunk = chunk.max(1);
+        progress.add_total(end.saturating_sub(start));
+        let mut pos = start;
+        while pos<end{
+            progress.check()?;
+            let step = chunk.min(end-pos);
+            let data = self.try_request_range(pos, pos+step).into_result()?;
+            consumer(pos, data)?;
+            progress.advance(step);
+            pos += step;
+        }
+        Ok(())
+    }
+
 
 }
 
@@ -81,6 +142,16 @@ where
         LazyArrayOperationBox::from_value(merged, TD_Opaque)
     }
 
+    /// Evaluates requests by sub-ranges of `chunk` frames on several threads.
+    /// Only for operations whose frames do not depend on neighbouring ones.
+    pub fn parallel(self, chunk:usize)->Self
+    where
+        T:Send+Sync
+    {
+        let parallel = parallel::LazyArrayOperationParallel::new(self, chunk);
+        LazyArrayOperationBox::from_value(parallel, TD_Opaque)
+    }
+
 
 }
 
@@ -94,22 +165,48 @@ where
     }
 }
 
+impl<T> LazyArrayOperationBox<T>
+where
+    T:disk_cache::DiskChunk+Clone+Debug+'static+Send+Sync
+{
+    /// Caches whole requested ranges on disk. For data that cannot be glued from parts, e.g. triggers.
+    pub fn range_disk_cached(self, dir:disk_cache::DiskCacheDir)->Self{
+        let cached = disk_cache::LazyRangeDiskCache::new(self, dir);
+        LazyArrayOperationBox::from_value(cached, TD_Opaque)
+    }
+}
+
+impl<T> LazyArrayOperationBox<T>
+where
+    T:disk_cache::DiskChunk+cache::Cache+Clone+Debug+'static+Send+Sync
+{
+    /// Caches data on disk by chunks of `cache::CACHE_CHUNK` frames.
+    pub fn disk_cached(self, dir:disk_cache::DiskCacheDir)->Self{
+        let cached = disk_cache::LazyArrayOperationDiskCache::new(self, dir, cache::CACHE_CHUNK);
+        LazyArrayOperationBox::from_value(cached, TD_Opaque)
+    }
+}
+
 impl LazyTimeSignal{
     pub fn find_unixtime(&self,unixtime:f64)->usize{
+        self.try_find_unixtime(unixtime).unwrap()
+    }
+
+    pub fn try_find_unixtime(&self,unixtime:f64)->Result<usize,ExecutionError>{
         let op = self;
         //let unixtime:f64 = (dt.naive_utc().timestamp_millis() as f64)
         let mut start:usize = 0;
         let op_length = op.length();
         let mut end:usize = op_length;
         let mut middle:usize = (start+end)/2;
-        if unixtime>op.request_range(end-1,end)[0]{
-            return end-1;
+        if unixtime>op.try_request_range(end-1,end).into_result()?[0]{
+            return Ok(end-1);
         }
-        if unixtime<op.request_range(0,1)[0]{
-            return 0;
+        if unixtime<op.try_request_range(0,1).into_result()?[0]{
+            return Ok(0);
         }
         while start != middle{
-            let item = op.request_range(middle,middle+1)[0];
+            let item = op.try_request_range(middle,middle+1).into_result()?[0];
             if item<=unixtime{
                 start = middle;
             }
@@ -121,18 +218,18 @@ impl LazyTimeSignal{
         //println!("Datetime search result. req: {}, actual: {}",unixtime, op.request_item(middle));
         let mut res = middle;
         if middle>0{
-            let twoval = op.request_range(middle-1,middle+1);
+            let twoval = op.try_request_range(middle-1,middle+1).into_result()?;
             if (twoval[0]-unixtime).abs()<(twoval[1]-unixtime).abs(){
                 res = middle-1;
             }
         }
         if middle<op_length-1{
-            let twoval = op.request_range(middle,middle+2);
+            let twoval = op.try_request_range(middle,middle+2).into_result()?;
             if (twoval[0]-unixtime).abs()>(twoval[1]-unixtime).abs(){
                 res = middle+1;
             }
         }
-        res
+        Ok(res)
     }
 
     #[cfg(feature = "chrono")]
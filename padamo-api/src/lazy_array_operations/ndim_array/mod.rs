@@ -8,6 +8,7 @@ pub mod lao;
 
 use abi_stable::StableAbi;
 pub use padamo_arraynd::ArrayND;
+pub use padamo_arraynd::{ArrayView, StridedArray};
 pub use padamo_arraynd::indexing::ShapeIterator;
 
 use crate::lazy_array_operations::{cache::Cache, merge::Merge};
@@ -69,4 +70,32 @@ T:StableAbi+Clone
         self.merge(data)
     }
 
+    fn split_front(self,count:usize)->(Self,Self) {
+        let step = self.shape.iter().skip(1).fold(1, |a,b| {a*b});
+        let mut head_shape = self.shape.clone();
+        head_shape[0] = count;
+        let mut tail_shape = self.shape;
+        tail_shape[0] -= count;
+        let mut head_flat = self.flat_data;
+        let tail_flat = head_flat.drain(count*step..).collect();
+        (ArrayND{flat_data:head_flat, shape:head_shape}, ArrayND{flat_data:tail_flat, shape:tail_shape})
+    }
+
+    fn copy_range(&self,start:usize,end:usize)->Self {
+        self.view().slice_axis(0, start..end).to_owned()
+    }
+
+    fn extend_range(&mut self,data:&Self,start:usize,end:usize) {
+        if self.shape.iter().skip(1).ne(data.shape.iter().skip(1)){
+            panic!("Cannot append frames of shape {:?} to array of shape {:?}", data.shape, self.shape);
+        }
+        let part = data.view().slice_axis(0, start..end);
+        self.flat_data.extend_from_slice(part.as_slice().expect("Frames of array are contiguous"));
+        self.shape[0] += end-start;
+    }
+
+    fn memory_size(&self)->usize {
+        self.flat_data.len()*std::mem::size_of::<T>()
+    }
+
 }
@@ -0,0 +1,134 @@
+//! Helpers for evaluating lazy operations on several threads.
+//! Work is split into tasks run on rayon thread pool, so nested helpers share workers instead of spawning threads of their own.
+use super::merge::Merge;
+use super::{ArrayND, LazyArrayOperation, LazyArrayOperationBox};
+use crate::calculation_nodes::errors::ExecutionError;
+use padamo_arraynd::ndim_array::ArrayStrideIterator;
+use padamo_arraynd::ArrayView;
+use abi_stable::std_types::RResult;
+use std::fmt::Debug;
+use rayon::prelude::*;
+
+/// Number of frames in one task of [`LazyArrayOperationParallel`]
+pub const DEFAULT_PARALLEL_CHUNK:usize = 64;
+
+/// Number of workers used by helpers
+pub fn worker_count()->usize{
+    rayon::current_num_threads()
+}
+
+/// Runs `task` for every index in `0..count` on rayon thread pool. Results are returned in order of indices.
+pub fn par_tasks<R,F>(count:usize, task:F)->Vec<R>
+where
+    R:Send,
+    F:Fn(usize)->R+Sync+Send
+{
+    (0..count).into_par_iter().map(task).collect()
+}
+
+/// Splits range `[start, end)` into sub-ranges of at most `chunk` frames, evaluates them with `request` in parallel and merges results in order.
+pub fn par_request_range<T,F>(start:usize, end:usize, chunk:usize, request:F)->Result<T,ExecutionError>
+where
+    T:Merge+Send,
+    F:Fn(usize,usize)->Result<T,ExecutionError>+Sync
+{
+    let chunk = chunk.max(1);
+    if end<=start+chunk{
+        return request(start,end);
+    }
+    let count = (end-start).div_ceil(chunk);
+    let parts = par_tasks(count, |i| {
+        let part_start = start+i*chunk;
+        request(part_start, (part_start+chunk).min(end))
+    });
+    let mut parts = parts.into_iter();
+    let mut res = parts.next().expect("At least one part is requested")?;
+    for part in parts{
+        res = res.merge(part?);
+    }
+    Ok(res)
+}
+
+/// Applies `f` to time series of every pixel of `data` (first axis is time). Pixels are processed by groups in parallel.
+/// `f` must return series of same length as input.
+pub fn par_map_pixels<F>(data:&ArrayND<f64>, f:F)->ArrayND<f64>
+where
+    F:Fn(&[f64])->Vec<f64>+Sync
+{
+    let length = data.shape.first().copied().unwrap_or(0);
+    let frame_size:usize = data.shape.iter().skip(1).product();
+    if length==0 || frame_size==0{
+        return data.clone();
+    }
+    // Several groups per worker balance pixels of different cost
+    let group = frame_size.div_ceil(worker_count()*4).max(1);
+    // Results are written pixel after pixel straight into their places, then transposed back to frames
+    let mut by_pixel = vec![0.0; length*frame_size];
+    by_pixel.par_chunks_mut(group*length).enumerate().for_each(|(g,block)|{
+        let mut series = Vec::with_capacity(length);
+        for (k,target) in block.chunks_mut(length).enumerate(){
+            series.clear();
+            series.extend(ArrayStrideIterator::from_slice(&data.flat_data, frame_size, g*group+k, length));
+            let res = f(&series);
+            assert_eq!(res.len(), length, "Pixel function must keep length of series");
+            target.copy_from_slice(&res);
+        }
+    });
+    let mut res = ArrayView::from_slice(&by_pixel, vec![frame_size,length]).transpose().to_owned();
+    res.shape = data.shape.clone();
+    res
+}
+
+/// Evaluates requests of `src` by sub-ranges in parallel. Suitable for operations processing frames independently.
+#[derive(Clone,Debug)]
+pub struct LazyArrayOperationParallel<T>{
+    src:LazyArrayOperationBox<T>,
+    chunk:usize,
+}
+
+impl<T> LazyArrayOperationParallel<T>{
+    pub fn new(src:LazyArrayOperationBox<T>, chunk:usize)->Self{
+        Self { src, chunk:chunk.max(1) }
+    }
+}
+
+impl<T:Merge+Clone+Debug+Send+Sync+'static> LazyArrayOperation<T> for LazyArrayOperationParallel<T>{
+    fn length(&self,) -> usize where {
+        self.src.length()
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.src.calculate_overhead(start,end)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> T where {
+        self.try_request_range(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<T,ExecutionError> where {
+        par_request_range(start, end, self.chunk, |a,b| self.src.try_request_range(a,b).into_result()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::lazy_array_operations::make_lao_box;
+    use abi_stable::std_types::RVec;
+
+    #[test]
+    fn test_parallel_request(){
+        let src:RVec<usize> = (0..1000).collect();
+        let op = make_lao_box(src).parallel(7);
+        assert_eq!(op.request_range(3, 998).as_slice(), (3..998).collect::<Vec<usize>>().as_slice());
+        assert_eq!(op.request_range(5, 6).as_slice(), &[5]);
+    }
+
+    #[test]
+    fn test_map_pixels(){
+        let data = ArrayND { flat_data: (0..12).map(|x| x as f64).collect(), shape: vec![3,2,2].into() };
+        let res = par_map_pixels(&data, |series| series.iter().map(|x| x*2.0).collect());
+        assert_eq!(res.shape, data.shape);
+        assert!(res.flat_data.iter().zip(data.flat_data.iter()).all(|(a,b)| *a==b*2.0));
+    }
+}
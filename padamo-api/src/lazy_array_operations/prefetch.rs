@@ -0,0 +1,253 @@
+//! Read-ahead of lazy operations for consumers walking signal forward by ranges of same length.
+//! After range `[start, end)` is requested, next ranges `[end, end+chunk)`, ... are calculated on worker threads,
+//! so data is ready by the time consumer asks for it.
+//! Ranges which are not needed anymore are cancelled, so workers skip them if they have not started yet.
+use super::{LazyArrayOperation, LazyArrayOperationBox};
+use crate::calculation_nodes::errors::ExecutionError;
+use abi_stable::std_types::RResult;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+/// Number of ranges calculated ahead by long running tools
+pub const DEFAULT_PREFETCH_DEPTH:usize = 2;
+
+/// Result of range being calculated by worker
+struct Slot<T>{
+    result:Mutex<Option<Result<T,ExecutionError>>>,
+    ready:Condvar,
+    /// Set when nobody is going to wait for result
+    cancelled:AtomicBool,
+}
+
+impl<T> Slot<T>{
+    fn new()->Self{
+        Self { result: Mutex::new(None), ready: Condvar::new(), cancelled:AtomicBool::new(false) }
+    }
+
+    fn cancel(&self){
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self)->bool{
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn set(&self, value:Result<T,ExecutionError>){
+        *self.result.lock().unwrap() = Some(value);
+        self.ready.notify_all();
+    }
+
+    fn wait(&self)->Result<T,ExecutionError>{
+        let mut result = self.result.lock().unwrap();
+        loop{
+            if let Some(v) = result.take(){
+                return v;
+            }
+            result = self.ready.wait(result).unwrap();
+        }
+    }
+}
+
+type Task<T> = (usize,usize,Arc<Slot<T>>);
+
+/// Scheduled ranges by their bounds
+type Slots<T> = HashMap<(usize,usize),Arc<Slot<T>>>;
+
+struct Pending<T>(Mutex<Slots<T>>);
+
+impl<T> Drop for Pending<T>{
+    /// Queued ranges are not needed once the last clone of operation is dropped
+    fn drop(&mut self) {
+        if let Ok(pending) = self.0.get_mut(){
+            pending.values().for_each(|slot| slot.cancel());
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LazyArrayOperationPrefetch<T>{
+    src:LazyArrayOperationBox<T>,
+    chunk:usize,
+    depth:usize,
+    end:usize,
+    pending:Arc<Pending<T>>,
+    tasks:Sender<Task<T>>,
+}
+
+impl<T> Debug for LazyArrayOperationPrefetch<T>{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyArrayOperationPrefetch").field("chunk", &self.chunk).field("depth", &self.depth).finish()
+    }
+}
+
+impl<T:Clone+Debug+Send+Sync+'static> LazyArrayOperationPrefetch<T>{
+    /// Starts `depth` worker threads. Workers stop when the last clone of operation is dropped.
+    /// Ranges past `end` are not prefetched.
+    pub fn new(src:LazyArrayOperationBox<T>, chunk:usize, depth:usize, end:usize)->Self{
+        let depth = depth.max(1);
+        let (tasks, rx) = channel::<Task<T>>();
+        let rx = Arc::new(Mutex::new(rx));
+        for _ in 0..depth{
+            let rx = rx.clone();
+            let src = src.clone();
+            std::thread::spawn(move ||{
+                loop{
+                    // Lock is released before calculation so workers run in parallel
+                    let task = rx.lock().unwrap().recv();
+                    let Ok((start,end,slot)) = task else {break;};
+                    if slot.is_cancelled(){
+                        continue;
+                    }
+                    // Consumer waits for slot, so it must be filled even if calculation panics
+                    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| src.try_request_range(start, end).into_result()))
+                        .unwrap_or_else(|_| Err(ExecutionError::OtherError("Prefetch worker panicked".into())));
+                    slot.set(res);
+                }
+            });
+        }
+        let end = end.min(src.length());
+        Self { src, chunk:chunk.max(1), depth, end, pending:Arc::new(Pending(Mutex::new(HashMap::new()))), tasks }
+    }
+
+    /// Schedules calculation of `depth` ranges following `from`.
+    fn schedule(&self, pending:&mut Slots<T>, from:usize){
+        let mut pos = from;
+        for _ in 0..self.depth{
+            if pos>=self.end{
+                break;
+            }
+            let end = (pos+self.chunk).min(self.end);
+            if let Entry::Vacant(entry) = pending.entry((pos,end)){
+                let slot = Arc::new(Slot::new());
+                if self.tasks.send((pos,end,slot.clone())).is_err(){
+                    break;
+                }
+                entry.insert(slot);
+            }
+            pos = end;
+        }
+    }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<T,ExecutionError>{
+        let slot = {
+            let mut pending = self.pending.0.lock().unwrap();
+            let slot = pending.remove(&(start,end));
+            if slot.is_some(){
+                // Ranges consumer has already passed are not needed anymore
+                pending.retain(|k,slot|{
+                    let keep = k.0>=end;
+                    if !keep{
+                        slot.cancel();
+                    }
+                    keep
+                });
+            }
+            else{
+                // Consumer jumped somewhere else
+                pending.drain().for_each(|(_,slot)| slot.cancel());
+            }
+            self.schedule(&mut pending, end);
+            slot
+        };
+        if let Some(slot) = slot{
+            slot.wait()
+        }
+        else{
+            self.src.try_request_range(start,end).into_result()
+        }
+    }
+}
+
+impl<T:Clone+Debug+Send+Sync+'static> LazyArrayOperation<T> for LazyArrayOperationPrefetch<T>{
+    fn length(&self,) -> usize where {
+        self.src.length()
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.src.calculate_overhead(start,end)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> T where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<T,ExecutionError> where {
+        self.try_request(start,end).into()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use crate::lazy_array_operations::make_lao_box;
+    use crate::lazy_array_operations::testing::Counting;
+    use abi_stable::std_types::RVec;
+
+    #[test]
+    fn test_sequential_reading(){
+        let source = Counting::new(10);
+        let op = LazyArrayOperationPrefetch::new(source.indices(), 3, 2, 10);
+        let mut data = Vec::new();
+        for start in (0..10).step_by(3){
+            data.extend(op.request_range(start, (start+3).min(10)));
+        }
+        assert_eq!(data, (0..10).collect::<Vec<usize>>());
+        // Every range is calculated once: first one directly, the rest by workers
+        assert_eq!(source.calls(), 4);
+
+        // Jump back is calculated directly
+        assert_eq!(op.request_range(1, 2).as_slice(), &[1]);
+    }
+
+    /// Remembers requested ranges. Calculation of range starting at 3 waits for gate after it is remembered.
+    #[derive(Clone,Debug)]
+    struct Recording{
+        ranges:Arc<Mutex<Vec<(usize,usize)>>>,
+        gate:Arc<Mutex<()>>,
+    }
+
+    impl LazyArrayOperation<RVec<usize>> for Recording{
+        fn length(&self,) -> usize {
+            10
+        }
+
+        fn request_range(&self,start:usize, end:usize,) -> RVec<usize> {
+            self.ranges.lock().unwrap().push((start,end));
+            if start==3{
+                drop(self.gate.lock().unwrap());
+            }
+            (start..end).collect()
+        }
+    }
+
+    #[test]
+    fn test_jump_cancels_queued_ranges(){
+        let ranges = Arc::new(Mutex::new(Vec::new()));
+        let gate = Arc::new(Mutex::new(()));
+        let closed = gate.lock().unwrap();
+        let op = LazyArrayOperationPrefetch::new(make_lao_box(Recording { ranges: ranges.clone(), gate:gate.clone() }), 3, 1, 10);
+        // The only worker is stuck at [3,6), so ranges scheduled after it stay in queue
+        op.request_range(0, 3);
+        while !ranges.lock().unwrap().contains(&(3,6)){
+            std::thread::yield_now();
+        }
+        op.request_range(8, 9);
+        op.request_range(0, 1);
+        drop(closed);
+        assert_eq!(op.request_range(1, 4).as_slice(), &[1,2,3]);
+        assert!(!ranges.lock().unwrap().contains(&(9,10)));
+    }
+
+    #[test]
+    fn test_prefetch_stops_at_end(){
+        let ranges = Arc::new(Mutex::new(Vec::new()));
+        let op = LazyArrayOperationPrefetch::new(make_lao_box(Recording { ranges: ranges.clone(), gate:Arc::new(Mutex::new(())) }), 3, 1, 5);
+        op.request_range(0, 3);
+        op.request_range(3, 5);
+        assert_eq!(ranges.lock().unwrap().as_slice(), &[(0,3),(3,5)]);
+    }
+}
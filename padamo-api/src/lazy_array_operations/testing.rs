@@ -0,0 +1,54 @@
+//! Sources shared by tests of lazy operations
+use super::{make_lao_box, ArrayND, LazyArrayOperation, LazyArrayOperationBox};
+use abi_stable::std_types::RVec;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Source whose frames are equal to their indices. Counts requests, so tests can check what is calculated again.
+#[derive(Clone,Debug)]
+pub(crate) struct Counting{
+    length:usize,
+    calls:Arc<AtomicUsize>,
+}
+
+impl Counting{
+    pub(crate) fn new(length:usize)->Self{
+        Self { length, calls:Arc::new(AtomicUsize::new(0)) }
+    }
+
+    /// Number of requests made so far by all clones
+    pub(crate) fn calls(&self)->usize{
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn signal(&self)->LazyArrayOperationBox<ArrayND<f64>>{
+        make_lao_box(self.clone())
+    }
+
+    pub(crate) fn indices(&self)->LazyArrayOperationBox<RVec<usize>>{
+        make_lao_box(self.clone())
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for Counting{
+    fn length(&self,) -> usize {
+        self.length
+    }
+
+    fn request_range(&self,start:usize, end:usize,) -> ArrayND<f64> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let flat_data:Vec<f64> = (start..end).map(|x| x as f64).collect();
+        ArrayND { flat_data: flat_data.into(), shape: vec![end-start].into() }
+    }
+}
+
+impl LazyArrayOperation<RVec<usize>> for Counting{
+    fn length(&self,) -> usize {
+        self.length
+    }
+
+    fn request_range(&self,start:usize, end:usize,) -> RVec<usize> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        (start..end).collect()
+    }
+}
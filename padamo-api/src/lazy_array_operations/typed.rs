@@ -0,0 +1,287 @@
+//! Detector signals with element types other than `f64`.
+//! Photon counts and masks keep their native type, so they take less memory and cache space.
+//! Nodes working with `f64` signals get them through explicit conversion.
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use abi_stable::StableAbi;
+use abi_stable::std_types::RResult;
+
+use super::{make_lao_box, ArrayND, LazyArrayOperation, LazyArrayOperationBox, LazyDetectorSignal};
+use crate::calculation_nodes::errors::ExecutionError;
+
+/// Element type of [`TypedDetectorSignal`]
+#[repr(C)]
+#[derive(StableAbi,Clone,Copy,Debug,PartialEq,Eq)]
+pub enum SignalDType{
+    F64,
+    F32,
+    U8,
+    U16,
+    U32,
+    I32,
+    I64,
+    Bool,
+}
+
+impl SignalDType{
+    pub fn all()->Vec<Self>{
+        vec![Self::F64, Self::F32, Self::U8, Self::U16, Self::U32, Self::I32, Self::I64, Self::Bool]
+    }
+
+    /// NumPy-like name of type
+    pub fn name(&self)->&'static str{
+        match self {
+            Self::F64 => "f64",
+            Self::F32 => "f32",
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::Bool => "bool",
+        }
+    }
+
+    pub fn from_name(name:&str)->Option<Self>{
+        Self::all().into_iter().find(|x| x.name()==name.trim().to_lowercase())
+    }
+
+    /// Parses name of type for node constants
+    pub fn parse(name:&str)->Result<Self,ExecutionError>{
+        Self::from_name(name).ok_or_else(|| {
+            let known:Vec<&str> = Self::all().iter().map(|x| x.name()).collect();
+            ExecutionError::OtherError(format!("Unknown signal type \"{}\". Known types: {}", name, known.join(", ")).into())
+        })
+    }
+}
+
+/// Element of typed signal
+pub trait SignalElement: Clone+Copy+Debug+StableAbi+Send+Sync+'static{
+    const DTYPE:SignalDType;
+
+    fn to_f64(self)->f64;
+
+    /// Conversion from float. Integers are rounded and saturated, nonzero values are true.
+    fn from_f64(value:f64)->Self;
+
+    fn wrap(signal:LazyArrayOperationBox<ArrayND<Self>>)->TypedDetectorSignal;
+}
+
+macro_rules! impl_signal_element {
+    ($t:ty, $variant:ident, $from:expr) => {
+        impl SignalElement for $t{
+            const DTYPE:SignalDType = SignalDType::$variant;
+
+            fn to_f64(self)->f64{
+                self as f64
+            }
+
+            fn from_f64(value:f64)->Self{
+                let f:fn(f64)->$t = $from;
+                f(value)
+            }
+
+            fn wrap(signal:LazyArrayOperationBox<ArrayND<Self>>)->TypedDetectorSignal{
+                TypedDetectorSignal::$variant(signal)
+            }
+        }
+    };
+}
+
+impl_signal_element!(f64, F64, |x| x);
+impl_signal_element!(f32, F32, |x| x as f32);
+// `as` casts from float saturate
+impl_signal_element!(u8, U8, |x| x.round() as u8);
+impl_signal_element!(u16, U16, |x| x.round() as u16);
+impl_signal_element!(u32, U32, |x| x.round() as u32);
+impl_signal_element!(i32, I32, |x| x.round() as i32);
+impl_signal_element!(i64, I64, |x| x.round() as i64);
+
+impl SignalElement for bool{
+    const DTYPE:SignalDType = SignalDType::Bool;
+
+    fn to_f64(self)->f64{
+        if self {1.0} else {0.0}
+    }
+
+    fn from_f64(value:f64)->Self{
+        value!=0.0
+    }
+
+    fn wrap(signal:LazyArrayOperationBox<ArrayND<Self>>)->TypedDetectorSignal{
+        TypedDetectorSignal::Bool(signal)
+    }
+}
+
+/// Detector signal keeping element type of its source
+#[repr(C)]
+#[derive(StableAbi,Clone,Debug)]
+pub enum TypedDetectorSignal{
+    F64(LazyDetectorSignal),
+    F32(LazyArrayOperationBox<ArrayND<f32>>),
+    U8(LazyArrayOperationBox<ArrayND<u8>>),
+    U16(LazyArrayOperationBox<ArrayND<u16>>),
+    U32(LazyArrayOperationBox<ArrayND<u32>>),
+    I32(LazyArrayOperationBox<ArrayND<i32>>),
+    I64(LazyArrayOperationBox<ArrayND<i64>>),
+    Bool(LazyArrayOperationBox<ArrayND<bool>>),
+}
+
+/// Evaluates `$body` with `$x` bound to lazy operation inside typed signal, whatever its element type is.
+#[macro_export]
+macro_rules! with_typed_signal {
+    ($signal:expr, $x:ident => $body:expr) => {
+        match $signal {
+            $crate::lazy_array_operations::typed::TypedDetectorSignal::F64($x) => $body,
+            $crate::lazy_array_operations::typed::TypedDetectorSignal::F32($x) => $body,
+            $crate::lazy_array_operations::typed::TypedDetectorSignal::U8($x) => $body,
+            $crate::lazy_array_operations::typed::TypedDetectorSignal::U16($x) => $body,
+            $crate::lazy_array_operations::typed::TypedDetectorSignal::U32($x) => $body,
+            $crate::lazy_array_operations::typed::TypedDetectorSignal::I32($x) => $body,
+            $crate::lazy_array_operations::typed::TypedDetectorSignal::I64($x) => $body,
+            $crate::lazy_array_operations::typed::TypedDetectorSignal::Bool($x) => $body,
+        }
+    };
+}
+
+impl TypedDetectorSignal{
+    pub fn new<T:SignalElement>(signal:LazyArrayOperationBox<ArrayND<T>>)->Self{
+        T::wrap(signal)
+    }
+
+    pub fn dtype(&self)->SignalDType{
+        match self {
+            Self::F64(_) => SignalDType::F64,
+            Self::F32(_) => SignalDType::F32,
+            Self::U8(_) => SignalDType::U8,
+            Self::U16(_) => SignalDType::U16,
+            Self::U32(_) => SignalDType::U32,
+            Self::I32(_) => SignalDType::I32,
+            Self::I64(_) => SignalDType::I64,
+            Self::Bool(_) => SignalDType::Bool,
+        }
+    }
+
+    pub fn length(&self)->usize{
+        with_typed_signal!(self, x => x.length())
+    }
+
+    /// Signal converted to `f64` on request. `f64` signal is returned as is.
+    pub fn to_f64(self)->LazyDetectorSignal{
+        match self {
+            Self::F64(x) => x,
+            other => with_typed_signal!(other, x => make_lao_box(LazyToF64::new(x))),
+        }
+    }
+
+    /// Signal converted from `f64` on request
+    pub fn from_f64(signal:LazyDetectorSignal, dtype:SignalDType)->Self{
+        fn convert<T:SignalElement>(signal:LazyDetectorSignal)->TypedDetectorSignal{
+            T::wrap(make_lao_box(LazyFromF64::<T>::new(signal)))
+        }
+        match dtype {
+            SignalDType::F64 => Self::F64(signal),
+            SignalDType::F32 => convert::<f32>(signal),
+            SignalDType::U8 => convert::<u8>(signal),
+            SignalDType::U16 => convert::<u16>(signal),
+            SignalDType::U32 => convert::<u32>(signal),
+            SignalDType::I32 => convert::<i32>(signal),
+            SignalDType::I64 => convert::<i64>(signal),
+            SignalDType::Bool => convert::<bool>(signal),
+        }
+    }
+
+    /// Changes element type. Conversion goes through `f64`.
+    pub fn convert(self, dtype:SignalDType)->Self{
+        if self.dtype()==dtype{
+            self
+        }
+        else{
+            Self::from_f64(self.to_f64(), dtype)
+        }
+    }
+}
+
+impl From<LazyDetectorSignal> for TypedDetectorSignal{
+    fn from(value: LazyDetectorSignal) -> Self {
+        Self::F64(value)
+    }
+}
+
+/// Converts elements of typed signal to `f64`
+#[derive(Clone,Debug)]
+pub struct LazyToF64<T:SignalElement>{
+    src:LazyArrayOperationBox<ArrayND<T>>,
+}
+
+impl<T:SignalElement> LazyToF64<T>{
+    pub fn new(src:LazyArrayOperationBox<ArrayND<T>>)->Self{
+        Self { src }
+    }
+}
+
+impl<T:SignalElement> LazyArrayOperation<ArrayND<f64>> for LazyToF64<T>{
+    fn length(&self,) -> usize where {
+        self.src.length()
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.src.calculate_overhead(start,end)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+        self.src.request_range(start,end).map(|x| x.to_f64())
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<ArrayND<f64>,ExecutionError> where {
+        self.src.try_request_range(start,end).map(|x| x.map(|v| v.to_f64()))
+    }
+}
+
+/// Converts elements of `f64` signal to `T`
+#[derive(Clone,Debug)]
+pub struct LazyFromF64<T:SignalElement>{
+    src:LazyDetectorSignal,
+    _marker:PhantomData<T>,
+}
+
+impl<T:SignalElement> LazyFromF64<T>{
+    pub fn new(src:LazyDetectorSignal)->Self{
+        Self { src, _marker:PhantomData }
+    }
+}
+
+impl<T:SignalElement> LazyArrayOperation<ArrayND<T>> for LazyFromF64<T>{
+    fn length(&self,) -> usize where {
+        self.src.length()
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.src.calculate_overhead(start,end)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<T> where {
+        self.src.request_range(start,end).map(|x| T::from_f64(*x))
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<ArrayND<T>,ExecutionError> where {
+        self.src.try_request_range(start,end).map(|x| x.map(|v| T::from_f64(*v)))
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_round_trip(){
+        let data = ArrayND { flat_data: vec![0.0,1.4,2.6,300.0].into(), shape: vec![4].into() };
+        let signal:LazyDetectorSignal = make_lao_box(data);
+        let typed = TypedDetectorSignal::from_f64(signal, SignalDType::U8);
+        assert_eq!(typed.dtype(), SignalDType::U8);
+        let back = typed.to_f64().request_range(0, 4);
+        assert_eq!(back.flat_data.as_slice(), &[0.0,1.0,3.0,255.0]);
+        assert_eq!(SignalDType::from_name(" U16"), Some(SignalDType::U16));
+    }
+}
@@ -1,3 +1,6 @@
+// Lets derives referring to `::padamo_api` be used inside this crate
+extern crate self as padamo_api;
+
 pub mod calculation_nodes;
 pub mod prelude;
 pub mod lazy_array_operations;
@@ -6,6 +9,8 @@ pub mod trigger_operations;
 pub mod function_operator;
 pub mod common_categories;
 pub mod rng;
+pub mod linalg;
+pub mod plugin;
 
 #[cfg(feature = "headless")]
 pub mod headless_helpers;
@@ -13,6 +18,7 @@ pub mod headless_helpers;
 pub use calculation_nodes::ad_hoc_input_node::AdHocInputNode;
 pub use calculation_nodes::ad_hoc_output_node::AdHocOutputNode;
 pub use calculation_nodes::signal_time_node::SignalTimeEmbeddedMergingNode;
+pub use lazy_array_operations::cache::{share_cache, CacheBudget};
 
 use abi_stable::sabi_trait::TD_Opaque;
 use abi_stable::std_types::{RString, RVec};
@@ -88,6 +94,12 @@ pub struct PadamoModule{
 
     #[sabi(last_prefix_field)]
     pub nodes: extern "C" fn(RString) -> RVec<CalculationNodeBox>,
+
+    /// Receives cache budget of application. Set it to [`share_cache`].
+    pub share_cache: extern "C" fn(CacheBudget),
+
+    /// Name, version and author of plugin. Set it to [`plugin_info!`].
+    pub info: extern "C" fn() -> plugin::PluginInfo,
     //
     //pub indicate: extern "C" fn(&mut State),
 }
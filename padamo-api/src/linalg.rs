@@ -0,0 +1,88 @@
+//! Small fixed size values passed between nodes: transform matrices.
+use abi_stable::StableAbi;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize,Deserialize};
+
+use crate::lazy_array_operations::ArrayND;
+
+/// 4x4 matrix of homogeneous transform. Stored row by row.
+#[repr(C)]
+#[derive(StableAbi,Clone,Copy,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Mat4(pub [[f64;4];4]);
+
+impl Mat4{
+    pub fn identity()->Self{
+        let mut res = [[0.0;4];4];
+        for (i,row) in res.iter_mut().enumerate(){
+            row[i] = 1.0;
+        }
+        Self(res)
+    }
+}
+
+impl Default for Mat4{
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl From<Mat4> for ArrayND<f64>{
+    fn from(value: Mat4) -> Self {
+        let flat_data:Vec<f64> = value.0.iter().flatten().copied().collect();
+        Self { flat_data: flat_data.into(), shape: vec![4,4].into() }
+    }
+}
+
+impl TryFrom<ArrayND<f64>> for Mat4{
+    type Error = ();
+
+    fn try_from(value: ArrayND<f64>) -> Result<Self, Self::Error> {
+        if value.shape.as_slice()!=[4,4]{
+            return Err(());
+        }
+        let mut res = [[0.0;4];4];
+        for (i,v) in value.flat_data.iter().enumerate(){
+            res[i/4][i%4] = *v;
+        }
+        Ok(Self(res))
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_support{
+    use super::Mat4;
+
+    impl From<nalgebra::Matrix4<f64>> for Mat4{
+        fn from(value: nalgebra::Matrix4<f64>) -> Self {
+            let mut res = [[0.0;4];4];
+            for (i,row) in res.iter_mut().enumerate(){
+                for (j,v) in row.iter_mut().enumerate(){
+                    *v = value[(i,j)];
+                }
+            }
+            Self(res)
+        }
+    }
+
+    impl From<Mat4> for nalgebra::Matrix4<f64>{
+        fn from(value: Mat4) -> Self {
+            nalgebra::Matrix4::from_fn(|i,j| value.0[i][j])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_array_roundtrip(){
+        let mut m = Mat4::identity();
+        m.0[0][3] = 5.0;
+        let arr:ArrayND<f64> = m.into();
+        assert_eq!(arr.try_get(&[0,3]), Some(&5.0));
+        assert_eq!(Mat4::try_from(arr), Ok(m));
+    }
+}
@@ -0,0 +1,221 @@
+//! Plugin metadata and loading of plugin libraries with report of what was loaded.
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use abi_stable::library::lib_header_from_path;
+use abi_stable::std_types::RString;
+use abi_stable::StableAbi;
+
+use crate::{CalculationNodeBox, PadamoModule_Ref};
+
+/// Version of API plugins are built against
+pub const API_VERSION:&str = env!("CARGO_PKG_VERSION");
+
+/// Plugin description exposed by [`crate::PadamoModule`]
+#[repr(C)]
+#[derive(StableAbi,Clone,Debug)]
+pub struct PluginInfo{
+    pub name:RString,
+    pub version:RString,
+    pub author:RString,
+    /// Lowest version of API plugin can work with
+    pub min_api_version:RString,
+}
+
+/// Makes function describing plugin from its Cargo manifest.
+/// Minimal API version is the version of API plugin is compiled with.
+///
+/// ```ignore
+/// PadamoModule{nodes, share_cache:padamo_api::share_cache, info:padamo_api::plugin_info!()}
+/// ```
+#[macro_export]
+macro_rules! plugin_info {
+    () => {
+        {
+            extern "C" fn info()->$crate::plugin::PluginInfo{
+                $crate::plugin::PluginInfo{
+                    name: env!("CARGO_PKG_NAME").into(),
+                    version: env!("CARGO_PKG_VERSION").into(),
+                    author: env!("CARGO_PKG_AUTHORS").into(),
+                    min_api_version: $crate::plugin::API_VERSION.into(),
+                }
+            }
+            info
+        }
+    };
+}
+
+fn parse_version(version:&str)->Option<Vec<u64>>{
+    version.trim().split('.').map(|x| x.parse().ok()).collect()
+}
+
+/// Checks that `available` version of API is not older than `required` one and has the same major version.
+/// Major version changes break layout of node interface.
+pub fn is_api_compatible(required:&str, available:&str)->bool{
+    match (parse_version(required), parse_version(available)) {
+        (Some(required), Some(available))=>required.first()==available.first() && available>=required,
+        _=>false,
+    }
+}
+
+#[derive(Clone,Debug)]
+pub enum PluginStatus{
+    Loaded,
+    /// File is not a plugin or cannot be used with this version of application
+    Skipped(String),
+    /// Plugin library failed to load
+    Failed(String),
+}
+
+impl Display for PluginStatus{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Loaded=>write!(f,"Loaded"),
+            Self::Skipped(x)=>write!(f,"Skipped: {}", x),
+            Self::Failed(x)=>write!(f,"Failed: {}", x),
+        }
+    }
+}
+
+/// What happened to one plugin file
+#[derive(Clone,Debug)]
+pub struct PluginReport{
+    pub path:PathBuf,
+    pub info:Option<PluginInfo>,
+    pub status:PluginStatus,
+    /// Identifiers of registered nodes
+    pub nodes:Vec<String>,
+    /// Nodes rejected because their identifiers are already taken
+    pub conflicts:Vec<String>,
+}
+
+impl PluginReport{
+    fn new(path:&Path, status:PluginStatus)->Self{
+        Self { path: path.to_path_buf(), info: None, status, nodes: Vec::new(), conflicts: Vec::new() }
+    }
+
+    pub fn title(&self)->String{
+        if let Some(info) = &self.info{
+            format!("{} {}", info.name, info.version)
+        }
+        else{
+            self.path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default()
+        }
+    }
+}
+
+#[derive(Clone,Debug,Default)]
+pub struct LoadReport{
+    pub plugins:Vec<PluginReport>,
+}
+
+impl LoadReport{
+    pub fn new()->Self{
+        Self::default()
+    }
+
+    pub fn conflicts_count(&self)->usize{
+        self.plugins.iter().map(|x| x.conflicts.len()).sum()
+    }
+}
+
+impl Display for LoadReport{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for plugin in self.plugins.iter(){
+            writeln!(f, "{} ({}): {}, {} nodes", plugin.title(), plugin.path.display(), plugin.status, plugin.nodes.len())?;
+            for conflict in plugin.conflicts.iter(){
+                writeln!(f, "    conflict: {}", conflict)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Loads plugin library and passes its nodes to `register`.
+/// `register` returns error message if node cannot be added, for example if its identifier is taken.
+pub fn load_plugin<F:FnMut(CalculationNodeBox)->Result<(),String>>(path:&Path, mut register:F)->PluginReport{
+    if path.extension().and_then(|x| x.to_str())!=Some(std::env::consts::DLL_EXTENSION){
+        return PluginReport::new(path, PluginStatus::Skipped("Not a dynamic library".into()));
+    }
+    let parent_dir = match path.parent().and_then(|x| x.to_str()) {
+        Some(x)=>x,
+        None=>return PluginReport::new(path, PluginStatus::Failed("Bad path".into())),
+    };
+
+    let plugin = (|| {
+        let header = lib_header_from_path(path)?;
+        header.init_root_module::<PadamoModule_Ref>()
+    })();
+    let plugin = match plugin {
+        Ok(p)=>p,
+        Err(e)=>return PluginReport::new(path, PluginStatus::Failed(e.to_string())),
+    };
+
+    let mut report = PluginReport::new(path, PluginStatus::Loaded);
+    report.info = plugin.info().map(|info| info());
+    if let Some(info) = &report.info{
+        if !is_api_compatible(&info.min_api_version, API_VERSION){
+            report.status = PluginStatus::Skipped(format!("Requires API {}, application has {}", info.min_api_version, API_VERSION));
+            return report;
+        }
+    }
+
+    // Caches of plugin are limited by the same memory budget as the rest of application
+    if let Some(share_cache) = plugin.share_cache(){
+        share_cache(crate::lazy_array_operations::cache::cache_budget());
+    }
+    let nodes_fn = plugin.nodes();
+    let mut nodes = nodes_fn(parent_dir.into());
+    for node in nodes.drain(..){
+        let identifier:String = node.identifier().into();
+        match register(node) {
+            Ok(())=>report.nodes.push(identifier),
+            Err(e)=>report.conflicts.push(e),
+        }
+    }
+    report
+}
+
+/// Loads all plugins in `seekdir` and in its direct subdirectories
+pub fn load_plugins<F:FnMut(CalculationNodeBox)->Result<(),String>>(seekdir:&Path, register:&mut F, report:&mut LoadReport){
+    load_plugins_inner(seekdir, register, report, true);
+}
+
+fn load_plugins_inner<F:FnMut(CalculationNodeBox)->Result<(),String>>(seekdir:&Path, register:&mut F, report:&mut LoadReport, look_in_directories:bool){
+    let paths = match std::fs::read_dir(seekdir) {
+        Ok(x)=>x,
+        Err(e)=>{
+            report.plugins.push(PluginReport::new(seekdir, PluginStatus::Failed(format!("Cannot read directory: {}", e))));
+            return;
+        }
+    };
+    let mut paths:Vec<PathBuf> = paths.filter_map(|x| x.ok()).map(|x| x.path()).collect();
+    // Order defines which plugin wins identifier conflict, so it must not depend on file system
+    paths.sort();
+    for p in paths{
+        if p.is_dir(){
+            //NO FULL RECURSIVE SEARCH. Only first layer.
+            if look_in_directories{
+                load_plugins_inner(&p, register, report, false);
+            }
+        }
+        else if p.is_file(){
+            report.plugins.push(load_plugin(&p, &mut *register));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_api_compatibility(){
+        assert!(is_api_compatible("6.0.1", "6.0.1"));
+        assert!(is_api_compatible("6.0.1", "6.1.0"));
+        assert!(!is_api_compatible("6.2.0", "6.1.9"));
+        assert!(!is_api_compatible("6.x", "6.1.9"));
+        assert!(!is_api_compatible("6.0.1", "7.0.0"));
+        assert!(!is_api_compatible("7.0.0", "6.0.1"));
+    }
+}
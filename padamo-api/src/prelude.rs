@@ -11,4 +11,9 @@ pub use crate::PadamoModule;
 pub use crate::PadamoModule_Ref;
 pub use crate::lazy_array_operations::make_lao_box;
 pub use crate::rng::RandomState;
+pub use crate::linalg::Mat4;
 pub use crate::calculation_nodes::node::CalculationNodeArguments;
+pub use crate::calculation_nodes::progress::ProgressToken;
+pub use crate::calculation_nodes::typed_node::{TypedCalculationNode, NodePorts, NodeConstants};
+// Derives share names with traits they implement
+pub use padamo_api_macros_internal::{CalculationNode, NodePorts, NodeConstants};
@@ -2,6 +2,10 @@ pub mod ndim_array;
 pub mod indexing;
 pub use ndim_array::ArrayND;
 pub mod operators;
+pub mod reductions;
+pub use operators::ArrayOpError;
+pub mod view;
+pub use view::{ArrayView, StridedArray};
 
 #[cfg(feature = "nalgebra")]
 pub mod nalgebra_support;
@@ -1,4 +1,4 @@
-use abi_stable::{StableAbi, std_types::RVec, rvec};
+use abi_stable::{StableAbi, std_types::{RSlice, RVec}, rvec};
 
 #[cfg(feature = "ndarray")]
 use ndarray::{IxDyn, OwnedRepr};
@@ -8,6 +8,7 @@ use std::fmt::Debug;
 use std::ops::{IndexMut, Index};
 //use ndarray::prelude::*;
 use super::indexing::ShapeIterator;
+use super::view::ArrayView;
 
 pub fn calculate_offset(shape: &[usize], indices: &[usize]) -> usize {
     if indices.len()!=shape.len(){
@@ -103,16 +104,7 @@ where
 
 
     pub fn extract_dataset(&self, reference:&Vec<usize>)->Vec<T>{
-        let mut index = vec![0usize];
-        index.extend_from_slice(reference);
-        let length = self.shape[0];
-        let mut res = Vec::with_capacity(length);
-        for i in 0..length{
-            index[0] = i;
-            let v = self[&index].clone();
-            res.push(v);
-        }
-        res
+        self.pixel_series(reference).collect()
     }
 
     fn remap_indices(&self,indices:&[usize])->usize{
@@ -173,7 +165,23 @@ where
     pub fn to_ndarray(self)->ndarray::ArrayBase<OwnedRepr<T>,IxDyn>{
         let shape = IxDyn(&self.shape.to_vec());
         //println!("CONV {:?} {:?}",self.shape,&self.flat_data.len());
-        ndarray::ArrayBase::from_shape_vec(shape, self.flat_data.to_vec()).unwrap()
+        ndarray::ArrayBase::from_shape_vec(shape, self.flat_data.into_vec()).unwrap()
+    }
+
+    /// Zero-copy `ndarray` view of array
+    #[cfg(feature = "ndarray")]
+    pub fn as_ndarray_view(&self)->ndarray::ArrayViewD<'_,T>{
+        ndarray::ArrayView::from_shape(IxDyn(&self.shape), &self.flat_data).unwrap()
+    }
+
+    /// Borrowed strided view of whole array
+    pub fn view(&self)->ArrayView<'_,T>{
+        ArrayView::from_slice(&self.flat_data, self.shape.to_vec())
+    }
+
+    /// Time series of pixel, i.e. values along first axis at index `pixel` of other axes. Data is not copied.
+    pub fn pixel_series(&self, pixel:&[usize])->ArrayStrideIterator<'_,T>{
+        self.view().lane(pixel)
     }
 
     pub fn flatten(self)->Self{
@@ -198,11 +206,10 @@ where
     }
 
 
+    /// Converts elements in one pass
     pub fn cast<U:From<T>+Clone+abi_stable::StableAbi>(self)->ArrayND<U>{
-        let shape = self.shape;
-        let mut old_flat_data = self.flat_data;
-        let flat_data:RVec<U> = old_flat_data.drain(..).map(|x| x.into()).collect();
-        ArrayND { flat_data, shape }
+        let flat_data:RVec<U> = self.flat_data.into_iter().map(U::from).collect();
+        ArrayND { flat_data, shape:self.shape }
     }
 
     pub fn stack(data:&[ArrayND<T>])-> ArrayND<T> {
@@ -225,38 +232,18 @@ where
     }
 
     pub fn flip_indices(&self)->ArrayND<T>{
-        let shape:RVec<usize> = self.shape.as_ref().iter().cloned().rev().collect::<Vec<usize>>().into();
-        let mut res = self.clone();
-        res.shape = shape;
-        for index in self.enumerate(){
-            let target_index:Vec<usize> = index.iter().cloned().rev().collect();
-            res.set(&target_index, self[&index].clone());
-        }
-        res
+        self.view().transpose().to_owned()
     }
 
+    /// Removes first frame. Remaining frames are shifted, so use `view().frames()` to go through all frames without copying.
     pub fn take_frame(&mut self)->Option<Self>{
-        if self.shape.len()==0{
+        if self.shape.first().is_none_or(|x| *x==0) || self.flat_data.len()<self.frame_size()*self.shape[0]{
             return None;
         }
-        if self.shape[0]==0{
-            return None;
-        }
-
-        let new_shape:Vec<usize> = self.shape.as_slice().iter().skip(1).copied().collect();
-        let frame_size:usize = new_shape.iter().product();
-        // println!("Frame size {}", frame_size);
-
-        // println!("Frame size {}", frame_size);
-
-        let flat_part:RVec<T> = self.flat_data.drain(..frame_size).collect();
-        if flat_part.len()==frame_size{
-            self.shape[0] -= 1;
-            Some(ArrayND { flat_data:flat_part, shape: new_shape.into() })
-        }
-        else{
-            None
-        }
+        let frame = self.view().index_axis(0, 0).to_owned();
+        self.flat_data.drain(..frame.flat_data.len());
+        self.shape[0] -= 1;
+        Some(frame)
     }
 
     pub fn make_pixel_iterators<'a>(&'a self)->ArrayND<ArrayStrideIterator<'a, T>>{
@@ -266,11 +253,9 @@ where
         let new_shape:Vec<usize> = self.shape.iter().skip(1).map(|x|*x).collect();
 
         let stride = new_shape.iter().fold(1, |a,b| a*b);
-        let mut res = ArrayND::new(new_shape.clone(), ArrayStrideIterator::new(self, 0, 0));
-        for pixel_id in res.enumerate(){
-            let offset = calculate_offset(&new_shape, &pixel_id);
-            res[&pixel_id] = ArrayStrideIterator::new(self, stride, offset);
-        }
+        let length = self.shape[0];
+        let flat_data:RVec<ArrayStrideIterator<'a, T>> = (0..stride).map(|offset| ArrayStrideIterator::from_slice(&self.flat_data, stride, offset, length)).collect();
+        let res = ArrayND { flat_data, shape: new_shape.into() };
 
         // let mut flat_data:Vec<ArrayStrideIterator<'a, T>> = Vec::with_capacity(stride);
         // for pixel_id in super::indexing::ShapeIterator::new(new_shape.clone()){
@@ -407,17 +392,26 @@ where
     }
 }
 
+/// Iterator over every `stride`-th element of data starting from `offset`
 #[repr(C)]
 #[derive(Clone, StableAbi)]
 pub struct ArrayStrideIterator<'a, T:Clone+StableAbi>{
-    array: &'a ArrayND<T>,
+    data: RSlice<'a, T>,
     stride:usize,
     offset:usize,
+    remaining:usize,
 }
 
 impl<'a, T: Clone + StableAbi> ArrayStrideIterator<'a, T> {
+    /// Iterates data of array until its end
     pub fn new(array: &'a ArrayND<T>, stride: usize, offset:usize) -> Self {
-        Self { array, stride, offset }
+        let remaining = if stride==0 {usize::MAX} else {array.flat_data.len().saturating_sub(offset).div_ceil(stride)};
+        Self::from_slice(&array.flat_data, stride, offset, remaining)
+    }
+
+    /// Iterates `count` elements of data
+    pub fn from_slice(data: &'a [T], stride: usize, offset:usize, count:usize) -> Self {
+        Self { data:data.into(), stride, offset, remaining:count }
     }
 }
 
@@ -425,8 +419,17 @@ impl<'a, T: Clone + StableAbi> ArrayStrideIterator<'a, T> {
 impl<'a, T: Clone + StableAbi> Iterator for ArrayStrideIterator<'a, T>{
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
-        let v = self.array.flat_data.get(self.offset);
+        if self.remaining==0{
+            return None;
+        }
+        let v = self.data.get(self.offset);
         self.offset += self.stride;
+        self.remaining -= 1;
         v.map(std::clone::Clone::clone)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = if self.stride==0 {self.remaining} else {self.data.len().saturating_sub(self.offset).div_ceil(self.stride).min(self.remaining)};
+        (left, Some(left))
+    }
 }
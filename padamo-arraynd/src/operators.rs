@@ -1,12 +1,118 @@
+//! Elementwise arithmetic of arrays.
+//! Shapes are broadcast by NumPy rules: shapes are aligned by last axes, missing leading axes and axes of length 1 are repeated.
+//! For example signal of shape (T,H,W) may be divided by map of shape (H,W).
 use std::ops::{Add, Sub, Mul, Div};
 
 use abi_stable::{StableAbi, std_types::RVec};
 
 use super::ndim_array::ArrayND;
 
+/// Error of array operation with incompatible arguments
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum ArrayOpError{
+    /// Shapes cannot be broadcast together
+    IncompatibleShapes(Vec<usize>,Vec<usize>),
+    AxisOutOfRange{axis:usize, ndim:usize},
+    /// Reduction without identity (min, max, ...) over axis of zero length
+    EmptyAxis(usize),
+    ReshapeSize{from:Vec<usize>, to:Vec<usize>},
+}
+
+impl std::fmt::Display for ArrayOpError{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IncompatibleShapes(a,b)=>write!(f, "Arrays of shapes {:?} and {:?} cannot be broadcast together", a, b),
+            Self::AxisOutOfRange { axis, ndim }=>write!(f, "Axis {} is out of range for array with {} dimensions", axis, ndim),
+            Self::EmptyAxis(axis)=>write!(f, "Cannot reduce empty axis {}", axis),
+            Self::ReshapeSize { from, to }=>write!(f, "Cannot reshape array of shape {:?} to {:?}", from, to),
+        }
+    }
+}
+
+impl std::error::Error for ArrayOpError{}
+
+/// Shape of result of elementwise operation on arrays of shapes `a` and `b`
+pub fn broadcast_shape(a:&[usize], b:&[usize])->Result<Vec<usize>,ArrayOpError>{
+    let ndim = a.len().max(b.len());
+    let mut res = Vec::with_capacity(ndim);
+    for i in 0..ndim{
+        // Missing leading axes have length 1
+        let x = if i+a.len()>=ndim {a[i+a.len()-ndim]} else {1};
+        let y = if i+b.len()>=ndim {b[i+b.len()-ndim]} else {1};
+        if x==y || y==1{
+            res.push(x);
+        }
+        else if x==1{
+            res.push(y);
+        }
+        else{
+            return Err(ArrayOpError::IncompatibleShapes(a.to_vec(), b.to_vec()));
+        }
+    }
+    Ok(res)
+}
+
+impl<T> ArrayND<T>
+where
+    T: Clone + StableAbi
+{
+    /// Applies `f` to pairs of elements of broadcast arrays
+    pub fn try_zip_with<U,R,F>(&self, other:&ArrayND<U>, mut f:F)->Result<ArrayND<R>,ArrayOpError>
+    where
+        U:Clone+StableAbi,
+        R:Clone+StableAbi,
+        F:FnMut(&T,&U)->R
+    {
+        let shape = broadcast_shape(&self.shape, &other.shape)?;
+        let flat_data:RVec<R> = if self.is_compatible(other){
+            self.flat_data.iter().zip(other.flat_data.iter()).map(|(a,b)| f(a,b)).collect()
+        }
+        else{
+            let a = self.view().broadcast(&shape).expect("Shape is checked");
+            let b = other.view().broadcast(&shape).expect("Shape is checked");
+            a.iter().zip(b.iter()).map(|(a,b)| f(a,b)).collect()
+        };
+        Ok(ArrayND { flat_data, shape: shape.into() })
+    }
+
+    /// Applies `f` to every element
+    pub fn map<R:Clone+StableAbi,F:FnMut(&T)->R>(&self, f:F)->ArrayND<R>{
+        ArrayND { flat_data: self.flat_data.iter().map(f).collect(), shape: self.shape.clone() }
+    }
+
+    /// Reshapes array keeping C order of elements
+    pub fn try_reshape(self, shape:Vec<usize>)->Result<Self,ArrayOpError>{
+        if shape.iter().product::<usize>()!=self.flat_data.len(){
+            return Err(ArrayOpError::ReshapeSize { from: self.shape.into(), to: shape });
+        }
+        Ok(Self { flat_data: self.flat_data, shape: shape.into() })
+    }
+}
 
 macro_rules! implement_elementwise_ndarray {
-    ($traitid:ident, $inner_funcname:ident) => {
+    ($traitid:ident, $inner_funcname:ident, $try_funcname:ident) => {
+        impl<T> ArrayND<T>
+        where
+            T:Clone+StableAbi
+        {
+            #[doc = concat!("Broadcasting `", stringify!($inner_funcname), "` returning error for incompatible shapes instead of panic")]
+            pub fn $try_funcname<U>(self, rhs:ArrayND<U>)->Result<ArrayND<T>,ArrayOpError>
+            where
+                T:$traitid<U, Output=T>,
+                U:Clone+StableAbi
+            {
+                if self.is_compatible(&rhs){
+                    // Same shape, reuse buffer of left array
+                    let mut newdata:RVec<T> = self.flat_data;
+                    for (a,b) in newdata.iter_mut().zip(rhs.flat_data.into_iter()){
+                        *a = $traitid::$inner_funcname(a.clone(), b);
+                    }
+                    return Ok(ArrayND { flat_data:newdata, shape:self.shape });
+                }
+                self.try_zip_with(&rhs, |a,b| $traitid::$inner_funcname(a.clone(), b.clone()))
+            }
+        }
+
         impl<T,U> $traitid<ArrayND<U>> for ArrayND<T>
         where
             T:Clone+StableAbi+$traitid<U, Output=T>,
@@ -15,25 +121,68 @@ macro_rules! implement_elementwise_ndarray {
             type Output = ArrayND<T>;
 
             fn $inner_funcname(self, rhs: ArrayND<U>) -> Self::Output {
-                if self.is_compatible(&rhs){
-                    let mut newdata:RVec<T> = self.flat_data;//RVec::with_capacity(self.flat_data.len());
-                    let mut auxdata = rhs.flat_data;
-                    for (i,v) in auxdata.drain(..).enumerate(){
-                        newdata[i] = $traitid::$inner_funcname (newdata[i].clone(),v);
-                        //newdata.push($traitid::$inner_funcname (self.flat_data[i].clone(), rhs.flat_data[i].clone()));
-                        //newdata.push(a.flat_data[i].clone()+b.flat_data[i].clone())
-                    }
-                    return Self::Output{flat_data:newdata, shape:self.shape.clone()};
-                }
-                else{
-                    panic!("Incompatible arrays operation")
+                match self.$try_funcname(rhs){
+                    Ok(v)=>v,
+                    Err(e)=>panic!("Incompatible arrays operation: {}", e),
                 }
             }
         }
     };
 }
 
-implement_elementwise_ndarray!{Add, add}
-implement_elementwise_ndarray!{Sub, sub}
-implement_elementwise_ndarray!{Mul, mul}
-implement_elementwise_ndarray!{Div, div}
+implement_elementwise_ndarray!{Add, add, try_add}
+implement_elementwise_ndarray!{Sub, sub, try_sub}
+implement_elementwise_ndarray!{Mul, mul, try_mul}
+implement_elementwise_ndarray!{Div, div, try_div}
+
+// Scalars are listed explicitly: blanket impl for T would overlap with array-array impls
+macro_rules! implement_scalar_ndarray {
+    ($traitid:ident, $inner_funcname:ident, $($scalar:ty),*) => {
+        $(
+            impl $traitid<$scalar> for ArrayND<$scalar>{
+                type Output = ArrayND<$scalar>;
+
+                fn $inner_funcname(mut self, rhs: $scalar) -> Self::Output {
+                    self.flat_data.iter_mut().for_each(|x| *x = $traitid::$inner_funcname(*x, rhs));
+                    self
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! implement_scalar_ops {
+    ($($scalar:ty),*) => {
+        implement_scalar_ndarray!{Add, add, $($scalar),*}
+        implement_scalar_ndarray!{Sub, sub, $($scalar),*}
+        implement_scalar_ndarray!{Mul, mul, $($scalar),*}
+        implement_scalar_ndarray!{Div, div, $($scalar),*}
+    };
+}
+
+implement_scalar_ops!{f32, f64, i8, i16, i32, i64, u8, u16, u32, u64, usize}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_broadcast_shape(){
+        assert_eq!(broadcast_shape(&[5,2,3], &[2,3]), Ok(vec![5,2,3]));
+        assert_eq!(broadcast_shape(&[5,1,3], &[4,1]), Ok(vec![5,4,3]));
+        assert!(broadcast_shape(&[5,2,3], &[3,2]).is_err());
+    }
+
+    #[test]
+    fn test_broadcast_ops(){
+        // 2 frames of 2x2 pixels divided by 2x2 map
+        let signal:ArrayND<f64> = ArrayND { flat_data: vec![2.0,4.0,6.0,8.0, 4.0,8.0,12.0,16.0].into(), shape: vec![2,2,2].into() };
+        let map = ArrayND { flat_data: vec![2.0,4.0,6.0,8.0].into(), shape: vec![2,2].into() };
+        let res = signal.clone()/map.clone();
+        assert_eq!(res.shape.as_slice(), &[2,2,2]);
+        assert_eq!(res.flat_data.as_slice(), &[1.0,1.0,1.0,1.0, 2.0,2.0,2.0,2.0]);
+        assert_eq!((signal.clone()*0.5).flat_data.as_slice(), &[1.0,2.0,3.0,4.0, 2.0,4.0,6.0,8.0]);
+        let wrong = ArrayND { flat_data: vec![1.0;3].into(), shape: vec![3].into() };
+        assert!(signal.try_add(wrong).is_err());
+    }
+}
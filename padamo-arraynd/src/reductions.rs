@@ -0,0 +1,128 @@
+//! Reductions of arrays along axis. Reduced axis is removed from shape of result,
+//! e.g. `mean_axis(0)` of signal with shape (T,H,W) gives average frame with shape (H,W).
+use std::ops::Add;
+
+use abi_stable::StableAbi;
+
+use super::ndim_array::{ArrayND, ArrayStrideIterator};
+use super::operators::ArrayOpError;
+
+impl<T> ArrayND<T>
+where
+    T: Clone + StableAbi
+{
+    /// Applies `f` to every lane of values along `axis`
+    pub fn reduce_axis<R,F>(&self, axis:usize, mut f:F)->Result<ArrayND<R>,ArrayOpError>
+    where
+        R:Clone+StableAbi,
+        F:FnMut(ArrayStrideIterator<'_,T>)->R
+    {
+        if axis>=self.shape.len(){
+            return Err(ArrayOpError::AxisOutOfRange { axis, ndim: self.shape.len() });
+        }
+        let length = self.shape[axis];
+        let inner:usize = self.shape[axis+1..].iter().product();
+        let outer:usize = self.shape[..axis].iter().product();
+        let mut flat_data = Vec::with_capacity(outer*inner);
+        // Lanes are visited in C order of remaining axes
+        for o in 0..outer{
+            for i in 0..inner{
+                flat_data.push(f(ArrayStrideIterator::from_slice(&self.flat_data, inner, o*length*inner+i, length)));
+            }
+        }
+        let mut shape:Vec<usize> = self.shape.clone().into();
+        shape.remove(axis);
+        Ok(ArrayND { flat_data: flat_data.into(), shape: shape.into() })
+    }
+
+    /// Reduction without identity element. Fails on empty axis.
+    fn reduce_nonempty<F:FnMut(T,T)->T>(&self, axis:usize, mut f:F)->Result<ArrayND<T>,ArrayOpError>{
+        if self.shape.get(axis)==Some(&0){
+            return Err(ArrayOpError::EmptyAxis(axis));
+        }
+        self.reduce_axis(axis, |lane| lane.reduce(&mut f).expect("Axis is not empty"))
+    }
+
+    pub fn sum_axis(&self, axis:usize)->Result<ArrayND<T>,ArrayOpError>
+    where
+        T:Add<Output=T>+Default
+    {
+        self.reduce_axis(axis, |lane| lane.fold(T::default(), |a,b| a+b))
+    }
+
+    pub fn min_axis(&self, axis:usize)->Result<ArrayND<T>,ArrayOpError>
+    where
+        T:PartialOrd
+    {
+        self.reduce_nonempty(axis, |a,b| if b<a {b} else {a})
+    }
+
+    pub fn max_axis(&self, axis:usize)->Result<ArrayND<T>,ArrayOpError>
+    where
+        T:PartialOrd
+    {
+        self.reduce_nonempty(axis, |a,b| if b>a {b} else {a})
+    }
+
+    /// Index of first maximum along `axis`
+    pub fn argmax_axis(&self, axis:usize)->Result<ArrayND<usize>,ArrayOpError>
+    where
+        T:PartialOrd
+    {
+        if self.shape.get(axis)==Some(&0){
+            return Err(ArrayOpError::EmptyAxis(axis));
+        }
+        self.reduce_axis(axis, |lane|{
+            let mut lane = lane.enumerate();
+            let first = lane.next().expect("Axis is not empty");
+            lane.fold(first, |a,b| if b.1>a.1 {b} else {a}).0
+        })
+    }
+}
+
+impl ArrayND<f64>{
+    /// Mean along `axis`. Empty axis gives NaN.
+    pub fn mean_axis(&self, axis:usize)->Result<ArrayND<f64>,ArrayOpError>{
+        let n = self.shape.get(axis).copied().unwrap_or(0) as f64;
+        self.reduce_axis(axis, |lane| lane.sum::<f64>()/n)
+    }
+
+    /// Population standard deviation (without Bessel correction) along `axis`
+    pub fn std_axis(&self, axis:usize)->Result<ArrayND<f64>,ArrayOpError>{
+        let n = self.shape.get(axis).copied().unwrap_or(0) as f64;
+        self.reduce_axis(axis, |lane|{
+            let (s,s2) = lane.fold((0.0,0.0), |(s,s2),x| (s+x, s2+x*x));
+            let mean = s/n;
+            (s2/n-mean*mean).max(0.0).sqrt()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn sample()->ArrayND<f64>{
+        // 3 frames of 2x2 pixels
+        ArrayND { flat_data: vec![1.0,2.0,3.0,4.0, 3.0,6.0,1.0,0.0, 5.0,4.0,2.0,8.0].into(), shape: vec![3,2,2].into() }
+    }
+
+    #[test]
+    fn test_time_reductions(){
+        let a = sample();
+        assert_eq!(a.sum_axis(0).unwrap().flat_data.as_slice(), &[9.0,12.0,6.0,12.0]);
+        assert_eq!(a.mean_axis(0).unwrap().flat_data.as_slice(), &[3.0,4.0,2.0,4.0]);
+        assert_eq!(a.max_axis(0).unwrap().shape.as_slice(), &[2,2]);
+        assert_eq!(a.argmax_axis(0).unwrap().flat_data.as_slice(), &[2,1,0,2]);
+        let std = a.std_axis(0).unwrap();
+        assert!((std[&vec![0,0]]-(8.0f64/3.0).sqrt()).abs()<1e-12);
+    }
+
+    #[test]
+    fn test_frame_reductions(){
+        let frames = sample().try_reshape(vec![3,4]).unwrap();
+        assert_eq!(frames.max_axis(1).unwrap().flat_data.as_slice(), &[4.0,6.0,8.0]);
+        assert_eq!(frames.min_axis(1).unwrap().flat_data.as_slice(), &[1.0,0.0,2.0]);
+        assert!(frames.sum_axis(2).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+//! Strided views of arrays. Slicing, transposing and taking frames or pixels of view does not copy data.
+use std::ops::Range;
+use std::sync::Arc;
+
+use abi_stable::StableAbi;
+use abi_stable::std_types::RVec;
+
+use super::ndim_array::{ArrayND, ArrayStrideIterator};
+
+/// Strides of C-order (row major) array with given shape
+pub fn c_strides(shape:&[usize])->Vec<usize>{
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev(){
+        strides[i] = strides[i+1]*shape[i+1];
+    }
+    strides
+}
+
+/// Borrowed strided view of array data
+#[derive(Debug)]
+pub struct ArrayView<'a,T>{
+    data:&'a [T],
+    offset:usize,
+    shape:Vec<usize>,
+    strides:Vec<usize>,
+}
+
+// Derive would require T:Clone
+impl<'a,T> Clone for ArrayView<'a,T>{
+    fn clone(&self) -> Self {
+        Self { data: self.data, offset: self.offset, shape: self.shape.clone(), strides: self.strides.clone() }
+    }
+}
+
+impl<'a,T> ArrayView<'a,T>{
+    /// View of C-order data with given shape
+    pub fn from_slice(data:&'a [T], shape:Vec<usize>)->Self{
+        let size:usize = shape.iter().product();
+        if size>data.len(){
+            panic!("Data of length {} is too short for shape {:?}", data.len(), shape);
+        }
+        let strides = c_strides(&shape);
+        Self { data, offset: 0, shape, strides }
+    }
+
+    pub fn shape(&self)->&[usize]{
+        &self.shape
+    }
+
+    /// Strides in elements
+    pub fn strides(&self)->&[usize]{
+        &self.strides
+    }
+
+    pub fn ndim(&self)->usize{
+        self.shape.len()
+    }
+
+    pub fn len(&self)->usize{
+        self.shape.iter().product()
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.len()==0
+    }
+
+    fn flat_index(&self, index:&[usize])->Option<usize>{
+        if index.len()!=self.shape.len() || index.iter().zip(self.shape.iter()).any(|(i,n)| i>=n){
+            return None;
+        }
+        Some(self.offset+index.iter().zip(self.strides.iter()).map(|(i,s)| i*s).sum::<usize>())
+    }
+
+    pub fn get(&self, index:&[usize])->Option<&'a T>{
+        self.flat_index(index).map(|i| &self.data[i])
+    }
+
+    /// Keeps only `range` of indices along `axis`
+    pub fn slice_axis(mut self, axis:usize, range:Range<usize>)->Self{
+        if range.start>range.end || range.end>self.shape[axis]{
+            panic!("Range {:?} is out of bounds of axis {} with length {}", range, axis, self.shape[axis]);
+        }
+        if range.start<range.end{
+            self.offset += range.start*self.strides[axis];
+        }
+        self.shape[axis] = range.end-range.start;
+        self
+    }
+
+    /// Takes subview at `index` along `axis`. Resulting view has one dimension less.
+    pub fn index_axis(mut self, axis:usize, index:usize)->Self{
+        if index>=self.shape[axis]{
+            panic!("Index {} is out of bounds of axis {} with length {}", index, axis, self.shape[axis]);
+        }
+        self.offset += index*self.strides[axis];
+        self.shape.remove(axis);
+        self.strides.remove(axis);
+        self
+    }
+
+    /// Reorders axes: axis `i` of result is axis `axes[i]` of source
+    pub fn permute_axes(self, axes:&[usize])->Self{
+        let mut used = vec![false; self.ndim()];
+        if axes.len()!=self.ndim() || axes.iter().any(|a| *a>=used.len() || std::mem::replace(&mut used[*a], true)){
+            panic!("{:?} is not permutation of {} axes", axes, self.ndim());
+        }
+        let shape = axes.iter().map(|a| self.shape[*a]).collect();
+        let strides = axes.iter().map(|a| self.strides[*a]).collect();
+        Self { data: self.data, offset: self.offset, shape, strides }
+    }
+
+    /// Repeats view to `shape` by NumPy rules: missing leading axes and axes of length 1 are stretched without copying.
+    pub fn broadcast(self, shape:&[usize])->Option<Self>{
+        if shape.len()<self.ndim(){
+            return None;
+        }
+        let lead = shape.len()-self.ndim();
+        let mut strides = vec![0; shape.len()];
+        for (i,(n,s)) in self.shape.iter().zip(self.strides.iter()).enumerate(){
+            let target = shape[lead+i];
+            if *n==target{
+                strides[lead+i] = *s;
+            }
+            else if *n!=1{
+                return None;
+            }
+        }
+        Some(Self { data: self.data, offset: self.offset, shape: shape.to_vec(), strides })
+    }
+
+    /// Reverses order of axes
+    pub fn transpose(mut self)->Self{
+        self.shape.reverse();
+        self.strides.reverse();
+        self
+    }
+
+    /// Views of subarrays along first axis, e.g. frames of signal
+    pub fn frames(&self)->impl Iterator<Item = ArrayView<'a,T>> + '_{
+        let count = self.shape.first().copied().unwrap_or(0);
+        (0..count).map(|i| self.clone().index_axis(0, i))
+    }
+
+    /// Values along first axis at index `rest` of other axes, e.g. time series of pixel
+    pub fn lane(&self, rest:&[usize])->ArrayStrideIterator<'a,T>
+    where
+        T:Clone+StableAbi
+    {
+        if self.ndim()==0 || rest.len()+1!=self.ndim(){
+            panic!("Index {:?} is not compatible with shape {:?}", rest, self.shape);
+        }
+        let count = self.shape[0];
+        let mut index = vec![0];
+        index.extend_from_slice(rest);
+        let start = if count>0 {self.flat_index(&index).expect("Index is out of bounds")} else {self.offset};
+        ArrayStrideIterator::from_slice(self.data, self.strides[0], start, count)
+    }
+
+    /// Data as slice if view is contiguous C-order array
+    pub fn as_slice(&self)->Option<&'a [T]>{
+        if self.strides==c_strides(&self.shape){
+            Some(&self.data[self.offset..self.offset+self.len()])
+        }
+        else{
+            None
+        }
+    }
+
+    /// Iterates elements in C order (last axis changes first)
+    pub fn iter(&self)->ViewIter<'a,'_,T>{
+        ViewIter { view: self, index: vec![0; self.ndim()], remaining: self.len() }
+    }
+}
+
+impl<'a,T:Clone+StableAbi> ArrayView<'a,T>{
+    /// Copies viewed data into new contiguous array
+    pub fn to_owned(&self)->ArrayND<T>{
+        let flat_data:RVec<T> = match self.as_slice(){
+            Some(s)=>s.into(),
+            None=>self.iter().cloned().collect(),
+        };
+        ArrayND { flat_data, shape: self.shape.clone().into() }
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<'a,T> ArrayView<'a,T>{
+    /// Zero-copy `ndarray` view
+    pub fn to_ndarray_view(&self)->ndarray::ArrayViewD<'a,T>{
+        use ndarray::ShapeBuilder;
+        let shape = ndarray::IxDyn(&self.shape).strides(ndarray::IxDyn(&self.strides));
+        let data:&'a [T] = self.data;
+        ndarray::ArrayView::from_shape(shape, &data[self.offset.min(data.len())..]).expect("View is always within data")
+    }
+}
+
+pub struct ViewIter<'a,'b,T>{
+    view:&'b ArrayView<'a,T>,
+    index:Vec<usize>,
+    remaining:usize,
+}
+
+impl<'a,'b,T> Iterator for ViewIter<'a,'b,T>{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining==0{
+            return None;
+        }
+        self.remaining -= 1;
+        let res = self.view.get(&self.index);
+        for axis in (0..self.index.len()).rev(){
+            self.index[axis] += 1;
+            if self.index[axis]<self.view.shape[axis]{
+                break;
+            }
+            self.index[axis] = 0;
+        }
+        res
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a,'b,T> ExactSizeIterator for ViewIter<'a,'b,T>{}
+
+/// Owned strided view. Data is shared between clones, so slicing and transposing does not copy it.
+#[derive(Clone,Debug)]
+pub struct StridedArray<T>{
+    data:Arc<RVec<T>>,
+    offset:usize,
+    shape:Vec<usize>,
+    strides:Vec<usize>,
+}
+
+impl<T:Clone+StableAbi> StridedArray<T>{
+    pub fn view(&self)->ArrayView<'_,T>{
+        ArrayView { data: &self.data, offset: self.offset, shape: self.shape.clone(), strides: self.strides.clone() }
+    }
+
+    fn with_view<F:FnOnce(ArrayView<'_,T>)->ArrayView<'_,T>>(self, f:F)->Self{
+        let (offset,shape,strides) = {
+            let v = f(self.view());
+            (v.offset, v.shape, v.strides)
+        };
+        Self { data: self.data, offset, shape, strides }
+    }
+
+    pub fn shape(&self)->&[usize]{
+        &self.shape
+    }
+
+    pub fn slice_axis(self, axis:usize, range:Range<usize>)->Self{
+        self.with_view(|v| v.slice_axis(axis, range))
+    }
+
+    pub fn index_axis(self, axis:usize, index:usize)->Self{
+        self.with_view(|v| v.index_axis(axis, index))
+    }
+
+    pub fn permute_axes(self, axes:&[usize])->Self{
+        self.with_view(|v| v.permute_axes(axes))
+    }
+
+    pub fn transpose(self)->Self{
+        self.with_view(|v| v.transpose())
+    }
+
+    /// Converts back to array. Data is copied only if it is shared or is not contiguous.
+    pub fn into_array(self)->ArrayND<T>{
+        let whole = self.offset==0 && self.strides==c_strides(&self.shape) && self.view().len()==self.data.len();
+        if whole{
+            match Arc::try_unwrap(self.data){
+                Ok(flat_data)=>return ArrayND { flat_data, shape: self.shape.into() },
+                Err(data)=>return ArrayView::from_slice(&data, self.shape).to_owned(),
+            }
+        }
+        self.view().to_owned()
+    }
+}
+
+impl<T:Clone+StableAbi> From<ArrayND<T>> for StridedArray<T>{
+    fn from(value: ArrayND<T>) -> Self {
+        let shape:Vec<usize> = value.shape.into();
+        let strides = c_strides(&shape);
+        Self { data: Arc::new(value.flat_data), offset: 0, shape, strides }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn sample()->ArrayND<usize>{
+        // 4 frames of 2x3 pixels
+        ArrayND { flat_data: (0..24).collect(), shape: vec![4,2,3].into() }
+    }
+
+    #[test]
+    fn test_slicing(){
+        let array = sample();
+        let view = array.view().slice_axis(0, 1..3).index_axis(2, 1);
+        assert_eq!(view.shape(), &[2,2]);
+        assert_eq!(view.iter().copied().collect::<Vec<_>>(), vec![7,10,13,16]);
+        assert!(view.as_slice().is_none());
+        assert_eq!(array.view().index_axis(0, 2).as_slice(), Some(&array.flat_data[12..18]));
+    }
+
+    #[test]
+    fn test_transpose(){
+        let array = sample();
+        let transposed = array.view().transpose().to_owned();
+        assert_eq!(transposed.shape.as_slice(), &[3,2,4]);
+        assert_eq!(transposed[&vec![2,1,3]], array[&vec![3,1,2]]);
+        assert_eq!(StridedArray::from(transposed).transpose().into_array().flat_data, array.flat_data);
+    }
+
+    #[test]
+    fn test_frames_and_lanes(){
+        let array = sample();
+        let sums:Vec<usize> = array.view().frames().map(|f| f.iter().sum()).collect();
+        assert_eq!(sums, vec![15,51,87,123]);
+        assert_eq!(array.pixel_series(&[1,2]).collect::<Vec<_>>(), vec![5,11,17,23]);
+    }
+}
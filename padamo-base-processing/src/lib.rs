@@ -28,5 +28,5 @@ pub fn nodes(_library_dir:RString)->RVec<CalculationNodeBox>{
 
 #[export_root_module]
 pub fn plugin_root()->PadamoModule_Ref{
-    PadamoModule{nodes}.leak_into_prefix()
+    PadamoModule{nodes, share_cache:padamo_api::share_cache, info:padamo_api::plugin_info!()}.leak_into_prefix()
 }
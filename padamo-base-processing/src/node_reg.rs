@@ -34,7 +34,7 @@ impl SlidingQuantileNode{
 
         let bg = LazySlidingQuantile::new(source.0.clone(), window, quantile);
         let bg = LazyArrayOperationBox::from_value(bg, TD_Opaque);
-        let bg = crate::padding::make_padding(bg, window/2, window-window/2-1);
+        let bg = crate::padding::make_padding(bg, window/2, window-window/2-1)?;
         let bg = bg.cached();
 
         //let cut_signal = LazySkipper::new(source.0, window);
@@ -122,7 +122,7 @@ impl SlidingQuantileNodeNormalizer{
         }
 
         let norm = LazySlidingQuantileNormalize::new(source.0.clone(), window, quantile,gauss, variance);
-        let norm = crate::padding::make_padding(make_lao_box(norm), window/2, window-window/2-1);
+        let norm = crate::padding::make_padding(make_lao_box(norm), window/2, window-window/2-1)?;
         let norm = norm.cached();
 
 
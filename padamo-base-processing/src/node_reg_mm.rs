@@ -26,7 +26,7 @@ impl SlidingMedianNode{
 
         let bg = LazySlidingMedian::new(source.0.clone(), window);
         //let bg = LazyArrayOperationBox::from_value(bg, TD_Opaque);
-        let bg = crate::padding::make_padding(make_lao_box(bg), window/2, window-window/2-1);
+        let bg = crate::padding::make_padding(make_lao_box(bg), window/2, window-window/2-1)?;
         let bg = bg.cached();
 
         let cut_signal = make_lao_box(source.0);//LazySkipper::new(source.0, window);
@@ -111,7 +111,7 @@ impl SlidingMedianNodeNormalizer{
 
         let norm = LazySlidingMedianNormalize::new(source.0.clone(), window, gauss, variance);
         let norm = make_lao_box(norm);
-        let norm = crate::padding::make_padding(norm, window/2, window-window/2-1);
+        let norm = crate::padding::make_padding(norm, window/2, window-window/2-1)?;
         let norm = norm.cached();
 
 
@@ -3,6 +3,8 @@ use std::sync::{Arc, Mutex};
 
 use padamo_api::lazy_array_operations::{LazyArrayOperation, LazyDetectorSignal, LazyArrayOperationBox};
 use padamo_api::lazy_array_operations::ndim_array::ArrayND;
+use padamo_api::prelude::ExecutionError;
+use abi_stable::std_types::RResult;
 use rayon::prelude::*;
 
 use standalone_quantiles::slide_quantile;
@@ -22,22 +24,14 @@ impl LazySlidingQuantile{
 
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazySlidingQuantile{
-    fn length(&self,) -> usize where {
-        self.source.length()-self.window+1
-    }
-
-    fn calculate_overhead(&self,start:usize,end:usize) -> usize{
-        2*(end-start)+self.window-1
-    }
-
-    fn request_range(&self,start:usize,end:usize) -> ArrayND<f64> {
+impl LazySlidingQuantile{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
         //let time_start = Instant::now();
         let range_start = start;
         let range_end = end+self.window-1;
         //let range_len = range_end - range_start;
 
-        let sourced: ArrayND<f64> = self.source.request_range(range_start,range_end);
+        let sourced: ArrayND<f64> = self.source.try_request_range(range_start,range_end).into_result()?;
 
         let mut target_shape = sourced.shape.clone();
         target_shape[0] = end-start;
@@ -80,7 +74,25 @@ impl LazyArrayOperation<ArrayND<f64>> for LazySlidingQuantile{
         // let res:ArrayND<f64> = preres.into();
         //println!("Calculated sliding median in {:.2?}", time_start.elapsed());
         //println!("SHAPE OF RESULT {:?}", &res.shape);
-        Arc::try_unwrap(res).unwrap().into_inner().unwrap()
+        Ok(Arc::try_unwrap(res).unwrap().into_inner().unwrap())
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazySlidingQuantile{
+    fn length(&self,) -> usize where {
+        self.source.length()-self.window+1
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize) -> usize{
+        2*(end-start)+self.window-1
+    }
+
+    fn request_range(&self,start:usize,end:usize) -> ArrayND<f64> {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
 
@@ -116,6 +128,10 @@ where
     fn request_range(&self,start:usize,end:usize) -> T {
         self.source.request_range(start+self.window/2,end+self.window/2)
     }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<T,ExecutionError>{
+        self.source.try_request_range(start+self.window/2,end+self.window/2)
+    }
 }
 
 
@@ -137,6 +153,16 @@ impl LazySubtractor {
 }
 
 
+impl LazySubtractor{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let a = self.a.try_request_range(start,end).into_result()?.to_ndarray();
+        let b = self.b.try_request_range(start,end).into_result()?.to_ndarray();
+        //println!("Shape test, {:?}, {:?}", a.shape, b.shape);
+        let res = (a-b).to_owned();
+        Ok(res.into())
+    }
+}
+
 impl LazyArrayOperation<ArrayND<f64>> for LazySubtractor{
     fn length(&self,) -> usize where {
         self.a.length()
@@ -147,11 +173,11 @@ impl LazyArrayOperation<ArrayND<f64>> for LazySubtractor{
     }
 
     fn request_range(&self,start:usize,end:usize) -> ArrayND<f64> {
-        let a = self.a.request_range(start,end).to_ndarray();
-        let b = self.b.request_range(start,end).to_ndarray();
-        //println!("Shape test, {:?}, {:?}", a.shape, b.shape);
-        let res = (a-b).to_owned();
-        res.into()
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 
 
@@ -189,21 +215,13 @@ impl LazySlidingQuantileNormalize{
 //
 // }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazySlidingQuantileNormalize{
-    fn length(&self,) -> usize where {
-        self.source.length()-self.window+1
-    }
-
-    fn calculate_overhead(&self,start:usize,end:usize) -> usize{
-        2*(end-start)+self.window-1
-    }
-
-    fn request_range(&self,start:usize,end:usize) -> ArrayND<f64> {
+impl LazySlidingQuantileNormalize{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
         //let time_start = Instant::now();
         let range_start = start;
         let range_end = end+self.window-1;
         //let range_len = range_end - range_start;
-        let sourced = self.source.request_range(range_start,range_end);
+        let sourced = self.source.try_request_range(range_start,range_end).into_result()?;
 
         let mut target_shape = sourced.shape.clone();
         target_shape[0] = end-start;
@@ -273,7 +291,25 @@ impl LazyArrayOperation<ArrayND<f64>> for LazySlidingQuantileNormalize{
         //println!("{:?}", preres);
         // let res:ArrayND<f64> = preres.into();
         //assert_eq!(res.shape[0],end-start);
-        Arc::try_unwrap(res).unwrap().into_inner().unwrap()
+        Ok(Arc::try_unwrap(res).unwrap().into_inner().unwrap())
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazySlidingQuantileNormalize{
+    fn length(&self,) -> usize where {
+        self.source.length()-self.window+1
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize) -> usize{
+        2*(end-start)+self.window-1
+    }
+
+    fn request_range(&self,start:usize,end:usize) -> ArrayND<f64> {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
 
@@ -303,6 +339,21 @@ fn suppress_flash(frame:&mut [f64], q:f64){
     frame.iter_mut().for_each(|x| *x -= base);
 }
 
+impl LazyFlashSuppress{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let mut src = self.source.try_request_range(start,end).into_result()?;
+        //let q = self.q;
+        //let tgt = src.clone();
+        //(0..end-start).par_bridge().for_each(|i| suppress_flash(&mut src.index_axis_mut(Axis(0),i), q));
+        let frame_size = src.frame_size();
+        for i in 0..end-start{
+            suppress_flash(&mut src.flat_data[i*frame_size..(i+1)*frame_size], self.q);
+             // suppress_flash(&mut src.index_axis_mut(Axis(0),i), self.q);
+        }
+        Ok(src)
+    }
+}
+
 impl LazyArrayOperation<ArrayND<f64>> for LazyFlashSuppress{
     #[allow(clippy::let_and_return)]
     fn length(&self,) -> usize where {
@@ -315,16 +366,11 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyFlashSuppress{
 
     #[allow(clippy::let_and_return)]
     fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
-        let mut src = self.source.request_range(start,end);
-        //let q = self.q;
-        //let tgt = src.clone();
-        //(0..end-start).par_bridge().for_each(|i| suppress_flash(&mut src.index_axis_mut(Axis(0),i), q));
-        let frame_size = src.frame_size();
-        for i in 0..end-start{
-            suppress_flash(&mut src.flat_data[i*frame_size..(i+1)*frame_size], self.q);
-             // suppress_flash(&mut src.index_axis_mut(Axis(0),i), self.q);
-        }
-        src
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
 
@@ -337,15 +383,9 @@ pub struct LazyThreshold{
     pub invert:bool,
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazyThreshold{
-    #[allow(clippy::let_and_return)]
-    fn length(&self,) -> usize where {
-        self.source.length()
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
-        let mut workon:ArrayND<f64> = self.source.request_range(start,end);
+impl LazyThreshold{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let mut workon:ArrayND<f64> = self.source.try_request_range(start,end).into_result()?;
         let thresh = self.threshold_value;
         let inv = self.invert;
         let blank = self.blank_value;
@@ -354,7 +394,23 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyThreshold{
                 *x = blank;
             }
         });
-        workon
+        Ok(workon)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyThreshold{
+    #[allow(clippy::let_and_return)]
+    fn length(&self,) -> usize where {
+        self.source.length()
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 
     #[allow(clippy::let_and_return)]
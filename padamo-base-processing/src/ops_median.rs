@@ -2,6 +2,8 @@ use std::fmt::Debug;
 
 use padamo_api::lazy_array_operations::{LazyArrayOperation, LazyDetectorSignal};
 use padamo_api::lazy_array_operations::ndim_array::ArrayND;
+use padamo_api::prelude::ExecutionError;
+use abi_stable::std_types::RResult;
 use rayon::prelude::*;
 use crate::moving_median::temporal_moving_median;
 
@@ -19,23 +21,15 @@ impl LazySlidingMedian{
 
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazySlidingMedian{
-    fn length(&self,) -> usize where {
-        self.source.length()-self.window+1
-    }
-
-    fn calculate_overhead(&self,start:usize,end:usize) -> usize{
-        2*(end-start)+self.window-1
-    }
-
-    fn request_range(&self,start:usize,end:usize) -> ArrayND<f64> {
+impl LazySlidingMedian{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
 
         //let time_start = Instant::now();
         let range_start = start;
         let range_end = end+self.window-1;
         //let range_len = range_end - range_start;
 
-        let sourced: ArrayND<f64> = self.source.request_range(range_start,range_end);
+        let sourced: ArrayND<f64> = self.source.try_request_range(range_start,range_end).into_result()?;
 
         //println!("{:?}",sourced);
         // let sourced = sourced.to_ndarray();
@@ -62,7 +56,25 @@ impl LazyArrayOperation<ArrayND<f64>> for LazySlidingMedian{
         //let testdata = res.clone().to_ndarray();
         //println!("{:?}", testdata);
         //assert_eq!(res.shape[0],end-start);
-        res
+        Ok(res)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazySlidingMedian{
+    fn length(&self,) -> usize where {
+        self.source.length()-self.window+1
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize) -> usize{
+        2*(end-start)+self.window-1
+    }
+
+    fn request_range(&self,start:usize,end:usize) -> ArrayND<f64> {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
 
@@ -113,22 +125,14 @@ fn safe_divide_arrs(a:ArrayND<f64>,b:ArrayND<f64>)->ArrayND<f64>{
     a
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazySlidingMedianNormalize{
-    fn length(&self,) -> usize where {
-        self.source.length()-self.window+1
-    }
-
-    fn calculate_overhead(&self,start:usize,end:usize) -> usize{
-        2*(end-start)+self.window-1
-    }
-
-    fn request_range(&self,start:usize,end:usize) -> ArrayND<f64> {
+impl LazySlidingMedianNormalize{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
         //let time_start = Instant::now();
         let range_start = start;
         let range_end = end+self.window-1;
         //let range_len = range_end - range_start;
 
-        let sourced = self.source.request_range(range_start,range_end);
+        let sourced = self.source.try_request_range(range_start,range_end).into_result()?;
         let k = if self.gaussmode {1.4826} else {1.0};
         let use_variance = self.variance;
         let window = self.window;
@@ -162,7 +166,7 @@ impl LazyArrayOperation<ArrayND<f64>> for LazySlidingMedianNormalize{
 
 
         //divisor
-        safe_divide_arrs(divisor,divider)
+        Ok(safe_divide_arrs(divisor,divider))
         // let preres = (divisor/divider).to_owned();
         //
         //
@@ -170,3 +174,21 @@ impl LazyArrayOperation<ArrayND<f64>> for LazySlidingMedianNormalize{
     }
 }
 
+impl LazyArrayOperation<ArrayND<f64>> for LazySlidingMedianNormalize{
+    fn length(&self,) -> usize where {
+        self.source.length()-self.window+1
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize) -> usize{
+        2*(end-start)+self.window-1
+    }
+
+    fn request_range(&self,start:usize,end:usize) -> ArrayND<f64> {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
+    }
+}
+
@@ -3,6 +3,7 @@ use std::fmt::Debug;
 use abi_stable::{rvec, StableAbi};
 use padamo_api::lazy_array_operations::{make_lao_box, ArrayND, LazyArrayOperation, LazyArrayOperationBox};
 use padamo_api::lazy_array_operations::merge::Merge;
+use padamo_api::prelude::ExecutionError;
 
 
 #[derive(Debug,Clone)]
@@ -55,22 +56,22 @@ where
 }
 
 
-pub fn make_padding<T>(source:LazyArrayOperationBox<ArrayND<T>>, left_padding:usize, right_padding:usize)->LazyArrayOperationBox<ArrayND<T>>
+pub fn make_padding<T>(source:LazyArrayOperationBox<ArrayND<T>>, left_padding:usize, right_padding:usize)->Result<LazyArrayOperationBox<ArrayND<T>>,ExecutionError>
 where
     T: Clone+Debug+StableAbi+Send+Sync+'static
 {
     let mut res = source.clone();
     if left_padding>0{
-        let first_frame = source.request_range(0,1);
+        let first_frame = source.try_request_range(0,1).into_result()?;
         let pad1 = make_lao_box(RepeatFrame::new(first_frame, left_padding));
         res = pad1.merge(res);
     }
     if right_padding>0{
         let srclen = source.length();
-        let last_frame = source.request_range(srclen-1,srclen);
+        let last_frame = source.try_request_range(srclen-1,srclen).into_result()?;
 
         let pad2 = make_lao_box(RepeatFrame::new(last_frame, right_padding));
         res = res.merge(pad2);
     }
-    res
+    Ok(res)
 }
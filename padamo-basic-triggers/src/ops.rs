@@ -1,5 +1,7 @@
 use padamo_api::lazy_array_operations::{LazyArrayOperation, LazyDetectorSignal};
 use padamo_api::trigger_operations::SparseTagArray;
+use padamo_api::prelude::ExecutionError;
+use abi_stable::std_types::RResult;
 // use rayon::iter::ParallelIterator;
 use medians::Medianf64;
 
@@ -17,7 +19,7 @@ impl LazyPixelThresholdTrigger{
 }
 
 
-fn threshold_trigger<F1, F2,S>(src:&LazyDetectorSignal, start:usize, end:usize, mut f:F1, threshold:f64, tag:S, mut fmt:F2)->SparseTagArray
+fn threshold_trigger<F1, F2,S>(src:&LazyDetectorSignal, start:usize, end:usize, mut f:F1, threshold:f64, tag:S, mut fmt:F2)->Result<SparseTagArray,ExecutionError>
 where
     F1: FnMut(&[f64])->f64 + Copy + Clone,
     F2: FnMut(f64,&f64)->f64 + Copy + Clone,
@@ -26,13 +28,13 @@ where
     let len = src.length();
     let tag_prefix = tag.into();
     if len==0{
-        return SparseTagArray::new(); // Found no negative values. Therefore no events can be reported. :(
+        return Ok(SparseTagArray::new()); // Found no negative values. Therefore no events can be reported. :(
     }
 
     //Firstly, let's check if the start is a continuation of another event.
     let mut real_start = start;
     if real_start>0{
-        let initial_arr = src.request_range(start-1,end);
+        let initial_arr = src.try_request_range(start-1,end).into_result()?;
         let initial_ampls = initial_arr.apply_on_frames(f);
         if initial_ampls[0]>threshold{
             // This is a continuation of the event.
@@ -43,19 +45,19 @@ where
     }
 
     if real_start>=end{
-        return SparseTagArray::new(); // Found no negative values. Therefore no events can be reported. :(
+        return Ok(SparseTagArray::new()); // Found no negative values. Therefore no events can be reported. :(
     }
 
     let mut real_end = end;
     //Second thing to consider:let's correct the end the same way
-    let mut last_frame = src.request_range(real_end-1,real_end).take_frame().unwrap();
+    let mut last_frame = src.try_request_range(real_end-1,real_end).into_result()?.take_frame().unwrap();
     while real_end<len && f(&last_frame.flat_data)>threshold{
         real_end+= 1;
-        last_frame = src.request_range(real_end-1,real_end).take_frame().unwrap();
+        last_frame = src.try_request_range(real_end-1,real_end).into_result()?.take_frame().unwrap();
     }
 
     //println!("Corrected interval: {} {}", real_start, real_end);
-    let final_source = src.request_range(real_start,real_end).apply_on_frames(f);
+    let final_source = src.try_request_range(real_start,real_end).into_result()?.apply_on_frames(f);
     let mut res = SparseTagArray::new();
     let mut current_state = None;
     for (i,x) in final_source.iter().enumerate(){
@@ -81,17 +83,11 @@ where
         let duration = real_end-start;
         res.push(format!("{} {}",tag_prefix, amp), start, duration);
     }
-    res
+    Ok(res)
 }
 
-impl LazyArrayOperation<SparseTagArray> for LazyPixelThresholdTrigger{
-    fn length(&self) -> usize {
-        self.src.length()
-    }
-    fn calculate_overhead(&self, start: usize, end: usize) -> usize{
-        self.src.calculate_overhead(start,end)
-    }
-    fn request_range(&self, start: usize, end: usize) -> SparseTagArray {
+impl LazyPixelThresholdTrigger{
+    fn try_request(&self,start:usize,end:usize) -> Result<SparseTagArray,ExecutionError>{
         threshold_trigger(&self.src, start, end,
                           |x| x.iter().fold(std::f64::MIN, |a,b| a.max(*b)),
                           self.threshold, "Peak:", |a,b| a.max(*b))
@@ -103,6 +99,22 @@ impl LazyArrayOperation<SparseTagArray> for LazyPixelThresholdTrigger{
     }
 }
 
+impl LazyArrayOperation<SparseTagArray> for LazyPixelThresholdTrigger{
+    fn length(&self) -> usize {
+        self.src.length()
+    }
+    fn calculate_overhead(&self, start: usize, end: usize) -> usize{
+        self.src.calculate_overhead(start,end)
+    }
+    fn request_range(&self, start: usize, end: usize) -> SparseTagArray {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<SparseTagArray,ExecutionError>{
+        self.try_request(start,end).into()
+    }
+}
+
 #[derive(Clone,Debug)]
 pub struct LazyLCThresholdTrigger{
     src: LazyDetectorSignal,
@@ -116,14 +128,8 @@ impl LazyLCThresholdTrigger{
     }
 }
 
-impl LazyArrayOperation<SparseTagArray> for LazyLCThresholdTrigger{
-    fn length(&self) -> usize {
-        self.src.length()
-    }
-    fn calculate_overhead(&self, start: usize, end: usize) -> usize{
-        self.src.calculate_overhead(start,end)
-    }
-    fn request_range(&self, start: usize, end: usize) -> SparseTagArray {
+impl LazyLCThresholdTrigger{
+    fn try_request(&self,start:usize,end:usize) -> Result<SparseTagArray,ExecutionError>{
         threshold_trigger(&self.src, start, end,
                           |x| x.iter().fold(0.0, |a,b| a + *b),
                           self.threshold, "Peak:", |a,b| a.max(*b))
@@ -142,6 +148,22 @@ impl LazyArrayOperation<SparseTagArray> for LazyLCThresholdTrigger{
     }
 }
 
+impl LazyArrayOperation<SparseTagArray> for LazyLCThresholdTrigger{
+    fn length(&self) -> usize {
+        self.src.length()
+    }
+    fn calculate_overhead(&self, start: usize, end: usize) -> usize{
+        self.src.calculate_overhead(start,end)
+    }
+    fn request_range(&self, start: usize, end: usize) -> SparseTagArray {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<SparseTagArray,ExecutionError>{
+        self.try_request(start,end).into()
+    }
+}
+
 
 #[derive(Clone,Debug)]
 pub struct LazyMedianTrigger{
@@ -157,14 +179,8 @@ impl LazyMedianTrigger{
 }
 
 
-impl LazyArrayOperation<SparseTagArray> for LazyMedianTrigger{
-    fn length(&self,) -> usize where {
-        self.src.length()
-    }
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        self.src.calculate_overhead(start,end)
-    }
-    fn request_range(&self,start:usize,end:usize,) -> SparseTagArray {
+impl LazyMedianTrigger{
+    fn try_request(&self,start:usize,end:usize) -> Result<SparseTagArray,ExecutionError>{
         threshold_trigger(&self.src, start, end,
                           |x| x.medf_checked().expect("Median computation failed"),
                           self.threshold, "Peak:", |a,b| a.max(*b))
@@ -206,3 +222,19 @@ impl LazyArrayOperation<SparseTagArray> for LazyMedianTrigger{
         // lock.into_inner().expect("Mutex cannot be locked")
     }
 }
+
+impl LazyArrayOperation<SparseTagArray> for LazyMedianTrigger{
+    fn length(&self,) -> usize where {
+        self.src.length()
+    }
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.src.calculate_overhead(start,end)
+    }
+    fn request_range(&self,start:usize,end:usize,) -> SparseTagArray {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<SparseTagArray,ExecutionError>{
+        self.try_request(start,end).into()
+    }
+}
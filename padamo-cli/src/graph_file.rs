@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use abi_stable::std_types::{RHashMap, RString};
+use padamo_api::calculation_nodes::immediate::{CompiledGraph, CompiledNode, SmallLink};
+use padamo_api::prelude::*;
+
+use padamo_api::calculation_nodes::graph_file::SerializedNodes;
+
+/// Reads graph saved by editor
+pub fn load_graph<T:AsRef<Path>>(path:T)->anyhow::Result<SerializedNodes>{
+    let s = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&s)?)
+}
+
+/// Constant override from command line. `NAME=VALUE` or `INDEX:NAME=VALUE`.
+#[derive(Clone, Debug)]
+pub struct ConstantOverride{
+    pub node_index:Option<usize>,
+    pub name:String,
+    pub value:String,
+}
+
+impl std::str::FromStr for ConstantOverride{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s.split_once('=').ok_or(format!("Expected NAME=VALUE, got \"{}\"", s))?;
+        let (node_index, name) = if let Some((index,name)) = key.split_once(':'){
+            if let Ok(i) = index.trim().parse::<usize>(){
+                (Some(i), name)
+            }
+            else{
+                (None, key)
+            }
+        }
+        else{
+            (None, key)
+        };
+        Ok(Self { node_index, name:name.trim().into(), value:value.into() })
+    }
+}
+
+fn parse_constant(template:&ConstantContent, value:&str)->anyhow::Result<ConstantContent>{
+    let res = match template{
+        ConstantContent::Integer(_)=>ConstantContent::Integer(value.trim().parse()?),
+        ConstantContent::Float(_)=>ConstantContent::Float(value.trim().parse()?),
+        ConstantContent::Boolean(_)=>ConstantContent::Boolean(value.trim().parse()?),
+        ConstantContent::String(_)=>ConstantContent::String(value.into()),
+    };
+    Ok(res)
+}
+
+/// Resolves node identifier through current and old identifiers of loaded nodes.
+fn resolve_identifier(identifier:&str, registry:&HashMap<String,CalculationNodeBox>)->Option<String>{
+    if registry.contains_key(identifier){
+        return Some(identifier.into());
+    }
+    registry.values()
+        .find(|node| node.old_identifier().into_option().map(|x| x.as_str()==identifier).unwrap_or(false))
+        .map(|node| node.identifier().into())
+}
+
+/// Builds graph for execution. Constants missing in file are taken from node defaults.
+/// Returns graph and list of overrides that did not match any constant.
+pub fn compile_graph(nodes:&SerializedNodes, registry:&HashMap<String,CalculationNodeBox>, overrides:&[ConstantOverride])->anyhow::Result<(CompiledGraph,Vec<ConstantOverride>)>{
+    let mut graph = CompiledGraph::new();
+    let mut used = vec![false; overrides.len()];
+
+    for (i,entry) in nodes.0.iter().enumerate(){
+        let identifier = resolve_identifier(&entry.identifier, registry)
+            .ok_or(anyhow::anyhow!("Node #{}: unknown node \"{}\"", i, entry.identifier))?;
+        let node = &registry[&identifier];
+
+        let mut constants = ConstantContentContainer::new();
+        let mut externals:RHashMap<RString,bool> = RHashMap::new();
+        for def in node.constants().iter(){
+            let key = def.name.as_str();
+            let mut value = if let Some(v) = entry.constants.get(key){
+                let v:ConstantContent = v.clone().into();
+                if std::mem::discriminant(&v)!=std::mem::discriminant(&def.default_value){
+                    anyhow::bail!("Node #{} ({}): constant \"{}\" has wrong type", i, identifier, key);
+                }
+                v
+            }
+            else{
+                def.default_value.clone()
+            };
+
+            for (j,ov) in overrides.iter().enumerate(){
+                if ov.node_index.map(|x| x==i).unwrap_or(true) && (ov.name==key || ov.name==def.display_name.as_str()){
+                    value = parse_constant(&def.default_value, &ov.value)
+                        .map_err(|e| anyhow::anyhow!("Node #{} ({}): cannot set \"{}\" to \"{}\": {}", i, identifier, ov.name, ov.value, e))?;
+                    used[j] = true;
+                }
+            }
+
+            constants.0.insert(key.into(), value);
+            externals.insert(key.into(), entry.constants_external_flags.get(key).copied().unwrap_or(false));
+        }
+
+        graph.nodes.push(CompiledNode::new(constants, externals, identifier));
+    }
+
+    for (end_i,entry) in nodes.0.iter().enumerate(){
+        for (input_port,conn) in entry.connections.iter(){
+            if let Some(conn) = conn{
+                if conn.node_index>=graph.nodes.len(){
+                    anyhow::bail!("Node #{}: input \"{}\" is linked to missing node #{}", end_i, input_port, conn.node_index);
+                }
+                graph.nodes[conn.node_index].links.push(SmallLink{
+                    output:conn.port.clone(),
+                    target_input:input_port.clone(),
+                    target_index:end_i,
+                });
+            }
+        }
+    }
+
+    let unused = overrides.iter().zip(used.iter()).filter(|(_,u)| !**u).map(|(o,_)| o.clone()).collect();
+    Ok((graph, unused))
+}
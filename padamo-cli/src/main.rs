@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use clap::Parser;
+use padamo_api::calculation_nodes::graph::CalculationSequenceStorage;
+use padamo_api::calculation_nodes::subgraph::{SubgraphLibrary, SubgraphNode};
+use padamo_api::calculation_nodes::viewer_nodes::viewer::VIEWER_FILENAME_VAR;
+use padamo_api::prelude::*;
+use padamo_detectors::loaded_detectors_storage::LoadedDetectors;
+use padamo_detectors::polygon::Detector;
+
+mod graph_file;
+
+use graph_file::{compile_graph, load_graph, ConstantOverride};
+
+/// Runs graph saved from PADAMO graph editor without GUI.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args{
+    /// Graph file saved by graph editor
+    graph:PathBuf,
+
+    /// Override constant: NAME=VALUE or NODE_INDEX:NAME=VALUE. NAME is constant key or its display name.
+    /// "File path" also sets file reported by "Opened file" node.
+    #[arg(short, long = "set", value_name = "NAME=VALUE")]
+    set:Vec<ConstantOverride>,
+
+    /// Random seed
+    #[arg(long, default_value_t = 0)]
+    seed:u64,
+
+    /// Detector JSON file. Can be repeated; first one is primary. Default VTL detector is used if none given.
+    #[arg(short, long, value_name = "FILE")]
+    detector:Vec<PathBuf>,
+
+    /// Plugins directory. Defaults to "plugins" next to executable.
+    #[arg(long, value_name = "DIR")]
+    plugins:Option<PathBuf>,
+
+    /// Subgraph library saved by graph editor
+    #[arg(long, value_name = "FILE")]
+    subgraphs:Option<PathBuf>,
+}
+
+fn load_registry(plugins:Option<PathBuf>, subgraphs:Option<PathBuf>)->anyhow::Result<HashMap<String,CalculationNodeBox>>{
+    let plugins_dir = if let Some(p) = plugins{
+        p
+    }
+    else{
+        let current_exe = std::env::current_exe()?;
+        let current_dir = current_exe.parent().ok_or(anyhow::Error::msg("No parent of executable path"))?;
+        current_dir.join("plugins")
+    };
+
+    let mut nodes = padamo_api::headless_helpers::load_nodes(&plugins_dir)?;
+    let mut builtin = padamo_api::calculation_nodes::viewer_nodes::nodes();
+    builtin.push(padamo_api::make_node_box(padamo_api::calculation_nodes::full_reader::FullReaderNode));
+    for node in builtin.drain(..){
+        nodes.insert(node.identifier().into(), node);
+    }
+    if let Some(path) = subgraphs{
+        let library = SubgraphLibrary::load(&path)?;
+        for definition in library.0.into_iter(){
+            let node = SubgraphNode::new(definition, Arc::new(nodes.clone()))?;
+            nodes.insert(node.identifier().into(), padamo_api::make_node_box(node));
+        }
+    }
+    Ok(nodes)
+}
+
+fn run(args:Args)->anyhow::Result<()>{
+    let registry = load_registry(args.plugins, args.subgraphs)?;
+    let graph_data = load_graph(&args.graph)?;
+    let (graph, unused) = compile_graph(&graph_data, &registry, &args.set)?;
+
+    let mut compute_graph = CalculationSequenceStorage::new();
+
+    let mut detectors = LoadedDetectors::new();
+    let mut primary_json = None;
+    for path in args.detector.iter(){
+        let s = std::fs::read_to_string(path)?;
+        let detector:Detector = serde_json::from_str(&s)?;
+        if primary_json.is_none(){
+            primary_json = Some(s);
+        }
+        detectors.add_detector(detector);
+    }
+    if detectors.len()==0{
+        let detector = Detector::default_vtl();
+        primary_json = Some(serde_json::to_string(&detector)?);
+        detectors.add_detector(detector);
+    }
+    if let Some(s) = primary_json{
+        compute_graph.environment.0.insert("detector".into(), Content::String(s.into()));
+    }
+
+    let mut unmatched = Vec::new();
+    for ov in unused.iter(){
+        if ov.name=="File path" && graph.nodes.iter().any(|x| x.identifier=="builtin.viewer.opened_file"){
+            compute_graph.environment.0.insert(VIEWER_FILENAME_VAR.into(), Content::String(ov.value.clone().into()));
+        }
+        else{
+            unmatched.push(ov.name.clone());
+        }
+    }
+    if !unmatched.is_empty(){
+        anyhow::bail!("No constants matched: {}", unmatched.join(", "));
+    }
+
+    let diagnostics = graph.validate(&registry);
+    if !diagnostics.is_empty(){
+        let lines:Vec<String> = diagnostics.iter().map(|x| x.to_string()).collect();
+        anyhow::bail!("Graph is invalid:\n{}", lines.join("\n"));
+    }
+
+    graph.make_compute_graph(&mut compute_graph, &registry);
+    let graph_json = std::fs::read_to_string(&args.graph)?;
+    compute_graph.set_provenance(&graph_json, env!("CARGO_PKG_VERSION"), args.seed);
+    compute_graph.execute(args.seed, detectors.get_detectors())?;
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(args){
+        Ok(())=>{
+            println!("Execution success");
+            ExitCode::SUCCESS
+        }
+        Err(e)=>{
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,4 +1,6 @@
 use abi_stable::rvec;
+use abi_stable::std_types::ROption;
+use padamo_api::lazy_array_operations::disk_cache::DiskCacheDir;
 use padamo_api::{constants, ports, prelude::*};
 
 
@@ -46,3 +48,56 @@ impl CalculationNode for ForcedCacheNode{
         self.calculate(args).into()
     }
 }
+
+/// Stores signal on disk, so it is not recalculated after restart of application.
+/// Cache is keyed by fingerprint of node, i.e. it is recalculated if anything upstream or any input file changes.
+#[derive(Clone,Debug)]
+pub struct DiskCacheNode;
+
+impl DiskCacheNode{
+    fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError>where {
+        let mut signal = args.inputs.request_detectorfulldata("Signal")?;
+        let root = padamo_state_persistence::data::get_disk_cache_dir();
+        let limit = padamo_state_persistence::PersistentState::default().disk_cache_limit();
+
+        signal.0 = signal.0.disk_cached(DiskCacheDir::new(&root, args.fingerprint, "signal", limit));
+        if let ROption::RSome(trigger) = signal.2{
+            signal.2 = ROption::RSome(trigger.range_disk_cached(DiskCacheDir::new(&root, args.fingerprint, "trigger", limit)));
+        }
+        args.outputs.set_value("Signal", signal.into())
+    }
+}
+
+impl CalculationNode for DiskCacheNode{
+    fn name(&self,) -> abi_stable::std_types::RString where {
+        "Disk cache signal".into()
+    }
+
+    fn category(&self,) -> abi_stable::std_types::RVec<abi_stable::std_types::RString>where {
+        rvec![]
+    }
+
+    fn identifier(&self,) -> abi_stable::std_types::RString where {
+        "padamocore.disk_cache".into()
+    }
+
+    fn constants(&self,) -> abi_stable::std_types::RVec<CalculationConstant>where {
+        constants!()
+    }
+
+    fn inputs(&self,) -> abi_stable::std_types::RVec<CalculationIO>where {
+        ports![
+            ("Signal", ContentType::DetectorFullData)
+        ]
+    }
+
+    fn outputs(&self,) -> abi_stable::std_types::RVec<CalculationIO>where {
+        ports![
+            ("Signal", ContentType::DetectorFullData)
+        ]
+    }
+
+    fn calculate(&self, args:CalculationNodeArguments) -> abi_stable::std_types::RResult<(),ExecutionError>where {
+        self.calculate(args).into()
+    }
+}
@@ -43,6 +43,10 @@ impl CalculationNode for EnvOutputNode{
         format!("padamocore.env_output.{}",idmark).into()
     }
 
+    fn reads_environment(&self,) -> bool {
+        true
+    }
+
     fn outputs(&self,) -> RVec<CalculationIO>where {
         ports!(
             ("Value", self.0.into())
@@ -19,11 +19,12 @@ pub mod strings_old;
 pub mod cache;
 pub mod io_nodes;
 pub mod temporal;
+pub mod typed;
 pub mod remapper;
 
 #[export_root_module]
 pub fn plugin_root()->PadamoModule_Ref{
-    PadamoModule{nodes}.leak_into_prefix()
+    PadamoModule{nodes, share_cache:padamo_api::share_cache, info:padamo_api::plugin_info!()}.leak_into_prefix()
 }
 
 #[sabi_extern_fn]
@@ -38,9 +39,11 @@ pub fn nodes(_library_dir:RString)->RVec<CalculationNodeBox>{
     node_list.extend(trigger_nodes::nodes());
     node_list.extend(io_nodes::nodes());
     node_list.extend(temporal::nodes());
+    node_list.extend(typed::nodes());
     // node_list.push(make_node_box(trigger_nodes::TriggerExpandNode));
     // node_list.push(make_node_box(trigger_nodes::TriggerExchangeNode));
     node_list.push(make_node_box(cache::ForcedCacheNode));
+    node_list.push(make_node_box(cache::DiskCacheNode));
     node_list.push(make_node_box(remapper::nodes::RemapperNode));
     node_list
 }
@@ -12,7 +12,7 @@ impl RemapperNode{
         if src_signal.0.length()==0{
             return Err(ExecutionError::OtherError("Cannot remap empty data".into()))
         }
-        let mut testframe = src_signal.0.request_range(0,1);
+        let mut testframe = src_signal.0.try_request_range(0,1).into_result()?;
         testframe.shape.drain(..1);
 
 
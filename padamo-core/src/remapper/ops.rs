@@ -1,6 +1,8 @@
 use std::fmt::Debug;
 
+use abi_stable::std_types::RResult;
 use padamo_api::lazy_array_operations::{ArrayND, LazyArrayOperation, LazyArrayOperationBox};
+use padamo_api::prelude::ExecutionError;
 
 
 #[derive(Clone, Debug)]
@@ -16,21 +18,31 @@ impl<T: Clone + Debug + abi_stable::StableAbi> LazyRemapper<T> {
 }
 
 
+impl<T:Clone+Debug+abi_stable::StableAbi+Send+Sync> LazyRemapper<T>{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<T>,ExecutionError>{
+        let unmapped:ArrayND<T> = self.source.try_request_range(start, end).into_result()?;
+
+        let mut target_shape = vec![end-start];
+        target_shape.extend(self.remapper.target_shape.clone());
+        let target_shape = target_shape;
+        let mut res = Vec::new();
+        for frame in unmapped.view().frames(){
+            res.extend(self.remapper.apply_view(&frame).unwrap().flat_data);
+        }
+        Ok(ArrayND { flat_data: res.into(), shape: target_shape.into() })
+    }
+}
+
 impl<T:Clone+Debug+abi_stable::StableAbi+Send+Sync> LazyArrayOperation<ArrayND<T>> for LazyRemapper<T>{
     fn length(&self,) -> usize where {
         self.source.length()
     }
 
     fn request_range(&self,start:usize,end:usize,) -> ArrayND<T>{
-        let mut unmapped:ArrayND<T> = self.source.request_range(start, end);
+        self.try_request(start,end).unwrap()
+    }
 
-        let mut target_shape = vec![end-start];
-        target_shape.extend(self.remapper.target_shape.clone());
-        let target_shape = target_shape;
-        let mut res = Vec::new();
-        while let Some(frame) = unmapped.take_frame(){
-            res.extend(self.remapper.apply(&frame).unwrap().flat_data);
-        }
-        ArrayND { flat_data: res.into(), shape: target_shape.into() }
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<T>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
@@ -1,9 +1,11 @@
 use std::collections::HashMap;
 
 
+use abi_stable::std_types::RResult;
 use padamo_api::lazy_array_operations::{LazyArrayOperation,LazyTrigger};
 use padamo_api::trigger_operations::SparseTagArray;
 use padamo_api::lazy_array_operations::merge::Merge;
+use padamo_api::prelude::ExecutionError;
 
 #[derive(Clone,Debug)]
 pub struct LazyTriggerMerge{
@@ -18,6 +20,14 @@ impl LazyTriggerMerge {
     }
 }
 
+impl LazyTriggerMerge{
+    fn try_request(&self,start:usize,end:usize) -> Result<SparseTagArray,ExecutionError>{
+        let a = self.source1.try_request_range(start,end).into_result()?;
+        let b = self.source2.try_request_range(start,end).into_result()?;
+        Ok(a.merge(b))
+    }
+}
+
 impl LazyArrayOperation<SparseTagArray> for LazyTriggerMerge{
     #[allow(clippy::let_and_return)]
     fn length(&self,) -> usize where {
@@ -26,9 +36,11 @@ impl LazyArrayOperation<SparseTagArray> for LazyTriggerMerge{
 
     #[allow(clippy::let_and_return)]
     fn request_range(&self,start:usize,end:usize,) -> SparseTagArray where {
-        let a = self.source1.request_range(start,end);
-        let b = self.source2.request_range(start,end);
-        a.merge(b)
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<SparseTagArray,ExecutionError>{
+        self.try_request(start,end).into()
     }
 
     fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
@@ -52,20 +64,14 @@ impl LazyTriggerRemoveOverlap {
     }
 }
 
-impl LazyArrayOperation<SparseTagArray> for LazyTriggerRemoveOverlap{
-    #[allow(clippy::let_and_return)]
-    fn length(&self,) -> usize where {
-        self.source.length()
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn request_range(&self,start:usize,end:usize,) -> SparseTagArray where {
-        let mut res = self.source.request_range(start,end);
+impl LazyTriggerRemoveOverlap{
+    fn try_request(&self,start:usize,end:usize) -> Result<SparseTagArray,ExecutionError>{
+        let mut res = self.source.try_request_range(start,end).into_result()?;
         let mut current_end = end;
         let mut deduplicating = true;
         let mut extending = true;
         if res.tags.is_empty(){
-            return SparseTagArray::new();
+            return Ok(SparseTagArray::new());
         }
         while deduplicating || extending{
             deduplicating = false;
@@ -100,7 +106,7 @@ impl LazyArrayOperation<SparseTagArray> for LazyTriggerRemoveOverlap{
                     break;
                 }
                 println!("Adding {}={}",current_end, sub_end);
-                let addenum_tags = self.source.request_range(current_end, sub_end);
+                let addenum_tags = self.source.try_request_range(current_end, sub_end).into_result()?;
 
                 is_running = false;
                 if !addenum_tags.tags.is_empty(){
@@ -114,7 +120,23 @@ impl LazyArrayOperation<SparseTagArray> for LazyTriggerRemoveOverlap{
 
         }
 
-        res
+        Ok(res)
+    }
+}
+
+impl LazyArrayOperation<SparseTagArray> for LazyTriggerRemoveOverlap{
+    #[allow(clippy::let_and_return)]
+    fn length(&self,) -> usize where {
+        self.source.length()
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn request_range(&self,start:usize,end:usize,) -> SparseTagArray where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<SparseTagArray,ExecutionError>{
+        self.try_request(start,end).into()
     }
 
     fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
@@ -137,29 +159,20 @@ impl LazyTriggerExpand {
     }
 }
 
-impl LazyArrayOperation<SparseTagArray> for LazyTriggerExpand{
-    #[allow(clippy::let_and_return)]
-    fn length(&self,) -> usize where {
-        self.source.length()
-    }
-
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        self.source.calculate_overhead(start,end)
-    }
-
-    fn request_range(&self,start:usize,end:usize,) -> SparseTagArray where {
+impl LazyTriggerExpand{
+    fn try_request(&self,start:usize,end:usize) -> Result<SparseTagArray,ExecutionError>{
         let len = self.length();
 
         let request_start = start+self.left;
         let mut request_end = end+self.left;
 
         if request_start>=len{
-            return SparseTagArray::new();
+            return Ok(SparseTagArray::new());
         }
 
         request_end = request_end.min(len);
 
-        let mut sourcepart = self.source.request_range(request_start,request_end);
+        let mut sourcepart = self.source.try_request_range(request_start,request_end).into_result()?;
         sourcepart.tags.iter_mut().for_each(|tag|{
             let new_position = if tag.position > self.left {
                 tag.position - self.left
@@ -174,6 +187,25 @@ impl LazyArrayOperation<SparseTagArray> for LazyTriggerExpand{
             tag.position = new_position;
             tag.duration = new_duration;
         });
-        sourcepart
+        Ok(sourcepart)
+    }
+}
+
+impl LazyArrayOperation<SparseTagArray> for LazyTriggerExpand{
+    #[allow(clippy::let_and_return)]
+    fn length(&self,) -> usize where {
+        self.source.length()
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.source.calculate_overhead(start,end)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> SparseTagArray where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<SparseTagArray,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
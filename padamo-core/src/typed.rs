@@ -0,0 +1,153 @@
+use padamo_api::{constants, nodes_vec, ports, prelude::*};
+use padamo_api::lazy_array_operations::typed::{SignalDType, TypedDetectorSignal};
+use abi_stable::{rvec, std_types::{RResult, RString, RVec}};
+
+fn category()->RVec<RString>{
+    rvec!["Signal manipulation".into(), "Signal types".into()]
+}
+
+#[derive(Clone,Debug)]
+pub struct TypedToFloatNode;
+
+impl TypedToFloatNode{
+    fn calculate(&self,args:CalculationNodeArguments)->Result<(),ExecutionError>{
+        let signal = args.inputs.request_typedsignal("Typed signal")?;
+        args.outputs.set_value("Signal", signal.to_f64().into())
+    }
+}
+
+impl CalculationNode for TypedToFloatNode{
+    fn name(&self,) -> RString where {
+        "Typed signal to float".into()
+    }
+
+    fn category(&self,) -> RVec<RString>where {
+        category()
+    }
+
+    fn identifier(&self,) -> RString where {
+        "padamocore.typed_to_float".into()
+    }
+
+    fn inputs(&self,) -> RVec<CalculationIO>where {
+        ports!(
+            ("Typed signal", ContentType::TypedSignal)
+        )
+    }
+
+    fn outputs(&self,) -> RVec<CalculationIO>where {
+        ports!(
+            ("Signal", ContentType::DetectorSignal)
+        )
+    }
+
+    fn constants(&self,) -> RVec<CalculationConstant>where {
+        constants!()
+    }
+
+    fn calculate(&self,args:CalculationNodeArguments,) -> RResult<(),ExecutionError>{
+        self.calculate(args).into()
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct FloatToTypedNode;
+
+impl FloatToTypedNode{
+    fn calculate(&self,args:CalculationNodeArguments)->Result<(),ExecutionError>{
+        let signal = args.inputs.request_detectorsignal("Signal")?;
+        let dtype = SignalDType::parse(&args.constants.request_string("dtype")?)?;
+        args.outputs.set_value("Typed signal", TypedDetectorSignal::from_f64(signal, dtype).into())
+    }
+}
+
+impl CalculationNode for FloatToTypedNode{
+    fn name(&self,) -> RString where {
+        "Float signal to typed".into()
+    }
+
+    fn category(&self,) -> RVec<RString>where {
+        category()
+    }
+
+    fn identifier(&self,) -> RString where {
+        "padamocore.float_to_typed".into()
+    }
+
+    fn inputs(&self,) -> RVec<CalculationIO>where {
+        ports!(
+            ("Signal", ContentType::DetectorSignal)
+        )
+    }
+
+    fn outputs(&self,) -> RVec<CalculationIO>where {
+        ports!(
+            ("Typed signal", ContentType::TypedSignal)
+        )
+    }
+
+    fn constants(&self,) -> RVec<CalculationConstant>where {
+        constants!(
+            ("dtype", "Type (f64, f32, u8, u16, u32, i32, i64, bool)", "f32")
+        )
+    }
+
+    fn calculate(&self,args:CalculationNodeArguments,) -> RResult<(),ExecutionError>{
+        self.calculate(args).into()
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct ConvertTypedNode;
+
+impl ConvertTypedNode{
+    fn calculate(&self,args:CalculationNodeArguments)->Result<(),ExecutionError>{
+        let signal = args.inputs.request_typedsignal("Typed signal")?;
+        let dtype = SignalDType::parse(&args.constants.request_string("dtype")?)?;
+        args.outputs.set_value("Converted", signal.convert(dtype).into())
+    }
+}
+
+impl CalculationNode for ConvertTypedNode{
+    fn name(&self,) -> RString where {
+        "Convert signal type".into()
+    }
+
+    fn category(&self,) -> RVec<RString>where {
+        category()
+    }
+
+    fn identifier(&self,) -> RString where {
+        "padamocore.convert_typed".into()
+    }
+
+    fn inputs(&self,) -> RVec<CalculationIO>where {
+        ports!(
+            ("Typed signal", ContentType::TypedSignal)
+        )
+    }
+
+    fn outputs(&self,) -> RVec<CalculationIO>where {
+        ports!(
+            ("Converted", ContentType::TypedSignal)
+        )
+    }
+
+    fn constants(&self,) -> RVec<CalculationConstant>where {
+        constants!(
+            ("dtype", "Type (f64, f32, u8, u16, u32, i32, i64, bool)", "f32")
+        )
+    }
+
+    fn calculate(&self,args:CalculationNodeArguments,) -> RResult<(),ExecutionError>{
+        self.calculate(args).into()
+    }
+}
+
+pub fn nodes()->RVec<CalculationNodeBox>{
+    nodes_vec![
+        TypedToFloatNode,
+        FloatToTypedNode,
+        ConvertTypedNode
+    ]
+}
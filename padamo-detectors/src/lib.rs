@@ -26,6 +26,8 @@ pub use diagrams::scaling::Scaling;
 
 
 pub use polygon::Detector;
+// Scripted nodes must use the same Rhai as detector scripts
+pub use rhai;
 
 // const COLORBAR_SEGMENTS:usize = 256;
 
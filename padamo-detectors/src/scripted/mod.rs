@@ -4,11 +4,17 @@ use rhai::{Engine, EvalAltResult, packages::Package};
 use rhai_sci::SciPackage;
 // pub mod errors;
 
-pub fn parse_scripted(src:&str)->Result<Detector,Box<EvalAltResult>>{
+/// Engine with scientific package registered. Base for detector scripts and scripted calculation nodes.
+pub fn make_engine()->Engine{
     let mut engine = Engine::new();
+    engine.register_global_module(SciPackage::new().as_shared_module());
+    engine
+}
+
+pub fn parse_scripted(src:&str)->Result<Detector,Box<EvalAltResult>>{
+    let mut engine = make_engine();
     engine.build_type::<crate::polygon::DetectorPixel>();
     engine.build_type::<crate::polygon::Detector>();
-    engine.register_global_module(SciPackage::new().as_shared_module());
     let res:Detector = engine.eval(src)?;
     // let res1:DetectorContent = from_dynamic(&res)?;
     Ok(res)
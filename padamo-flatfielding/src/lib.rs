@@ -13,7 +13,7 @@ pub mod nodes;
 
 #[export_root_module]
 pub fn plugin_root()->PadamoModule_Ref{
-    PadamoModule{nodes}.leak_into_prefix()
+    PadamoModule{nodes, share_cache:padamo_api::share_cache, info:padamo_api::plugin_info!()}.leak_into_prefix()
 }
 
 #[sabi_extern_fn]
@@ -41,7 +41,7 @@ macro_rules! impl_operator {
             fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError>where {
                 let mut signal = args.inputs.request_detectorfulldata("Signal")?;
                 let coeffs = args.inputs.request_detectorsignal("Coefficients")?;
-                let mut coeffs = coeffs.request_range(0,coeffs.length());
+                let mut coeffs = coeffs.try_request_range(0,coeffs.length()).into_result()?;
                 if args.constants.request_boolean("squeeze_map")?{
                     coeffs = coeffs.squeeze();
                 }
@@ -49,7 +49,7 @@ macro_rules! impl_operator {
                 if signal.0.length()==0{
                     return Err(ExecutionError::OtherError("Cannot check signal shape compatibility".into()));
                 }
-                let mut test_data = signal.0.request_range(0,1);
+                let mut test_data = signal.0.try_request_range(0,1).into_result()?;
                 test_data.shape.drain(..1);
 
                 if !test_data.is_compatible(&coeffs){
@@ -125,8 +125,8 @@ impl PhysicalFFNode{
         let mut signal = args.inputs.request_detectorfulldata("Signal")?;
         let eff_2d = args.inputs.request_detectorsignal("Eff_2D")?;
         let tau = args.inputs.request_detectorsignal("Tau")?;
-        let mut eff_2d = eff_2d.request_range(0,eff_2d.length());
-        let mut tau = tau.request_range(0,tau.length());
+        let mut eff_2d = eff_2d.try_request_range(0,eff_2d.length()).into_result()?;
+        let mut tau = tau.try_request_range(0,tau.length()).into_result()?;
         if args.constants.request_boolean("squeeze_map")?{
             tau = tau.squeeze();
             eff_2d = eff_2d.squeeze();
@@ -134,7 +134,7 @@ impl PhysicalFFNode{
         if signal.0.length()==0{
             return Err(ExecutionError::OtherError("Cannot check signal shape compatibility".into()));
         }
-        let test_data = signal.0.request_range(0,1).squeeze();
+        let test_data = signal.0.try_request_range(0,1).into_result()?.squeeze();
 
         if !test_data.is_compatible(&tau){
             return Err(ExecutionError::OtherError(format!("flat fielding tau {:?} is not compatible with signal {:?}", test_data.shape, tau.shape).into()));
@@ -23,7 +23,7 @@ impl QuantileNode{
         let signal = args.inputs.request_detectorfulldata("Signal")?;
         let l = signal.0.length();
         let q = args.constants.request_float("Quantile")?;
-        let signal:ArrayND<f64> = signal.0.request_range(0,l);
+        let signal:ArrayND<f64> = signal.0.try_request_range(0,l).into_result()?;
         let target_shape = signal.shape.iter().skip(1).map(|x|*x).collect::<Vec<usize>>();
 
         let medians = Arc::new(Mutex::new(ArrayND::defaults(target_shape)));
@@ -1,7 +1,6 @@
 use std::f64::consts::PI;
 
-use abi_stable::std_types::RVec;
-use atomic_float::AtomicF64;
+use abi_stable::std_types::{RResult, RVec};
 use rayon::prelude::*;
 use padamo_api::lazy_array_operations::{LazyArrayOperation,ArrayND, LazyDetectorSignal};
 use crate::lambert::lambertw0;
@@ -105,6 +104,25 @@ impl PhysicalFF {
 // const NTS:f64 = T/DT*1e9;
 // const CR_TO_INT:f64 = 1.0/(WT*LT*PIX_FOV*S*T);
 
+impl PhysicalFF{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let mut data = self.source.try_request_range(start,end).into_result()?;
+        let frame_size = data.frame_size();
+        data.flat_data.as_mut_slice().par_iter_mut().enumerate().for_each(|(i,x)|{
+            // Calibration arrays have shape of frame
+            let eff = self.eff_2d.flat_data[i%frame_size];
+            let tau = self.tau_calib.flat_data[i%frame_size];
+            let b = tau*eff/self.dt;
+            *x = if eff>0.0 && tau>0.0{
+                //-CR_TO_INT*NTS*lambertw0(-b*x/NTS/eff)/b
+                -self.cr_to_int*self.nts*lambertw0(-b*(*x)/self.nts/eff)/b
+            }
+            else{0.0};
+        });
+        Ok(data)
+    }
+}
+
 impl LazyArrayOperation<ArrayND<f64>> for PhysicalFF{
     #[allow(clippy::let_and_return)]
     fn length(&self,) -> usize where {
@@ -117,45 +135,11 @@ impl LazyArrayOperation<ArrayND<f64>> for PhysicalFF{
 
     #[allow(clippy::let_and_return)]
     fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64>{
-        let src_data = self.source.request_range(start,end);
-        // let tgt:Arc<Mutex<ArrayND<f64>>> = Arc::new(Mutex::new(ArrayND::new(src_data.shape.clone().into(), 0.0)));
-        let tgt_flat_len:usize = src_data.shape.iter().map(|x|*x).product();
-        let mut tgt_flat:Vec<AtomicF64> = Vec::with_capacity(tgt_flat_len);
-        tgt_flat.resize_with(tgt_flat_len, || AtomicF64::new(0.0));
-
-        let enumerator = src_data.enumerate();
-
-        let tau_calib = &self.tau_calib;
-        let eff_2d = &self.eff_2d;
-
-        let nts = self.nts;
-        let cr_to_int = self.cr_to_int;
-        let dt = self.dt;
-
-        enumerator.par_bridge().for_each(|i|{
-            let mut pixel_index = i.clone();
-            pixel_index.drain(0..1);
-            let x =src_data[&i];
-            let eff = eff_2d[&pixel_index];
-            let tau = tau_calib[&pixel_index];
-            let b = tau*eff/dt;
-            let v = if eff>0.0 && tau>0.0{
-                //-CR_TO_INT*NTS*lambertw0(-b*x/NTS/eff)/b
-                -cr_to_int*nts*lambertw0(-b*x/nts/eff)/b
-            }
-            else{0.0};
-
-            // tgt.lock().unwrap()[&i] = v;
-            let off = padamo_arraynd::calculate_offset(&src_data.shape,&i);
-            tgt_flat[off].fetch_add(v, std::sync::atomic::Ordering::Relaxed);
-        });
-
+        self.try_request(start,end).unwrap()
+    }
 
-        // let tgt = Arc::try_unwrap(tgt).unwrap();
-        // tgt.into_inner().unwrap()
-        let tgt = ArrayND {shape: src_data.shape.clone().into(), flat_data:tgt_flat.drain(..).map(|x| x.into_inner()).collect()};
-        tgt.assert_shape();
-        tgt
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
 
@@ -173,6 +157,17 @@ impl ApplyByMap {
     }
 }
 
+impl ApplyByMap{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let mut data = self.source.try_request_range(start,end).into_result()?;
+        let frame_size = data.frame_size();
+        data.flat_data.as_mut_slice().par_iter_mut().enumerate().for_each(|(i,x)|{
+            *x = (self.operation)(*x, self.coeffs.flat_data[i%frame_size]);
+        });
+        Ok(data)
+    }
+}
+
 impl LazyArrayOperation<ArrayND<f64>> for ApplyByMap{
     fn length(&self,) -> usize where {
         self.source.length()
@@ -183,29 +178,10 @@ impl LazyArrayOperation<ArrayND<f64>> for ApplyByMap{
     }
 
     fn request_range(&self,start:usize,end:usize) -> ArrayND<f64>{
-        let src_data = self.source.request_range(start,end);
-        // let tgt:Arc<Mutex<ArrayND<f64>>> = Arc::new(Mutex::new(ArrayND::new(src_data.shape.clone().into(), 0.0)));
-        let tgt_flat_len:usize = src_data.shape.iter().map(|x|*x).product();
-        let mut tgt_flat:Vec<AtomicF64> = Vec::with_capacity(tgt_flat_len);
-        tgt_flat.resize_with(tgt_flat_len, || AtomicF64::new(0.0));
-
-        let enumerator = src_data.enumerate();
-
-        let coeffs = &self.coeffs;
-        enumerator.par_bridge().for_each(|i|{
-            let mut pixel_index = i.clone();
-            pixel_index.drain(0..1);
-            let x =src_data[&i];
-            let coeff = coeffs[&pixel_index];
-            let v = (self.operation)(x,coeff);
-            // tgt.lock().unwrap()[&i] = v;
-            let off = padamo_arraynd::calculate_offset(&src_data.shape,&i);
-            tgt_flat[off].fetch_add(v, std::sync::atomic::Ordering::Relaxed);
-        });
-        // let tgt = Arc::try_unwrap(tgt).unwrap();
-        // tgt.into_inner().unwrap()
-        let tgt = ArrayND {shape: src_data.shape.clone().into(), flat_data:tgt_flat.drain(..).map(|x| x.into_inner()).collect()};
-        tgt.assert_shape();
-        tgt
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
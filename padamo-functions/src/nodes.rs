@@ -12,7 +12,7 @@ impl FCalculateNode{
     fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError>where {
         let f0 = args.inputs.request_function("F")?;
         let x = args.inputs.request_float("x")?;
-        let y = f0.calculate(x);
+        let y = f0.try_calculate(x).into_result()?;
         args.outputs.set_value("y", y.into())?;
         Ok(())
     }
@@ -27,7 +27,7 @@ impl FCalculateNode2{
     fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError>where {
         let f0 = args.inputs.request_function("F")?;
         let x = args.constants.request_float("x")?;
-        let y = f0.calculate(x);
+        let y = f0.try_calculate(x).into_result()?;
         args.outputs.set_value("y", y.into())?;
         Ok(())
     }
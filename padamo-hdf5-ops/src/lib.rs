@@ -1,14 +1,17 @@
+//! Lazy readers of HDF5 datasets. Used by HDF5 plugin and by MAT plugin for MAT v7.3 files.
 use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
 
-use abi_stable::std_types::RVec;
+use abi_stable::std_types::{RResult, RVec};
 use hdf5::{Selection, SliceOrIndex};
 use hdf5::Hyperslab;
 use padamo_api::lazy_array_operations::{LazyArrayOperation,LazyArrayOperationBox};
 use padamo_api::lazy_array_operations::ndim_array::ArrayND;
+use padamo_api::prelude::ExecutionError;
+use padamo_api::trigger_operations::SparseTagArray;
 
-fn make_slab(shapelen:usize,indexes:std::ops::Range<usize>)->hdf5::Selection{
-    let slab:Vec<SliceOrIndex> = (0..shapelen).map(|i| if i==0 {indexes.clone().into()} else {(..).into()}).collect();
+fn make_slab(shapelen:usize,axis:usize,indexes:std::ops::Range<usize>)->hdf5::Selection{
+    let slab:Vec<SliceOrIndex> = (0..shapelen).map(|i| if i==axis {indexes.clone().into()} else {(..).into()}).collect();
     //Selection::Hyperslab()
     let slab:Hyperslab = slab.into();
     slab.into()
@@ -25,6 +28,8 @@ where
     // hdf5_file: hdf5::File,
     dataset: hdf5::Dataset,
     _marker:PhantomData<T>,
+    /// Dataset is written by MATLAB, so its axes are reversed and frames go along the last one
+    column_major:bool,
     //cache:Mutex<Option<(usize,usize,ArrayND<T>)>>,
     //_dim_marker:PhantomData<D>,
 }
@@ -37,12 +42,23 @@ where
     pub fn new(filename:String, dataset_path:String)->Result<Self, hdf5::Error>{
         let hdf5_file = hdf5::File::open(&filename)?;
         let dataset = hdf5_file.dataset(&dataset_path)?;
-        Ok(Self{dataset, _marker:PhantomData, })//cache:Mutex::new(None)})
+        Ok(Self{dataset, _marker:PhantomData, column_major:false})//cache:Mutex::new(None)})
+    }
+
+    /// Reader of MATLAB array. Frames go along first axis of MATLAB array.
+    pub fn new_column_major(filename:String, dataset_path:String)->Result<Self, hdf5::Error>{
+        let mut res = Self::new(filename, dataset_path)?;
+        res.column_major = true;
+        Ok(res)
+    }
+
+    fn frame_axis(&self)->usize{
+        if self.column_major {self.dataset.shape().len().saturating_sub(1)} else {0}
     }
 
     pub fn check_read(&self)->bool{
         let l = self.dataset.shape().len();
-        if let Ok(_) = self.dataset.read_slice::<T, Selection, ndarray::IxDyn>(make_slab(l, 0..1)){
+        if let Ok(_) = self.dataset.read_slice::<T, Selection, ndarray::IxDyn>(make_slab(l, self.frame_axis(), 0..1)){
             true
         }
         else{
@@ -57,6 +73,18 @@ where
             println!("{:?}",self.dataset.shape());
         }
     }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<T>,ExecutionError>{
+        let sliced = self.dataset.read_slice::<T, Selection, ndarray::IxDyn>(make_slab(self.dataset.shape().len(), self.frame_axis(), start..end))
+            .map_err(|e| ExecutionError::OtherError(format!("Could not read frames {}-{} of {}: {}",start,end,self.dataset.name(),e).into()))?;
+        let sliced:ArrayND<T> = sliced.into();
+        if self.column_major{
+            Ok(sliced.flip_indices())
+        }
+        else{
+            Ok(sliced)
+        }
+    }
 }
 
 impl<T> LazyArrayOperation<ArrayND<T>> for LazyHDF5Reader3D<T>
@@ -69,29 +97,16 @@ where
             0
         }
         else{
-            shape[0]
+            shape[self.frame_axis()]
         }
     }
 
     fn request_range(&self,start:usize,end:usize) -> ArrayND<T>{
-        // let mut cache = self.cache.borrow_mut();
-        // if let Some((old_start,old_end,old_data)) = cache.as_ref(){
-        //     if *old_start==start && *old_end==end{
-        //         return old_data.clone();
-        //     }
-        // }
-        //let sliced:ndarray::Array3<T> = self.dataset.read_slice((start..end,..,..)).unwrap();
-        let sliced = self.dataset.read_slice::<T, Selection, ndarray::IxDyn>(make_slab(self.dataset.shape().len(), start..end)).unwrap();
-
-        // let mut shape = vec![end-start];
-        // let shape_add:Vec<_> = self.dataset.shape().iter().skip(1).map(|x| *x).collect();
-        // shape.extend(shape_add);
-        // //let flat:Vec<T> = sliced.into();
-        // let flat_data:Vec<T> = sliced.into_raw_vec();
-        // let res = ArrayND{shape:shape.into(), flat_data:flat_data.into()};
-        let res:ArrayND<T> = sliced.into();
-        //*cache = Some((start,end,res.clone()));
-        res
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<T>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
 
@@ -109,6 +124,8 @@ where
     dataset: hdf5::Dataset,
     _marker:PhantomData<T>,
     is_matlab:bool,
+    /// Axis of 2D MATLAB time having all entries. MATLAB column vector is stored as 1xN dataset.
+    time_axis:usize,
     //_dim_marker:PhantomData<D>,
 }
 
@@ -146,16 +163,20 @@ where
         if s.len()>2{
             return Err(ReaderCreationError::TimeFormatError);
         }
+        let mut time_axis = 0;
         if s.len()>1{
             is_matlab = true;
+            if s[0]==1 && s[1]>1{
+                time_axis = 1;
+            }
             println!("MATLAB time detected");
         }
-        Ok(Self{dataset, _marker:PhantomData, is_matlab})
+        Ok(Self{dataset, _marker:PhantomData, is_matlab, time_axis})
     }
 
     pub fn check_read(&self)->bool{
         if self.is_matlab{
-            if let Ok(_)= self.dataset.read_slice_2d::<T, _>((0..1,..)) {true} else {false}
+            if let Ok(_)= self.read_matlab(0, 1) {true} else {false}
             //let shape:Vec<usize> = self.dataset.shape().iter().skip(1).map(|x| *x).collect();
             //let flat:Vec<T> = sliced.into();
 
@@ -170,6 +191,30 @@ where
     pub fn print_dtype(&self){
         println!("{:?}",self.dataset.dtype());
     }
+
+    fn read_matlab(&self, start:usize, end:usize) -> Result<ndarray::Array2<T>, hdf5::Error>{
+        if self.time_axis==0{
+            self.dataset.read_slice_2d::<T,_>((start..end,..))
+        }
+        else{
+            self.dataset.read_slice_2d::<T,_>((..,start..end))
+        }
+    }
+
+    fn try_request(&self,start:usize, end:usize) -> Result<RVec<T>,ExecutionError>{
+        let map_err = |e:hdf5::Error| ExecutionError::OtherError(format!("Could not read time {}-{} of {}: {}",start,end,self.dataset.name(),e).into());
+        let sliced = if self.is_matlab{
+            self.read_matlab(start, end).map_err(map_err)?.into_raw_vec_and_offset()
+        }
+        else{
+            self.dataset.read_slice_1d::<T,_>(start..end).map_err(map_err)?.into_raw_vec_and_offset()
+        };
+        let mut res = sliced.0;
+        if let Some(s) = sliced.1{
+            res = res[s..].into();
+        }
+        Ok(res.into())
+    }
 }
 
 impl<T> LazyArrayOperation<RVec<T>> for LazyTimeHDF5Reader<T>
@@ -183,29 +228,11 @@ where
 
 
     fn request_range(&self,start:usize, end:usize) -> RVec<T>{
-        if self.is_matlab{
-            let sliced = self.dataset.read_slice_2d::<T,_>((start..end,..)).unwrap();
-            //let shape:Vec<usize> = self.dataset.shape().iter().skip(1).map(|x| *x).collect();
-            //let flat:Vec<T> = sliced.into();
-            let flat_data = sliced.into_raw_vec_and_offset();
-            let mut res = flat_data.0;
-            if let Some(s) = flat_data.1{
-                res = res[s..].into();
-            }
-            res.into()
-        }
-        else{
-            let sliced = self.dataset.read_slice_1d::<T,_>(start..end).unwrap();
-            //let shape:Vec<usize> = self.dataset.shape().iter().skip(1).map(|x| *x).collect();
-            //let flat:Vec<T> = sliced.into();
-            let flat_data = sliced.into_raw_vec_and_offset();
-            let mut res = flat_data.0;
-            if let Some(s) = flat_data.1{
-                res = res[s..].into();
-            }
-            res.into()
-        }
+        self.try_request(start,end).unwrap()
+    }
 
+    fn try_request_range(&self,start:usize, end:usize) -> RResult<RVec<T>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
 
@@ -228,6 +255,10 @@ impl<T: Clone+Debug+Into<U>,U> LazyArrayOperation<U> for Caster<T>{
         return tmp.into();
 
     }
+
+    fn try_request_range(&self,start:usize, end:usize) -> RResult<U,ExecutionError>{
+        self.0.try_request_range(start,end).map(|x| x.into())
+    }
 }
 
 
@@ -250,6 +281,10 @@ impl<T: Clone+Debug+abi_stable::StableAbi,U: abi_stable::StableAbi+Clone+From<T>
         return tmp.cast();
 
     }
+
+    fn try_request_range(&self,start:usize, end:usize) -> RResult<ArrayND<U>,ExecutionError>{
+        self.0.try_request_range(start,end).map(|x| x.cast())
+    }
 }
 
 
@@ -260,6 +295,10 @@ impl UnsignedToFloatArrayCaster{
     pub fn new(src: LazyArrayOperationBox<ArrayND<u64>>) -> Self{
         Self(src)
     }
+
+    fn convert(tmp:ArrayND<u64>)->ArrayND<f64>{
+        tmp.map(|x| *x as f64)
+    }
 }
 
 impl LazyArrayOperation<ArrayND<f64>> for UnsignedToFloatArrayCaster{
@@ -269,10 +308,55 @@ impl LazyArrayOperation<ArrayND<f64>> for UnsignedToFloatArrayCaster{
 
     fn request_range(&self,start:usize, end:usize) -> ArrayND<f64>{
         let tmp:ArrayND<u64> = self.0.request_range(start,end);
-        let shape = tmp.shape;
-        let mut old_flat_data = tmp.flat_data;
-        let flat_data:RVec<f64> = old_flat_data.drain(..).map(|x| x as f64).collect();
-        ArrayND { flat_data, shape }
+        Self::convert(tmp)
+    }
+
+    fn try_request_range(&self,start:usize, end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.0.try_request_range(start,end).map(Self::convert)
+    }
+}
+
+/// Element of trigger dataset written by Save HDF5 signal
+#[derive(hdf5::H5Type,Clone,Debug)]
+#[repr(C)]
+pub struct TriggerRecord{
+    pub position:u64,
+    pub duration:u64,
+    pub tag:hdf5::types::VarLenUnicode,
+}
+
+/// Triggers loaded from file. Tags are sorted by position.
+#[derive(Clone,Debug)]
+pub struct StoredTrigger{
+    tags:std::sync::Arc<SparseTagArray>,
+    length:usize,
+}
+
+impl StoredTrigger{
+    pub fn new(mut records:Vec<TriggerRecord>, length:usize)->Self{
+        // Range requests rely on sorted positions, but file may be written by other software
+        records.sort_by_key(|x| x.position);
+        let mut tags = SparseTagArray::with_capacity(records.len());
+        for record in records.into_iter(){
+            tags.push(record.tag.as_str(), record.position as usize, record.duration as usize);
+        }
+        Self { tags: std::sync::Arc::new(tags), length }
+    }
+}
+
+impl LazyArrayOperation<SparseTagArray> for StoredTrigger{
+    fn length(&self) -> usize where {
+        self.length
+    }
+
+    fn calculate_overhead(&self,_start:usize,_end:usize,) -> usize where {
+        0
+    }
 
+    fn request_range(&self,start:usize, end:usize) -> SparseTagArray{
+        let tags = &self.tags.tags;
+        let first = tags.partition_point(|x| x.position<start);
+        let last = tags.partition_point(|x| x.position<end);
+        SparseTagArray { tags: tags[first..last].iter().cloned().collect() }
     }
 }
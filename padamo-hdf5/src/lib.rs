@@ -5,11 +5,11 @@ use padamo_api::nodes_vec;
 use abi_stable::sabi_extern_fn;
 
 pub mod nodes_mod;
-pub mod ops;
+pub use padamo_hdf5_ops as ops;
 
 #[export_root_module]
 pub fn plugin_root()->PadamoModule_Ref{
-    PadamoModule{nodes}.leak_into_prefix()
+    PadamoModule{nodes, share_cache:padamo_api::share_cache, info:padamo_api::plugin_info!()}.leak_into_prefix()
 }
 
 #[sabi_extern_fn]
@@ -22,6 +22,9 @@ pub fn nodes(_library_dir:RString)->RVec<CalculationNodeBox>{
         crate::nodes_mod::LazyHDF5ArrayNode,
         crate::nodes_mod::LazyHDF5TimeNode,
         crate::nodes_mod::SaveHDF5Node,
-        crate::nodes_mod::SaveHDF5ArrayNode
+        crate::nodes_mod::SaveHDF5ArrayNode,
+        crate::nodes_mod::LazyHDF5TypedArrayNode,
+        crate::nodes_mod::SaveHDF5TypedArrayNode,
+        crate::nodes_mod::LoadHDF5TriggersNode
     )
 }
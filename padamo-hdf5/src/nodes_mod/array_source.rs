@@ -59,3 +59,53 @@ impl CalculationNode for LazyHDF5ArrayNode{
     }
 
 }
+
+
+#[derive(Debug,Clone)]
+pub struct LazyHDF5TypedArrayNode;
+
+impl LazyHDF5TypedArrayNode{
+    fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError>{
+        let filename = args.inputs.request_string("Filename")?;
+        let spatial = args.constants.request_string("Field")?;
+        let spatial_reader = super::make_typed_spatial(&filename, &spatial).map_err(|e| ExecutionError::OtherError(format!("HDF error : {}",e).into()))?;
+        args.outputs.set_value("Array", spatial_reader.into())
+    }
+}
+
+impl CalculationNode for LazyHDF5TypedArrayNode{
+    fn name(&self,) -> RString where {
+        "Lazy HDF5 typed array reader".into()
+    }
+
+    fn category(&self,) -> abi_stable::std_types::RVec<abi_stable::std_types::RString>where {
+        padamo_api::common_categories::array_sources()
+    }
+
+    fn identifier(&self,) -> RString where {
+        "padamohdf5.typed_array_reader".into()
+    }
+
+    fn inputs(&self) -> RVec<CalculationIO>{
+        ports!(
+            ("Filename", ContentType::String)
+        )
+    }
+
+    fn outputs(&self) -> RVec<CalculationIO> {
+        ports!(
+            ("Array", ContentType::TypedSignal)
+        )
+    }
+
+    fn constants(&self,) -> RVec<CalculationConstant>where {
+        constants!(
+            ("Field", "Spatial/Array field", "pdm_2d_rot_global")
+        )
+    }
+
+    fn calculate(&self, args:CalculationNodeArguments) -> abi_stable::std_types::RResult<(),ExecutionError> {
+        self.calculate(args).into()
+    }
+
+}
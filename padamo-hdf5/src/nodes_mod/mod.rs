@@ -6,6 +6,7 @@ pub use joined::LazyHDF5DirSignalNode;
 
 pub mod array_source;
 pub use array_source::LazyHDF5ArrayNode;
+pub use array_source::LazyHDF5TypedArrayNode;
 
 pub mod time_source;
 pub use time_source::LazyHDF5TimeNode;
@@ -13,57 +14,67 @@ pub use time_source::LazyHDF5TimeNode;
 pub mod save;
 pub use save::SaveHDF5Node;
 pub use save::SaveHDF5ArrayNode;
+pub use save::SaveHDF5TypedArrayNode;
+
+pub mod triggers;
+pub use triggers::LoadHDF5TriggersNode;
 
 use abi_stable::sabi_trait::prelude::TD_Opaque;
 
 
 use crate::ops::{LazyHDF5Reader3D,ArrayCaster,UnsignedToFloatArrayCaster};
 use padamo_api::lazy_array_operations::{LazyArrayOperationBox,LazyDetectorSignal};
+use padamo_api::lazy_array_operations::typed::{SignalElement, TypedDetectorSignal};
 use padamo_api::lazy_array_operations::ndim_array;
 
 
+/// Reader of dataset as `f64` signal. Elements of other types are converted when frames are requested.
 pub fn make_spatial(filename:String, spatial:String)->Result<LazyDetectorSignal,hdf5::Error>{
-    let first_reader = LazyHDF5Reader3D::<f64>::new(filename.clone().into(), spatial.clone())?;
-    //first_reader.print_dtype();
-    if first_reader.check_read(){
-        return Ok(LazyDetectorSignal::from_value(first_reader,TD_Opaque));
-    }
-    else{
-        println!("Failed reading as f64");
-    }
+    make_typed_spatial(&filename, &spatial).map(TypedDetectorSignal::to_f64)
+}
 
-    let reader_u64 = LazyHDF5Reader3D::<u64>::new(filename.clone().into(), spatial.clone())?;
-    if reader_u64.check_read(){
-        let ubox:LazyArrayOperationBox<ndim_array::ArrayND<u64>> = LazyArrayOperationBox::from_value(reader_u64,TD_Opaque);
-        let conv = UnsignedToFloatArrayCaster::new(ubox);
-        let res = LazyDetectorSignal::from_value(conv,TD_Opaque);
-        return Ok(res);
-    }
-    else{
-        println!("Failed reading as u64");
-    }
 
-    let reader_u32 = LazyHDF5Reader3D::<u32>::new(filename.clone().into(), spatial.clone())?;
-    if reader_u32.check_read(){
-        let ubox:LazyArrayOperationBox<ndim_array::ArrayND<u32>> = LazyArrayOperationBox::from_value(reader_u32,TD_Opaque);
-        let conv = ArrayCaster::new(ubox);
-        let res = LazyDetectorSignal::from_value(conv,TD_Opaque);
-        return Ok(res);
-    }
-    else{
-        println!("Failed reading as u32");
-    }
+fn typed_reader<T>(filename:&str, spatial:&str)->Result<TypedDetectorSignal,hdf5::Error>
+where
+    T:SignalElement+Default+hdf5::H5Type
+{
+    let reader = LazyHDF5Reader3D::<T>::new(filename.into(), spatial.into())?;
+    Ok(TypedDetectorSignal::new(LazyArrayOperationBox::from_value(reader,TD_Opaque)))
+}
 
-    let reader_i32 = LazyHDF5Reader3D::<i32>::new(filename.clone().into(), spatial)?;
-    if reader_i32.check_read(){
-        let ubox:LazyArrayOperationBox<ndim_array::ArrayND<i32>> = LazyArrayOperationBox::from_value(reader_i32,TD_Opaque);
-        let conv = ArrayCaster::new(ubox);
-        let res = LazyDetectorSignal::from_value(conv,TD_Opaque);
-        return Ok(res);
-    }
-    else{
-        println!("Failed reading as i32");
-    }
+fn widened_reader<T,U>(filename:&str, spatial:&str)->Result<TypedDetectorSignal,hdf5::Error>
+where
+    T:Clone+std::fmt::Debug+Default+abi_stable::StableAbi+hdf5::H5Type+Send+Sync+'static,
+    U:SignalElement+From<T>
+{
+    let reader = LazyHDF5Reader3D::<T>::new(filename.into(), spatial.into())?;
+    let ubox:LazyArrayOperationBox<ndim_array::ArrayND<T>> = LazyArrayOperationBox::from_value(reader,TD_Opaque);
+    let conv:ArrayCaster<T> = ArrayCaster::new(ubox);
+    Ok(TypedDetectorSignal::new::<U>(LazyArrayOperationBox::from_value(conv,TD_Opaque)))
+}
 
-    panic!("Could not determine reader type");
+/// Reader keeping element type of dataset. Types without own signal variant are widened to nearest supported one.
+pub fn make_typed_spatial(filename:&str, spatial:&str)->Result<TypedDetectorSignal,hdf5::Error>{
+    use hdf5::types::{TypeDescriptor, IntSize, FloatSize};
+    let descriptor = hdf5::File::open(filename)?.dataset(spatial)?.dtype()?.to_descriptor()?;
+    match descriptor {
+        TypeDescriptor::Float(FloatSize::U4) => typed_reader::<f32>(filename, spatial),
+        TypeDescriptor::Float(_) => typed_reader::<f64>(filename, spatial),
+        TypeDescriptor::Unsigned(IntSize::U1) => typed_reader::<u8>(filename, spatial),
+        TypeDescriptor::Unsigned(IntSize::U2) => typed_reader::<u16>(filename, spatial),
+        TypeDescriptor::Unsigned(IntSize::U4) => typed_reader::<u32>(filename, spatial),
+        TypeDescriptor::Integer(IntSize::U1) => widened_reader::<i8,i32>(filename, spatial),
+        TypeDescriptor::Integer(IntSize::U2) => widened_reader::<i16,i32>(filename, spatial),
+        TypeDescriptor::Integer(IntSize::U4) => typed_reader::<i32>(filename, spatial),
+        TypeDescriptor::Integer(IntSize::U8) => typed_reader::<i64>(filename, spatial),
+        TypeDescriptor::Boolean => typed_reader::<bool>(filename, spatial),
+        // u64 does not fit any integer signal
+        TypeDescriptor::Unsigned(IntSize::U8) => {
+            let reader = LazyHDF5Reader3D::<u64>::new(filename.into(), spatial.into())?;
+            let ubox:LazyArrayOperationBox<ndim_array::ArrayND<u64>> = LazyArrayOperationBox::from_value(reader,TD_Opaque);
+            Ok(TypedDetectorSignal::F64(LazyDetectorSignal::from_value(UnsignedToFloatArrayCaster::new(ubox),TD_Opaque)))
+        },
+        // Let HDF5 convert the rest if it can
+        _ => typed_reader::<f64>(filename, spatial),
+    }
 }
@@ -1,57 +1,163 @@
 use abi_stable::std_types::ROption::{self, RSome};
 use abi_stable::std_types::{RVec, RString, RResult};
+use std::fmt::Debug;
+use std::path::Path;
+use abi_stable::StableAbi;
+
 use padamo_api::{constants, ports, prelude::*};
+use padamo_api::calculation_nodes::progress::write_file_atomically;
+use padamo_api::lazy_array_operations::{ArrayND, LazyArrayOperationBox, LazyTriSignal};
+use padamo_api::lazy_array_operations::typed::{SignalDType, TypedDetectorSignal};
+use padamo_api::lazy_array_operations::cache::cache_budget;
+use padamo_api::lazy_array_operations::prefetch::DEFAULT_PREFETCH_DEPTH;
+use padamo_api::calculation_nodes::provenance::Provenance;
+use crate::ops::TriggerRecord;
+use super::triggers::{trigger_records, write_provenance};
 // use crate::compat::arraynd_to_ndarray;
 
+/// Upper limit of HDF5 chunks requested from signal at once
+const CHUNKS_PER_REQUEST:usize = 64;
+
+/// Writers take this part of cache budget for frames being written
+const WRITE_BUDGET_PART:usize = 8;
+
+/// Selection of frames [start, end) in dataset with frames of shape `frame_shape`
+fn frames_slab(start:usize, end:usize, frame_shape:&[usize])->hdf5::Hyperslab{
+    let mut slabs:Vec<hdf5::SliceOrIndex> = Vec::with_capacity(frame_shape.len()+1);
+    slabs.push((start..end).into());
+    for dim in frame_shape.iter(){
+        slabs.push((0..*dim).into());
+    }
+    slabs.into()
+}
+
+fn request_chunk_size(args:&CalculationNodeArguments)->Result<usize,ExecutionError>{
+    let chunk_size = args.constants.request_integer("chunk")?;
+    let chunk_size:usize = chunk_size.try_into().map_err(ExecutionError::from_error)?;
+    if chunk_size==0{
+        return Err(ExecutionError::OtherError("Chunk must be positive".into()));
+    }
+    Ok(chunk_size)
+}
+
+/// Number of frames requested at once. It is a multiple of HDF5 chunk such that
+/// prefetched ranges, range being written and its copy fit into part of memory budget.
+fn write_step(chunk_size:usize, frame_bytes:usize)->usize{
+    let budget = cache_budget().budget()/WRITE_BUDGET_PART;
+    let in_flight = DEFAULT_PREFETCH_DEPTH+2;
+    let frames = budget/(frame_bytes.max(1)*in_flight);
+    (frames/chunk_size).clamp(1, CHUNKS_PER_REQUEST)*chunk_size
+}
+
+/// Creates empty dataset growing along first axis as frames are appended
+fn create_growing_dataset<T:hdf5::H5Type>(file:&hdf5::File, name:&str, frame_shape:&[usize], chunk_size:usize, args:&CalculationNodeArguments)->Result<hdf5::Dataset,ExecutionError>{
+    let mut extents:Vec<hdf5::Extent> = vec![(0..).into()];
+    extents.extend(frame_shape.iter().map(|x| hdf5::Extent::from(*x)));
+    let mut chunk = vec![chunk_size];
+    chunk.extend(frame_shape.iter().copied());
+
+    let mut builder = file.new_dataset::<T>()
+        .chunk(chunk)
+        .shape(hdf5::SimpleExtents::new(extents));
+    if args.constants.request_boolean("deflate")?{
+        let deflate_level = args.constants.request_integer("deflate_level")?;
+        let deflate_level = deflate_level.try_into().map_err(ExecutionError::from_error)?;
+        builder = builder.deflate(deflate_level);
+    }
+    builder.create(name).map_err(ExecutionError::from_error)
+}
+
+/// Grows dataset to `end` frames
+fn grow_dataset(ds:&hdf5::Dataset, end:usize, frame_shape:&[usize])->Result<(),ExecutionError>{
+    let mut shape = vec![end];
+    shape.extend(frame_shape.iter().copied());
+    ds.resize(shape).map_err(ExecutionError::from_error)
+}
+
 #[derive(Clone,Debug)]
 pub struct SaveHDF5Node;
 
+/// Element type of spatial dataset. Constant `dtype` overrides type of optional `Typed signal` input, `f64` is used if neither is given.
+fn signal_dtype(args:&CalculationNodeArguments)->Result<SignalDType,ExecutionError>{
+    let name = args.constants.request_string("dtype")?;
+    if !name.trim().is_empty(){
+        return SignalDType::parse(&name);
+    }
+    match args.inputs.0.get("Typed signal"){
+        Some(Content::TypedSignal(typed))=>Ok(typed.dtype()),
+        Some(_)=>Err(ExecutionError::TypeError),
+        None=>Ok(SignalDType::F64),
+    }
+}
+
 impl SaveHDF5Node{
+    /// Signals read from integer datasets are saved without widening if typed output of reader is connected too.
+    fn write_file(&self, path:&Path, signal:&LazyTriSignal, args:&CalculationNodeArguments) -> Result<(),ExecutionError> {
+        let dtype = signal_dtype(args)?;
+        let spatial = TypedDetectorSignal::from_f64(signal.0.clone(), dtype);
+        padamo_api::with_typed_signal!(&spatial, x => write_signal_file(path, x, signal, args))
+    }
+
     fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError> {
         let signal = args.inputs.request_detectorfulldata("Signal")?;
         let file_path = args.inputs.request_string("File path")?.to_string();
-        let h5_file = hdf5::File::create(file_path).map_err(ExecutionError::from_error)?;
-
-        let chunk_size = args.constants.request_integer("chunk")?;
-        let chunk_size:usize = chunk_size.try_into().map_err(ExecutionError::from_error)?;
-
-        let spatial_name = args.constants.request_string("spatial_field")?.into_string();
-        let temporal_name = args.constants.request_string("temporal_field")?.into_string();
-
-        let spatial = signal.0.request_range(0,signal.0.length());
-        let deflate = args.constants.request_boolean("deflate")?;
+        // File appears only when all data is written
+        write_file_atomically(Path::new(&file_path), |path| self.write_file(path, &signal, &args))
+    }
+}
 
-        let ds_shape:Vec<usize> = spatial.shape.clone().into();
-        let mut chunk_3d = ds_shape.clone();
-        chunk_3d[0] = chunk_size;
+/// Frames are written by ranges fitting into memory budget. Signal, time and triggers grow together and file is flushed
+/// after every range, so `.part` file left by killed application is readable up to the last written range.
+/// Triggers are stored as compound dataset (position, duration, tag) and provenance as attributes of file root.
+fn write_signal_file<T>(path:&Path, spatial:&LazyArrayOperationBox<ArrayND<T>>, signal:&LazyTriSignal, args:&CalculationNodeArguments) -> Result<(),ExecutionError>
+where
+    T:Clone+Debug+StableAbi+hdf5::H5Type+Send+Sync+'static
+{
+    let h5_file = hdf5::File::create(path).map_err(ExecutionError::from_error)?;
+
+    let chunk_size = request_chunk_size(args)?;
+
+    let spatial_name = args.constants.request_string("spatial_field")?.into_string();
+    let temporal_name = args.constants.request_string("temporal_field")?.into_string();
+
+    let length = spatial.length();
+    let sample = spatial.try_request_range(0,length.min(1)).into_result()?;
+    let frame_shape:Vec<usize> = sample.shape.iter().skip(1).copied().collect();
+
+    let space_ds = create_growing_dataset::<T>(&h5_file, &spatial_name, &frame_shape, chunk_size, args)?;
+    let time_ds = create_growing_dataset::<f64>(&h5_file, &temporal_name, &[], chunk_size, args)?;
+    let trigger_ds = if let RSome(_) = &signal.2{
+        let trigger_name = args.constants.request_string("trigger_field")?.into_string();
+        Some(create_growing_dataset::<TriggerRecord>(&h5_file, &trigger_name, &[], chunk_size, args)?)
+    }
+    else{
+        None
+    };
+    let mut triggers_written = 0;
+    if args.constants.request_boolean("provenance")?{
+        write_provenance(&h5_file, &Provenance::from_args(args))?;
+    }
 
-        let mut space_ds = h5_file.new_dataset::<f64>()
-            .chunk(chunk_3d)
-            .shape(ds_shape);
-        if deflate{
-            let deflate_level = args.constants.request_integer("deflate_level")?;
-            let deflate_level = deflate_level.try_into().map_err(ExecutionError::from_error)?;
-            space_ds = space_ds.deflate(deflate_level);
+    let frame_bytes = frame_shape.iter().product::<usize>()*std::mem::size_of::<T>();
+    let step = write_step(chunk_size, frame_bytes);
+    spatial.clone().prefetched(step, DEFAULT_PREFETCH_DEPTH).try_for_each_chunk(0, length, step, args.progress, |start, spatial|{
+        let end = start+spatial.shape[0];
+        let temporal = signal.1.try_request_range(start,end).into_result()?;
+        grow_dataset(&space_ds, end, &frame_shape)?;
+        space_ds.write_slice(&spatial.to_ndarray(), frames_slab(start, end, &frame_shape)).map_err(ExecutionError::from_error)?;
+        grow_dataset(&time_ds, end, &[])?;
+        time_ds.write_slice(&temporal, (start..end,)).map_err(ExecutionError::from_error)?;
+        if let (Some(ds), RSome(trigger)) = (&trigger_ds, &signal.2){
+            let records = trigger_records(&trigger.try_request_range(start,end).into_result()?)?;
+            if !records.is_empty(){
+                let trigger_end = triggers_written+records.len();
+                grow_dataset(ds, trigger_end, &[])?;
+                ds.write_slice(&records, (triggers_written..trigger_end,)).map_err(ExecutionError::from_error)?;
+                triggers_written = trigger_end;
+            }
         }
-        let space_ds = space_ds.create(spatial_name.as_str()).map_err(ExecutionError::from_error)?;
-        space_ds.write(&spatial.to_ndarray()).map_err(ExecutionError::from_error)?;
-
-        let temporal = signal.1.request_range(0,signal.0.length());
-
-        let mut time_ds = h5_file.new_dataset::<f64>()
-            .chunk(chunk_size)
-            .shape(vec![temporal.len()]);
-        if deflate{
-            let deflate_level = args.constants.request_integer("deflate_level")?;
-            let deflate_level = deflate_level.try_into().map_err(ExecutionError::from_error)?;
-            time_ds = time_ds.deflate(deflate_level);
-        }
-
-        let time_ds = time_ds.create(temporal_name.as_str()).map_err(ExecutionError::from_error)?;
-        time_ds.write(&temporal).map_err(ExecutionError::from_error)?;
-
-        Ok(())
-    }
+        h5_file.flush().map_err(ExecutionError::from_error)
+    })
 }
 
 impl CalculationNode for SaveHDF5Node{
@@ -77,7 +183,8 @@ impl CalculationNode for SaveHDF5Node{
     fn inputs(&self,) -> RVec<CalculationIO>where {
         ports!(
             ("Signal",ContentType::DetectorFullData),
-            ("File path",ContentType::String)
+            ("File path",ContentType::String),
+            CalculationIO::new("Typed signal", ContentType::TypedSignal).optional()
         )
     }
 
@@ -91,6 +198,9 @@ impl CalculationNode for SaveHDF5Node{
             ("deflate_level",3),
             ("spatial_field","pdm_2d_rot_global"),
             ("temporal_field","unixtime_dbl_global"),
+            ("trigger_field","triggers"),
+            ("dtype","Signal element type (empty to take from Typed signal)",""),
+            ("provenance","Store graph and sources",true),
             ("chunk",16)
         )
     }
@@ -106,36 +216,46 @@ impl CalculationNode for SaveHDF5Node{
 #[derive(Clone,Debug)]
 pub struct SaveHDF5ArrayNode;
 
+/// Writes `array` to dataset `field` of existing file. Dataset has element type of array.
+fn write_array<T>(file_path:&str, array:&LazyArrayOperationBox<ArrayND<T>>, args:&CalculationNodeArguments) -> Result<(),ExecutionError>
+where
+    T:Clone+Debug+StableAbi+hdf5::H5Type+Send+Sync+'static
+{
+    let h5_file = hdf5::File::append(file_path).map_err(ExecutionError::from_error)?;
+
+    let chunk_size = request_chunk_size(args)?;
+
+    let spatial_name = args.constants.request_string("field")?.into_string();
+
+    let length = array.length();
+    let sample = array.try_request_range(0,length.min(1)).into_result()?;
+    let frame_shape:Vec<usize> = sample.shape.iter().skip(1).copied().collect();
+
+    let space_ds = create_growing_dataset::<T>(&h5_file, &spatial_name, &frame_shape, chunk_size, args)?;
+
+    let frame_bytes = frame_shape.iter().product::<usize>()*std::mem::size_of::<T>();
+    let step = write_step(chunk_size, frame_bytes);
+    let res = array.clone().prefetched(step, DEFAULT_PREFETCH_DEPTH).try_for_each_chunk(0, length, step, args.progress, |start, spatial|{
+        let end = start+spatial.shape[0];
+        grow_dataset(&space_ds, end, &frame_shape)?;
+        space_ds.write_slice(&spatial.to_ndarray(), frames_slab(start, end, &frame_shape)).map_err(ExecutionError::from_error)?;
+        h5_file.flush().map_err(ExecutionError::from_error)
+    });
+    if let Err(e) = res{
+        // Dataset is added to existing file, so only the dataset is removed
+        drop(space_ds);
+        let _ = h5_file.unlink(spatial_name.as_str());
+        return Err(e);
+    }
+
+    Ok(())
+}
+
 impl SaveHDF5ArrayNode{
     fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError> {
         let array = args.inputs.request_detectorsignal("Array")?;
         let file_path = args.inputs.request_string("File path")?.to_string();
-        let h5_file = hdf5::File::append(file_path).map_err(ExecutionError::from_error)?;
-
-        let chunk_size = args.constants.request_integer("chunk")?;
-        let chunk_size:usize = chunk_size.try_into().map_err(ExecutionError::from_error)?;
-
-        let spatial_name = args.constants.request_string("field")?.into_string();
-
-        let spatial = array.request_range(0,array.length());
-        let deflate = args.constants.request_boolean("deflate")?;
-
-        let ds_shape:Vec<usize> = spatial.shape.clone().into();
-        let mut chunk_3d = ds_shape.clone();
-        chunk_3d[0] = chunk_size;
-
-        let mut space_ds = h5_file.new_dataset::<f64>()
-            .chunk(chunk_3d)
-            .shape(ds_shape);
-        if deflate{
-            let deflate_level = args.constants.request_integer("deflate_level")?;
-            let deflate_level = deflate_level.try_into().map_err(ExecutionError::from_error)?;
-            space_ds = space_ds.deflate(deflate_level);
-        }
-        let space_ds = space_ds.create(spatial_name.as_str()).map_err(ExecutionError::from_error)?;
-        space_ds.write(&spatial.to_ndarray()).map_err(ExecutionError::from_error)?;
-
-        Ok(())
+        write_array(&file_path, &array, &args)
     }
 }
 
@@ -183,3 +303,57 @@ impl CalculationNode for SaveHDF5ArrayNode{
         self.calculate(args).into()
     }
 }
+
+
+
+#[derive(Clone,Debug)]
+pub struct SaveHDF5TypedArrayNode;
+
+impl SaveHDF5TypedArrayNode{
+    fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError> {
+        let array = args.inputs.request_typedsignal("Array")?;
+        let file_path = args.inputs.request_string("File path")?.to_string();
+        padamo_api::with_typed_signal!(&array, x => write_array(&file_path, x, &args))
+    }
+}
+
+impl CalculationNode for SaveHDF5TypedArrayNode{
+    fn name(&self,) -> RString {
+        "Save HDF5 typed array".into()
+    }
+    fn category(&self,) -> RVec<RString>{
+        padamo_api::common_categories::data_savers()
+    }
+
+    fn identifier(&self,) -> RString where {
+        "padamohdf5.typed_array_writer".into()
+    }
+
+    fn is_primary(&self,) -> bool where {
+        true
+    }
+
+    fn inputs(&self,) -> RVec<CalculationIO>where {
+        ports!(
+            ("Array",ContentType::TypedSignal),
+            ("File path",ContentType::String)
+        )
+    }
+
+    fn outputs(&self,) -> RVec<CalculationIO>where {
+        ports!()
+    }
+
+    fn constants(&self,) -> RVec<CalculationConstant>where {
+        constants!(
+            ("deflate",true),
+            ("deflate_level",3),
+            ("field","data"),
+            ("chunk",16)
+        )
+    }
+
+    fn calculate(&self, args:CalculationNodeArguments) -> RResult<(),ExecutionError> {
+        self.calculate(args).into()
+    }
+}
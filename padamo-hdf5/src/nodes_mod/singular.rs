@@ -14,17 +14,28 @@ pub struct LazyHDF5SignalNode;
 impl LazyHDF5SignalNode{
     fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError>{
         let filename = args.inputs.request_string("Filename")?;
-        let spatial = args.constants.request_string("Spatial")?.into();
+        let spatial = args.constants.request_string("Spatial")?;
         //let spatial = super::make_spatial()
         let temporal = args.constants.request_string("Temporal")?.into();
         //let spatial_reader = LazyHDF5Reader3D::<f64>::new(filename.clone().into(), spatial);
-        let spatial_reader = super::make_spatial(filename.clone().into(), spatial);
-        let temporal_reader = LazyTimeHDF5Reader::<f64>::new(filename.into(), temporal);
+        let spatial_reader = super::make_typed_spatial(&filename, &spatial);
+        let temporal_reader = LazyTimeHDF5Reader::<f64>::new(filename.clone().into(), temporal);
         match (spatial_reader,temporal_reader){
             (Ok(sp),Ok(tmp))=>{
-                let signal:LazyTriSignal = (sp,LazyTimeSignal::from_value(tmp,TD_Opaque) ,ROption::RNone).into();
+                // Triggers are present in files written by Save HDF5 signal. Empty field name means that they are not read.
+                let triggers = args.constants.request_string("Triggers")?;
+                let trigger = if triggers.is_empty(){
+                    None
+                }
+                else{
+                    super::triggers::read_triggers(&filename, &triggers, sp.length()).map_err(ExecutionError::from_error)?
+                };
+                // Processing nodes take f64 signals, so main output is converted lazily. Save HDF5 signal restores element type from typed output.
+                let signal:LazyTriSignal = (sp.clone().to_f64(),LazyTimeSignal::from_value(tmp,TD_Opaque) ,trigger.into()).into();
                 //let signal:LazyTriSignal = (LazyDetectorSignal::from_value(sp,TD_Opaque),LazyTimeSignal::from_value(tmp,TD_Opaque) ,ROption::RNone).into();
-                args.outputs.set_value("Signal", Content::DetectorFullData(signal))
+                args.outputs.set_value("Signal", Content::DetectorFullData(signal))?;
+                // Element type of dataset is kept, so it can be saved back without widening
+                args.outputs.set_value("Typed signal", sp.into())
             },
             (Err(sp),Err(tmp))=>{
                 Err(ExecutionError::OtherError(format!("HDF error (spatiotemporal): {}; {}",sp,tmp).into()))
@@ -68,14 +79,16 @@ impl CalculationNode for LazyHDF5SignalNode{
 
     fn outputs(&self) -> RVec<CalculationIO> {
         ports!(
-            ("Signal", ContentType::DetectorFullData)
+            ("Signal", ContentType::DetectorFullData),
+            ("Typed signal", ContentType::TypedSignal)
         )
     }
 
     fn constants(&self,) -> RVec<CalculationConstant>where {
         constants!(
             ("Spatial", "pdm_2d_rot_global"),
-            ("Temporal", "unixtime_dbl_global")
+            ("Temporal", "unixtime_dbl_global"),
+            ("Triggers", "")
         )
     }
 
@@ -0,0 +1,103 @@
+use abi_stable::std_types::{ROption, RResult, RString, RVec};
+use abi_stable::sabi_trait::prelude::TD_Opaque;
+use hdf5::types::VarLenUnicode;
+
+use padamo_api::{constants, ports, prelude::*};
+use padamo_api::calculation_nodes::provenance::Provenance;
+use padamo_api::lazy_array_operations::LazyTrigger;
+use padamo_api::trigger_operations::SparseTagArray;
+
+use crate::ops::{StoredTrigger, TriggerRecord};
+
+pub fn trigger_records(tags:&SparseTagArray)->Result<Vec<TriggerRecord>,ExecutionError>{
+    tags.tags.iter().map(|x|{
+        let tag:VarLenUnicode = x.tag.as_str().parse().map_err(ExecutionError::from_error)?;
+        Ok(TriggerRecord { position: x.position as u64, duration: x.duration as u64, tag })
+    }).collect()
+}
+
+/// Reads triggers stored by Save HDF5 signal. Returns `None` if file has no dataset `field`.
+pub fn read_triggers(filename:&str, field:&str, length:usize)->Result<Option<LazyTrigger>,hdf5::Error>{
+    let file = hdf5::File::open(filename)?;
+    if !file.link_exists(field){
+        return Ok(None);
+    }
+    let records:Vec<TriggerRecord> = file.dataset(field)?.read_raw()?;
+    Ok(Some(LazyTrigger::from_value(StoredTrigger::new(records, length), TD_Opaque)))
+}
+
+fn string_attr(location:&hdf5::Location, name:&str, value:&str)->Result<(),ExecutionError>{
+    let value:VarLenUnicode = value.parse().map_err(ExecutionError::from_error)?;
+    location.new_attr::<VarLenUnicode>().shape(()).create(name)
+        .and_then(|attr| attr.write_scalar(&value))
+        .map_err(ExecutionError::from_error)
+}
+
+/// Attaches graph, constants of writer node, seed, version and source files to root of file
+pub fn write_provenance(file:&hdf5::File, provenance:&Provenance)->Result<(),ExecutionError>{
+    if let Some(graph) = &provenance.graph{
+        string_attr(file, "padamo_graph", graph)?;
+    }
+    string_attr(file, "padamo_node_constants", &provenance.constants)?;
+    string_attr(file, "padamo_version", &provenance.version)?;
+    file.new_attr::<u64>().shape(()).create("padamo_seed")
+        .and_then(|attr| attr.write_scalar(&provenance.seed))
+        .map_err(ExecutionError::from_error)?;
+    let sources:Vec<VarLenUnicode> = provenance.sources.iter()
+        .map(|x| x.parse().map_err(ExecutionError::from_error))
+        .collect::<Result<_,_>>()?;
+    file.new_attr_builder().with_data(&sources).create("padamo_sources").map_err(ExecutionError::from_error)?;
+    Ok(())
+}
+
+#[derive(Clone,Debug)]
+pub struct LoadHDF5TriggersNode;
+
+impl LoadHDF5TriggersNode{
+    fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError>{
+        let mut signal = args.inputs.request_detectorfulldata("Signal")?;
+        let filename = args.inputs.request_string("Filename")?;
+        let field = args.constants.request_string("Triggers")?;
+        let trigger = read_triggers(&filename, &field, signal.0.length()).map_err(ExecutionError::from_error)?;
+        let trigger = trigger.ok_or_else(|| ExecutionError::OtherError(format!("File {} has no triggers {}", filename, field).into()))?;
+        signal.2 = ROption::RSome(trigger);
+        args.outputs.set_value("Signal", signal.into())
+    }
+}
+
+impl CalculationNode for LoadHDF5TriggersNode{
+    fn name(&self,) -> RString where {
+        "Load HDF5 triggers".into()
+    }
+
+    fn category(&self,) -> RVec<RString>where {
+        padamo_api::common_categories::data_sources()
+    }
+
+    fn identifier(&self,) -> RString where {
+        "padamohdf5.trigger_reader".into()
+    }
+
+    fn inputs(&self) -> RVec<CalculationIO>{
+        ports!(
+            ("Signal", ContentType::DetectorFullData),
+            ("Filename", ContentType::String)
+        )
+    }
+
+    fn outputs(&self) -> RVec<CalculationIO> {
+        ports!(
+            ("Signal", ContentType::DetectorFullData)
+        )
+    }
+
+    fn constants(&self,) -> RVec<CalculationConstant>where {
+        constants!(
+            ("Triggers", "triggers")
+        )
+    }
+
+    fn calculate(&self, args:CalculationNodeArguments) -> RResult<(),ExecutionError> {
+        self.calculate(args).into()
+    }
+}
@@ -1,9 +1,18 @@
 
-use abi_stable::{rvec, std_types::RVec};
+use abi_stable::std_types::{RResult, RVec};
 use padamo_api::lazy_array_operations::{merge::Merge, ArrayND, LazyArrayOperation};
+use padamo_api::prelude::ExecutionError;
 use oxyroot::RootFile;
 pub use pseudotime::ops::AddTime;
 
+fn root_read_error<E:std::fmt::Display>(file_path:&str, e:E)->ExecutionError{
+    ExecutionError::OtherError(format!("Could not read ROOT file {}: {}", file_path, e).into())
+}
+
+fn missing_branch_error(file_path:&str, branch:&str)->ExecutionError{
+    ExecutionError::OtherError(format!("ROOT file {} has no branch {}", file_path, branch).into())
+}
+
 
 #[derive(Clone,Debug)]
 pub struct LazyROOTSpatialReader{
@@ -16,6 +25,33 @@ impl LazyROOTSpatialReader{
     pub fn new(file_path: String, tree: String, branch: String) -> Self {
         Self { file_path, tree, branch }
     }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let mut rootfile = RootFile::open(&self.file_path).map_err(|e| root_read_error(&self.file_path, e))?;
+        let tree = rootfile.get_tree(&self.tree).map_err(|e| root_read_error(&self.file_path, e))?;
+        let branch = tree.branch(&self.branch).ok_or_else(|| missing_branch_error(&self.file_path, &self.branch))?;
+        let branch_iter = branch.as_iter_manual::<crate::scalable_array::NDArrayRootWrapper>().skip(start).take(end-start);
+        // println!("BRANCH OK");
+        let frames:Vec<ArrayND<f64>> = branch_iter.map(|x| {
+            // println!("data {:?}", x.data);
+            let mut data:ArrayND<f64> = x.data.into();
+            data.shape.insert(0, 1);
+            // println!("data {:?}", data.shape);
+            data
+        }).collect();
+        if frames.len()!=end-start{
+            Err(ExecutionError::OtherError(format!("ROOT branch {} has only {} of {} requested frames", self.branch, frames.len(), end-start).into()))
+        }
+        else if let Some(first) = frames.first(){
+            let mut shape = first.shape.clone();
+            shape[0] = 0;
+            let res = frames.iter().fold(ArrayND::new(shape.into(), 0.0), |a,b| a.merge(b.clone()));
+            Ok(res)
+        }
+        else{
+            Ok(ArrayND::new(vec![0,1], 0.0))
+        }
+    }
 }
 
 
@@ -35,28 +71,11 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyROOTSpatialReader{
     }
 
     fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64>where {
-        let mut rootfile = if let Ok(v) = RootFile::open(&self.file_path) {v} else {return ArrayND::new(vec![1,1], 0.0);};
-        let tree = if let Ok(v) = rootfile.get_tree(&self.tree) {v} else {return ArrayND::new(vec![1,1], 0.0);};
-        let branch = if let Some(v) = tree.branch(&self.branch) {v} else {return ArrayND::new(vec![1,1], 0.0);};
-        let branch_iter = branch.as_iter_manual::<crate::scalable_array::NDArrayRootWrapper>().skip(start).take(end-start);
-        // println!("BRANCH OK");
-        let frames:Vec<ArrayND<f64>> = branch_iter.map(|x| {
-            // println!("data {:?}", x.data);
-            let mut data:ArrayND<f64> = x.data.into();
-            data.shape.insert(0, 1);
-            // println!("data {:?}", data.shape);
-            data
-        }).collect();
-        if frames.len()==0{
-            return ArrayND::new(vec![1,1], 0.0);
-        }
-        else{
-            let mut shape = frames.first().unwrap().shape.clone();
-            shape[0] = 0;
-            let res = frames.iter().fold(ArrayND::new(shape.into(), 0.0), |a,b| a.merge(b.clone()));
-            res
-        }
+        self.try_request(start,end).unwrap()
+    }
 
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<ArrayND<f64>,ExecutionError>where {
+        self.try_request(start,end).into()
     }
 }
 
@@ -74,6 +93,20 @@ impl LazyROOTTemporalReader{
     pub fn new(file_path: String, tree: String, branch: String) -> Self {
         Self { file_path, tree, branch }
     }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<RVec<f64>,ExecutionError>{
+        let mut rootfile = RootFile::open(&self.file_path).map_err(|e| root_read_error(&self.file_path, e))?;
+        let tree = rootfile.get_tree(&self.tree).map_err(|e| root_read_error(&self.file_path, e))?;
+        let branch = tree.branch(&self.branch).ok_or_else(|| missing_branch_error(&self.file_path, &self.branch))?;
+        let branch_iter = branch.as_iter::<f64>().map_err(|e| root_read_error(&self.file_path, e))?;
+        let branch_iter = branch_iter.skip(start).take(end-start);
+        // println!("BRANCH OK");
+        let ticks:Vec<f64> = branch_iter.collect();
+        if ticks.len()!=end-start{
+            return Err(ExecutionError::OtherError(format!("ROOT branch {} has only {} of {} requested entries", self.branch, ticks.len(), end-start).into()));
+        }
+        Ok(ticks.into())
+    }
 }
 
 
@@ -93,15 +126,10 @@ impl LazyArrayOperation<RVec<f64>> for LazyROOTTemporalReader{
     }
 
     fn request_range(&self,start:usize,end:usize,) -> RVec<f64>{
-        let mut rootfile = if let Ok(v) = RootFile::open(&self.file_path) {v} else {return rvec![];};
-        let tree = if let Ok(v) = rootfile.get_tree(&self.tree) {v} else {return rvec![];};
-        let branch = if let Some(v) = tree.branch(&self.branch) {v} else {return rvec![];};
-        let branch_iter = if let Ok(v) = branch.as_iter::<f64>() {v} else {return rvec![];};
-        let branch_iter = branch_iter.skip(start).take(end-start);
-        // println!("BRANCH OK");
-        let ticks:Vec<f64> = branch_iter.collect();
-        ticks.into()
-
+        self.try_request(start,end).unwrap()
+    }
 
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<RVec<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
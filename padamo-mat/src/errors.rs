@@ -0,0 +1,25 @@
+#[derive(thiserror::Error,Debug)]
+pub enum MatError{
+    #[error("IO error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("Invalid MAT file: {0}")]
+    Format(String),
+    #[error("Variable {0} is not a real numeric matrix")]
+    Unsupported(String),
+    #[error("Variable {name} of shape {dims:?} is too large")]
+    Oversized{
+        name:String,
+        dims:Vec<usize>,
+    },
+    #[error("Range {start}..{end} is out of length {length}")]
+    OutOfRange{
+        start:usize,
+        end:usize,
+        length:usize,
+    },
+    #[error("Variable {name} takes {bytes} bytes. MAT v5 variables are limited to 4 GiB")]
+    TooLarge{
+        name:String,
+        bytes:usize,
+    },
+}
@@ -4,19 +4,23 @@ use abi_stable::{std_types::RVec, export_root_module, prefix_type::PrefixTypeTra
 use padamo_api::nodes_vec;
 use abi_stable::sabi_extern_fn;
 
+pub mod errors;
 pub mod ops;
 pub mod nodes;
+pub mod v5;
+pub mod writer;
 
 
 #[export_root_module]
 pub fn plugin_root()->PadamoModule_Ref{
-    PadamoModule{nodes}.leak_into_prefix()
+    PadamoModule{nodes, share_cache:padamo_api::share_cache, info:padamo_api::plugin_info!()}.leak_into_prefix()
 }
 
 #[sabi_extern_fn]
 pub fn nodes(_library_dir:RString)->RVec<CalculationNodeBox>{
     nodes_vec!(
         nodes::MatReadNode,
-        nodes::MatReadTimeNode
+        nodes::MatReadTimeNode,
+        nodes::SaveMatNode
     )
 }
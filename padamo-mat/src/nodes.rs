@@ -1,157 +1,207 @@
-use abi_stable::std_types::{ROption::RSome, RResult, RString, RVec};
-use padamo_api::{constants, lazy_array_operations::ArrayND, ports, prelude::*};
-use crate::ops::{self, ConstantArray, ConstantVec};
-
-#[derive(Clone,Debug)]
-pub struct MatReadNode;
+use std::path::Path;
+
+use abi_stable::std_types::RString;
+use padamo_api::{lazy_array_operations::ArrayND, prelude::*};
+use padamo_api::calculation_nodes::progress::write_file_atomically;
+use padamo_api::lazy_array_operations::{LazyDetectorSignal, LazyTimeSignal, LazyTriSignal};
+use padamo_api::lazy_array_operations::cache::cache_budget;
+use padamo_api::lazy_array_operations::prefetch::DEFAULT_PREFETCH_DEPTH;
+use padamo_hdf5_ops::{LazyHDF5Reader3D, LazyTimeHDF5Reader};
+use crate::ops::{self, ConstantArray, ConstantVec, LazyMatSignal, LazyMatTime};
+use crate::v5::{find_variable, mat_version, MatFrames, MatVersion};
+use crate::writer::MatV5Writer;
+
+/// Writer takes this part of cache budget for frames being written
+const WRITE_BUDGET_PART:usize = 8;
+
+/// Number of frames requested at once such that prefetched ranges, range being written and its copy fit into part of memory budget
+fn write_step(frame_bytes:usize)->usize{
+    let budget = cache_budget().budget()/WRITE_BUDGET_PART;
+    (budget/(frame_bytes.max(1)*(DEFAULT_PREFETCH_DEPTH+2))).max(1)
+}
 
-impl MatReadNode{
-    pub fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError>{
-        let filename = args.inputs.request_string("Filename")?.to_string();
-        let field = args.constants.request_string("field")?.to_string();
-        let file = std::fs::File::open(&filename).map_err(|e| ExecutionError::OtherError(format!("{}",e).into()))?;
-        let mat_file = matfile::MatFile::parse(file).map_err(|e| ExecutionError::OtherError(format!("{}",e).into()))?;
-        if let Some(d) = mat_file.find_by_name(&field){
-            if let matfile::NumericData::Double { real:real_data, imag:None } = d.data(){
-                let mut data:padamo_api::lazy_array_operations::ArrayND<f64> = ArrayND::from_f(d.size().clone().into(), real_data);
-
-                if args.constants.request_boolean("flip")?{
-                    data = data.flip_indices();
-                }
+fn other_error<E:std::fmt::Display>(e:E)->ExecutionError{
+    ExecutionError::OtherError(format!("{}",e).into())
+}
 
-                let data:ConstantArray<f64> = ops::ConstantArray::new(data);
-                args.outputs.set_value("Array", Content::DetectorSignal(make_lao_box(data)))?;
-                return Ok(());
-            }
+/// Reads whole variable with `matfile`. Used for compressed MAT v5 variables.
+fn parse_whole(filename:&str, field:&str)->Result<ArrayND<f64>,ExecutionError>{
+    let file = std::fs::File::open(filename).map_err(other_error)?;
+    let mat_file = matfile::MatFile::parse(file).map_err(other_error)?;
+    if let Some(d) = mat_file.find_by_name(field){
+        if let matfile::NumericData::Double { real:real_data, imag:None } = d.data(){
+            return Ok(ArrayND::from_f(d.size().clone(), real_data));
         }
-        Err(ExecutionError::OtherError(format!("Field {} is not found", field).into()))
     }
+    Err(ExecutionError::OtherError(format!("Field {} is not found", field).into()))
 }
 
-impl CalculationNode for MatReadNode{
-    #[allow(clippy::let_and_return)]
-    #[doc = r" Name of node displayed in graph editor or node list"]
-    fn name(&self,) -> RString {
-        "MAT file matrix".into()
+fn read_signal(filename:&str, field:&str, flip:bool)->Result<LazyDetectorSignal,ExecutionError>{
+    match mat_version(filename).map_err(other_error)?{
+        MatVersion::V73=>{
+            // MATLAB writes arrays column major, so HDF5 dataset has reversed axes
+            let reader = if flip{
+                LazyHDF5Reader3D::<f64>::new(filename.into(), field.into())
+            }
+            else{
+                LazyHDF5Reader3D::<f64>::new_column_major(filename.into(), field.into())
+            }.map_err(other_error)?;
+            if !reader.check_read(){
+                return Err(ExecutionError::OtherError(format!("Field {} is not a numeric array", field).into()));
+            }
+            Ok(make_lao_box(reader))
+        }
+        MatVersion::V5=>{
+            if let Some(array) = find_variable(filename, field).map_err(other_error)?{
+                return Ok(make_lao_box(LazyMatSignal::new(MatFrames::new(array, flip))));
+            }
+            let mut data = parse_whole(filename, field)?;
+            if flip{
+                data = data.flip_indices();
+            }
+            let data:ConstantArray<f64> = ops::ConstantArray::new(data);
+            Ok(make_lao_box(data))
+        }
     }
+}
 
-    fn category(&self,) -> RVec<RString>{
-        padamo_api::common_categories::array_sources()
+fn read_time(filename:&str, field:&str)->Result<LazyTimeSignal,ExecutionError>{
+    match mat_version(filename).map_err(other_error)?{
+        MatVersion::V73=>{
+            let reader = LazyTimeHDF5Reader::<f64>::new(filename.into(), field.into()).map_err(other_error)?;
+            if !reader.check_read(){
+                return Err(ExecutionError::OtherError(format!("Field {} is not a numeric array", field).into()));
+            }
+            Ok(make_lao_box(reader))
+        }
+        MatVersion::V5=>{
+            if let Some(array) = find_variable(filename, field).map_err(other_error)?{
+                if array.dims().iter().filter(|x| **x!=1).count()>1{
+                    return Err(ExecutionError::OtherError("MAT data time length is wrong".into()));
+                }
+                return Ok(make_lao_box(LazyMatTime::new(array)));
+            }
+            let data = parse_whole(filename, field)?.squeeze();
+            if data.shape.len()!=1{
+                return Err(ExecutionError::OtherError("MAT data time length is wrong".into()));
+            }
+            let data:ConstantVec<f64> = ops::ConstantVec::new(data.flat_data);
+            Ok(make_lao_box(data))
+        }
     }
+}
 
-    fn identifier(&self,) -> RString {
-        "padamomat.mat_reader".into()
-    }
+#[derive(NodePorts)]
+pub struct FileInput{
+    #[port(name = "Filename")]
+    filename:RString,
+}
 
-    fn old_identifier(&self,) -> abi_stable::std_types::ROption<RString>{
-        RSome("MAT/MAT file matrix".into())
-    }
+#[derive(NodePorts)]
+pub struct MatReadOutputs{
+    #[port(name = "Array")]
+    array:LazyDetectorSignal,
+}
 
-    #[allow(clippy::let_and_return)]
-    #[doc = r" Input definitions of node"]
-    fn inputs(&self,) -> RVec<CalculationIO>{
-        ports![
-            ("Filename", ContentType::String)
-        ]
-    }
+#[derive(NodeConstants)]
+pub struct MatReadConstants{
+    #[constant(default = "data")]
+    field:RString,
+    flip:bool,
+}
 
-    #[allow(clippy::let_and_return)]
-    #[doc = r" Output definition of node"]
-    fn outputs(&self,) -> RVec<CalculationIO>{
-        ports![
-            ("Array", ContentType::DetectorSignal)
-        ]
-    }
+#[derive(Clone,Debug,CalculationNode)]
+#[node(name = "MAT file matrix", identifier = "padamomat.mat_reader", old_identifier = "MAT/MAT file matrix", category = padamo_api::common_categories::array_sources)]
+pub struct MatReadNode;
 
-    #[allow(clippy::let_and_return)]
-    #[doc = r" Constants definition of node with default values."]
-    fn constants(&self,) -> RVec<CalculationConstant>{
-        constants![
-            ("field", "data"),
-            ("flip", false)
-        ]
-    }
+impl TypedCalculationNode for MatReadNode{
+    type Inputs = FileInput;
+    type Outputs = MatReadOutputs;
+    type Constants = MatReadConstants;
 
-    #[allow(clippy::let_and_return)]
-    #[doc = r" Main calculation"]
-    fn calculate(&self, args:CalculationNodeArguments) -> RResult<(),ExecutionError> {
-        self.calculate(args).into()
+    fn run(&self, inputs:FileInput, constants:MatReadConstants, _args:&mut CalculationNodeArguments) -> Result<MatReadOutputs,ExecutionError>{
+        let array = read_signal(&inputs.filename, &constants.field, constants.flip)?;
+        Ok(MatReadOutputs { array })
     }
 }
 
-#[derive(Clone,Debug)]
-pub struct MatReadTimeNode;
-
-impl MatReadTimeNode{
-    pub fn calculate(&self, args:CalculationNodeArguments) -> Result<(),ExecutionError>{
-        let filename = args.inputs.request_string("Filename")?.to_string();
-        let field = args.constants.request_string("field")?.to_string();
-        let file = std::fs::File::open(&filename).map_err(|e| ExecutionError::OtherError(format!("{}",e).into()))?;
-        let mat_file = matfile::MatFile::parse(file).map_err(|e| ExecutionError::OtherError(format!("{}",e).into()))?;
-        if let Some(d) = mat_file.find_by_name(&field){
-            if let matfile::NumericData::Double { real:real_data, imag:None } = d.data(){
-                let data:padamo_api::lazy_array_operations::ArrayND<f64> = ArrayND::from_f(d.size().clone().into(), real_data);
-                // data.assert_shape();
-
-                let data = data.squeeze();
-                if data.shape.len()!=1{
-                    return Err(ExecutionError::OtherError("MAT data time length is wrong".into()));
-                }
+#[derive(NodePorts)]
+pub struct MatReadTimeOutputs{
+    #[port(name = "Time")]
+    time:LazyTimeSignal,
+}
 
-                let data:ConstantVec<f64> = ops::ConstantVec::new(data.flat_data);
-                args.outputs.set_value("Time", Content::DetectorTime(make_lao_box(data)))?;
-                return Ok(())
-            }
-            // let data:ndarray::ArrayBase<OwnedRepr<f64>,IxDyn, f64> = d.try_into().map_err(|e| ExecutionError::OtherError(format!("{}",e).into()))?;
-            // let mat_data = d.size().into();
-        }
-        Err(ExecutionError::OtherError(format!("Field {} is not found", field).into()))
-    }
+#[derive(NodeConstants)]
+pub struct MatReadTimeConstants{
+    #[constant(default = "data")]
+    field:RString,
 }
 
-impl CalculationNode for MatReadTimeNode{
-    #[allow(clippy::let_and_return)]
-    #[doc = r" Name of node displayed in graph editor or node list"]
-    fn name(&self,) -> RString {
-        "MAT file time".into()
-    }
+#[derive(Clone,Debug,CalculationNode)]
+#[node(name = "MAT file time", identifier = "padamomat.mat_time_reader", category = padamo_api::common_categories::time_sources)]
+pub struct MatReadTimeNode;
 
-    fn category(&self,) -> RVec<RString>{
-        padamo_api::common_categories::time_sources()
-    }
+impl TypedCalculationNode for MatReadTimeNode{
+    type Inputs = FileInput;
+    type Outputs = MatReadTimeOutputs;
+    type Constants = MatReadTimeConstants;
 
-    fn identifier(&self,) -> RString {
-        "padamomat.mat_time_reader".into()
+    fn run(&self, inputs:FileInput, constants:MatReadTimeConstants, _args:&mut CalculationNodeArguments) -> Result<MatReadTimeOutputs,ExecutionError>{
+        let time = read_time(&inputs.filename, &constants.field)?;
+        Ok(MatReadTimeOutputs { time })
     }
+}
 
+#[derive(NodePorts)]
+pub struct SaveMatInputs{
+    #[port(name = "Signal")]
+    signal:LazyTriSignal,
+    #[port(name = "File path")]
+    file_path:RString,
+}
 
-    #[allow(clippy::let_and_return)]
-    #[doc = r" Input definitions of node"]
-    fn inputs(&self,) -> RVec<CalculationIO>{
-        ports![
-            ("Filename", ContentType::String)
-        ]
-    }
+#[derive(NodeConstants)]
+pub struct SaveMatConstants{
+    #[constant(display = "Signal variable", default = "data")]
+    spatial_field:RString,
+    #[constant(display = "Time variable", default = "time")]
+    temporal_field:RString,
+}
 
-    #[allow(clippy::let_and_return)]
-    #[doc = r" Output definition of node"]
-    fn outputs(&self,) -> RVec<CalculationIO>{
-        ports![
-            ("Time", ContentType::DetectorTime)
-        ]
+#[derive(Clone,Debug,CalculationNode)]
+#[node(name = "Save MAT signal", identifier = "padamomat.signal_writer", category = padamo_api::common_categories::data_savers, primary)]
+pub struct SaveMatNode;
+
+impl SaveMatNode{
+    /// Signal is written by ranges fitting into memory budget, then time. Triggers are not stored.
+    fn write_file(&self, path:&Path, signal:&LazyTriSignal, constants:&SaveMatConstants, args:&CalculationNodeArguments) -> Result<(),ExecutionError> {
+        let length = signal.0.length();
+        let sample = signal.0.try_request_range(0,length.min(1)).into_result()?;
+        let mut dims = vec![length];
+        dims.extend(sample.shape.iter().skip(1).copied());
+        let step = write_step(dims[1..].iter().product::<usize>()*std::mem::size_of::<f64>());
+
+        let mut writer = MatV5Writer::create(path).map_err(ExecutionError::from_error)?;
+        let spatial = writer.add_matrix(&constants.spatial_field, &dims).map_err(ExecutionError::from_error)?;
+        signal.0.clone().prefetched(step, DEFAULT_PREFETCH_DEPTH).try_for_each_chunk(0, length, step, args.progress, |start, chunk|{
+            writer.write_frames(&spatial, start, &chunk.flat_data).map_err(ExecutionError::from_error)
+        })?;
+        // MATLAB vectors are matrices, time is stored as column
+        let temporal = writer.add_matrix(&constants.temporal_field, &[length, 1]).map_err(ExecutionError::from_error)?;
+        signal.1.try_for_each_chunk(0, length, write_step(std::mem::size_of::<f64>()), args.progress, |start, chunk|{
+            writer.write_frames(&temporal, start, &chunk).map_err(ExecutionError::from_error)
+        })?;
+        writer.finish().map_err(ExecutionError::from_error)
     }
+}
 
-    #[allow(clippy::let_and_return)]
-    #[doc = r" Constants definition of node with default values."]
-    fn constants(&self,) -> RVec<CalculationConstant>{
-        constants![
-            ("field", "data")
-        ]
-    }
+impl TypedCalculationNode for SaveMatNode{
+    type Inputs = SaveMatInputs;
+    type Outputs = ();
+    type Constants = SaveMatConstants;
 
-    #[allow(clippy::let_and_return)]
-    #[doc = r" Main calculation"]
-    fn calculate(&self, args:CalculationNodeArguments) -> RResult<(),ExecutionError> {
-        self.calculate(args).into()
+    fn run(&self, inputs:SaveMatInputs, constants:SaveMatConstants, args:&mut CalculationNodeArguments) -> Result<(),ExecutionError>{
+        // File appears only when all data is written
+        write_file_atomically(Path::new(inputs.file_path.as_str()), |path| self.write_file(path, &inputs.signal, &constants, args))
     }
 }
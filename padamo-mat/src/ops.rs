@@ -1,9 +1,12 @@
-use abi_stable::std_types::RVec;
+use abi_stable::std_types::{RResult, RVec};
+use padamo_api::calculation_nodes::errors::ExecutionError;
 use padamo_api::lazy_array_operations::cache::Cache;
 use padamo_api::lazy_array_operations::LazyArrayOperation;
 use padamo_api::lazy_array_operations::ArrayND;
 use std::fmt::Debug;
 
+use crate::v5::{check_range, MatFrames, MatV5Array};
+
 #[derive(Clone,Debug)]
 pub struct ConstantArray<T:Clone+Debug+Sync+Send+abi_stable::StableAbi>{
     data:ArrayND<T>
@@ -11,6 +14,15 @@ pub struct ConstantArray<T:Clone+Debug+Sync+Send+abi_stable::StableAbi>{
 
 impl<T: Clone + Debug + Sync + Send + abi_stable::StableAbi> ConstantArray<T> {
     pub fn new(data: ArrayND<T>) -> Self { Self { data } }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<T>,ExecutionError>{
+        let length = self.length();
+        check_range(start, end, length).map_err(ExecutionError::from_error)?;
+        if end==length && start==0{
+            return Ok(self.data.clone());
+        }
+        Ok(self.data.copy_range(start, end))
+    }
 }
 
 impl<T:Clone+Debug+Sync+Send+abi_stable::StableAbi> LazyArrayOperation<ArrayND<T>> for ConstantArray<T>{
@@ -25,14 +37,11 @@ impl<T:Clone+Debug+Sync+Send+abi_stable::StableAbi> LazyArrayOperation<ArrayND<T
     }
 
     fn request_range(&self,start:usize,end:usize,) -> ArrayND<T> where {
-        let mut data = self.data.clone();
-        let length = self.length();
-        if end==length && start==0{
-            return data;
-        }
-        data = data.cut_front(start);
-        data = data.cut_end(length-end);
-        data
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<ArrayND<T>,ExecutionError> where {
+        self.try_request(start,end).into()
     }
 }
 
@@ -44,6 +53,15 @@ pub struct ConstantVec<T:Clone+Debug+Sync+Send+abi_stable::StableAbi>{
 
 impl<T: Clone + Debug + Sync + Send + abi_stable::StableAbi> ConstantVec<T> {
     pub fn new<U:Into<RVec<T>>>(data: U) -> Self { Self { data:data.into() } }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<RVec<T>,ExecutionError>{
+        let length = self.length();
+        check_range(start, end, length).map_err(ExecutionError::from_error)?;
+        if end==length && start==0{
+            return Ok(self.data.clone());
+        }
+        Ok(self.data[start..end].into())
+    }
 }
 
 impl<T:Clone+Debug+Sync+Send+abi_stable::StableAbi> LazyArrayOperation<RVec<T>> for ConstantVec<T>{
@@ -53,11 +71,70 @@ impl<T:Clone+Debug+Sync+Send+abi_stable::StableAbi> LazyArrayOperation<RVec<T>>
     }
 
     fn request_range(&self,start:usize,end:usize,) -> RVec<T> where {
-        // let mut data = self.data.clone();
-        let length = self.length();
-        if end==length && start==0{
-            return self.data.clone();
-        }
-        self.data.as_slice().iter().skip(start).take(end-start).map(|x| x.clone()).collect::<Vec<T>>().into()
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<RVec<T>,ExecutionError> where {
+        self.try_request(start,end).into()
+    }
+}
+
+/// Frames of uncompressed MAT v5 matrix read on request
+#[derive(Clone,Debug)]
+pub struct LazyMatSignal{
+    frames:MatFrames,
+}
+
+impl LazyMatSignal{
+    pub fn new(frames:MatFrames)->Self{
+        Self { frames }
+    }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let flat_data = self.frames.try_read_frames(start, end).map_err(ExecutionError::from_error)?;
+        let mut shape:RVec<usize> = RVec::new();
+        shape.push(end-start);
+        shape.extend(self.frames.frame_shape());
+        Ok(ArrayND { flat_data: flat_data.into(), shape })
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyMatSignal{
+    fn length(&self,) -> usize where {
+        self.frames.length()
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<ArrayND<f64>,ExecutionError> where {
+        self.try_request(start,end).into()
+    }
+}
+
+/// Time stored as MAT v5 vector
+#[derive(Clone,Debug)]
+pub struct LazyMatTime{
+    array:MatV5Array,
+}
+
+impl LazyMatTime{
+    pub fn new(array:MatV5Array)->Self{
+        Self { array }
+    }
+}
+
+impl LazyArrayOperation<RVec<f64>> for LazyMatTime{
+    fn length(&self,) -> usize where {
+        self.array.element_count()
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> RVec<f64> where {
+        self.array.try_read_linear(start, end).unwrap().into()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<RVec<f64>,ExecutionError> where {
+        self.array.try_read_linear(start, end).map(RVec::from).map_err(ExecutionError::from_error).into()
     }
 }
@@ -0,0 +1,312 @@
+//! MAT v5 files without reading them whole. Uncompressed numeric matrices are memory mapped and read by frames.
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+
+use memmap2::Mmap;
+
+use crate::errors::MatError;
+
+pub const HEADER_LENGTH:usize = 128;
+
+pub const MI_INT8:u32 = 1;
+pub const MI_UINT8:u32 = 2;
+pub const MI_INT16:u32 = 3;
+pub const MI_UINT16:u32 = 4;
+pub const MI_INT32:u32 = 5;
+pub const MI_UINT32:u32 = 6;
+pub const MI_SINGLE:u32 = 7;
+pub const MI_DOUBLE:u32 = 9;
+pub const MI_INT64:u32 = 12;
+pub const MI_UINT64:u32 = 13;
+pub const MI_MATRIX:u32 = 14;
+pub const MI_COMPRESSED:u32 = 15;
+
+/// Numeric classes from mxDOUBLE_CLASS to mxUINT64_CLASS
+const NUMERIC_CLASSES:std::ops::RangeInclusive<u32> = 6..=15;
+const COMPLEX_FLAG:u32 = 0x0800;
+
+/// Converts bytes of one element to f64
+type Decoder = fn(&[u8])->f64;
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum MatVersion{
+    V5,
+    /// HDF5 file with MAT header in user block
+    V73,
+}
+
+/// Reads version from MAT file header
+pub fn mat_version<P:AsRef<Path>>(path:P)->Result<MatVersion,MatError>{
+    let mut header = [0u8;HEADER_LENGTH];
+    File::open(path)?.read_exact(&mut header)?;
+    let version = if &header[126..128]==b"MI" {u16::from_be_bytes([header[124], header[125]])} else {u16::from_le_bytes([header[124], header[125]])};
+    match version {
+        0x0100=>Ok(MatVersion::V5),
+        0x0200=>Ok(MatVersion::V73),
+        v=>Err(MatError::Format(format!("unsupported version {:#06x}", v))),
+    }
+}
+
+pub fn round8(x:usize)->usize{
+    x.div_ceil(8)*8
+}
+
+/// Column major positions of array elements enumerated in C order
+pub fn column_major_offsets(dims:&[usize])->Vec<usize>{
+    let mut res = vec![0];
+    let mut stride = 1;
+    let mut strides = Vec::with_capacity(dims.len());
+    for dim in dims.iter(){
+        strides.push(stride);
+        stride *= dim;
+    }
+    for (dim, stride) in dims.iter().zip(strides.iter()){
+        res = res.iter().flat_map(|base| (0..*dim).map(move |i| base+i*stride)).collect();
+    }
+    res
+}
+
+macro_rules! decoder {
+    ($t:ty, $big_endian:expr) => {
+        if $big_endian{
+            (|b:&[u8]| <$t>::from_be_bytes(b.try_into().unwrap()) as f64) as Decoder
+        }
+        else{
+            (|b:&[u8]| <$t>::from_le_bytes(b.try_into().unwrap()) as f64) as Decoder
+        }
+    };
+}
+
+/// Size and decoder of numeric data element type
+fn element_decoder(data_type:u32, big_endian:bool)->Option<(usize, Decoder)>{
+    let be = big_endian;
+    let res:(usize, Decoder) = match data_type {
+        MI_INT8=>(1, |b| b[0] as i8 as f64),
+        MI_UINT8=>(1, |b| b[0] as f64),
+        MI_INT16=>(2, decoder!(i16, be)),
+        MI_UINT16=>(2, decoder!(u16, be)),
+        MI_INT32=>(4, decoder!(i32, be)),
+        MI_UINT32=>(4, decoder!(u32, be)),
+        MI_SINGLE=>(4, decoder!(f32, be)),
+        MI_DOUBLE=>(8, decoder!(f64, be)),
+        MI_INT64=>(8, decoder!(i64, be)),
+        MI_UINT64=>(8, decoder!(u64, be)),
+        _=>return None,
+    };
+    Some(res)
+}
+
+/// Data element: type, position of data, length of data and position of next element
+struct Element{
+    data_type:u32,
+    start:usize,
+    length:usize,
+    next:usize,
+}
+
+struct ElementReader<'a>{
+    data:&'a [u8],
+    big_endian:bool,
+}
+
+impl<'a> ElementReader<'a>{
+    fn u32_at(&self, pos:usize)->Result<u32,MatError>{
+        let bytes:[u8;4] = self.data.get(pos..pos+4)
+            .ok_or_else(|| MatError::Format("unexpected end of file".into()))?
+            .try_into().unwrap();
+        Ok(if self.big_endian {u32::from_be_bytes(bytes)} else {u32::from_le_bytes(bytes)})
+    }
+
+    fn element(&self, pos:usize)->Result<Element,MatError>{
+        let first = self.u32_at(pos)?;
+        // Small data element keeps length in upper bytes of type and data in 4 bytes after it
+        let res = if first>>16 != 0{
+            Element { data_type: first & 0xFFFF, start: pos+4, length: (first>>16) as usize, next: pos+8 }
+        }
+        else{
+            let length = self.u32_at(pos+4)? as usize;
+            // Compressed elements are not padded
+            let padded = if first==MI_COMPRESSED {length} else {round8(length)};
+            Element { data_type: first, start: pos+8, length, next: pos+8+padded }
+        };
+        if res.start+res.length>self.data.len(){
+            return Err(MatError::Format(format!("element at {} is truncated", pos)));
+        }
+        Ok(res)
+    }
+
+    fn bytes(&self, element:&Element)->&'a [u8]{
+        &self.data[element.start..element.start+element.length]
+    }
+}
+
+pub fn check_range(start:usize, end:usize, length:usize)->Result<(),MatError>{
+    if start>end || end>length{
+        Err(MatError::OutOfRange { start, end, length })
+    }
+    else{
+        Ok(())
+    }
+}
+
+/// Real numeric matrix of MAT v5 file. Elements are stored in column major order.
+#[derive(Clone)]
+pub struct MatV5Array{
+    bytes:Arc<Mmap>,
+    start:usize,
+    dims:Vec<usize>,
+    element_size:usize,
+    decode:Decoder,
+}
+
+impl Debug for MatV5Array{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MatV5Array").field("dims", &self.dims).field("element_size", &self.element_size).finish()
+    }
+}
+
+impl MatV5Array{
+    fn parse_matrix(reader:&ElementReader, bytes:&Arc<Mmap>, pos:usize, name:&str)->Result<Self,MatError>{
+        let unsupported = || MatError::Unsupported(name.into());
+        let flags = reader.element(pos)?;
+        let flags_value = reader.u32_at(flags.start)?;
+        let dims_element = reader.element(flags.next)?;
+        let name_element = reader.element(dims_element.next)?;
+        let real = reader.element(name_element.next)?;
+        if !NUMERIC_CLASSES.contains(&(flags_value & 0xFF)) || flags_value & COMPLEX_FLAG!=0 || dims_element.data_type!=MI_INT32{
+            return Err(unsupported());
+        }
+        let dims:Vec<usize> = (0..dims_element.length/4)
+            .map(|i| reader.u32_at(dims_element.start+4*i).map(|x| x as usize))
+            .collect::<Result<_,_>>()?;
+        let (element_size, decode) = element_decoder(real.data_type, reader.big_endian).ok_or_else(unsupported)?;
+        let data_length = dims.iter().try_fold(element_size, |a,b| a.checked_mul(*b))
+            .ok_or_else(|| MatError::Oversized { name: name.into(), dims: dims.clone() })?;
+        if real.length!=data_length{
+            return Err(MatError::Format(format!("size of {} does not match its dimensions {:?}", name, dims)));
+        }
+        Ok(Self { bytes: bytes.clone(), start: real.start, dims, element_size, decode })
+    }
+
+    pub fn dims(&self)->&[usize]{
+        &self.dims
+    }
+
+    pub fn element_count(&self)->usize{
+        self.dims.iter().product()
+    }
+
+    /// Elements [start, end) in column major order
+    pub fn try_read_linear(&self, start:usize, end:usize)->Result<Vec<f64>,MatError>{
+        check_range(start, end, self.element_count())?;
+        Ok(self.read_linear(start, end))
+    }
+
+    /// Elements [start, end) in column major order. Panics if range is out of array.
+    pub fn read_linear(&self, start:usize, end:usize)->Vec<f64>{
+        let size = self.element_size;
+        self.bytes[self.start+start*size..self.start+end*size].chunks_exact(size).map(self.decode).collect()
+    }
+
+    fn element(&self, index:usize)->f64{
+        let pos = self.start+index*self.element_size;
+        (self.decode)(&self.bytes[pos..pos+self.element_size])
+    }
+}
+
+/// Searches variable `name` among uncompressed matrices of MAT v5 file. Returns `None` if it is not found,
+/// it may be compressed then.
+pub fn find_variable<P:AsRef<Path>>(path:P, name:&str)->Result<Option<MatV5Array>,MatError>{
+    let file = File::open(path)?;
+    // Safety: file is only read. Changing it while it is mapped is not supported as it is for other readers.
+    let bytes = Arc::new(unsafe { Mmap::map(&file)? });
+    if bytes.len()<HEADER_LENGTH{
+        return Err(MatError::Format("file is shorter than header".into()));
+    }
+    let reader = ElementReader { data: &bytes, big_endian: &bytes[126..128]==b"MI" };
+    let mut pos = HEADER_LENGTH;
+    while pos+8<=bytes.len(){
+        let element = reader.element(pos)?;
+        if element.data_type==MI_MATRIX && element.length>0{
+            let flags = reader.element(element.start)?;
+            let dims = reader.element(flags.next)?;
+            let name_element = reader.element(dims.next)?;
+            if reader.bytes(&name_element)==name.as_bytes(){
+                return MatV5Array::parse_matrix(&reader, &bytes, element.start, name).map(Some);
+            }
+        }
+        pos = element.next;
+    }
+    Ok(None)
+}
+
+/// Frames of MATLAB array. Frames go along first axis, or along last one if array is flipped.
+#[derive(Clone,Debug)]
+pub struct MatFrames{
+    array:MatV5Array,
+    flip:bool,
+    /// Column major positions of frame elements relative to first element of frame. Empty for flipped arrays, their frames are contiguous.
+    frame_offsets:Arc<Vec<usize>>,
+}
+
+impl MatFrames{
+    pub fn new(array:MatV5Array, flip:bool)->Self{
+        let frame_offsets = if flip || array.dims.is_empty(){
+            Vec::new()
+        }
+        else{
+            column_major_offsets(&array.dims[1..]).into_iter().map(|x| x*array.dims[0]).collect()
+        };
+        Self { array, flip, frame_offsets: Arc::new(frame_offsets) }
+    }
+
+    pub fn length(&self)->usize{
+        let dims = &self.array.dims;
+        if self.flip {dims.last()} else {dims.first()}.copied().unwrap_or(0)
+    }
+
+    pub fn frame_shape(&self)->Vec<usize>{
+        let dims = &self.array.dims;
+        if dims.is_empty(){
+            Vec::new()
+        }
+        else if self.flip{
+            dims[..dims.len()-1].iter().rev().copied().collect()
+        }
+        else{
+            dims[1..].to_vec()
+        }
+    }
+
+    /// Flat frames [start, end) in C order
+    pub fn try_read_frames(&self, start:usize, end:usize)->Result<Vec<f64>,MatError>{
+        check_range(start, end, self.length())?;
+        Ok(self.read_frames(start, end))
+    }
+
+    /// Flat frames [start, end) in C order. Panics if range is out of array.
+    pub fn read_frames(&self, start:usize, end:usize)->Vec<f64>{
+        if self.flip{
+            let frame:usize = self.frame_shape().iter().product();
+            self.array.read_linear(start*frame, end*frame)
+        }
+        else{
+            (start..end).flat_map(|t| self.frame_offsets.iter().map(move |o| self.array.element(t+o))).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_major_offsets() {
+        // Array 2x3: C order (0,0),(0,1),(0,2),(1,0)... has column major positions 0,2,4,1,3,5
+        assert_eq!(column_major_offsets(&[2, 3]), vec![0, 2, 4, 1, 3, 5]);
+        assert_eq!(column_major_offsets(&[]), vec![0]);
+    }
+}
@@ -0,0 +1,158 @@
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::errors::MatError;
+use crate::v5::{column_major_offsets, round8, HEADER_LENGTH, MI_DOUBLE, MI_INT32, MI_INT8, MI_MATRIX, MI_UINT32};
+
+const MX_DOUBLE_CLASS:u32 = 6;
+/// Header text ends before subsystem data offset and version
+const HEADER_TEXT_LENGTH:usize = 116;
+
+/// Position of double matrix in file being written
+pub struct MatrixData{
+    data_start:u64,
+    length:usize,
+    /// Column major positions of frame elements enumerated in C order
+    pixel_positions:Vec<usize>,
+}
+
+/// Writes little endian MAT v5 file with uncompressed double matrices
+pub struct MatV5Writer{
+    file:File,
+}
+
+fn check_name(name:&str)->Result<(),MatError>{
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c=='_');
+    if valid {Ok(())} else {Err(MatError::Format(format!("invalid variable name \"{}\"", name)))}
+}
+
+fn push_u32(buffer:&mut Vec<u8>, value:u32){
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn pad(buffer:&mut Vec<u8>){
+    buffer.resize(round8(buffer.len()), 0);
+}
+
+impl MatV5Writer{
+    pub fn create<P:AsRef<Path>>(path:P)->Result<Self,MatError>{
+        let mut header = format!("MATLAB 5.0 MAT-file, Platform: PADAMO {}", env!("CARGO_PKG_VERSION")).into_bytes();
+        header.resize(HEADER_TEXT_LENGTH, b' ');
+        header.resize(HEADER_LENGTH-4, 0);
+        header.extend_from_slice(&0x0100u16.to_le_bytes());
+        header.extend_from_slice(b"IM");
+        let mut file = File::create(path)?;
+        file.write_all(&header)?;
+        Ok(Self { file })
+    }
+
+    /// Appends matrix `name` of shape `dims` filled with zeros. Frames along first axis are written with `write_frames`.
+    pub fn add_matrix(&mut self, name:&str, dims:&[usize])->Result<MatrixData,MatError>{
+        check_name(name)?;
+        let data_bytes = dims.iter().try_fold(std::mem::size_of::<f64>(), |a,b| a.checked_mul(*b))
+            .ok_or_else(|| MatError::Oversized { name: name.into(), dims: dims.to_vec() })?;
+        let too_large = || MatError::TooLarge { name: name.into(), bytes: data_bytes };
+        let mut head = Vec::new();
+        push_u32(&mut head, MI_MATRIX);
+        push_u32(&mut head, 0);
+
+        push_u32(&mut head, MI_UINT32);
+        push_u32(&mut head, 8);
+        push_u32(&mut head, MX_DOUBLE_CLASS);
+        push_u32(&mut head, 0);
+
+        push_u32(&mut head, MI_INT32);
+        push_u32(&mut head, (4*dims.len()) as u32);
+        for dim in dims.iter(){
+            let dim = i32::try_from(*dim).map_err(|_| too_large())?;
+            head.extend_from_slice(&dim.to_le_bytes());
+        }
+        pad(&mut head);
+
+        push_u32(&mut head, MI_INT8);
+        push_u32(&mut head, name.len() as u32);
+        head.extend_from_slice(name.as_bytes());
+        pad(&mut head);
+
+        push_u32(&mut head, MI_DOUBLE);
+        push_u32(&mut head, u32::try_from(data_bytes).map_err(|_| too_large())?);
+
+        let matrix_bytes = u32::try_from(head.len()-8+round8(data_bytes)).map_err(|_| too_large())?;
+        head[4..8].copy_from_slice(&matrix_bytes.to_le_bytes());
+
+        let start = self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(&head)?;
+        let data_start = start+head.len() as u64;
+        self.file.set_len(data_start+round8(data_bytes) as u64)?;
+        Ok(MatrixData {
+            data_start,
+            length: dims.first().copied().unwrap_or(0),
+            pixel_positions: column_major_offsets(dims.get(1..).unwrap_or(&[])),
+        })
+    }
+
+    /// Writes frames starting from `start`. Frames are flat in C order.
+    /// Values of one pixel are adjacent in MATLAB layout, so every pixel is written as one run.
+    pub fn write_frames(&mut self, matrix:&MatrixData, start:usize, frames:&[f64])->Result<(),MatError>{
+        let frame = matrix.pixel_positions.len();
+        if frame==0{
+            return Ok(());
+        }
+        let count = frames.len()/frame;
+        let mut buffer = Vec::with_capacity(count*std::mem::size_of::<f64>());
+        for (j, position) in matrix.pixel_positions.iter().enumerate(){
+            buffer.clear();
+            buffer.extend(frames[j..].iter().step_by(frame).take(count).flat_map(|x| x.to_le_bytes()));
+            let index = start+matrix.length*position;
+            self.file.seek(SeekFrom::Start(matrix.data_start+(index*std::mem::size_of::<f64>()) as u64))?;
+            self.file.write_all(&buffer)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self)->Result<(),MatError>{
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::v5::{find_variable, mat_version, MatFrames, MatVersion};
+
+    #[test]
+    fn test_write_read() {
+        let path = std::env::temp_dir().join(format!("padamo_mat_test_{}.mat", std::process::id()));
+        let values:Vec<f64> = (0..24).map(|x| x as f64).collect();
+        let mut writer = MatV5Writer::create(&path).unwrap();
+        let signal = writer.add_matrix("pdm", &[4, 2, 3]).unwrap();
+        writer.write_frames(&signal, 0, &values[..6]).unwrap();
+        writer.write_frames(&signal, 1, &values[6..]).unwrap();
+        let time = writer.add_matrix("time", &[4, 1]).unwrap();
+        writer.write_frames(&time, 0, &[0.5, 1.5, 2.5, 3.5]).unwrap();
+        writer.finish().unwrap();
+        assert!(check_name("1x").is_err());
+
+        assert_eq!(mat_version(&path).unwrap(), MatVersion::V5);
+        let signal = MatFrames::new(find_variable(&path, "pdm").unwrap().unwrap(), false);
+        assert_eq!(signal.length(), 4);
+        assert_eq!(signal.frame_shape(), vec![2, 3]);
+        assert_eq!(signal.read_frames(1, 3), values[6..18].to_vec());
+        assert!(signal.try_read_frames(3, 5).is_err());
+
+        let flipped = MatFrames::new(find_variable(&path, "pdm").unwrap().unwrap(), true);
+        assert_eq!(flipped.length(), 3);
+        assert_eq!(flipped.frame_shape(), vec![2, 4]);
+        // Element (w=0, h=1, t=2) of flipped array is element (t=2, h=1, w=0) of signal
+        assert_eq!(flipped.read_frames(0, 1)[4+2], values[2*6+3]);
+
+        let time = find_variable(&path, "time").unwrap().unwrap();
+        assert_eq!(time.read_linear(0, 4), vec![0.5, 1.5, 2.5, 3.5]);
+        assert!(find_variable(&path, "missing").unwrap().is_none());
+        assert!(MatV5Writer::create(&path).unwrap().add_matrix("big", &[usize::MAX, 2]).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,7 +1,9 @@
 use std::{fmt::Debug, sync::{Arc, Mutex}};
 
+use abi_stable::std_types::RResult;
 use ort::value::Tensor;
 use padamo_api::{lazy_array_operations::{cutter::CutError, ArrayND, LazyArrayOperation, LazyArrayOperationBox, LazyDetectorSignal}, trigger_operations::SparseTagArray};
+use padamo_api::prelude::ExecutionError;
 // use tract_core::ndarray::{IxDyn, OwnedRepr};
 // use tract_onnx::prelude::*;
 use ndarray::prelude::*;
@@ -76,7 +78,7 @@ impl LazyANNTrigger3D{
         if source_length==0{
             return Err(Box::new(ANNError::EmptySource));
         }
-        let probe:ArrayND<f64> = source.request_range(0,1);
+        let probe:ArrayND<f64> = source.try_request_range(0,1).into_result()?;
 
         // let probe_len:usize = if squeeze_source{
         //     probe.shape.iter().skip(1).filter(|x| **x!=1).count()+1
@@ -125,14 +127,8 @@ impl LazyANNTrigger3D{
     }
 }
 
-impl LazyArrayOperation<SparseTagArray> for LazyANNTrigger3D{
-    #[allow(clippy::let_and_return)]
-    fn length(&self,) -> usize {
-        self.source.length()
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn request_range(&self,start:usize,end:usize,) -> SparseTagArray where {
+impl LazyANNTrigger3D{
+    fn try_request(&self,start:usize,end:usize) -> Result<SparseTagArray,ExecutionError>{
         let window = self.size_hint.0;
         let len = self.length();
         let snapped_start = start/self.stride*self.stride;
@@ -162,7 +158,7 @@ impl LazyArrayOperation<SparseTagArray> for LazyANNTrigger3D{
         // };
 
         if cut_start>=cut_end{
-            return SparseTagArray::new();
+            return Ok(SparseTagArray::new());
         }
 
         // let mut cut_end = snapped_end+window;
@@ -176,11 +172,11 @@ impl LazyArrayOperation<SparseTagArray> for LazyANNTrigger3D{
             println!("Request {}, {}", start,end);
             println!("Cut {}, {}", cut_start,cut_end);
             println!("No possible events on interval.");
-            return SparseTagArray::new();
+            return Ok(SparseTagArray::new());
         }
         println!("Cut {}, {}", cut_start,cut_end);
 
-        let mut source_data = self.source.request_range(cut_start,cut_end);
+        let mut source_data = self.source.try_request_range(cut_start,cut_end).into_result()?;
         if self.squeeze_source{
             let src_shape:Vec<usize> = source_data.shape.clone().into();
             let mut squeezed_shape = vec![src_shape[0]];
@@ -207,7 +203,7 @@ impl LazyArrayOperation<SparseTagArray> for LazyANNTrigger3D{
 
         if src_time<self.size_hint.0{
             println!("Additional safety check failed.");
-            return SparseTagArray::new();
+            return Ok(SparseTagArray::new());
         }
 
         let windows_amount = (src_time-self.size_hint.0)/self.stride+1;
@@ -237,9 +233,12 @@ impl LazyArrayOperation<SparseTagArray> for LazyANNTrigger3D{
 
 
                     let mut model_lock = self.model.lock().unwrap();
-                    let outputs = model_lock.run(ort::inputs![Tensor::from_array(slided.clone()).unwrap()]).unwrap();
+                    let input = Tensor::from_array(slided.clone()).map_err(ExecutionError::from_error)?;
+                    let outputs = model_lock.run(ort::inputs![input]).map_err(ExecutionError::from_error)?;
 
-                    let output = outputs[self.output_layer.as_str()].try_extract_array::<f32>().unwrap().to_owned();
+                    let output = outputs.get(self.output_layer.as_str())
+                        .ok_or_else(|| ExecutionError::OtherError(format!("ANN has no output layer {}", self.output_layer).into()))?
+                        .try_extract_array::<f32>().map_err(ExecutionError::from_error)?.to_owned();
                     println!("ANN OK");
                     // drop(model_lock);
 
@@ -288,7 +287,23 @@ impl LazyArrayOperation<SparseTagArray> for LazyANNTrigger3D{
             }
         }
 
-        res
+        Ok(res)
+    }
+}
+
+impl LazyArrayOperation<SparseTagArray> for LazyANNTrigger3D{
+    #[allow(clippy::let_and_return)]
+    fn length(&self,) -> usize {
+        self.source.length()
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn request_range(&self,start:usize,end:usize,) -> SparseTagArray where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<SparseTagArray,ExecutionError> where {
+        self.try_request(start,end).into()
     }
 
     #[allow(clippy::let_and_return)]
@@ -0,0 +1,286 @@
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::Arc;
+
+use memmap2::Mmap;
+use zip::CompressionMethod;
+use zip::write::SimpleFileOptions;
+
+use crate::errors::NpyError;
+use crate::header::{f64_header, NpyHeader};
+
+/// Values are encoded by this many frames at once while writing
+const WRITE_BUFFER:usize = 1<<20;
+
+pub enum NpyBytes{
+    Mapped(Mmap),
+    /// Decompressed member of .npz archive
+    Owned(Vec<u8>),
+}
+
+impl Deref for NpyBytes{
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Mapped(m)=>m,
+            Self::Owned(v)=>v,
+        }
+    }
+}
+
+fn map_file<P:AsRef<Path>>(path:P)->Result<Mmap,NpyError>{
+    let file = File::open(path)?;
+    // Safety: data is only read. Changing file while it is mapped is not supported as it is for other readers.
+    let mapped = unsafe { Mmap::map(&file)? };
+    Ok(mapped)
+}
+
+fn npz_member_name(member:&str)->String{
+    if member.ends_with(".npy") {member.into()} else {format!("{}.npy", member)}
+}
+
+/// Array stored in .npy format. Frames along first axis are decoded to f64 on request.
+#[derive(Clone)]
+pub struct NpyArray{
+    bytes:Arc<NpyBytes>,
+    /// Position of first element in `bytes`
+    start:usize,
+    header:NpyHeader,
+    decode:fn(&[u8])->f64,
+    /// Offsets of frame elements in C order from element of the same time. Present for Fortran ordered arrays only.
+    frame_offsets:Option<Arc<Vec<usize>>>,
+}
+
+impl Debug for NpyArray{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NpyArray").field("header", &self.header).finish()
+    }
+}
+
+/// In Fortran order first axis is the fastest one
+fn fortran_frame_offsets(shape:&[usize])->Vec<usize>{
+    let frame_shape = &shape[1..];
+    let mut strides = Vec::with_capacity(frame_shape.len());
+    let mut stride = shape[0];
+    for dim in frame_shape.iter(){
+        strides.push(stride);
+        stride *= dim;
+    }
+    let mut res = vec![0];
+    // Index in C order: last axis is the fastest
+    for (dim, stride) in frame_shape.iter().zip(strides.iter()){
+        res = res.iter().flat_map(|base| (0..*dim).map(move |i| base+i*stride)).collect();
+    }
+    res
+}
+
+impl NpyArray{
+    /// Reads .npy data beginning at `offset` of `bytes`
+    pub fn from_bytes(bytes:NpyBytes, offset:usize)->Result<Self,NpyError>{
+        let header = NpyHeader::parse(&bytes[offset..])?;
+        let start = offset+header.data_offset;
+        let available = bytes.len().saturating_sub(start);
+        let expected = header.data_length().ok_or_else(|| NpyError::TooLarge(header.shape.clone()))?;
+        if available<expected{
+            return Err(NpyError::Truncated { shape: header.shape.clone(), expected, available });
+        }
+        let decode = header.dtype.decoder().ok_or_else(|| NpyError::UnsupportedDtype(format!("{:?}", header.dtype)))?;
+        let frame_offsets = if header.fortran_order && header.shape.len()>1{
+            Some(Arc::new(fortran_frame_offsets(&header.shape)))
+        }
+        else{
+            None
+        };
+        Ok(Self { bytes: Arc::new(bytes), start, header, decode, frame_offsets })
+    }
+
+    pub fn open<P:AsRef<Path>>(path:P)->Result<Self,NpyError>{
+        Self::from_bytes(NpyBytes::Mapped(map_file(path)?), 0)
+    }
+
+    /// Opens `member` of .npz archive. Extension `.npy` of member may be omitted.
+    /// Stored members are memory mapped, compressed ones are decompressed into memory.
+    pub fn open_npz<P:AsRef<Path>>(path:P, member:&str)->Result<Self,NpyError>{
+        let path = path.as_ref();
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        let name = npz_member_name(member);
+        if archive.index_for_name(&name).is_none(){
+            let available:Vec<&str> = archive.file_names().map(|x| x.trim_end_matches(".npy")).collect();
+            return Err(NpyError::MissingMember { member: member.into(), available: available.join(", ") });
+        }
+        let mut entry = archive.by_name(&name)?;
+        if entry.compression()==CompressionMethod::Stored{
+            let start = entry.data_start() as usize;
+            drop(entry);
+            Self::from_bytes(NpyBytes::Mapped(map_file(path)?), start)
+        }
+        else{
+            let mut buffer = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut buffer)?;
+            Self::from_bytes(NpyBytes::Owned(buffer), 0)
+        }
+    }
+
+    pub fn shape(&self)->&[usize]{
+        &self.header.shape
+    }
+
+    pub fn frame_shape(&self)->&[usize]{
+        self.header.shape.get(1..).unwrap_or(&[])
+    }
+
+    pub fn length(&self)->usize{
+        self.header.shape.first().copied().unwrap_or(0)
+    }
+
+    /// Ensures array has `ndim` dimensions or at least one if `ndim` is `None`
+    pub fn check_ndim(&self, ndim:Option<usize>)->Result<(),NpyError>{
+        let ok = match ndim {
+            Some(n)=>self.shape().len()==n,
+            None=>!self.shape().is_empty(),
+        };
+        if ok{
+            Ok(())
+        }
+        else{
+            let expected = ndim.map(|x| x.to_string()).unwrap_or("1 or more".into());
+            Err(NpyError::InvalidShape { expected, shape: self.shape().to_vec() })
+        }
+    }
+
+    /// Flat frames `[start, end)` in C order
+    pub fn try_read_frames(&self, start:usize, end:usize)->Result<Vec<f64>,NpyError>{
+        if start>end || end>self.length(){
+            return Err(NpyError::OutOfRange { start, end, length: self.length() });
+        }
+        Ok(self.read_frames(start, end))
+    }
+
+    /// Flat frames `[start, end)` in C order. Panics if range is out of array.
+    pub fn read_frames(&self, start:usize, end:usize)->Vec<f64>{
+        let size = self.header.dtype.size;
+        let data = &self.bytes[self.start..];
+        let decode = self.decode;
+        match &self.frame_offsets {
+            None=>{
+                let frame:usize = self.frame_shape().iter().product();
+                data[start*frame*size..end*frame*size].chunks_exact(size).map(decode).collect()
+            }
+            Some(offsets)=>{
+                (start..end).flat_map(|t| offsets.iter().map(move |o| decode(&data[(t+o)*size..(t+o+1)*size]))).collect()
+            }
+        }
+    }
+}
+
+fn write_values<W:Write>(writer:&mut W, values:&[f64])->Result<(),NpyError>{
+    let mut buffer = Vec::with_capacity(values.len().min(WRITE_BUFFER)*8);
+    for chunk in values.chunks(WRITE_BUFFER){
+        buffer.clear();
+        buffer.extend(chunk.iter().flat_map(|x| x.to_le_bytes()));
+        writer.write_all(&buffer)?;
+    }
+    Ok(())
+}
+
+/// Writes little endian f64 .npy file. Values are appended in C order.
+pub struct NpyWriter{
+    file:BufWriter<File>,
+}
+
+impl NpyWriter{
+    pub fn create<P:AsRef<Path>>(path:P, shape:&[usize])->Result<Self,NpyError>{
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(&f64_header(shape))?;
+        Ok(Self { file })
+    }
+
+    pub fn write(&mut self, values:&[f64])->Result<(),NpyError>{
+        write_values(&mut self.file, values)
+    }
+
+    pub fn finish(mut self)->Result<(),NpyError>{
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes uncompressed .npz archive array by array, so it can be memory mapped on reading
+pub struct NpzWriter{
+    archive:zip::ZipWriter<BufWriter<File>>,
+}
+
+impl NpzWriter{
+    pub fn create<P:AsRef<Path>>(path:P)->Result<Self,NpyError>{
+        Ok(Self { archive: zip::ZipWriter::new(BufWriter::new(File::create(path)?)) })
+    }
+
+    /// Starts array `name`. Values of previous array must be written by now.
+    pub fn start_array(&mut self, name:&str, shape:&[usize])->Result<(),NpyError>{
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(true);
+        self.archive.start_file(npz_member_name(name), options)?;
+        self.archive.write_all(&f64_header(shape))?;
+        Ok(())
+    }
+
+    pub fn write(&mut self, values:&[f64])->Result<(),NpyError>{
+        write_values(&mut self.archive, values)
+    }
+
+    pub fn finish(self)->Result<(),NpyError>{
+        self.archive.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fortran_bytes(shape:&[usize], values_c:&[f64])->Vec<u8>{
+        let dict = format!("{{'descr': '<f8', 'fortran_order': True, 'shape': ({}, {}, {}), }}\n", shape[0], shape[1], shape[2]);
+        let mut res = b"\x93NUMPY\x01\x00".to_vec();
+        res.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+        res.extend_from_slice(dict.as_bytes());
+        for k in 0..shape[2]{
+            for j in 0..shape[1]{
+                for i in 0..shape[0]{
+                    res.extend_from_slice(&values_c[(i*shape[1]+j)*shape[2]+k].to_le_bytes());
+                }
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn test_read_orders() {
+        let shape = [4, 2, 3];
+        let values:Vec<f64> = (0..24).map(|x| x as f64).collect();
+        let array = NpyArray::from_bytes(NpyBytes::Owned(fortran_bytes(&shape, &values)), 0).unwrap();
+        assert_eq!(array.length(), 4);
+        assert_eq!(array.read_frames(1, 3), values[6..18].to_vec());
+        assert!(array.try_read_frames(3, 5).is_err());
+
+        let path = std::env::temp_dir().join(format!("padamo_npy_test_{}.npz", std::process::id()));
+        let mut writer = NpzWriter::create(&path).unwrap();
+        writer.start_array("signal", &shape).unwrap();
+        writer.write(&values).unwrap();
+        writer.start_array("time", &[4]).unwrap();
+        writer.write(&[0.0, 1.0, 2.0, 3.0]).unwrap();
+        writer.finish().unwrap();
+
+        let signal = NpyArray::open_npz(&path, "signal").unwrap();
+        assert_eq!(signal.frame_shape(), &[2, 3]);
+        assert_eq!(signal.read_frames(2, 4), values[12..].to_vec());
+        let time = NpyArray::open_npz(&path, "time.npy").unwrap();
+        assert_eq!(time.read_frames(0, 4), vec![0.0, 1.0, 2.0, 3.0]);
+        assert!(NpyArray::open_npz(&path, "pdm").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
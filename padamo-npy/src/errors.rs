@@ -0,0 +1,35 @@
+#[derive(thiserror::Error,Debug)]
+pub enum NpyError{
+    #[error("IO error: {0}")]
+    IOError(#[from] std::io::Error),
+    #[error("Zip error: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+    #[error("Invalid .npy header: {0}")]
+    Header(String),
+    #[error("Unsupported dtype {0}")]
+    UnsupportedDtype(String),
+    #[error("Array of shape {shape:?} needs {expected} bytes, {available} bytes available")]
+    Truncated{
+        shape:Vec<usize>,
+        expected:usize,
+        available:usize,
+    },
+    #[error("Archive has no array {member}. Available arrays: {available}")]
+    MissingMember{
+        member:String,
+        available:String,
+    },
+    #[error("Array of shape {0:?} is too large")]
+    TooLarge(Vec<usize>),
+    #[error("Frames {start}..{end} are out of array of length {length}")]
+    OutOfRange{
+        start:usize,
+        end:usize,
+        length:usize,
+    },
+    #[error("Expected array with {expected} dimensions, got shape {shape:?}")]
+    InvalidShape{
+        expected:String,
+        shape:Vec<usize>,
+    },
+}
@@ -0,0 +1,197 @@
+use crate::errors::NpyError;
+
+pub const MAGIC:&[u8] = b"\x93NUMPY";
+/// Header of version 1.0 files is padded so data starts at multiple of this
+const ALIGNMENT:usize = 64;
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum DTypeKind{
+    Float,
+    Int,
+    Uint,
+    Bool,
+}
+
+/// Numeric element type of array, e.g. `<f8`
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct DType{
+    pub kind:DTypeKind,
+    pub size:usize,
+    pub big_endian:bool,
+}
+
+macro_rules! decoder {
+    ($t:ty, $big_endian:expr) => {
+        if $big_endian{
+            (|b:&[u8]| <$t>::from_be_bytes(b.try_into().unwrap()) as f64) as fn(&[u8])->f64
+        }
+        else{
+            (|b:&[u8]| <$t>::from_le_bytes(b.try_into().unwrap()) as f64) as fn(&[u8])->f64
+        }
+    };
+}
+
+impl DType{
+    pub fn parse(descr:&str)->Result<Self,NpyError>{
+        let unsupported = || NpyError::UnsupportedDtype(descr.into());
+        let mut chars = descr.chars();
+        let big_endian = match chars.next().ok_or_else(unsupported)? {
+            '<' | '|' => false,
+            '>' => true,
+            '=' => cfg!(target_endian = "big"),
+            _ => return Err(unsupported()),
+        };
+        let kind = match chars.next().ok_or_else(unsupported)? {
+            'f' => DTypeKind::Float,
+            'i' => DTypeKind::Int,
+            'u' => DTypeKind::Uint,
+            'b' => DTypeKind::Bool,
+            _ => return Err(unsupported()),
+        };
+        let size:usize = chars.as_str().parse().map_err(|_| unsupported())?;
+        let res = Self { kind, size, big_endian };
+        res.decoder().ok_or_else(unsupported)?;
+        Ok(res)
+    }
+
+    /// Function converting bytes of one element to f64. `None` if type is not supported.
+    pub fn decoder(&self)->Option<fn(&[u8])->f64>{
+        let be = self.big_endian;
+        let res:fn(&[u8])->f64 = match (self.kind, self.size) {
+            (DTypeKind::Float, 4) => decoder!(f32, be),
+            (DTypeKind::Float, 8) => decoder!(f64, be),
+            (DTypeKind::Int, 1) => |b| b[0] as i8 as f64,
+            (DTypeKind::Int, 2) => decoder!(i16, be),
+            (DTypeKind::Int, 4) => decoder!(i32, be),
+            (DTypeKind::Int, 8) => decoder!(i64, be),
+            (DTypeKind::Uint, 1) => |b| b[0] as f64,
+            (DTypeKind::Uint, 2) => decoder!(u16, be),
+            (DTypeKind::Uint, 4) => decoder!(u32, be),
+            (DTypeKind::Uint, 8) => decoder!(u64, be),
+            (DTypeKind::Bool, 1) => |b| if b[0]!=0 {1.0} else {0.0},
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub struct NpyHeader{
+    pub dtype:DType,
+    pub fortran_order:bool,
+    pub shape:Vec<usize>,
+    /// Position of first element relative to start of .npy data
+    pub data_offset:usize,
+}
+
+/// Value of `'key':` entry of header dictionary up to the next top level comma
+fn dict_value<'a>(dict:&'a str, key:&str)->Result<&'a str,NpyError>{
+    let pattern = format!("'{}':", key);
+    let start = dict.find(&pattern).ok_or_else(|| NpyError::Header(format!("no key {}", key)))?+pattern.len();
+    let rest = dict[start..].trim_start();
+    let end = if rest.starts_with('('){
+        rest.find(')').map(|x| x+1)
+    }
+    else{
+        rest.find([',', '}'])
+    };
+    let end = end.ok_or_else(|| NpyError::Header(format!("unterminated value of {}", key)))?;
+    Ok(rest[..end].trim())
+}
+
+fn parse_shape(value:&str)->Result<Vec<usize>,NpyError>{
+    let inner = value.strip_prefix('(').and_then(|x| x.strip_suffix(')'))
+        .ok_or_else(|| NpyError::Header(format!("invalid shape {}", value)))?;
+    inner.split(',')
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .map(|x| x.trim_end_matches('L').parse().map_err(|_| NpyError::Header(format!("invalid shape {}", value))))
+        .collect()
+}
+
+impl NpyHeader{
+    pub fn parse(data:&[u8])->Result<Self,NpyError>{
+        if data.len()<10 || &data[..6]!=MAGIC{
+            return Err(NpyError::Header("not a .npy file".into()));
+        }
+        let (length, start) = match data[6] {
+            1 => (u16::from_le_bytes([data[8], data[9]]) as usize, 10),
+            2 | 3 if data.len()>=12 => (u32::from_le_bytes([data[8], data[9], data[10], data[11]]) as usize, 12),
+            v => return Err(NpyError::Header(format!("unsupported version {}", v))),
+        };
+        let end = start+length;
+        let dict = data.get(start..end).ok_or_else(|| NpyError::Header("truncated header".into()))?;
+        let dict = std::str::from_utf8(dict).map_err(|e| NpyError::Header(e.to_string()))?;
+
+        let descr = dict_value(dict, "descr")?;
+        let descr = descr.trim_matches(|c| c=='\'' || c=='"');
+        let fortran_order = match dict_value(dict, "fortran_order")? {
+            "True" => true,
+            "False" => false,
+            v => return Err(NpyError::Header(format!("invalid fortran_order {}", v))),
+        };
+        let shape = parse_shape(dict_value(dict, "shape")?)?;
+        Ok(Self { dtype: DType::parse(descr)?, fortran_order, shape, data_offset: end })
+    }
+
+    /// Number of elements, `None` if it does not fit into `usize`
+    pub fn element_count(&self)->Option<usize>{
+        self.shape.iter().try_fold(1usize, |a,b| a.checked_mul(*b))
+    }
+
+    /// Size of data in bytes, `None` if it does not fit into `usize`
+    pub fn data_length(&self)->Option<usize>{
+        self.element_count()?.checked_mul(self.dtype.size)
+    }
+}
+
+/// Header of C-ordered little endian f64 array with given shape
+pub fn f64_header(shape:&[usize])->Vec<u8>{
+    let shape_str = match shape {
+        [x] => format!("({},)", x),
+        _ => format!("({})", shape.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")),
+    };
+    let mut dict = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': {}, }}", shape_str);
+    // Dictionary ends with newline and is padded with spaces. Version 1.0 stores its length in 2 bytes, version 2.0 in 4 bytes.
+    let padded = |prefix:usize| (prefix+dict.len()+1).div_ceil(ALIGNMENT)*ALIGNMENT-prefix;
+    let (version, prefix) = if padded(MAGIC.len()+4)<=u16::MAX as usize {(1u8, MAGIC.len()+4)} else {(2u8, MAGIC.len()+6)};
+    let total = prefix+padded(prefix);
+    dict.extend(std::iter::repeat_n(' ', total-prefix-dict.len()-1));
+    dict.push('\n');
+
+    let mut res = Vec::with_capacity(total);
+    res.extend_from_slice(MAGIC);
+    res.extend_from_slice(&[version, 0]);
+    if version==1{
+        res.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    }
+    else{
+        res.extend_from_slice(&(dict.len() as u32).to_le_bytes());
+    }
+    res.extend_from_slice(dict.as_bytes());
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_roundtrip() {
+        let header = f64_header(&[10, 4, 3]);
+        assert_eq!(header.len()%ALIGNMENT, 0);
+        let parsed = NpyHeader::parse(&header).unwrap();
+        assert_eq!(parsed.shape, vec![10, 4, 3]);
+        assert!(!parsed.fortran_order);
+        assert_eq!(parsed.dtype, DType { kind: DTypeKind::Float, size: 8, big_endian: false });
+        assert_eq!(parsed.data_offset, header.len());
+
+        let parsed = NpyHeader::parse(&f64_header(&[7])).unwrap();
+        assert_eq!(parsed.shape, vec![7]);
+        assert_eq!(parsed.data_length(), Some(56));
+        assert_eq!(NpyHeader::parse(&f64_header(&[usize::MAX, 2])).unwrap().data_length(), None);
+
+        assert!(DType::parse("<c16").is_err());
+        assert_eq!(DType::parse(">u2").unwrap().decoder().unwrap()(&[1, 2]), 258.0);
+    }
+}
@@ -0,0 +1,29 @@
+use abi_stable::std_types::RString;
+use padamo_api::prelude::*;
+use abi_stable::{std_types::RVec, export_root_module, prefix_type::PrefixTypeTrait};
+use padamo_api::nodes_vec;
+use abi_stable::sabi_extern_fn;
+
+pub mod header;
+pub mod array;
+pub mod ops;
+
+pub mod errors;
+pub mod nodes;
+
+#[export_root_module]
+pub fn plugin_root()->PadamoModule_Ref{
+    PadamoModule{nodes, share_cache:padamo_api::share_cache, info:padamo_api::plugin_info!()}.leak_into_prefix()
+}
+
+#[sabi_extern_fn]
+pub fn nodes(_library_dir:RString)->RVec<CalculationNodeBox>{
+    nodes_vec!(
+        crate::nodes::LazyNpyArrayNode,
+        crate::nodes::LazyNpyTimeNode,
+        crate::nodes::LazyNpzArrayNode,
+        crate::nodes::LazyNpzSignalNode,
+        crate::nodes::SaveNpzNode,
+        crate::nodes::SaveNpyArrayNode
+    )
+}
@@ -0,0 +1,225 @@
+use std::path::Path;
+
+use abi_stable::std_types::{ROption, RString};
+use padamo_api::prelude::*;
+use padamo_api::calculation_nodes::progress::write_file_atomically;
+use padamo_api::lazy_array_operations::{LazyDetectorSignal, LazyTimeSignal, LazyTriSignal};
+use padamo_api::lazy_array_operations::cache::cache_budget;
+use padamo_api::lazy_array_operations::prefetch::DEFAULT_PREFETCH_DEPTH;
+
+use crate::array::{NpyArray, NpyWriter, NpzWriter};
+use crate::ops::{LazyNpySignal, LazyNpyTime};
+
+/// Writers take this part of cache budget for frames being written
+const WRITE_BUDGET_PART:usize = 8;
+
+/// Number of frames requested at once such that prefetched ranges, range being written and its copy fit into part of memory budget
+fn write_step(frame_bytes:usize)->usize{
+    let budget = cache_budget().budget()/WRITE_BUDGET_PART;
+    (budget/(frame_bytes.max(1)*(DEFAULT_PREFETCH_DEPTH+2))).max(1)
+}
+
+fn signal_reader(array:NpyArray)->Result<LazyDetectorSignal,ExecutionError>{
+    array.check_ndim(None).map_err(ExecutionError::from_error)?;
+    Ok(make_lao_box(LazyNpySignal::new(array)))
+}
+
+fn time_reader(array:NpyArray)->Result<LazyTimeSignal,ExecutionError>{
+    array.check_ndim(Some(1)).map_err(ExecutionError::from_error)?;
+    Ok(make_lao_box(LazyNpyTime::new(array)))
+}
+
+fn open_npz(filename:&str, member:&str)->Result<NpyArray,ExecutionError>{
+    NpyArray::open_npz(filename, member).map_err(|e| ExecutionError::OtherError(format!("Cannot read {} from {}: {}", member, filename, e).into()))
+}
+
+fn open_npy(filename:&str)->Result<NpyArray,ExecutionError>{
+    NpyArray::open(filename).map_err(|e| ExecutionError::OtherError(format!("Cannot read {}: {}", filename, e).into()))
+}
+
+#[derive(NodePorts)]
+pub struct FileInput{
+    #[port(name = "Filename")]
+    filename:RString,
+}
+
+#[derive(NodePorts)]
+pub struct ArrayOutput{
+    #[port(name = "Array")]
+    array:LazyDetectorSignal,
+}
+
+#[derive(NodePorts)]
+pub struct TimeOutput{
+    #[port(name = "Time")]
+    time:LazyTimeSignal,
+}
+
+#[derive(NodePorts)]
+pub struct SignalOutput{
+    #[port(name = "Signal")]
+    signal:LazyTriSignal,
+}
+
+#[derive(NodeConstants)]
+pub struct NpzArrayConstants{
+    #[constant(display = "Array name", default = "signal")]
+    member:RString,
+}
+
+#[derive(NodeConstants)]
+pub struct NpzSignalConstants{
+    #[constant(display = "Signal array", default = "signal")]
+    spatial_field:RString,
+    #[constant(display = "Time array", default = "time")]
+    temporal_field:RString,
+}
+
+#[derive(Clone,Debug,CalculationNode)]
+#[node(name = "Lazy NPY array reader", identifier = "padamonpy.array_reader", category = padamo_api::common_categories::array_sources)]
+pub struct LazyNpyArrayNode;
+
+impl TypedCalculationNode for LazyNpyArrayNode{
+    type Inputs = FileInput;
+    type Outputs = ArrayOutput;
+    type Constants = ();
+
+    fn run(&self, inputs:FileInput, _constants:(), _args:&mut CalculationNodeArguments) -> Result<ArrayOutput,ExecutionError>{
+        let array = signal_reader(open_npy(&inputs.filename)?)?;
+        Ok(ArrayOutput { array })
+    }
+}
+
+#[derive(Clone,Debug,CalculationNode)]
+#[node(name = "Lazy NPY time reader", identifier = "padamonpy.time_reader", category = padamo_api::common_categories::time_sources)]
+pub struct LazyNpyTimeNode;
+
+impl TypedCalculationNode for LazyNpyTimeNode{
+    type Inputs = FileInput;
+    type Outputs = TimeOutput;
+    type Constants = ();
+
+    fn run(&self, inputs:FileInput, _constants:(), _args:&mut CalculationNodeArguments) -> Result<TimeOutput,ExecutionError>{
+        let time = time_reader(open_npy(&inputs.filename)?)?;
+        Ok(TimeOutput { time })
+    }
+}
+
+#[derive(Clone,Debug,CalculationNode)]
+#[node(name = "Lazy NPZ array reader", identifier = "padamonpy.npz_array_reader", category = padamo_api::common_categories::array_sources)]
+pub struct LazyNpzArrayNode;
+
+impl TypedCalculationNode for LazyNpzArrayNode{
+    type Inputs = FileInput;
+    type Outputs = ArrayOutput;
+    type Constants = NpzArrayConstants;
+
+    fn run(&self, inputs:FileInput, constants:NpzArrayConstants, _args:&mut CalculationNodeArguments) -> Result<ArrayOutput,ExecutionError>{
+        let array = signal_reader(open_npz(&inputs.filename, &constants.member)?)?;
+        Ok(ArrayOutput { array })
+    }
+}
+
+#[derive(Clone,Debug,CalculationNode)]
+#[node(name = "Lazy NPZ signal reader", identifier = "padamonpy.signal_reader", category = padamo_api::common_categories::data_sources)]
+pub struct LazyNpzSignalNode;
+
+impl TypedCalculationNode for LazyNpzSignalNode{
+    type Inputs = FileInput;
+    type Outputs = SignalOutput;
+    type Constants = NpzSignalConstants;
+
+    fn run(&self, inputs:FileInput, constants:NpzSignalConstants, _args:&mut CalculationNodeArguments) -> Result<SignalOutput,ExecutionError>{
+        let spatial = signal_reader(open_npz(&inputs.filename, &constants.spatial_field)?)?;
+        let temporal = time_reader(open_npz(&inputs.filename, &constants.temporal_field)?)?;
+        if spatial.length()!=temporal.length(){
+            return Err(ExecutionError::OtherError(format!("Signal has {} frames, time has {} entries", spatial.length(), temporal.length()).into()));
+        }
+        Ok(SignalOutput { signal:(spatial, temporal, ROption::RNone).into() })
+    }
+}
+
+#[derive(NodePorts)]
+pub struct SaveNpzInputs{
+    #[port(name = "Signal")]
+    signal:LazyTriSignal,
+    #[port(name = "File path")]
+    file_path:RString,
+}
+
+#[derive(Clone,Debug,CalculationNode)]
+#[node(name = "Save NPZ signal", identifier = "padamonpy.signal_writer", category = padamo_api::common_categories::data_savers, primary)]
+pub struct SaveNpzNode;
+
+impl SaveNpzNode{
+    /// Signal is written by ranges fitting into memory budget, then time. Triggers are not stored.
+    fn write_file(&self, path:&Path, signal:&LazyTriSignal, constants:&NpzSignalConstants, args:&CalculationNodeArguments) -> Result<(),ExecutionError> {
+        let length = signal.0.length();
+        let sample = signal.0.try_request_range(0,length.min(1)).into_result()?;
+        let frame_shape:Vec<usize> = sample.shape.iter().skip(1).copied().collect();
+        let mut shape = vec![length];
+        shape.extend(frame_shape.iter().copied());
+
+        let mut writer = NpzWriter::create(path).map_err(ExecutionError::from_error)?;
+        writer.start_array(&constants.spatial_field, &shape).map_err(ExecutionError::from_error)?;
+        let step = write_step(frame_shape.iter().product::<usize>()*std::mem::size_of::<f64>());
+        signal.0.clone().prefetched(step, DEFAULT_PREFETCH_DEPTH).try_for_each_chunk(0, length, step, args.progress, |_, spatial|{
+            writer.write(&spatial.flat_data).map_err(ExecutionError::from_error)
+        })?;
+        writer.start_array(&constants.temporal_field, &[length]).map_err(ExecutionError::from_error)?;
+        signal.1.try_for_each_chunk(0, length, write_step(std::mem::size_of::<f64>()), args.progress, |_, temporal|{
+            writer.write(&temporal).map_err(ExecutionError::from_error)
+        })?;
+        writer.finish().map_err(ExecutionError::from_error)
+    }
+}
+
+impl TypedCalculationNode for SaveNpzNode{
+    type Inputs = SaveNpzInputs;
+    type Outputs = ();
+    type Constants = NpzSignalConstants;
+
+    fn run(&self, inputs:SaveNpzInputs, constants:NpzSignalConstants, args:&mut CalculationNodeArguments) -> Result<(),ExecutionError>{
+        // File appears only when all data is written
+        write_file_atomically(Path::new(inputs.file_path.as_str()), |path| self.write_file(path, &inputs.signal, &constants, args))
+    }
+}
+
+#[derive(NodePorts)]
+pub struct SaveNpyInputs{
+    #[port(name = "Array")]
+    array:LazyDetectorSignal,
+    #[port(name = "File path")]
+    file_path:RString,
+}
+
+#[derive(Clone,Debug,CalculationNode)]
+#[node(name = "Save NPY array", identifier = "padamonpy.array_writer", category = padamo_api::common_categories::array_savers, primary)]
+pub struct SaveNpyArrayNode;
+
+impl SaveNpyArrayNode{
+    fn write_file(&self, path:&Path, array:&LazyDetectorSignal, args:&CalculationNodeArguments) -> Result<(),ExecutionError> {
+        let length = array.length();
+        let sample = array.try_request_range(0,length.min(1)).into_result()?;
+        let frame_shape:Vec<usize> = sample.shape.iter().skip(1).copied().collect();
+        let mut shape = vec![length];
+        shape.extend(frame_shape.iter().copied());
+
+        let mut writer = NpyWriter::create(path, &shape).map_err(ExecutionError::from_error)?;
+        let step = write_step(frame_shape.iter().product::<usize>()*std::mem::size_of::<f64>());
+        array.clone().prefetched(step, DEFAULT_PREFETCH_DEPTH).try_for_each_chunk(0, length, step, args.progress, |_, spatial|{
+            writer.write(&spatial.flat_data).map_err(ExecutionError::from_error)
+        })?;
+        writer.finish().map_err(ExecutionError::from_error)
+    }
+}
+
+impl TypedCalculationNode for SaveNpyArrayNode{
+    type Inputs = SaveNpyInputs;
+    type Outputs = ();
+    type Constants = ();
+
+    fn run(&self, inputs:SaveNpyInputs, _constants:(), args:&mut CalculationNodeArguments) -> Result<(),ExecutionError>{
+        write_file_atomically(Path::new(inputs.file_path.as_str()), |path| self.write_file(path, &inputs.array, args))
+    }
+}
@@ -0,0 +1,64 @@
+use abi_stable::std_types::{RResult, RVec};
+use padamo_api::calculation_nodes::errors::ExecutionError;
+use padamo_api::lazy_array_operations::{ArrayND, LazyArrayOperation};
+
+use crate::array::NpyArray;
+
+/// Frames of array along its first axis
+#[derive(Clone,Debug)]
+pub struct LazyNpySignal{
+    array:NpyArray,
+}
+
+impl LazyNpySignal{
+    pub fn new(array:NpyArray)->Self{
+        Self { array }
+    }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let flat_data = self.array.try_read_frames(start, end).map_err(ExecutionError::from_error)?;
+        let mut shape:RVec<usize> = RVec::with_capacity(self.array.shape().len());
+        shape.push(end-start);
+        shape.extend(self.array.frame_shape().iter().copied());
+        Ok(ArrayND { flat_data: flat_data.into(), shape })
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyNpySignal{
+    fn length(&self) -> usize where {
+        self.array.length()
+    }
+
+    fn request_range(&self,start:usize,end:usize) -> ArrayND<f64> where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError> where {
+        self.try_request(start,end).into()
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct LazyNpyTime{
+    array:NpyArray,
+}
+
+impl LazyNpyTime{
+    pub fn new(array:NpyArray)->Self{
+        Self { array }
+    }
+}
+
+impl LazyArrayOperation<RVec<f64>> for LazyNpyTime{
+    fn length(&self) -> usize where {
+        self.array.length()
+    }
+
+    fn request_range(&self,start:usize,end:usize) -> RVec<f64> where {
+        self.array.try_read_frames(start, end).unwrap().into()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<RVec<f64>,ExecutionError> where {
+        self.array.try_read_frames(start, end).map(RVec::from).map_err(ExecutionError::from_error).into()
+    }
+}
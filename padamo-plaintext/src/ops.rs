@@ -1,7 +1,9 @@
 use std::{fs::File, io::{BufRead, BufReader}};
 use abi_stable::rvec;
+use abi_stable::std_types::RResult;
 
 use padamo_api::lazy_array_operations::{merge::Merge, ArrayND, LazyArrayOperation};
+use padamo_api::prelude::ExecutionError;
 use regex::Regex;
 
 use crate::errors::CSVError;
@@ -78,6 +80,21 @@ impl CSVReader{
         //     Ok(vec![])
         // }
     }
+
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let mut res = self.read_lines_csv(start+self.start_line, end-start,true).map_err(ExecutionError::from_error)?;
+        if res.len()!=end-start{
+            return Err(ExecutionError::OtherError(format!("File {} has only {} of {} requested frames", self.filename, res.len(), end-start).into()));
+        }
+        if let Some(i) = res.iter().position(|x| x.len()!=self.frame_size){
+            return Err(ExecutionError::OtherError(format!("Malformed line {} in file {}", start+i, self.filename).into()));
+        }
+        let res = res
+            .drain(..)
+            .map(|x| ArrayND{flat_data: x.into(), shape:rvec![1,self.frame_size]})
+            .fold(ArrayND::new(vec![0,self.frame_size], 0.0),|a,b| a.merge(b));
+        Ok(res)
+    }
 }
 
 
@@ -91,11 +108,10 @@ impl LazyArrayOperation<ArrayND<f64>> for CSVReader{
     }
 
     fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
-        let mut res = self.read_lines_csv(start+self.start_line, end-start,true).unwrap();
-        let res = res
-            .drain(..)
-            .map(|x| ArrayND{flat_data: x.into(), shape:rvec![1,self.frame_size]})
-            .fold(ArrayND::new(vec![0,self.frame_size], 0.0),|a,b| a.merge(b));
-        res
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<ArrayND<f64>,ExecutionError> where {
+        self.try_request(start,end).into()
     }
 }
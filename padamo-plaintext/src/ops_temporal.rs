@@ -1,9 +1,17 @@
-use abi_stable::std_types::RVec;
+use abi_stable::std_types::{RResult, RVec};
 
-use padamo_api::lazy_array_operations::LazyArrayOperation;
+use padamo_api::lazy_array_operations::{ArrayND, LazyArrayOperation};
+use padamo_api::prelude::ExecutionError;
 
 use crate::errors::CSVError;
 
+fn flatten_time(res:ArrayND<f64>, start:usize, end:usize)->Result<RVec<f64>,ExecutionError>{
+    if res.flat_data.len()!= end-start {
+        return Err(ExecutionError::OtherError("CSV time reader inner error: items length mismatch".into()));
+    }
+    Ok(res.flat_data)
+}
+
 
 #[derive(Clone,Debug)]
 pub struct CSVTimeColumnReader{
@@ -23,11 +31,13 @@ impl LazyArrayOperation<RVec<f64>> for CSVTimeColumnReader{
     }
 
     fn request_range(&self,start:usize,end:usize,) -> RVec<f64>where {
-        let res = self.reader.request_range(start, end);
-        if res.flat_data.len()!= end-start {
-            panic!("CSV time column reader inner error: items length mismatch");
-        }
-        res.flat_data
+        self.try_request_range(start, end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<RVec<f64>,ExecutionError>where {
+        self.reader.try_request_range(start, end).into_result()
+            .and_then(|res| flatten_time(res, start, end))
+            .into()
     }
 
     fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
@@ -53,11 +63,13 @@ impl LazyArrayOperation<RVec<f64>> for CSVTimeRowReader{
     }
 
     fn request_range(&self,start:usize,end:usize,) -> RVec<f64>where {
-        let res = self.reader.request_range(start, end);
-        if res.flat_data.len()!= end-start {
-            panic!("CSV time column reader inner error: items length mismatch");
-        }
-        res.flat_data
+        self.try_request_range(start, end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<RVec<f64>,ExecutionError>where {
+        self.reader.try_request_range(start, end).into_result()
+            .and_then(|res| flatten_time(res, start, end))
+            .into()
     }
 
     fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
@@ -0,0 +1,324 @@
+//! Nodes made from script files in `scripts` directory of plugin.
+//! Ports and constants of such node are declared in header of script:
+//!
+//! ```text
+//! //! name: Signal ratio
+//! //! mode: frame
+//! //! input signal: Signal
+//! //! input signal: Background
+//! //! input float: gain
+//! //! constant float: offset = 0.5
+//! //! output signal: Ratio
+//! //! output float: Scale
+//! //! output function: Curve
+//! ```
+//!
+//! Scalar inputs and constants are available in script as constants named after them.
+//! Signal output `Ratio` is made by `fn Ratio(signal, background, time)` (pixel mode gets series and times),
+//! time and trigger are taken from the first signal input.
+//! Scalar output `Scale` is value of `fn Scale()`, function output `Curve` is `fn Curve(x)`.
+//! Scripts are read when plugin is loaded, so application must be restarted after editing them.
+use std::path::Path;
+use std::sync::Arc;
+
+use abi_stable::rvec;
+use abi_stable::std_types::{RResult, RString, RVec};
+use padamo_api::function_operator::make_function_box;
+use padamo_api::lazy_array_operations::LazyTriSignal;
+use padamo_api::make_node_box;
+use padamo_api::prelude::*;
+use padamo_detectors::rhai::Dynamic;
+
+use crate::ops::{LazyScriptSignal, ScriptFunction, ScriptMode};
+use crate::script::{to_float, ScriptSource, ScriptValue};
+
+/// Directory inside plugin directory with script nodes
+pub const SCRIPTS_DIR:&str = "scripts";
+const HEADER_PREFIX:&str = "//!";
+
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum PortKind{
+    Signal,
+    Float,
+    Integer,
+    Boolean,
+    String,
+    Function,
+}
+
+impl PortKind{
+    pub fn parse(src:&str)->Result<Self,String>{
+        match src.trim() {
+            "signal"=>Ok(Self::Signal),
+            "float"=>Ok(Self::Float),
+            "integer"=>Ok(Self::Integer),
+            "boolean"=>Ok(Self::Boolean),
+            "string"=>Ok(Self::String),
+            "function"=>Ok(Self::Function),
+            other=>Err(format!("Unknown type \"{}\"", other)),
+        }
+    }
+
+    pub fn content_type(&self)->ContentType{
+        match self {
+            Self::Signal=>ContentType::DetectorFullData,
+            Self::Float=>ContentType::Float,
+            Self::Integer=>ContentType::Integer,
+            Self::Boolean=>ContentType::Boolean,
+            Self::String=>ContentType::String,
+            Self::Function=>ContentType::Function,
+        }
+    }
+
+    fn parse_value(&self, src:&str)->Result<ScriptValue,String>{
+        let src = src.trim();
+        match self {
+            Self::Float=>src.parse().map(ScriptValue::Float).map_err(|_| format!("\"{}\" is not a float", src)),
+            Self::Integer=>src.parse().map(ScriptValue::Integer).map_err(|_| format!("\"{}\" is not an integer", src)),
+            Self::Boolean=>src.parse().map(ScriptValue::Boolean).map_err(|_| format!("\"{}\" is not a boolean", src)),
+            Self::String=>Ok(ScriptValue::String(src.trim_matches('"').into())),
+            _=>Err(format!("Constants of type {:?} are not supported", self)),
+        }
+    }
+
+    fn content_of(&self, value:Dynamic)->Result<Content,ExecutionError>{
+        let type_name = value.type_name();
+        let res = match self {
+            Self::Float=>return to_float(value).map(Content::Float),
+            Self::Integer=>value.as_int().ok().map(Content::Integer),
+            Self::Boolean=>value.as_bool().ok().map(Content::Boolean),
+            Self::String=>value.into_string().ok().map(|x| Content::String(x.into())),
+            _=>None,
+        };
+        res.ok_or_else(|| ExecutionError::OtherError(format!("Script returned {} instead of {:?}", type_name, self).into()))
+    }
+}
+
+fn check_identifier(name:&str)->Result<(),String>{
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c=='_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c=='_');
+    if valid{
+        Ok(())
+    }
+    else{
+        Err(format!("\"{}\" cannot be used as name in script", name))
+    }
+}
+
+/// Ports and constants declared in script header
+#[derive(Clone,Debug,PartialEq)]
+pub struct ScriptDeclaration{
+    pub name:String,
+    pub mode:ScriptMode,
+    pub inputs:Vec<(String,PortKind)>,
+    pub outputs:Vec<(String,PortKind)>,
+    pub constants:Vec<(String,ScriptValue)>,
+}
+
+impl ScriptDeclaration{
+    /// Parses header lines starting with `//!`. Node is named `default_name` unless header tells otherwise.
+    pub fn parse(code:&str, default_name:&str)->Result<Self,String>{
+        let mut res = Self { name:default_name.into(), mode:ScriptMode::Frame, inputs:Vec::new(), outputs:Vec::new(), constants:Vec::new() };
+        let mut names:Vec<String> = Vec::new();
+        for line in code.lines().map(str::trim).filter_map(|x| x.strip_prefix(HEADER_PREFIX)){
+            let Some((key,value)) = line.split_once(':') else {continue;};
+            let value = value.trim();
+            let mut words = key.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("name"),None)=>res.name = value.into(),
+                (Some("mode"),None)=>res.mode = ScriptMode::parse(value).map_err(|e| e.to_string())?,
+                (Some("input"),Some(kind))=>{
+                    let kind = PortKind::parse(kind)?;
+                    if kind==PortKind::Function{
+                        return Err("Function inputs are not supported".into());
+                    }
+                    check_identifier(value)?;
+                    names.push(value.into());
+                    res.inputs.push((value.into(), kind));
+                },
+                (Some("output"),Some(kind))=>{
+                    check_identifier(value)?;
+                    if res.outputs.iter().any(|x| x.0==value){
+                        return Err(format!("Output {} is declared twice", value));
+                    }
+                    res.outputs.push((value.into(), PortKind::parse(kind)?));
+                },
+                (Some("constant"),Some(kind))=>{
+                    let (name,default) = value.split_once('=').ok_or_else(|| format!("Constant \"{}\" must be written as name = value", value))?;
+                    let name = name.trim();
+                    check_identifier(name)?;
+                    names.push(name.into());
+                    res.constants.push((name.into(), PortKind::parse(kind)?.parse_value(default)?));
+                },
+                _=>return Err(format!("Unknown declaration \"{}\"", key.trim())),
+            }
+        }
+        names.sort();
+        if let Some(w) = names.windows(2).find(|w| w[0]==w[1]){
+            return Err(format!("Name {} is declared twice", w[0]));
+        }
+        if res.outputs.is_empty(){
+            return Err("Script declares no outputs".into());
+        }
+        let has_signal = |ports:&[(String,PortKind)]| ports.iter().any(|x| x.1==PortKind::Signal);
+        if has_signal(&res.outputs) && !has_signal(&res.inputs){
+            return Err("Signal outputs require at least one signal input".into());
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct DeclaredScriptNode{
+    id:String,
+    code:String,
+    declaration:ScriptDeclaration,
+}
+
+impl DeclaredScriptNode{
+    pub fn new(id:&str, code:String)->Result<Self,String>{
+        let declaration = ScriptDeclaration::parse(&code, id)?;
+        Ok(Self { id:id.into(), code, declaration })
+    }
+
+    pub fn load(path:&Path)->Result<Self,String>{
+        let id = path.file_stem().and_then(|x| x.to_str()).ok_or("Invalid file name")?;
+        let code = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::new(id, code)
+    }
+
+    fn calculate(&self, args:CalculationNodeArguments)->Result<(),ExecutionError>{
+        let mut parameters = Vec::new();
+        let mut signals:Vec<LazyTriSignal> = Vec::new();
+        for (name,kind) in self.declaration.inputs.iter(){
+            if *kind==PortKind::Signal{
+                signals.push(args.inputs.request_detectorfulldata(name)?);
+            }
+            else{
+                let value = args.inputs.request_type(&kind.content_type(), name)?;
+                parameters.push((name.clone(), ScriptValue::from_content(&value).ok_or(ExecutionError::TypeError)?));
+            }
+        }
+        for (name,default) in self.declaration.constants.iter(){
+            let value = args.constants.0.get(name.as_str()).ok_or_else(|| ExecutionError::NotConnected(name.as_str().into()))?;
+            let value = ScriptValue::from_constant(value);
+            if std::mem::discriminant(&value)!=std::mem::discriminant(default){
+                return Err(ExecutionError::TypeError);
+            }
+            parameters.push((name.clone(), value));
+        }
+        if let Some(first) = signals.first(){
+            let length = first.0.length();
+            if signals.iter().any(|x| x.0.length()!=length){
+                return Err(ExecutionError::OtherError("Script signals have different lengths".into()));
+            }
+        }
+
+        // Outputs are lazy and outlive execution, so script is not tied to its progress token
+        let script = Arc::new(ScriptSource::new(self.code.clone(), parameters));
+        for (name,kind) in self.declaration.outputs.iter(){
+            let value = match kind {
+                PortKind::Signal=>{
+                    script.check(name, signals.len()+1)?;
+                    let mut signal = signals[0].clone();
+                    let op = LazyScriptSignal::new(signals.iter().map(|x| x.0.clone()).collect(), signal.1.clone(), script.clone(), self.declaration.mode, name);
+                    signal.0 = make_lao_box(op);
+                    Content::DetectorFullData(signal)
+                },
+                PortKind::Function=>{
+                    script.check(name, 1)?;
+                    Content::Function(make_function_box(ScriptFunction::new(script.clone(), name)))
+                },
+                scalar=>{
+                    script.check(name, 0)?;
+                    scalar.content_of(script.call(name, ())?)?
+                },
+            };
+            args.outputs.set_value(name, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl CalculationNode for DeclaredScriptNode{
+    fn name(&self,) -> RString where {
+        self.declaration.name.clone().into()
+    }
+
+    fn category(&self,) -> RVec<RString> where {
+        rvec!["Scripting".into(), "Library".into()]
+    }
+
+    fn identifier(&self,) -> RString where {
+        format!("padamoscripting.declared.{}", self.id).into()
+    }
+
+    fn setup_description(&self,) -> RString where {
+        self.code.clone().into()
+    }
+
+    fn inputs(&self,) -> RVec<CalculationIO> where {
+        self.declaration.inputs.iter().map(|(name,kind)| CalculationIO::new(name, kind.content_type())).collect()
+    }
+
+    fn outputs(&self,) -> RVec<CalculationIO> where {
+        self.declaration.outputs.iter().map(|(name,kind)| CalculationIO::new(name, kind.content_type())).collect()
+    }
+
+    fn constants(&self,) -> RVec<CalculationConstant> where {
+        self.declaration.constants.iter().map(|(name,value)| CalculationConstant::new(name, value.to_constant())).collect()
+    }
+
+    fn calculate(&self,args:CalculationNodeArguments,) -> RResult<(),ExecutionError> where {
+        self.calculate(args).into()
+    }
+}
+
+/// Makes nodes from `*.rhai` files in scripts directory of plugin. Broken scripts are reported and skipped.
+pub fn library_nodes(library_dir:&str)->RVec<CalculationNodeBox>{
+    let mut res = RVec::new();
+    let Ok(entries) = std::fs::read_dir(Path::new(library_dir).join(SCRIPTS_DIR)) else {return res;};
+    let mut paths:Vec<_> = entries.filter_map(|x| x.ok()).map(|x| x.path())
+        .filter(|x| x.extension().is_some_and(|e| e=="rhai"))
+        .collect();
+    paths.sort();
+    for path in paths{
+        match DeclaredScriptNode::load(&path) {
+            Ok(node)=>res.push(make_node_box(node)),
+            Err(e)=>eprintln!("Could not load script node {}: {}", path.display(), e),
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    const SCRIPT:&str = "//! name: Ratio
+//! input signal: Signal
+//! input signal: Background
+//! input float: gain
+//! constant integer: steps = 3
+//! output signal: Ratio
+//! output integer: Total
+fn Ratio(signal, background, time) { signal }
+fn Total() { steps*2 }
+";
+
+    #[test]
+    fn test_declaration(){
+        let decl = ScriptDeclaration::parse(SCRIPT, "ratio").unwrap();
+        assert_eq!(decl.name, "Ratio");
+        assert_eq!(decl.inputs, vec![("Signal".to_string(), PortKind::Signal), ("Background".to_string(), PortKind::Signal), ("gain".to_string(), PortKind::Float)]);
+        assert_eq!(decl.outputs, vec![("Ratio".to_string(), PortKind::Signal), ("Total".to_string(), PortKind::Integer)]);
+        assert_eq!(decl.constants, vec![("steps".to_string(), ScriptValue::Integer(3))]);
+
+        assert!(ScriptDeclaration::parse("//! output signal: Signal", "x").is_err());
+        assert!(ScriptDeclaration::parse("//! input float: k\n//! constant float: k = 1\n//! output float: Y", "x").is_err());
+
+        let script = ScriptSource::new(SCRIPT.into(), decl.constants.clone());
+        let total = PortKind::Integer.content_of(script.call("Total", ()).unwrap()).unwrap();
+        assert!(matches!(total, Content::Integer(6)));
+    }
+}
@@ -0,0 +1,19 @@
+use abi_stable::std_types::RString;
+use padamo_api::prelude::*;
+use abi_stable::{std_types::RVec, export_root_module, prefix_type::PrefixTypeTrait};
+use abi_stable::sabi_extern_fn;
+
+pub mod script;
+pub mod ops;
+pub mod nodes;
+pub mod declared;
+
+#[export_root_module]
+pub fn plugin_root()->PadamoModule_Ref{
+    PadamoModule{nodes, share_cache:padamo_api::share_cache, info:padamo_api::plugin_info!()}.leak_into_prefix()
+}
+
+#[sabi_extern_fn]
+pub fn nodes(library_dir:RString)->RVec<CalculationNodeBox>{
+    nodes::nodes(&library_dir)
+}
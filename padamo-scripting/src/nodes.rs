@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use abi_stable::std_types::{RString, RVec};
+use padamo_api::{nodes_vec, prelude::*};
+use padamo_api::function_operator::{make_function_box, DoubleFunctionOperatorBox};
+use padamo_api::lazy_array_operations::LazyTriSignal;
+
+use crate::ops::{LazyScriptSignal, ScriptFunction, ScriptMode, SCALAR_FUNCTION};
+use crate::script::ScriptSource;
+
+const SIGNAL_TEMPLATE:&str = "// frame mode: fn frame(values, time) gets pixel values of one frame
+// pixel mode: fn pixel(values, times) gets time series of one pixel in requested chunk
+// Both must return array of the same length. Parameters are available as constants.
+fn frame(values, time) {
+    values
+}
+";
+
+const FUNCTION_TEMPLATE:&str = "// Parameters are available as constants
+fn f(x) {
+    x
+}
+";
+
+#[derive(NodePorts)]
+pub struct ScriptSignalPorts{
+    #[port(name = "Signal")]
+    signal:LazyTriSignal,
+}
+
+#[derive(NodeConstants)]
+pub struct ScriptSignalConstants{
+    #[constant(default = SIGNAL_TEMPLATE)]
+    script:RString,
+    #[constant(display = "Script file (overrides script)")]
+    script_file:RString,
+    #[constant(display = "Parameters (a=1, b=2.5)")]
+    parameters:RString,
+    #[constant(display = "Mode (frame or pixel)", default = "frame")]
+    mode:RString,
+}
+
+#[derive(Clone,Debug,CalculationNode)]
+#[node(name = "Script signal", identifier = "padamoscripting.script_signal", category = ["Scripting"])]
+pub struct ScriptSignalNode;
+
+impl TypedCalculationNode for ScriptSignalNode{
+    type Inputs = ScriptSignalPorts;
+    type Outputs = ScriptSignalPorts;
+    type Constants = ScriptSignalConstants;
+
+    fn run(&self, inputs:ScriptSignalPorts, constants:ScriptSignalConstants, _args:&mut CalculationNodeArguments)->Result<ScriptSignalPorts,ExecutionError>{
+        let mode = ScriptMode::parse(&constants.mode)?;
+        // Output is lazy and is used after execution is finished or stopped, so script is not tied to progress of execution.
+        // Endless scripts are stopped by operation limit.
+        let script = ScriptSource::load(&constants.script, &constants.script_file, &constants.parameters)?;
+        script.check(mode.function(), 2)?;
+
+        let mut signal = inputs.signal;
+        let op = LazyScriptSignal::new(vec![signal.0], signal.1.clone(), Arc::new(script), mode, mode.function());
+        signal.0 = make_lao_box(op);
+        Ok(ScriptSignalPorts { signal })
+    }
+}
+
+#[derive(NodePorts)]
+pub struct ScriptFunctionOutputs{
+    #[port(name = "Function")]
+    function:DoubleFunctionOperatorBox,
+}
+
+#[derive(NodeConstants)]
+pub struct ScriptFunctionConstants{
+    #[constant(default = FUNCTION_TEMPLATE)]
+    script:RString,
+    #[constant(display = "Script file (overrides script)")]
+    script_file:RString,
+    #[constant(display = "Parameters (a=1, b=2.5)")]
+    parameters:RString,
+}
+
+#[derive(Clone,Debug,CalculationNode)]
+#[node(name = "Script function", identifier = "padamoscripting.script_function", category = ["Scripting"])]
+pub struct ScriptFunctionNode;
+
+impl TypedCalculationNode for ScriptFunctionNode{
+    type Inputs = ();
+    type Outputs = ScriptFunctionOutputs;
+    type Constants = ScriptFunctionConstants;
+
+    fn run(&self, _inputs:(), constants:ScriptFunctionConstants, _args:&mut CalculationNodeArguments)->Result<ScriptFunctionOutputs,ExecutionError>{
+        let script = ScriptSource::load(&constants.script, &constants.script_file, &constants.parameters)?;
+        script.check(SCALAR_FUNCTION, 1)?;
+        let function = make_function_box(ScriptFunction::new(Arc::new(script), SCALAR_FUNCTION));
+        Ok(ScriptFunctionOutputs { function })
+    }
+}
+
+pub fn nodes(library_dir:&str)->RVec<CalculationNodeBox>{
+    let mut res = nodes_vec![
+        ScriptSignalNode,
+        ScriptFunctionNode
+    ];
+    res.extend(crate::declared::library_nodes(library_dir));
+    res
+}
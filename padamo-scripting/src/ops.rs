@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use abi_stable::std_types::RResult;
+use padamo_api::function_operator::DoubleFunctionOperator;
+use padamo_api::lazy_array_operations::{ArrayND, LazyArrayOperation, LazyDetectorSignal, LazyTimeSignal};
+use padamo_api::prelude::ExecutionError;
+use padamo_arraynd::ndim_array::ArrayStrideIterator;
+use padamo_detectors::rhai::Dynamic;
+
+use crate::script::{from_array, to_array, to_float, ScriptSource};
+
+/// Name of script function processing frames: `frame(values, time)`
+pub const FRAME_FUNCTION:&str = "frame";
+/// Name of script function processing time series of pixels: `pixel(values, times)`
+pub const PIXEL_FUNCTION:&str = "pixel";
+/// Name of script function for scalar functions: `f(x)`
+pub const SCALAR_FUNCTION:&str = "f";
+
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum ScriptMode{
+    /// Script is called for every frame with flat C-order pixel values
+    Frame,
+    /// Script is called for every pixel with its time series in requested range
+    Pixel,
+}
+
+impl ScriptMode{
+    pub fn parse(src:&str)->Result<Self,ExecutionError>{
+        match src.trim().to_lowercase().as_str() {
+            "frame"=>Ok(Self::Frame),
+            "pixel"=>Ok(Self::Pixel),
+            other=>Err(ExecutionError::OtherError(format!("Unknown script mode \"{}\". Use frame or pixel", other).into())),
+        }
+    }
+
+    pub fn function(&self)->&'static str{
+        match self {
+            Self::Frame=>FRAME_FUNCTION,
+            Self::Pixel=>PIXEL_FUNCTION,
+        }
+    }
+}
+
+/// Signal made by script from one or more input signals of the same shape.
+/// Script function gets values of every signal followed by time, e.g. `frame(values, time)` or `f(a, b, time)` for two signals.
+#[derive(Clone,Debug)]
+pub struct LazyScriptSignal{
+    signals:Vec<LazyDetectorSignal>,
+    time:LazyTimeSignal,
+    script:Arc<ScriptSource>,
+    mode:ScriptMode,
+    function:String,
+}
+
+impl LazyScriptSignal{
+    pub fn new(signals:Vec<LazyDetectorSignal>, time:LazyTimeSignal, script:Arc<ScriptSource>, mode:ScriptMode, function:&str)->Self{
+        Self { signals, time, script, mode, function:function.into() }
+    }
+
+    fn check_length(&self, values:&[f64], expected:usize)->Result<(),ExecutionError>{
+        if values.len()==expected{
+            Ok(())
+        }
+        else{
+            Err(ExecutionError::OtherError(format!("Script function {} returned {} values instead of {}", self.function, values.len(), expected).into()))
+        }
+    }
+
+    fn try_request(&self, start:usize, end:usize)->Result<ArrayND<f64>,ExecutionError>{
+        let mut data:Vec<ArrayND<f64>> = Vec::with_capacity(self.signals.len());
+        for signal in self.signals.iter(){
+            let part = signal.try_request_range(start,end).into_result()?;
+            if let Some(first) = data.first(){
+                if first.shape!=part.shape{
+                    return Err(ExecutionError::OtherError(format!("Script signals have different shapes {:?} and {:?}", first.shape, part.shape).into()));
+                }
+            }
+            data.push(part);
+        }
+        let time = self.time.try_request_range(start,end).into_result()?;
+        let shape = data.first().map(|x| x.shape.clone()).unwrap_or_default();
+        let length = shape.first().copied().unwrap_or(0);
+        let frame_size:usize = shape.iter().skip(1).product();
+        let mut flat_data = vec![0.0; length*frame_size];
+        match self.mode {
+            ScriptMode::Frame=>{
+                for i in 0..length{
+                    let mut args:Vec<Dynamic> = data.iter().map(|x| to_array(&x.flat_data[i*frame_size..(i+1)*frame_size]).into()).collect();
+                    args.push(Dynamic::from_float(time[i]));
+                    let res = from_array(self.script.call(&self.function, args)?)?;
+                    self.check_length(&res, frame_size)?;
+                    flat_data[i*frame_size..(i+1)*frame_size].copy_from_slice(&res);
+                }
+            },
+            ScriptMode::Pixel=>{
+                let times:Dynamic = to_array(&time).into();
+                for pixel in 0..frame_size{
+                    let mut args:Vec<Dynamic> = data.iter().map(|x| {
+                        let series:Vec<f64> = ArrayStrideIterator::from_slice(&x.flat_data, frame_size, pixel, length).collect();
+                        to_array(&series).into()
+                    }).collect();
+                    args.push(times.clone());
+                    let res = from_array(self.script.call(&self.function, args)?)?;
+                    self.check_length(&res, length)?;
+                    for (t,v) in res.into_iter().enumerate(){
+                        flat_data[t*frame_size+pixel] = v;
+                    }
+                }
+            },
+        }
+        Ok(ArrayND { flat_data: flat_data.into(), shape })
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyScriptSignal{
+    fn length(&self,) -> usize where {
+        self.signals.iter().map(|x| x.length()).min().unwrap_or(0)
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.signals.iter().map(|x| x.calculate_overhead(start,end)).sum()
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize,) -> RResult<ArrayND<f64>,ExecutionError> where {
+        self.try_request(start,end).into()
+    }
+}
+
+/// Scalar function defined by script function of one argument.
+/// Consumers calling `try_calculate` get script errors, plain `calculate` gives NaN on error.
+#[derive(Clone,Debug)]
+pub struct ScriptFunction{
+    script:Arc<ScriptSource>,
+    function:String,
+}
+
+impl ScriptFunction{
+    pub fn new(script:Arc<ScriptSource>, function:&str)->Self{
+        Self { script, function:function.into() }
+    }
+
+    fn call(&self, x:f64)->Result<f64,ExecutionError>{
+        self.script.call(&self.function, (x,)).and_then(to_float)
+    }
+}
+
+impl DoubleFunctionOperator for ScriptFunction{
+    fn calculate(&self,x:f64,) -> f64 where {
+        self.call(x).unwrap_or(f64::NAN)
+    }
+
+    fn try_calculate(&self,x:f64,) -> RResult<f64,ExecutionError> where {
+        self.call(x).into()
+    }
+}
@@ -0,0 +1,221 @@
+//! Compiled Rhai scripts. Engine and AST are not thread safe, so every thread compiles script once and keeps it.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use padamo_api::calculation_nodes::progress::ProgressToken;
+use padamo_api::prelude::{ConstantContent, Content, ExecutionError};
+use padamo_detectors::rhai::{self, Array, Dynamic, Engine, EvalAltResult, Scope, AST};
+use padamo_detectors::scripted::make_engine;
+
+/// Compiled scripts kept by one thread. Old scripts are dropped when graph is edited many times.
+const MAX_COMPILED:usize = 16;
+
+/// Limit of operations in one call of script function, so script stuck in endless loop fails instead of hanging execution
+pub const MAX_OPERATIONS:u64 = 100_000_000;
+
+thread_local! {
+    static COMPILED: RefCell<HashMap<u64,Rc<(Engine,AST)>>> = RefCell::new(HashMap::new());
+    /// Script called on this thread. Engine takes parameters from it and stops script once its work is cancelled.
+    static CURRENT_CALL: RefCell<Option<CallContext>> = const { RefCell::new(None) };
+}
+
+#[derive(Clone,Debug)]
+struct CallContext{
+    parameters:Arc<Vec<(String,ScriptValue)>>,
+    progress:ProgressToken,
+}
+
+fn script_error<E:std::fmt::Display>(e:E)->ExecutionError{
+    ExecutionError::OtherError(format!("Script error: {}", e).into())
+}
+
+/// Value passed to script as constant. Rhai values are not thread safe, so they are made on every call.
+#[derive(Clone,Debug,PartialEq)]
+pub enum ScriptValue{
+    Float(f64),
+    Integer(i64),
+    Boolean(bool),
+    String(String),
+}
+
+impl ScriptValue{
+    pub fn to_dynamic(&self)->Dynamic{
+        match self {
+            Self::Float(x)=>Dynamic::from_float(*x),
+            Self::Integer(x)=>Dynamic::from_int(*x),
+            Self::Boolean(x)=>Dynamic::from_bool(*x),
+            Self::String(x)=>Dynamic::from(x.clone()),
+        }
+    }
+
+    /// Plain value of port. Other contents cannot be passed to script as constants.
+    pub fn from_content(content:&Content)->Option<Self>{
+        match content {
+            Content::Float(x)=>Some(Self::Float(*x)),
+            Content::Integer(x)=>Some(Self::Integer(*x)),
+            Content::Boolean(x)=>Some(Self::Boolean(*x)),
+            Content::String(x)=>Some(Self::String(x.to_string())),
+            _=>None,
+        }
+    }
+
+    pub fn to_constant(&self)->ConstantContent{
+        match self {
+            Self::Float(x)=>ConstantContent::Float(*x),
+            Self::Integer(x)=>ConstantContent::Integer(*x),
+            Self::Boolean(x)=>ConstantContent::Boolean(*x),
+            Self::String(x)=>ConstantContent::String(x.as_str().into()),
+        }
+    }
+
+    pub fn from_constant(value:&ConstantContent)->Self{
+        match value {
+            ConstantContent::Float(x)=>Self::Float(*x),
+            ConstantContent::Integer(x)=>Self::Integer(*x),
+            ConstantContent::Boolean(x)=>Self::Boolean(*x),
+            ConstantContent::String(x)=>Self::String(x.to_string()),
+        }
+    }
+}
+
+/// Script source with parameters available in script (including its functions) as constants
+#[derive(Clone,Debug)]
+pub struct ScriptSource{
+    code:String,
+    key:u64,
+    context:CallContext,
+}
+
+impl ScriptSource{
+    pub fn new(code:String, parameters:Vec<(String,ScriptValue)>)->Self{
+        let mut hasher = DefaultHasher::new();
+        code.hash(&mut hasher);
+        Self { code, key: hasher.finish(), context:CallContext { parameters:Arc::new(parameters), progress:ProgressToken::new() } }
+    }
+
+    /// Script stops as soon as `progress` is cancelled
+    pub fn with_progress(mut self, progress:&ProgressToken)->Self{
+        self.context.progress = progress.clone();
+        self
+    }
+
+    /// Reads script from `path` or takes `inline` code if path is empty
+    pub fn load(inline:&str, path:&str, parameters:&str)->Result<Self,ExecutionError>{
+        let code = if path.trim().is_empty(){
+            inline.to_string()
+        }
+        else{
+            std::fs::read_to_string(path.trim()).map_err(|e| ExecutionError::OtherError(format!("Cannot read script {}: {}", path, e).into()))?
+        };
+        let parameters = parse_parameters(parameters)?.into_iter().map(|(k,v)| (k, ScriptValue::Float(v))).collect();
+        Ok(Self::new(code, parameters))
+    }
+
+    fn compiled(&self)->Result<Rc<(Engine,AST)>,ExecutionError>{
+        COMPILED.with(|compiled|{
+            if let Some(x) = compiled.borrow().get(&self.key){
+                return Ok(x.clone());
+            }
+            let mut engine = make_engine();
+            engine.set_max_operations(MAX_OPERATIONS);
+            engine.on_progress(|_| {
+                let cancelled = CURRENT_CALL.with(|c| c.borrow().as_ref().is_some_and(|c| c.progress.is_cancelled()));
+                cancelled.then_some(Dynamic::UNIT)
+            });
+            // Scope is not visible inside script functions, so parameters are resolved here. Local variables (index>0) are not shadowed.
+            // Rhai marks variable resolver as volatile API with deprecation warning
+            #[allow(deprecated)]
+            engine.on_var(|name, index, _| {
+                if index>0{
+                    return Ok(None);
+                }
+                Ok(CURRENT_CALL.with(|c| {
+                    c.borrow().as_ref().and_then(|c| c.parameters.iter().find(|x| x.0==name).map(|x| x.1.to_dynamic()))
+                }))
+            });
+            let ast = engine.compile(&self.code).map_err(script_error)?;
+            let res = Rc::new((engine,ast));
+            let mut compiled = compiled.borrow_mut();
+            if compiled.len()>=MAX_COMPILED{
+                compiled.clear();
+            }
+            compiled.insert(self.key, res.clone());
+            Ok(res)
+        })
+    }
+
+    /// Compiles script and checks that it defines `function` with `arity` parameters
+    pub fn check(&self, function:&str, arity:usize)->Result<(),ExecutionError>{
+        let compiled = self.compiled()?;
+        if compiled.1.iter_functions().any(|f| f.name==function && f.params.len()==arity){
+            Ok(())
+        }
+        else{
+            Err(ExecutionError::OtherError(format!("Script must define function {} with {} parameters", function, arity).into()))
+        }
+    }
+
+    pub fn call(&self, function:&str, args:impl rhai::FuncArgs)->Result<Dynamic,ExecutionError>{
+        let compiled = self.compiled()?;
+        let (engine,ast) = &*compiled;
+        let mut scope = Scope::new();
+        let previous = CURRENT_CALL.with(|c| c.replace(Some(self.context.clone())));
+        let res = engine.call_fn::<Dynamic>(&mut scope, ast, function, args);
+        CURRENT_CALL.with(|c| *c.borrow_mut() = previous);
+        res.map_err(|e| match *e {
+            EvalAltResult::ErrorTerminated(..)=>ExecutionError::Cancelled,
+            other=>script_error(other),
+        })
+    }
+}
+
+/// Parses parameters in form `a=1, b=2.5`
+pub fn parse_parameters(src:&str)->Result<Vec<(String,f64)>,ExecutionError>{
+    let mut res = Vec::new();
+    for item in src.split(',').map(str::trim).filter(|x| !x.is_empty()){
+        let (name,value) = item.split_once('=').ok_or_else(|| ExecutionError::OtherError(format!("Parameter \"{}\" must be written as name=value", item).into()))?;
+        let value:f64 = value.trim().parse().map_err(|_| ExecutionError::OtherError(format!("Value of parameter \"{}\" is not a number", name.trim()).into()))?;
+        res.push((name.trim().to_string(), value));
+    }
+    Ok(res)
+}
+
+pub fn to_array(values:&[f64])->Array{
+    values.iter().map(|x| Dynamic::from_float(*x)).collect()
+}
+
+/// Number returned by script. Integers are accepted too.
+pub fn to_float(value:Dynamic)->Result<f64,ExecutionError>{
+    value.as_float().or_else(|_| value.as_int().map(|x| x as f64))
+        .map_err(|t| ExecutionError::OtherError(format!("Script returned {} instead of number", t).into()))
+}
+
+pub fn from_array(values:Dynamic)->Result<Vec<f64>,ExecutionError>{
+    let values:Array = values.into_array()
+        .map_err(|t| ExecutionError::OtherError(format!("Script returned {} instead of array", t).into()))?;
+    values.into_iter().map(to_float).collect()
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_call_with_parameters(){
+        let script = ScriptSource::new("fn f(x) { k*x + 1 }".into(), vec![("k".into(), ScriptValue::Float(2.0))]);
+        script.check("f", 1).unwrap();
+        assert!(script.check("g", 1).is_err());
+        assert_eq!(to_float(script.call("f", (3.0,)).unwrap()).unwrap(), 7.0);
+    }
+
+    #[test]
+    fn test_endless_script_is_cancelled(){
+        let progress = ProgressToken::new();
+        progress.cancel();
+        let script = ScriptSource::new("fn f(x) { loop { x += 1; } }".into(), Vec::new()).with_progress(&progress);
+        assert!(script.call("f", (3.0,)).unwrap_err().is_cancelled());
+    }
+}
@@ -212,7 +212,7 @@ impl SignalMultiplexerNode{
         let mut signal_false = args.inputs.request_detectorfulldata("Negative")?;
         let mut signal_true = args.inputs.request_detectorfulldata("Positive")?;
         let mask = args.inputs.request_detectorsignal("Mask")?;
-        let mask:ArrayND<f64> = mask.request_range(0, mask.length());
+        let mask:ArrayND<f64> = mask.try_request_range(0, mask.length()).into_result()?;
         let mask = ArrayND{shape:mask.shape, flat_data:mask.flat_data.iter().map(|x| * x!= 0.0).collect()};
 
         let rest_mux = args.constants.request_boolean("primary")?;
@@ -1,8 +1,9 @@
 use std::{collections::VecDeque, fmt::Debug, sync::{Arc, Mutex}, thread};
 
-use abi_stable::std_types::RVec;
+use abi_stable::std_types::{RResult, RVec};
 use atomic_float::AtomicF64;
 use padamo_api::lazy_array_operations::{ndim_array::ArrayND, LazyArrayOperation, LazyArrayOperationBox, LazyDetectorSignal, LazyTimeSignal};
+use padamo_api::prelude::ExecutionError;
 use rayon::iter::ParallelBridge;
 use rayon::iter::ParallelIterator;
 
@@ -60,18 +61,8 @@ impl LazySpaceConverter {
 //     }
 // }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazySpaceConverter{
-
-    fn length(&self,) -> usize {
-        let src_len = self.source.length();
-        src_len/self.divider
-    }
-
-    fn calculate_overhead(&self,start:usize,end:usize,)->usize{
-        self.source.calculate_overhead(start*self.divider, end*self.divider)
-    }
-
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64>{
+impl LazySpaceConverter{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
         // let start_src = start*self.divider;
         // let end_src = end*self.divider;
         let divider = self.divider;
@@ -84,7 +75,7 @@ impl LazyArrayOperation<ArrayND<f64>> for LazySpaceConverter{
         let mut threads:VecDeque<thread::JoinHandle<()>> = VecDeque::with_capacity(10);
         let frame_size = self.frame_shape.iter().fold(1usize,|a,b| a*b);
         for frame in 0..end-start{
-            let src_part = Arc::new(self.source.request_range((start+frame)*self.divider,(start+frame+1)*self.divider));
+            let src_part = Arc::new(self.source.try_request_range((start+frame)*self.divider,(start+frame+1)*self.divider).into_result()?);
             for pixel in 0..frame_size{
                 // let length = end-start;
                 let part_length = divider;
@@ -106,7 +97,7 @@ impl LazyArrayOperation<ArrayND<f64>> for LazySpaceConverter{
         }
         free_threads(&mut threads, 1);
         let lock = Arc::try_unwrap(target).unwrap();
-        lock.into_inner().unwrap()
+        Ok(lock.into_inner().unwrap())
 
         // let frame_size = raw_data.shape.iter().skip(1).fold(1usize,|a,b| a*b);
         // // let stepped_data:Vec<_> = (0..end-start).par_bridge()
@@ -158,6 +149,26 @@ impl LazyArrayOperation<ArrayND<f64>> for LazySpaceConverter{
     }
 }
 
+impl LazyArrayOperation<ArrayND<f64>> for LazySpaceConverter{
+
+    fn length(&self,) -> usize {
+        let src_len = self.source.length();
+        src_len/self.divider
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,)->usize{
+        self.source.calculate_overhead(start*self.divider, end*self.divider)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64>{
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
+    }
+}
+
 #[derive(Clone,Debug)]
 pub struct LazyTimeConverter{
     divider:usize,
@@ -169,6 +180,14 @@ impl LazyTimeConverter {
 }
 
 
+impl LazyTimeConverter{
+    fn try_request(&self,start:usize,end:usize) -> Result<RVec<f64>,ExecutionError>{
+        let unrarified: RVec<f64> = self.source.try_request_range(start*self.divider, end*self.divider).into_result()?;
+        let rarified:Vec<_> = unrarified.into_iter().skip(self.divider/2).step_by(self.divider).collect();
+        Ok(rarified.into())
+    }
+}
+
 impl LazyArrayOperation<RVec<f64>> for LazyTimeConverter{
     fn length(&self,) -> usize {
         let src_len = self.source.length();
@@ -176,9 +195,11 @@ impl LazyArrayOperation<RVec<f64>> for LazyTimeConverter{
     }
 
     fn request_range(&self,start:usize,end:usize,) -> RVec<f64>{
-        let unrarified: RVec<f64> = self.source.request_range(start*self.divider, end*self.divider);
-        let rarified:Vec<_> = unrarified.into_iter().skip(self.divider/2).step_by(self.divider).collect();
-        rarified.into()
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<RVec<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
 
@@ -205,6 +226,10 @@ impl<T:Clone+Debug> LazyArrayOperation<T> for CutterOperator<T>{
     fn request_range(&self,start:usize,end:usize,) -> T where {
         self.source.request_range(start+self.start,end+self.start)
     }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<T,ExecutionError>{
+        self.source.try_request_range(start+self.start,end+self.start)
+    }
 }
 
 
@@ -231,6 +256,10 @@ impl LazyArrayOperation<RVec<f64>> for TimeShift{
         self.source.request_range(start,end).iter().map(|x| x+self.offset).collect()
     }
 
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<RVec<f64>,ExecutionError>{
+        self.source.try_request_range(start,end).map(|x| x.iter().map(|x| x+self.offset).collect())
+    }
+
     #[allow(clippy::let_and_return)]
     fn calculate_overhead(&self,start:usize,end:usize,) -> usize {
         self.source.calculate_overhead(start,end)
@@ -250,18 +279,10 @@ impl SignalMux {
     }
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for SignalMux{
-    fn length(&self,) -> usize where {
-        self.source_false.length()
-    }
-
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize {
-        self.source_false.calculate_overhead(start,end)+self.source_true.calculate_overhead(start,end)
-    }
-
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> {
-        let signal_false = self.source_false.request_range(start,end);
-        let signal_true = self.source_true.request_range(start,end);
+impl SignalMux{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let signal_false = self.source_false.try_request_range(start,end).into_result()?;
+        let signal_true = self.source_true.try_request_range(start,end).into_result()?;
 
         let tgt_flat_len:usize = signal_false.shape.iter().map(|x|*x).product();
         let mut tgt_flat:Vec<AtomicF64> = Vec::with_capacity(tgt_flat_len);
@@ -287,6 +308,24 @@ impl LazyArrayOperation<ArrayND<f64>> for SignalMux{
         // tgt.into_inner().unwrap()
         let tgt = ArrayND {shape: signal_false.shape.clone().into(), flat_data:tgt_flat.drain(..).map(|x| x.into_inner()).collect()};
         tgt.assert_shape();
-        tgt
+        Ok(tgt)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for SignalMux{
+    fn length(&self,) -> usize where {
+        self.source_false.length()
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize {
+        self.source_false.calculate_overhead(start,end)+self.source_true.calculate_overhead(start,end)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
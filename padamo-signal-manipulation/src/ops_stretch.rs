@@ -1,6 +1,8 @@
 use std::{collections::VecDeque, fmt::Debug, sync::{Arc, Mutex}, thread};
 
+use abi_stable::std_types::RResult;
 use padamo_api::{lazy_array_operations::{ArrayND, LazyArrayOperation, LazyDetectorSignal, LazyTimeSignal, LazyTrigger}, trigger_operations::SparseTagArray};
+use padamo_api::prelude::ExecutionError;
 use super::ops::free_threads;
 
 
@@ -92,21 +94,11 @@ impl SyncedSignalStretcher {
     }
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for SyncedSignalStretcher{
-    fn length(&self)->usize {
-        self.target_time.length()
-    }
-
-    fn calculate_overhead(&self,start:usize, end:usize)->usize {
-        //let start_time = self.target_time.request_range();
+impl SyncedSignalStretcher{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
         let (src_start,src_end) = get_bounds(&self.source_time, &self.target_time, start, end);
-        self.source.calculate_overhead(src_start,src_end)
-    }
-
-    fn request_range(&self,start:usize, end:usize)->ArrayND<f64> {
-        let (src_start,src_end) = get_bounds(&self.source_time, &self.target_time, start, end);
-        let src_spatial = self.source.request_range(src_start,src_end);
-        let src_temporal = self.source_time.request_range(src_start,src_end);
+        let src_spatial = self.source.try_request_range(src_start,src_end).into_result()?;
+        let src_temporal = self.source_time.try_request_range(src_start,src_end).into_result()?;
 
         let mut target_shape:Vec<usize> = src_spatial.shape.clone().into();
         target_shape[0] = end-start;
@@ -119,7 +111,7 @@ impl LazyArrayOperation<ArrayND<f64>> for SyncedSignalStretcher{
         let target = Arc::new(Mutex::new(ArrayND::new(target_shape.clone(), 0.0)));
         let spatial_source = Arc::new(src_spatial);
         let temporal_source = Arc::new(src_temporal);
-        let temporal_target = Arc::new(self.target_time.request_range(start, end));
+        let temporal_target = Arc::new(self.target_time.try_request_range(start, end).into_result()?);
 
         for pixel in 0usize..frame_size{
             free_threads(&mut threads, threadcount);
@@ -146,7 +138,27 @@ impl LazyArrayOperation<ArrayND<f64>> for SyncedSignalStretcher{
 
         free_threads(&mut threads, 1);
         let lock = Arc::try_unwrap(target).unwrap();
-        lock.into_inner().unwrap()
+        Ok(lock.into_inner().unwrap())
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for SyncedSignalStretcher{
+    fn length(&self)->usize {
+        self.target_time.length()
+    }
+
+    fn calculate_overhead(&self,start:usize, end:usize)->usize {
+        //let start_time = self.target_time.request_range();
+        let (src_start,src_end) = get_bounds(&self.source_time, &self.target_time, start, end);
+        self.source.calculate_overhead(src_start,src_end)
+    }
+
+    fn request_range(&self,start:usize, end:usize)->ArrayND<f64> {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
 
@@ -164,26 +176,16 @@ impl SyncedTriggerStretcher {
     }
 }
 
-impl LazyArrayOperation<SparseTagArray> for SyncedTriggerStretcher{
-    fn length(&self)->usize {
-        self.target_time.length()
-    }
-
-    fn calculate_overhead(&self,start:usize, end:usize)->usize {
-        //let start_time = self.target_time.request_range();
+impl SyncedTriggerStretcher{
+    fn try_request(&self,start:usize,end:usize) -> Result<SparseTagArray,ExecutionError>{
         let (src_start,src_end) = get_bounds(&self.source_time, &self.target_time, start, end);
-        self.source.calculate_overhead(src_start,src_end)
-    }
-
-    fn request_range(&self,start:usize, end:usize)->SparseTagArray {
-        let (src_start,src_end) = get_bounds(&self.source_time, &self.target_time, start, end);
-        let mut events = self.source.request_range(src_start,src_end);
+        let mut events = self.source.try_request_range(src_start,src_end).into_result()?;
 
         for event in events.tags.iter_mut(){
-            let start_time = self.source_time.request_range(event.position,event.position+1)[0];
-            let new_position = self.target_time.find_unixtime(start_time);
-            let end_time = self.source_time.request_range(event.position+event.duration-1,event.position+event.duration)[0];
-            let mut new_end = self.target_time.find_unixtime(end_time)+1;
+            let start_time = self.source_time.try_request_range(event.position,event.position+1).into_result()?[0];
+            let new_position = self.target_time.try_find_unixtime(start_time)?;
+            let end_time = self.source_time.try_request_range(event.position+event.duration-1,event.position+event.duration).into_result()?[0];
+            let mut new_end = self.target_time.try_find_unixtime(end_time)?+1;
             if new_end<=new_position{
                 new_end = new_position+1;
                 event.tag = format!("(W) {}",event.tag).into();
@@ -192,7 +194,7 @@ impl LazyArrayOperation<SparseTagArray> for SyncedTriggerStretcher{
             event.position = new_position;
             event.duration = new_duration;
         }
-        events
+        Ok(events)
 
         // let src_temporal = self.source_time.request_range(src_start,src_end);
 
@@ -246,3 +248,23 @@ impl LazyArrayOperation<SparseTagArray> for SyncedTriggerStretcher{
         // lock.into_inner().unwrap()
     }
 }
+
+impl LazyArrayOperation<SparseTagArray> for SyncedTriggerStretcher{
+    fn length(&self)->usize {
+        self.target_time.length()
+    }
+
+    fn calculate_overhead(&self,start:usize, end:usize)->usize {
+        //let start_time = self.target_time.request_range();
+        let (src_start,src_end) = get_bounds(&self.source_time, &self.target_time, start, end);
+        self.source.calculate_overhead(src_start,src_end)
+    }
+
+    fn request_range(&self,start:usize, end:usize)->SparseTagArray {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<SparseTagArray,ExecutionError>{
+        self.try_request(start,end).into()
+    }
+}
@@ -1,7 +1,9 @@
 use std::{collections::VecDeque, fmt::Debug, sync::Arc, thread};
 
 use atomic_float::AtomicF64;
+use abi_stable::std_types::RResult;
 use padamo_api::lazy_array_operations::{ndim_array::ArrayND, LazyArrayOperation, LazyDetectorSignal};
+use padamo_api::prelude::ExecutionError;
 use super::ops::free_threads;
 
 #[derive(Clone,Debug)]
@@ -28,22 +30,12 @@ impl LazySpaceConverterPerformant {
 }
 
 
-impl LazyArrayOperation<ArrayND<f64>> for LazySpaceConverterPerformant{
-
-    fn length(&self,) -> usize {
-        let src_len = self.source.length();
-        src_len/self.divider
-    }
-
-    fn calculate_overhead(&self,start:usize,end:usize,)->usize{
-        self.source.calculate_overhead(start*self.divider, end*self.divider)
-    }
-
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64>{
+impl LazySpaceConverterPerformant{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
         let start_src = start*self.divider;
         let end_src = end*self.divider;
         let divider = self.divider;
-        let raw_data:ArrayND<f64> = self.source.request_range(start_src,end_src);
+        let raw_data:ArrayND<f64> = self.source.try_request_range(start_src,end_src).into_result()?;
         let is_sum = self.is_sum;
         let frame_size = raw_data.shape.iter().skip(1).fold(1usize,|a,b| a*b);
         // let stepped_data:Vec<_> = (0..end-start).par_bridge()
@@ -96,9 +88,29 @@ impl LazyArrayOperation<ArrayND<f64>> for LazySpaceConverterPerformant{
 
         let result = ArrayND {shape:tgt_shape.into(), flat_data: target_flat.drain(..).map(|x| x.into_inner()).collect()};
         result.assert_shape();
-        result
+        Ok(result)
 
         // let lock = Arc::try_unwrap(target).unwrap();
         // lock.into_inner().unwrap()
     }
 }
+
+impl LazyArrayOperation<ArrayND<f64>> for LazySpaceConverterPerformant{
+
+    fn length(&self,) -> usize {
+        let src_len = self.source.length();
+        src_len/self.divider
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,)->usize{
+        self.source.calculate_overhead(start*self.divider, end*self.divider)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64>{
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
+    }
+}
@@ -11,3 +11,12 @@ pub fn get_state_dir()->std::path::PathBuf{
 }
 
 
+
+/// Gets directory for on-disk cache of lazy operations
+pub fn get_disk_cache_dir()->std::path::PathBuf{
+    let cache = get_state_dir().join("disk_cache");
+    if !cache.is_dir(){
+        let _ = std::fs::create_dir(&cache);
+    }
+    cache
+}
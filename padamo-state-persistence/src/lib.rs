@@ -2,6 +2,10 @@ use std::path::PathBuf;
 
 pub mod data;
 
+/// Key of disk cache size limit in megabytes
+pub const DISK_CACHE_LIMIT_KEY:&str = "disk_cache_limit_mb";
+pub const DEFAULT_DISK_CACHE_LIMIT_MB:u64 = 4096;
+
 #[derive(Clone,Debug)]
 pub struct PersistentState{
     pub state_dir:PathBuf
@@ -61,6 +65,12 @@ impl PersistentState{
         None
     }
 
+    /// Size limit of disk cache in bytes
+    pub fn disk_cache_limit(&self)->u64{
+        let mb = self.read(DISK_CACHE_LIMIT_KEY).and_then(|x| x.trim().parse().ok()).unwrap_or(DEFAULT_DISK_CACHE_LIMIT_MB);
+        mb*1024*1024
+    }
+
     pub fn clear(&self){
         if self.state_dir.is_dir(){
             std::fs::remove_dir_all(&self.state_dir).unwrap();
@@ -1,7 +1,9 @@
+use abi_stable::std_types::RResult;
 use padamo_api::lazy_array_operations::cache::Cache;
 use padamo_api::lazy_array_operations::ArrayND;
 use padamo_api::lazy_array_operations::LazyArrayOperation;
 use padamo_api::lazy_array_operations::LazyDetectorSignal;
+use padamo_api::prelude::ExecutionError;
 use padamo_api::function_operator::DoubleFunctionOperatorBox;
 use crate::stft::STFTConverter;
 
@@ -22,19 +24,8 @@ impl FilterOp {
 
 
 
-impl LazyArrayOperation<ArrayND<f64>> for FilterOp{
-    fn length(&self,) -> usize{
-        let src_len = self.source.length();
-        let step = self.stft.window/2;
-        let last_window = (src_len-self.stft.window)/step;
-        last_window*step+self.stft.window
-    }
-
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        self.source.calculate_overhead(start,end)*self.stft.window
-    }
-
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> {
+impl FilterOp{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
         // let left_offset = self.stft.window;
         let length = self.length();
         // let right_offset = self.stft.window;
@@ -55,10 +46,31 @@ impl LazyArrayOperation<ArrayND<f64>> for FilterOp{
         let left_skip = start-actual_start;
         let right_skip = actual_end - end;
 
-        let signal:ArrayND<f64> = self.source.request_range(actual_start,actual_end);
+        let signal:ArrayND<f64> = self.source.try_request_range(actual_start,actual_end).into_result()?;
         let mut signal = self.stft.filter_arrays(signal, self.modifier.clone(), self.sample_rate);
         signal = signal.cut_front(left_skip);
         signal = signal.cut_end(right_skip);
-        signal
+        Ok(signal)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for FilterOp{
+    fn length(&self,) -> usize{
+        let src_len = self.source.length();
+        let step = self.stft.window/2;
+        let last_window = (src_len-self.stft.window)/step;
+        last_window*step+self.stft.window
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.source.calculate_overhead(start,end)*self.stft.window
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
@@ -1,6 +1,7 @@
-use std::{collections::VecDeque, f64::consts::PI, fmt::Debug, sync::{Arc, Mutex}, thread};
+use std::{f64::consts::PI, fmt::Debug, sync::Arc};
 
 use padamo_api::lazy_array_operations::ArrayND;
+use padamo_api::lazy_array_operations::parallel::par_map_pixels;
 use rustfft::Fft;
 use num::Complex;
 
@@ -27,27 +28,6 @@ pub fn frequencies(n:usize, sampling_rate:f64)->Vec<f64>{
 }
 
 
-fn free_threads(threads: &mut VecDeque<thread::JoinHandle<()>>, threadcount:usize){
-    while threads.len()>=threadcount{
-        //println!("Working threads: {}",threads.len());
-        if let Some(handle)= threads.pop_front() {
-            if handle.is_finished(){
-                //println!("Freeing one handle");
-                if let Err(e) = handle.join(){
-                    println!("{:?}",e);
-                }
-                //println!("Freed one handle");
-            }
-            else{
-                threads.push_back(handle)
-            }
-        }
-        else{
-            break;
-        }
-    }
-}
-
 #[derive(Clone,Copy)]
 pub struct STFTConverter{
     pub window: usize,
@@ -123,48 +103,10 @@ impl STFTConverter{
     }
 
     pub fn filter_arrays(self, signal:ArrayND<f64>, filter: padamo_api::function_operator::DoubleFunctionOperatorBox, sampling_rate:f64)->ArrayND<f64>{
-        let shape = signal.shape.clone().to_vec();
-        if shape[0]<self.window{
+        if signal.shape[0]<self.window{
             panic!("Cannot use stft with array smaller than window");
         }
-        let frame_size:usize = shape.iter().skip(1).fold(1, |a,b| a*b);
-        let target_shape = shape.clone();
-        let target_length = target_shape[0];
-        let target = Arc::new(Mutex::new(ArrayND::<f64>::new(target_shape.into(),-666.0)));
-        let source = Arc::new(signal);
-        let filter = Arc::new(filter);
-
-        let threadcount = num_cpus::get();
-        let mut threads:VecDeque<thread::JoinHandle<()>> = VecDeque::with_capacity(10);
-
-        for pixel in 0..frame_size{
-            free_threads(&mut threads, threadcount);
-            let tgt = target.clone();
-            let src = source.clone();
-            let filter_cloned = filter.clone();
-            let offset = pixel;
-            let handle = thread::spawn(move || {
-                let mut signal_in_pixel = Vec::with_capacity(target_length);
-                for i in 0..target_length{
-                    let index = i*frame_size+offset;
-                    let value = src.flat_data[index];
-                    signal_in_pixel.push(value);
-                }
-                let signal_data = self.filter(&signal_in_pixel, &filter_cloned, sampling_rate);
-                for i in 0..target_length{
-                    //tgt.lock().unwrap().flat_data[i*frame_size+offset] = roller.median();
-                    tgt.lock().unwrap().flat_data[i*frame_size+offset] = signal_data[i];
-                }
-
-            });
-            threads.push_back(handle);
-
-
-        }
-
-        free_threads(&mut threads, 1);
-        let lock = Arc::try_unwrap(target).unwrap();
-        lock.into_inner().unwrap()
+        par_map_pixels(&signal, |signal_in_pixel| self.filter(signal_in_pixel, &filter, sampling_rate))
     }
 }
 
@@ -1,10 +1,11 @@
 use std::hash::DefaultHasher;
 use std::hash::Hasher;
 
-use abi_stable::std_types::RVec;
+use abi_stable::std_types::{RResult, RVec};
 use padamo_api::lazy_array_operations::ArrayND;
 use padamo_api::lazy_array_operations::LazyArrayOperation;
 use padamo_api::lazy_array_operations::LazyDetectorSignal;
+use padamo_api::prelude::ExecutionError;
 use statrs::distribution::ContinuousCDF;
 
 use statrs::distribution::Normal;
@@ -78,17 +79,9 @@ impl LazyAdditiveNormalNoise {
     pub fn new(source: LazyDetectorSignal, seed: i64, sigma: f64) -> Self { Self { source, seed, sigma } }
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazyAdditiveNormalNoise{
-    fn length(&self,) -> usize where {
-        self.source.length()
-    }
-
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        self.source.calculate_overhead(start,end)
-    }
-
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
-        let mut background = self.source.request_range(start, end);
+impl LazyAdditiveNormalNoise{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let mut background = self.source.try_request_range(start, end).into_result()?;
         let norm = u64::MAX as f64;
         let dist = Normal::new(0.0,self.sigma).unwrap();
         for item in background.enumerate(){
@@ -113,6 +106,24 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyAdditiveNormalNoise{
 
             background[&item] += normalized;
         }
-        background
+        Ok(background)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyAdditiveNormalNoise{
+    fn length(&self,) -> usize where {
+        self.source.length()
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.source.calculate_overhead(start,end)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
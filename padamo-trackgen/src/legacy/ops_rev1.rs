@@ -2,10 +2,11 @@ use std::f64::consts::PI;
 use std::hash::DefaultHasher;
 use std::hash::Hasher;
 
-use abi_stable::std_types::RVec;
+use abi_stable::std_types::{RResult, RVec};
 use padamo_api::lazy_array_operations::ArrayND;
 use padamo_api::lazy_array_operations::LazyArrayOperation;
 use padamo_api::lazy_array_operations::LazyDetectorSignal;
+use padamo_api::prelude::ExecutionError;
 use statrs::distribution::ContinuousCDF;
 use crate::ensquared_energy;
 use crate::ensquared_energy::detector::DetectorWireframe;
@@ -39,29 +40,14 @@ pub struct LazyAnyLCGaussTrack{
 
 impl LazyAnyLCGaussTrack{
 
-    fn calculate_lc(&self, absolute_time:f64)->f64{
-        self.lc.calculate(absolute_time-self.pivot_frame)
+    fn calculate_lc(&self, absolute_time:f64)->Result<f64,ExecutionError>{
+        self.lc.try_calculate(absolute_time-self.pivot_frame).into_result()
     }
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCGaussTrack{
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        // match &self.data{
-        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
-        //     TrackData::Artificial { length: _ }=>{end-start},
-        // }
-        self.data.calculate_overhead(start,end)
-
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn length(&self,) -> usize where {
-        self.data.length()
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
-        let bg = self.data.request_range(start,end);
+impl LazyAnyLCGaussTrack{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let bg = self.data.try_request_range(start,end).into_result()?;
 
         let mut data = bg;
 
@@ -82,7 +68,7 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCGaussTrack{
                 };
 
                 let t = t_usize as f64 + offset;
-                let lc = self.calculate_lc(t)/divider;
+                let lc = self.calculate_lc(t)?/divider;
                 let dt = t-self.pivot_frame;
                 let displacement = self.v0*dt+self.a0*dt*dt/2.0;
                 let x = self.x0+displacement*(self.phi0*PI/180.0).cos();
@@ -97,9 +83,33 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCGaussTrack{
 
         }
         //println!("{:?}",&data.flat_data);
-        data
+        Ok(data)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCGaussTrack{
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        // match &self.data{
+        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
+        //     TrackData::Artificial { length: _ }=>{end-start},
+        // }
+        self.data.calculate_overhead(start,end)
 
     }
+
+    #[allow(clippy::let_and_return)]
+    fn length(&self,) -> usize where {
+        self.data.length()
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
+    }
 }
 
 
@@ -128,29 +138,14 @@ pub struct LazyAnyLCMoffatTrack{
 
 impl LazyAnyLCMoffatTrack{
 
-    fn calculate_lc(&self, absolute_time:f64)->f64{
-        self.lc.calculate(absolute_time-self.pivot_frame)
+    fn calculate_lc(&self, absolute_time:f64)->Result<f64,ExecutionError>{
+        self.lc.try_calculate(absolute_time-self.pivot_frame).into_result()
     }
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCMoffatTrack{
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        // match &self.data{
-        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
-        //     TrackData::Artificial { length: _ }=>{end-start},
-        // }
-        self.data.calculate_overhead(start,end)
-
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn length(&self,) -> usize where {
-        self.data.length()
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
-        let bg = self.data.request_range(start,end);
+impl LazyAnyLCMoffatTrack{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let bg = self.data.try_request_range(start,end).into_result()?;
 
         let mut data = bg;
 
@@ -172,7 +167,7 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCMoffatTrack{
                 };
 
                 let t = t_usize as f64 + offset;
-                let lc = self.calculate_lc(t)*norm/divider;
+                let lc = self.calculate_lc(t)?*norm/divider;
                 let dt = t-self.pivot_frame;
                 let displacement = self.v0*dt+self.a0*dt*dt/2.0;
                 let x = self.x0+displacement*(self.phi0*PI/180.0).cos();
@@ -187,9 +182,33 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCMoffatTrack{
 
         }
         //println!("{:?}",&data.flat_data);
-        data
+        Ok(data)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCMoffatTrack{
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        // match &self.data{
+        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
+        //     TrackData::Artificial { length: _ }=>{end-start},
+        // }
+        self.data.calculate_overhead(start,end)
 
     }
+
+    #[allow(clippy::let_and_return)]
+    fn length(&self,) -> usize where {
+        self.data.length()
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
+    }
 }
 
 
@@ -268,27 +287,8 @@ impl LazyTriangularE0Track{
     }
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazyTriangularE0Track{
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        // match &self.data{
-        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
-        //     TrackData::Artificial { length: _ }=>{end-start},
-        // }
-        self.data.calculate_overhead(start,end)
-
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn length(&self,) -> usize where {
-        self.data.length()
-        // match &self.data{
-        //     TrackData::Background(src)=>{src.length()},
-        //     TrackData::Artificial { length }=>{*length},
-        // }
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+impl LazyTriangularE0Track{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
         // let bg = self.data.request_range(start,end); /*match &self.data {
         //     TrackData::Background(src) => src.request_range(start,end),
         //     TrackData::Artificial { length:_} => {
@@ -298,14 +298,14 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyTriangularE0Track{
         //         ArrayND::new(tgt_shape, 0.0)
         //     },
         // };*/
-        let bg = self.data.request_range(start,end);
+        let bg = self.data.try_request_range(start,end).into_result()?;
 
 
         let effect_start = self.start_effect_time();
         let effect_end = self.end_effect_time();
 
         if (start as f64)>effect_end || (end as f64)<effect_start{
-            bg
+            Ok(bg)
         }
         else{
             let mut data = bg;
@@ -342,12 +342,41 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyTriangularE0Track{
 
             }
             //println!("{:?}",&data.flat_data);
-            data
+            Ok(data)
         }
 
     }
 }
 
+impl LazyArrayOperation<ArrayND<f64>> for LazyTriangularE0Track{
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        // match &self.data{
+        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
+        //     TrackData::Artificial { length: _ }=>{end-start},
+        // }
+        self.data.calculate_overhead(start,end)
+
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn length(&self,) -> usize where {
+        self.data.length()
+        // match &self.data{
+        //     TrackData::Background(src)=>{src.length()},
+        //     TrackData::Artificial { length }=>{*length},
+        // }
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
+    }
+}
+
 
 
 
@@ -418,17 +447,9 @@ impl LazyAdditiveNormalNoise {
     pub fn new(source: LazyDetectorSignal, seed: i64, sigma: f64) -> Self { Self { source, seed, sigma } }
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazyAdditiveNormalNoise{
-    fn length(&self,) -> usize where {
-        self.source.length()
-    }
-
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        self.source.calculate_overhead(start,end)
-    }
-
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
-        let mut background = self.source.request_range(start, end);
+impl LazyAdditiveNormalNoise{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let mut background = self.source.try_request_range(start, end).into_result()?;
         let norm = u64::MAX as f64;
         let dist = Normal::new(0.0,self.sigma).unwrap();
         for item in background.enumerate(){
@@ -453,6 +474,24 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyAdditiveNormalNoise{
 
             background[&item] += normalized;
         }
-        background
+        Ok(background)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyAdditiveNormalNoise{
+    fn length(&self,) -> usize where {
+        self.source.length()
+    }
+
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        self.source.calculate_overhead(start,end)
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
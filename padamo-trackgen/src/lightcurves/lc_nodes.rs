@@ -26,6 +26,15 @@ impl DoubleFunctionOperator for LCSwitch{
             self.right.calculate(x)
         }
     }
+
+    fn try_calculate(&self,x:f64,) -> RResult<f64,ExecutionError> where {
+        if x<self.pivot{
+            self.left.try_calculate(x)
+        }
+        else{
+            self.right.try_calculate(x)
+        }
+    }
 }
 
 #[derive(Clone,Debug)]
@@ -118,8 +127,8 @@ impl LCPivotNode{
 
         let pivot = args.constants.request_float("pivot")?;
 
-        let k_left = f_left.calculate(pivot);
-        let k_right = f_right.calculate(pivot);
+        let k_left = f_left.try_calculate(pivot).into_result()?;
+        let k_right = f_right.try_calculate(pivot).into_result()?;
 
         let combined = make_function_box(
             match (k_left==0.0, k_right==0.0) {
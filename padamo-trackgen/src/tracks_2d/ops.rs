@@ -1,8 +1,10 @@
 use std::f64::consts::PI;
+use abi_stable::std_types::RResult;
 
 use padamo_api::lazy_array_operations::ArrayND;
 use padamo_api::lazy_array_operations::LazyArrayOperation;
 use padamo_api::lazy_array_operations::LazyDetectorSignal;
+use padamo_api::prelude::ExecutionError;
 use crate::ensquared_energy;
 use crate::ensquared_energy::detector::DetectorWireframe;
 
@@ -32,29 +34,14 @@ pub struct LazyAnyLCGaussTrack{
 
 impl LazyAnyLCGaussTrack{
 
-    fn calculate_lc(&self, absolute_time:f64)->f64{
-        self.lc.calculate(absolute_time-self.pivot_frame)
+    fn calculate_lc(&self, absolute_time:f64)->Result<f64,ExecutionError>{
+        self.lc.try_calculate(absolute_time-self.pivot_frame).into_result()
     }
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCGaussTrack{
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        // match &self.data{
-        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
-        //     TrackData::Artificial { length: _ }=>{end-start},
-        // }
-        self.data.calculate_overhead(start,end)
-
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn length(&self,) -> usize where {
-        self.data.length()
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
-        let bg = self.data.request_range(start,end);
+impl LazyAnyLCGaussTrack{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let bg = self.data.try_request_range(start,end).into_result()?;
 
         let mut data = bg;
 
@@ -75,7 +62,7 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCGaussTrack{
                 };
 
                 let t = t_usize as f64 + offset;
-                let lc = self.calculate_lc(t)/divider;
+                let lc = self.calculate_lc(t)?/divider;
                 let dt = t-self.pivot_frame;
                 let displacement = self.v0*dt+self.a0*dt*dt/2.0;
                 let x = self.x0+displacement*(self.phi0*PI/180.0).cos();
@@ -90,8 +77,32 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCGaussTrack{
 
         }
         //println!("{:?}",&data.flat_data);
-        data
+        Ok(data)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCGaussTrack{
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        // match &self.data{
+        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
+        //     TrackData::Artificial { length: _ }=>{end-start},
+        // }
+        self.data.calculate_overhead(start,end)
+
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn length(&self,) -> usize where {
+        self.data.length()
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+        self.try_request(start,end).unwrap()
+    }
 
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
 
@@ -121,29 +132,14 @@ pub struct LazyAnyLCMoffatTrack{
 
 impl LazyAnyLCMoffatTrack{
 
-    fn calculate_lc(&self, absolute_time:f64)->f64{
-        self.lc.calculate(absolute_time-self.pivot_frame)
+    fn calculate_lc(&self, absolute_time:f64)->Result<f64,ExecutionError>{
+        self.lc.try_calculate(absolute_time-self.pivot_frame).into_result()
     }
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCMoffatTrack{
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        // match &self.data{
-        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
-        //     TrackData::Artificial { length: _ }=>{end-start},
-        // }
-        self.data.calculate_overhead(start,end)
-
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn length(&self,) -> usize where {
-        self.data.length()
-    }
-
-    #[allow(clippy::let_and_return)]
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
-        let bg = self.data.request_range(start,end);
+impl LazyAnyLCMoffatTrack{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let bg = self.data.try_request_range(start,end).into_result()?;
 
         let mut data = bg;
 
@@ -165,7 +161,7 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCMoffatTrack{
                 };
 
                 let t = t_usize as f64 + offset;
-                let lc = self.calculate_lc(t)*norm/divider;
+                let lc = self.calculate_lc(t)?*norm/divider;
                 let dt = t-self.pivot_frame;
                 let displacement = self.v0*dt+self.a0*dt*dt/2.0;
                 let x = self.x0+displacement*(self.phi0*PI/180.0).cos();
@@ -180,7 +176,31 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCMoffatTrack{
 
         }
         //println!("{:?}",&data.flat_data);
-        data
+        Ok(data)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyAnyLCMoffatTrack{
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        // match &self.data{
+        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
+        //     TrackData::Artificial { length: _ }=>{end-start},
+        // }
+        self.data.calculate_overhead(start,end)
+
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn length(&self,) -> usize where {
+        self.data.length()
+    }
+
+    #[allow(clippy::let_and_return)]
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64> where {
+        self.try_request(start,end).unwrap()
+    }
 
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
@@ -28,7 +28,7 @@ impl MeteorTrackNode{
         if data.0.length()==0{
             return Err(ExecutionError::OtherError("No background data".into()));
         }
-        let probe = data.0.request_range(0,1).take_frame().ok_or(ExecutionError::OtherError("Cannot take test frame".into()))?;
+        let probe = data.0.try_request_range(0,1).into_result()?.take_frame().ok_or(ExecutionError::OtherError("Cannot take test frame".into()))?;
 
         if !probe.form_compatible(&detector.shape){
             return Err(ExecutionError::OtherError(format!("Background shape {:?} is not compatible with detector {}", probe.shape, detector_name).into()));
@@ -45,10 +45,7 @@ impl MeteorTrackNode{
 
         let v0 = args.constants.request_float("v0")?;
 
-        let mv = args.inputs.request_detectorsignal("MV Matrix")?;
-        let mv = mv.request_range(0,mv.length());
-
-        let mv:nalgebra::Matrix4<f64> = mv.try_into().map_err(|_| ExecutionError::OtherError("Model-view matrix must be 4x4".into()))?;
+        let mv:nalgebra::Matrix4<f64> = args.inputs.request_matrix("MV Matrix")?.into();
 
         data.0 = make_lao_box(super::ops::LazyMeteorTrack{
             motion_blur_steps,
@@ -88,7 +85,7 @@ impl CalculationNode for MeteorTrackNode{
             ("Background", ContentType::DetectorFullData),
             ("Lightcurve", ContentType::Function),
             ("PSF", ContentType::Function),
-            ("MV Matrix", ContentType::DetectorSignal),
+            ("MV Matrix", ContentType::Matrix),
         ]
     }
 
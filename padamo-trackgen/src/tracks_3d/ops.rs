@@ -1,7 +1,10 @@
+use std::cell::RefCell;
+use abi_stable::std_types::RResult;
 use nalgebra::Vector4;
 use padamo_api::lazy_array_operations::ArrayND;
 use padamo_api::lazy_array_operations::LazyArrayOperation;
 use padamo_api::lazy_array_operations::LazyDetectorSignal;
+use padamo_api::prelude::ExecutionError;
 use padamo_detectors::loaded_detectors_storage::ProvidedDetectorInfo;
 use crate::ensquared_energy;
 use crate::ensquared_energy::detector::DetectorWireframe;
@@ -54,30 +57,18 @@ impl LazyMeteorTrack{
         }
     }
 
-    fn calculate_lc(&self, absolute_time:f64)->f64{
-        let mut res = self.lc.calculate(absolute_time-self.pivot_frame);
+    fn calculate_lc(&self, absolute_time:f64)->Result<f64,ExecutionError>{
+        let mut res = self.lc.try_calculate(absolute_time-self.pivot_frame).into_result()?;
         if self.modify_intensity{
             res = res*self.intensity_mod(absolute_time);
         }
-        res
+        Ok(res)
     }
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazyMeteorTrack{
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        // match &self.data{
-        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
-        //     TrackData::Artificial { length: _ }=>{end-start},
-        // }
-        self.data.calculate_overhead(start,end)
-    }
-
-    fn length(&self,) -> usize{
-        self.data.length()
-    }
-
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64>where {
-        let bg = self.data.request_range(start,end);
+impl LazyMeteorTrack{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let bg = self.data.try_request_range(start,end).into_result()?;
 
         let mut data = bg;
 
@@ -93,16 +84,47 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyMeteorTrack{
                 };
 
                 let t = t_usize as f64 + offset;
-                let lc = self.calculate_lc(t)/divider;
+                let lc = self.calculate_lc(t)?/divider;
                 let (x,y) = self.kinematics_2d(t);
 
-                let spot = ensquared_energy::any_spot(&self.detector, x, y, |x| self.psf.calculate(x), lc);
+                let psf_error = RefCell::new(None);
+                let spot = ensquared_energy::any_spot(&self.detector, x, y, |r| {
+                    self.psf.try_calculate(r).into_result().unwrap_or_else(|e| {
+                        psf_error.borrow_mut().get_or_insert(e);
+                        f64::NAN
+                    })
+                }, lc);
+                if let Some(e) = psf_error.into_inner(){
+                    return Err(e);
+                }
                 for i in 0..spot.flat_data.len(){
                     data.flat_data[(t_usize-start)*frame_size+i] += spot.flat_data[i];
                 }
             }
 
         }
-        data
+        Ok(data)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyMeteorTrack{
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        // match &self.data{
+        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
+        //     TrackData::Artificial { length: _ }=>{end-start},
+        // }
+        self.data.calculate_overhead(start,end)
+    }
+
+    fn length(&self,) -> usize{
+        self.data.length()
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64>where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
@@ -1,6 +1,8 @@
+use abi_stable::std_types::RResult;
 use padamo_api::lazy_array_operations::ArrayND;
 use padamo_api::lazy_array_operations::LazyArrayOperation;
 use padamo_api::lazy_array_operations::LazyDetectorSignal;
+use padamo_api::prelude::ExecutionError;
 use crate::ensquared_energy;
 use crate::ensquared_energy::detector::DetectorWireframe;
 
@@ -63,30 +65,18 @@ impl LazyGaussPSFMeteorTrack{
         }
     }
 
-    fn calculate_lc(&self, absolute_time:f64)->f64{
-        let mut res = self.lc.calculate(absolute_time-self.pivot_frame);
+    fn calculate_lc(&self, absolute_time:f64)->Result<f64,ExecutionError>{
+        let mut res = self.lc.try_calculate(absolute_time-self.pivot_frame).into_result()?;
         if self.modify_intensity{
             res = res*self.intensity_mod(absolute_time);
         }
-        res
+        Ok(res)
     }
 }
 
-impl LazyArrayOperation<ArrayND<f64>> for LazyGaussPSFMeteorTrack{
-    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
-        // match &self.data{
-        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
-        //     TrackData::Artificial { length: _ }=>{end-start},
-        // }
-        self.data.calculate_overhead(start,end)
-    }
-
-    fn length(&self,) -> usize{
-        self.data.length()
-    }
-
-    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64>where {
-        let bg = self.data.request_range(start,end);
+impl LazyGaussPSFMeteorTrack{
+    fn try_request(&self,start:usize,end:usize) -> Result<ArrayND<f64>,ExecutionError>{
+        let bg = self.data.try_request_range(start,end).into_result()?;
 
         let mut data = bg;
 
@@ -102,7 +92,7 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyGaussPSFMeteorTrack{
                 };
 
                 let t = t_usize as f64 + offset;
-                let lc = self.calculate_lc(t)/divider;
+                let lc = self.calculate_lc(t)?/divider;
                 let (x,y) = self.kinematics_2d(t);
 
                 let spot = ensquared_energy::gauss_spot(&self.detector, x, y, self.sigma_x, self.sigma_y,lc);
@@ -112,6 +102,28 @@ impl LazyArrayOperation<ArrayND<f64>> for LazyGaussPSFMeteorTrack{
             }
 
         }
-        data
+        Ok(data)
+    }
+}
+
+impl LazyArrayOperation<ArrayND<f64>> for LazyGaussPSFMeteorTrack{
+    fn calculate_overhead(&self,start:usize,end:usize,) -> usize where {
+        // match &self.data{
+        //     TrackData::Background(src)=>{src.calculate_overhead(start,end)},
+        //     TrackData::Artificial { length: _ }=>{end-start},
+        // }
+        self.data.calculate_overhead(start,end)
+    }
+
+    fn length(&self,) -> usize{
+        self.data.length()
+    }
+
+    fn request_range(&self,start:usize,end:usize,) -> ArrayND<f64>where {
+        self.try_request(start,end).unwrap()
+    }
+
+    fn try_request_range(&self,start:usize,end:usize) -> RResult<ArrayND<f64>,ExecutionError>{
+        self.try_request(start,end).into()
     }
 }
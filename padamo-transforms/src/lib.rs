@@ -5,6 +5,7 @@ use abi_stable::std_types::RVec;
 use abi_stable::prefix_type::PrefixTypeTrait;
 use padamo_api::lazy_array_operations::ArrayND;
 use padamo_api::lazy_array_operations::LazyDetectorSignal;
+use nalgebra::Matrix4;
 use padamo_api::{nodes_vec, prelude::CalculationNodeBox, PadamoModule, PadamoModule_Ref};
 use padamo_api::prelude::*;
 pub mod nodes;
@@ -14,13 +15,21 @@ fn matrix_err<T:Into<RString>>(msg:T)->ExecutionError{
     ExecutionError::OtherError(msg.into())
 }
 
-fn get_all(x:LazyDetectorSignal)->ArrayND<f64>{
-    x.request_range(0,x.length())
+fn get_all(x:LazyDetectorSignal)->Result<ArrayND<f64>,ExecutionError>{
+    x.try_request_range(0,x.length()).into_result()
+}
+
+fn get_matrix(inputs:&ContentContainer, key:&str)->Result<Matrix4<f64>,ExecutionError>{
+    Ok(inputs.request_matrix(key)?.into())
+}
+
+fn matrix_content(m:Matrix4<f64>)->Content{
+    Content::Matrix(m.into())
 }
 
 #[export_root_module]
 pub fn plugin_root()->PadamoModule_Ref{
-    PadamoModule{nodes}.leak_into_prefix()
+    PadamoModule{nodes, share_cache:padamo_api::share_cache, info:padamo_api::plugin_info!()}.leak_into_prefix()
 }
 
 #[sabi_extern_fn]
@@ -33,6 +42,7 @@ pub fn nodes(_library_dir:RString)->RVec<CalculationNodeBox>{
         nodes::RotationNode::new("Rotate XY", "xy", nalgebra::Vector3::new(0.0, 0.0, 1.0)),
         nodes::TransformParentNode,
         nodes::ModelViewNode,
+        nodes::MatrixFromArrayNode,
 
         nodes_geo::WGS84PositionNode,
         nodes_geo::DetectorRotatorNode,
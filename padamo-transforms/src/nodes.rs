@@ -1,7 +1,7 @@
 use abi_stable::std_types::{RResult, RString, RVec};
 use nalgebra::Matrix4;
-use super::{get_all, matrix_err};
-use padamo_api::{constants, lazy_array_operations::ArrayND, ports, prelude::*};
+use super::{get_all, get_matrix, matrix_content, matrix_err};
+use padamo_api::{constants, ports, prelude::*};
 
 #[derive(Clone,Debug)]
 pub struct IdentityNode;
@@ -22,6 +22,9 @@ pub struct TransformParentNode;
 #[derive(Clone,Debug)]
 pub struct ModelViewNode;
 
+#[derive(Clone,Debug)]
+pub struct MatrixFromArrayNode;
+
 fn category() -> RVec<RString>where {
     vec![
         "Transform".into()
@@ -33,25 +36,20 @@ fn category() -> RVec<RString>where {
 impl IdentityNode{
     fn calculate(&self,args:CalculationNodeArguments,) -> Result<(),ExecutionError>where {
         let v = nalgebra::Matrix4::identity();
-        let v:ArrayND<f64> = v.into();
-        let v = make_lao_box(v);
-        args.outputs.set_value("Matrix", v.into())
+        args.outputs.set_value("Matrix", matrix_content(v))
     }
 }
 
 impl PositionNode{
     fn calculate(&self,args:CalculationNodeArguments,) -> Result<(),ExecutionError>where {
-        let input = get_all(args.inputs.request_detectorsignal("Matrix")?);
-        let input:Matrix4<f64> = input.try_into().map_err(|_| matrix_err("Input matrix must be 4x4"))?;
+        let input = get_matrix(&args.inputs, "Matrix")?;
 
         let x = args.constants.request_float("x")?;
         let y = args.constants.request_float("y")?;
         let z = args.constants.request_float("z")?;
         let v = nalgebra::Vector3::new(x, y, z);
         let v = nalgebra::Matrix4::new_translation(&v) * input;
-        let v:ArrayND<f64> = v.into();
-        let v = make_lao_box(v);
-        args.outputs.set_value("Matrix", v.into())
+        args.outputs.set_value("Matrix", matrix_content(v))
     }
 }
 
@@ -61,8 +59,7 @@ impl RotationNode{
     }
 
     fn calculate(&self,args:CalculationNodeArguments,) -> Result<(),ExecutionError>where {
-        let input = get_all(args.inputs.request_detectorsignal("Matrix")?);
-        let input:Matrix4<f64> = input.try_into().map_err(|_| matrix_err("Input matrix must be 4x4"))?;
+        let input = get_matrix(&args.inputs, "Matrix")?;
 
         let mut angle = args.constants.request_float("Angle")?;
         if args.constants.request_boolean("Degrees")?{
@@ -70,39 +67,36 @@ impl RotationNode{
         }
 
         let v = nalgebra::Matrix4::new_rotation(self.axis*angle) * input;
-        let v:ArrayND<f64> = v.into();
-        let v = make_lao_box(v);
-        args.outputs.set_value("Matrix", v.into())
+        args.outputs.set_value("Matrix", matrix_content(v))
     }
 }
 
 impl TransformParentNode{
     fn calculate(&self,args:CalculationNodeArguments,) -> Result<(),ExecutionError>{
-        let child = get_all(args.inputs.request_detectorsignal("Child")?);
-        let parent = get_all(args.inputs.request_detectorsignal("Parent")?);
-
-
-        let child:Matrix4<f64> = child.try_into().map_err(|_| matrix_err("Child transform must be 4x4 matrix"))?;
-        let parent:Matrix4<f64> = parent.try_into().map_err(|_| matrix_err("Parent transform must be 4x4 matrix"))?;
+        let child = get_matrix(&args.inputs, "Child")?;
+        let parent = get_matrix(&args.inputs, "Parent")?;
         let combined = parent*child;
-        let combined:ArrayND<f64> = combined.into();
-        args.outputs.set_value("Combined", make_lao_box(combined).into())
+        args.outputs.set_value("Combined", matrix_content(combined))
     }
 }
 
 impl ModelViewNode{
     fn calculate(&self,args:CalculationNodeArguments,) -> Result<(),ExecutionError>{
-        let model = get_all(args.inputs.request_detectorsignal("Model")?);
-        let view = get_all(args.inputs.request_detectorsignal("View")?);
-
-        let model:Matrix4<f64> = model.try_into().map_err(|_| matrix_err("Child transform must be 4x4 matrix"))?;
-        let view:Matrix4<f64> = view.try_into().map_err(|_| matrix_err("Parent transform must be 4x4 matrix"))?;
+        let model = get_matrix(&args.inputs, "Model")?;
+        let view = get_matrix(&args.inputs, "View")?;
         let view = view.try_inverse().ok_or(matrix_err("Cannot construct view matrix. Ensure that View Transform matrix is inversible."))?;
 
         let combined =  view*model;
         // let combined = parent*child;
-        let combined:ArrayND<f64> = combined.into();
-        args.outputs.set_value("Combined", make_lao_box(combined).into())
+        args.outputs.set_value("Combined", matrix_content(combined))
+    }
+}
+
+impl MatrixFromArrayNode{
+    fn calculate(&self,args:CalculationNodeArguments,) -> Result<(),ExecutionError>{
+        let array = get_all(args.inputs.request_detectorsignal("Array")?)?;
+        let v:Matrix4<f64> = array.try_into().map_err(|_| matrix_err("Input array must be 4x4"))?;
+        args.outputs.set_value("Matrix", matrix_content(v))
     }
 }
 
@@ -125,7 +119,7 @@ impl CalculationNode for IdentityNode{
 
     fn outputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Matrix", ContentType::DetectorSignal)
+            ("Matrix", ContentType::Matrix)
         ]
     }
 
@@ -152,13 +146,13 @@ impl CalculationNode for PositionNode{
 
     fn inputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Matrix", ContentType::DetectorSignal)
+            ("Matrix", ContentType::Matrix)
         ]
     }
 
     fn outputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Matrix", ContentType::DetectorSignal)
+            ("Matrix", ContentType::Matrix)
         ]
     }
 
@@ -190,13 +184,13 @@ impl CalculationNode for RotationNode{
 
     fn inputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Matrix", ContentType::DetectorSignal)
+            ("Matrix", ContentType::Matrix)
         ]
     }
 
     fn outputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Matrix", ContentType::DetectorSignal)
+            ("Matrix", ContentType::Matrix)
         ]
     }
 
@@ -235,14 +229,14 @@ impl CalculationNode for TransformParentNode{
 
     fn inputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Parent", ContentType::DetectorSignal),
-            ("Child", ContentType::DetectorSignal),
+            ("Parent", ContentType::Matrix),
+            ("Child", ContentType::Matrix),
         ]
     }
 
     fn outputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Combined", ContentType::DetectorSignal),
+            ("Combined", ContentType::Matrix),
         ]
     }
 }
@@ -270,14 +264,48 @@ impl CalculationNode for ModelViewNode{
 
     fn inputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Model", ContentType::DetectorSignal),
-            ("View", ContentType::DetectorSignal),
+            ("Model", ContentType::Matrix),
+            ("View", ContentType::Matrix),
+        ]
+    }
+
+    fn outputs(&self,) -> RVec<CalculationIO>where {
+        ports![
+            ("Combined", ContentType::Matrix),
+        ]
+    }
+}
+
+impl CalculationNode for MatrixFromArrayNode{
+    fn name(&self,) -> RString where {
+        "Matrix from array".into()
+    }
+
+    fn category(&self,) -> RVec<RString>where {
+        category()
+    }
+
+    fn identifier(&self,) -> RString where {
+        "transforms.from_array".into()
+    }
+
+    fn constants(&self,) -> RVec<CalculationConstant>where {
+        constants!()
+    }
+
+    fn calculate(&self,args:CalculationNodeArguments,) -> RResult<(),ExecutionError>where {
+        self.calculate(args).into()
+    }
+
+    fn inputs(&self,) -> RVec<CalculationIO>where {
+        ports![
+            ("Array", ContentType::DetectorSignal),
         ]
     }
 
     fn outputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Combined", ContentType::DetectorSignal),
+            ("Matrix", ContentType::Matrix),
         ]
     }
 }
@@ -1,7 +1,7 @@
 use abi_stable::std_types::{RString, RVec};
 use nalgebra::{Matrix4, Vector3};
-use padamo_api::{constants, lazy_array_operations::ArrayND, ports, prelude::*};
-use super::{get_all, matrix_err};
+use padamo_api::{constants, ports, prelude::*};
+use super::{get_matrix, matrix_content};
 
 #[derive(Clone, Debug)]
 pub struct WGS84PositionNode;
@@ -19,8 +19,7 @@ const WGS84_E_SQR:f64 = WGS84_F* (2.0-WGS84_F);
 
 impl WGS84PositionNode{
     fn calculate(&self,args:CalculationNodeArguments,) -> Result<(),ExecutionError>{
-        let input = get_all(args.inputs.request_detectorsignal("Matrix")?);
-        let input:Matrix4<f64> = input.try_into().map_err(|_| matrix_err("Input matrix must be 4x4"))?;
+        let input = get_matrix(&args.inputs, "Matrix")?;
         let lambda = args.constants.request_float("latitude")?*std::f64::consts::PI/180.0;
         let phi = args.constants.request_float("longitude")?*std::f64::consts::PI/180.0;
         let h = args.constants.request_float("elevation")?/1000.0;
@@ -42,8 +41,7 @@ impl WGS84PositionNode{
         let op = nalgebra::Matrix4::new_translation(&vec);
 
         let output = op*input;
-        let output: ArrayND<f64> = output.into();
-        args.outputs.set_value("Matrix", make_lao_box(output).into())
+        args.outputs.set_value("Matrix", matrix_content(output))
     }
 }
 
@@ -69,13 +67,13 @@ impl CalculationNode for WGS84PositionNode{
 
     fn inputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Matrix", ContentType::DetectorSignal)
+            ("Matrix", ContentType::Matrix)
         ]
     }
 
     fn outputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Matrix", ContentType::DetectorSignal)
+            ("Matrix", ContentType::Matrix)
         ]
     }
 
@@ -98,8 +96,7 @@ pub struct DetectorRotatorNode;
 
 impl DetectorRotatorNode{
     fn calculate(&self,args:CalculationNodeArguments,) -> Result<(),ExecutionError>{
-        let input = get_all(args.inputs.request_detectorsignal("Matrix")?);
-        let input:Matrix4<f64> = input.try_into().map_err(|_| matrix_err("Input matrix must be 4x4"))?;
+        let input = get_matrix(&args.inputs, "Matrix")?;
 
         let mut workon = input;
 
@@ -131,9 +128,7 @@ impl DetectorRotatorNode{
 
         workon = longitudal_rotator*workon;
 
-        let output:ArrayND<f64> = workon.into();
-
-        args.outputs.set_value("Matrix", make_lao_box(output).into())
+        args.outputs.set_value("Matrix", matrix_content(workon))
 
     }
 }
@@ -153,13 +148,13 @@ impl CalculationNode for DetectorRotatorNode{
 
     fn inputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Matrix", ContentType::DetectorSignal)
+            ("Matrix", ContentType::Matrix)
         ]
     }
 
     fn outputs(&self,) -> RVec<CalculationIO>where {
         ports![
-            ("Matrix", ContentType::DetectorSignal)
+            ("Matrix", ContentType::Matrix)
         ]
     }
 
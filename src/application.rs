@@ -1,5 +1,4 @@
 use std::collections::VecDeque;
-use std::fs;
 use std::rc::Rc;
 
 use iced::widget::{button, container};
@@ -7,7 +6,7 @@ use iced::Length;
 use padamo_detectors::loaded_detectors_storage::DetectorLoadError;
 use padamo_detectors::polygon::Detector;
 use crate::messages::PadamoAppMessage;
-use crate::nodes_interconnect::NodesRegistry;
+use padamo_api::plugin::{LoadReport, PluginStatus};
 use crate::tools::{self as ctools};
 use crate::{builtin_nodes};
 
@@ -20,10 +19,12 @@ use crate::popup_message::MessageList;
 use crate::popup_message::PadamoPopupMessageType;
 // use iced_aw::{menu_bar, menu_items};
 
-use std::path::Path;
 use padamo_iced_forms::double_entry_state::EntryState;
 
 use rand::prelude::*;
+use sysinfo::{System,RefreshKind,MemoryRefreshKind};
+
+const DEFAULT_CACHE_RAM_PART:f64 = 0.25;
 
 
 fn menu_button(action:&str, msg:PadamoAppMessage)->iced::widget::Button<'_,PadamoAppMessage>{
@@ -47,10 +48,18 @@ pub struct PadamoState{
     pub add_delay_ms:u64,
     pub current_page:usize,
     pub current_seed:EntryState<u64>,
+    /// Part of RAM available for caches of lazy signals
+    pub cache_ram_part:EntryState<f64>,
+    /// Size limit of disk cache in megabytes
+    pub disk_cache_limit_mb:EntryState<u64>,
     pub persistent_state:padamo_state_persistence::PersistentState,
     popup_messages:MessageList,
     pub detectors:LoadedDetectors,
     pub is_editing_detectors: bool,
+    /// Result of loading plugins shown in plugins dialog
+    pub plugins_report: LoadReport,
+    pub is_viewing_plugins: bool,
+    tasks:Vec<iced::Task<PadamoAppMessage>>,
 }
 
 pub type PadamoStateRef<'a> = &'a mut PadamoState;
@@ -81,11 +90,35 @@ impl PadamoState{
         self.popup_messages.add_message(msg.into(), PadamoPopupMessageType::Error);
     }
 
+    /// Shows error with additional button sending `action`
+    pub fn show_error_with_action<T:Into<String>, U:Into<String>>(&mut self, msg:T, label:U, action:PadamoAppMessage){
+        self.popup_messages.add_message_with_action(msg.into(), PadamoPopupMessageType::Error, label.into(), action);
+    }
+
+    /// Schedules iced task (e.g. widget operation) to run after current update
+    pub fn run_task(&mut self, task:iced::Task<PadamoAppMessage>){
+        self.tasks.push(task);
+    }
+
     pub fn reroll(&mut self){
         let mut rng = rand::thread_rng();
         self.current_seed.set_value(rng.next_u64());
     }
 
+    /// Sets memory budget of caches from `cache_ram_part`. Invalid part is reported and ignored.
+    pub fn apply_cache_budget(&mut self){
+        let part = self.cache_ram_part.parsed_value;
+        if !self.cache_ram_part.is_valid || part<=0.0 || part>=1.0{
+            self.show_error("Cache RAM part must be in (0,1) interval");
+            return;
+        }
+        let mut sys = System::new_with_specifics(RefreshKind::new().with_memory(MemoryRefreshKind::new().with_ram()));
+        sys.refresh_memory();
+        let budget = ((sys.total_memory() as f64)*part) as usize;
+        padamo_api::lazy_array_operations::cache::cache_budget().set_budget(budget);
+        self.persistent_state.write("cache_ram_part", &part.to_string());
+    }
+
     pub fn save_detectors(&self){
         self.persistent_state.serialize("detectors", &self.detectors);
     }
@@ -180,30 +213,6 @@ impl Padamo{
 }
 
 
-fn register_nodes(nodes:&mut NodesRegistry, seekdir:&Path, look_in_directories:bool){
-    println!("Seeking for plugins in {}", seekdir.to_str().unwrap());
-    let paths = fs::read_dir(seekdir).unwrap();
-    for path in paths{
-        if let Ok(p_res) = path{
-            let p = p_res.path();
-            if p.is_dir() && look_in_directories{
-                println!("Looking into dir: {:?}",p);
-
-                //NO FULL RECURSIVE SEARCH. Only first layer.
-                register_nodes(nodes, &p, false);
-            }
-            else if p.is_file(){
-                if let Err(e) = nodes.load_lib(p.as_path()){
-                    println!("Error reading library: {}",e);
-                }
-            }
-            else {
-                println!("Skipped: {:?}",p);
-            }
-        }
-    }
-}
-
 type Message = PadamoAppMessage;
 
 impl Padamo{
@@ -227,7 +236,9 @@ impl Padamo{
         let current_exe = std::env::current_exe().unwrap();
         let current_dir = current_exe.parent().unwrap();
         let plugins_dir = current_dir.join("plugins");
-        register_nodes(&mut nodes, &plugins_dir, true);
+        println!("Seeking for plugins in {}", plugins_dir.display());
+        let plugins_report = nodes.load_plugins(&plugins_dir);
+        print!("{}", plugins_report);
         // println!("Seeking for plugins in {}", plugins_dir.to_str().unwrap());
         // let paths = fs::read_dir(plugins_dir).unwrap();
         // for path in paths{
@@ -294,10 +305,15 @@ impl Padamo{
             add_delay_ms: 0,
             current_page: 0,
             current_seed: EntryState::new(0),
+            cache_ram_part: EntryState::new(DEFAULT_CACHE_RAM_PART),
+            disk_cache_limit_mb: EntryState::new(padamo_state_persistence::DEFAULT_DISK_CACHE_LIMIT_MB),
             popup_messages:MessageList::new(),
             persistent_state: Default::default(),
             detectors: LoadedDetectors::new(),
             is_editing_detectors: false,
+            plugins_report,
+            is_viewing_plugins: false,
+            tasks: Vec::new(),
         };
 
 
@@ -310,6 +326,18 @@ impl Padamo{
             //,
             // iced::font::load(iced_aw::BOOTSTRAP_FONT_BYTES).map(PadamoAppMessage::FontLoaded)
 
+        if let Some(part) = res.state.persistent_state.read("cache_ram_part"){
+            res.state.cache_ram_part.set_string(part);
+        }
+        res.state.apply_cache_budget();
+        if let Some(limit) = res.state.persistent_state.read(padamo_state_persistence::DISK_CACHE_LIMIT_KEY){
+            res.state.disk_cache_limit_mb.set_string(limit);
+        }
+        let conflicts = res.state.plugins_report.conflicts_count();
+        if conflicts>0{
+            let msg = format!("{} nodes from plugins were not loaded because of identifier conflicts", conflicts);
+            res.state.popup_messages.add_message_with_action(msg, PadamoPopupMessageType::Warning, "Plugins".into(), PadamoAppMessage::SetShowPlugins(true));
+        }
         res.try_load_detector();
         res.initialize_tools();
         res
@@ -321,7 +349,7 @@ impl Padamo{
         }
     }
 
-    pub fn update(&mut self, msg: PadamoAppMessage){
+    pub fn update(&mut self, msg: PadamoAppMessage)->iced::Task<PadamoAppMessage>{
         match msg{
             PadamoAppMessage::TabSelect(tab)=> {
                 //println!("Tab select {}", tab);
@@ -332,9 +360,46 @@ impl Padamo{
             PadamoAppMessage::PopupMessageClick=>{
                 self.state.popup_messages.pop_oldest_message();
             },
+            PadamoAppMessage::PopupMessageAction=>{
+                if let Some(action) = self.state.popup_messages.take_oldest_action(){
+                    return self.update(action);
+                }
+            },
+            PadamoAppMessage::FocusGraphNode(index)=>{
+                if let Some(page) = self.tools.iter().position(|x| x.tab_name()=="Editor"){
+                    self.state.current_page = page;
+                }
+                self.update_tools_loop(Rc::new(PadamoAppMessage::FocusGraphNode(index)));
+            },
             PadamoAppMessage::SetSeed(seed)=>{
                 self.state.current_seed.set_string(seed);
             },
+            PadamoAppMessage::SetCacheRamPart(part)=>{
+                self.state.cache_ram_part.set_string(part);
+                let part = self.state.cache_ram_part.parsed_value;
+                // Entry is edited by typing, so errors are not reported for intermediate values
+                if self.state.cache_ram_part.is_valid && part>0.0 && part<1.0{
+                    self.state.apply_cache_budget();
+                }
+            },
+            PadamoAppMessage::SetDiskCacheLimit(limit)=>{
+                self.state.disk_cache_limit_mb.set_string(limit);
+                if self.state.disk_cache_limit_mb.is_valid{
+                    let limit = self.state.disk_cache_limit_mb.parsed_value;
+                    self.state.persistent_state.write(padamo_state_persistence::DISK_CACHE_LIMIT_KEY, &limit.to_string());
+                }
+            },
+            PadamoAppMessage::ShowCacheStats=>{
+                let stats = padamo_api::lazy_array_operations::cache::cache_budget().stats();
+                let disk_dir = padamo_state_persistence::data::get_disk_cache_dir();
+                let disk_used = padamo_api::lazy_array_operations::disk_cache::disk_usage(&disk_dir) as f64/(1024.0*1024.0);
+                self.state.show_info(format!("{}\nDisk cache: {:.1} MB used", stats, disk_used));
+            },
+            PadamoAppMessage::ClearDiskCache=>{
+                let disk_dir = padamo_state_persistence::data::get_disk_cache_dir();
+                padamo_api::lazy_array_operations::disk_cache::shrink_dir(&disk_dir, 0);
+                self.state.show_info("Disk cache is cleared");
+            },
             PadamoAppMessage::LoadedDetectorsMessage(msg)=>{
                 let getter = ||{
                     if let Some(path) = &self.state.workspace.workspace("detectors").open_dialog(vec![("Detector",vec!["json"])]){
@@ -366,7 +431,14 @@ impl Padamo{
                 //     self.state.save_detectors();
                 // }
             },
+            PadamoAppMessage::HistoryShortcut(redo)=>{
+                // Text fields do not capture Ctrl+Z, so focus is checked here
+                let msg = if redo {PadamoAppMessage::Redo} else {PadamoAppMessage::Undo};
+                return iced::advanced::widget::operate(iced::advanced::widget::operation::focusable::count())
+                    .map(move |count| if count.focused.is_some() {PadamoAppMessage::Noop} else {msg.clone()});
+            },
             PadamoAppMessage::SetEditLoadedDetectors(v)=>self.state.is_editing_detectors = v,
+            PadamoAppMessage::SetShowPlugins(v)=>self.state.is_viewing_plugins = v,
             PadamoAppMessage::ClearState=>{
                 self.state.persistent_state.clear();
                 let vtl = serde_json::to_string(&Detector::default_vtl()).unwrap();
@@ -377,6 +449,7 @@ impl Padamo{
                 self.update_tools_loop(Rc::new(other));
             }
         };
+        iced::Task::batch(std::mem::take(&mut self.state.tasks))
     }
 
 
@@ -392,6 +465,9 @@ impl Padamo{
             if self.state.is_editing_detectors{
                 self.view_loaded_detectors()
             }
+            else if self.state.is_viewing_plugins{
+                self.view_plugins()
+            }
             else{
                 self.view_normal()
             }
@@ -411,6 +487,44 @@ impl Padamo{
         cont.into()
     }
 
+    fn view_plugins(&self) -> iced::Element<'_, Message> {
+        let mut plugins = iced::widget::Column::new().spacing(10);
+        for plugin in self.state.plugins_report.plugins.iter(){
+            // Files which are not libraries are not interesting
+            if let PluginStatus::Skipped(_) = plugin.status{
+                if plugin.info.is_none(){
+                    continue;
+                }
+            }
+            let mut entry = iced::widget::Column::new().spacing(2);
+            entry = entry.push(iced::widget::text(plugin.title()).size(18));
+            entry = entry.push(iced::widget::text(format!("{}", plugin.path.display())).size(12));
+            if let Some(info) = &plugin.info{
+                if !info.author.is_empty(){
+                    entry = entry.push(iced::widget::text(format!("Author: {}", info.author)));
+                }
+                entry = entry.push(iced::widget::text(format!("Minimal API version: {}", info.min_api_version)));
+            }
+            else if let PluginStatus::Loaded = plugin.status{
+                entry = entry.push(iced::widget::text("Plugin provides no metadata"));
+            }
+            entry = entry.push(iced::widget::text(format!("{}", plugin.status)));
+            if !plugin.nodes.is_empty(){
+                entry = entry.push(iced::widget::text(format!("Nodes: {}", plugin.nodes.join(", "))));
+            }
+            for conflict in plugin.conflicts.iter(){
+                entry = entry.push(iced::widget::text(format!("Conflict: {}", conflict)));
+            }
+            plugins = plugins.push(entry);
+        }
+        let body = iced::widget::scrollable(plugins).height(Length::Fixed(500.0));
+        let res = iced_aw::card(iced::widget::text(format!("Plugins (API {})", padamo_api::plugin::API_VERSION)), body)
+            .on_close(PadamoAppMessage::SetShowPlugins(false))
+            .max_width(700.0);
+        let cont = container(res).center_x(Length::Fill).center_y(Length::Fill);
+        cont.into()
+    }
+
     fn view_normal(&self) -> iced::Element<'_, Message> {
         let mut vlist = iced::widget::Column::new();
         vlist = vlist.spacing(10);
@@ -433,6 +547,8 @@ impl Padamo{
 
         //#[cfg(feature = "buttons_edit")]
         {
+            edit_menu.push(Item::new(menu_button("Undo", PadamoAppMessage::Undo)));
+            edit_menu.push(Item::new(menu_button("Redo", PadamoAppMessage::Redo)));
             edit_menu.push(Item::new(menu_button("Select all", PadamoAppMessage::SelectAll)));
             edit_menu.push(Item::new(menu_button("Copy", PadamoAppMessage::Copy)));
             edit_menu.push(Item::new(menu_button("Paste", PadamoAppMessage::Paste)));
@@ -444,6 +560,8 @@ impl Padamo{
         //let v = self.state.current_seed.view_row("Seed", "Seed", )
         let mut run_menu = Vec::new();
         run_menu.push(Item::new(menu_button("Run", PadamoAppMessage::Run)));
+        run_menu.push(Item::new(menu_button("Recompute all", PadamoAppMessage::FullRun)));
+        run_menu.push(Item::new(menu_button("Stop", PadamoAppMessage::StopRun)));
         #[cfg(feature = "buttons_random")]
         {
             run_menu.push(Item::new(menu_button("Reroll and run", PadamoAppMessage::RerollRun)));
@@ -453,16 +571,22 @@ impl Padamo{
 
         let run_menu = Item::with_menu(title_menu_button("Run"), Menu::new(run_menu).max_width(150.0).offset(0.0).spacing(5.0));
 
-        let settings_menu = Item::with_menu(title_menu_button("Settings"), Menu::new(vec![
-            Item::new(menu_button("Choose workspace directory", PadamoAppMessage::ResetWorkspace)),
-        ]).max_width(200.0).offset(0.0).spacing(5.0));
+        let mut settings_menu = Vec::new();
+        #[cfg(feature = "feature_workspace")]
+        settings_menu.push(Item::new(menu_button("Choose workspace directory", PadamoAppMessage::ResetWorkspace)));
+        settings_menu.push(Item::new(self.state.cache_ram_part.view_row("Cache RAM part","0.25",PadamoAppMessage::SetCacheRamPart)));
+        settings_menu.push(Item::new(self.state.disk_cache_limit_mb.view_row("Disk cache limit, MB","4096",PadamoAppMessage::SetDiskCacheLimit)));
+        settings_menu.push(Item::new(menu_button("Cache statistics", PadamoAppMessage::ShowCacheStats)));
+        settings_menu.push(Item::new(menu_button("Clear disk cache", PadamoAppMessage::ClearDiskCache)));
+        settings_menu.push(Item::new(menu_button("Plugins", PadamoAppMessage::SetShowPlugins(true))));
+
+        let settings_menu = Item::with_menu(title_menu_button("Settings"), Menu::new(settings_menu).max_width(200.0).offset(0.0).spacing(5.0));
 
         let mut menu_bar = Vec::new();//vec![file_menu,edit_menu,run_menu,settings_menu]
         menu_bar.push(file_menu);
         #[cfg(feature = "buttons_edit")]
         menu_bar.push(edit_menu);
         menu_bar.push(run_menu);
-        #[cfg(feature = "feature_workspace")] // because currently it has only button
         menu_bar.push(settings_menu);
         let menu_bar = MenuBar::new(menu_bar)
             .draw_path(iced_aw::menu::DrawPath::Backdrop)
@@ -517,6 +641,10 @@ impl Padamo{
                                     "c"=>PadamoAppMessage::Copy,
                                     "v"=>PadamoAppMessage::Paste,
                                     "a"=>PadamoAppMessage::SelectAll,
+                                    "z" if modifiers.shift()=>PadamoAppMessage::HistoryShortcut(true),
+                                    "z"=>PadamoAppMessage::HistoryShortcut(false),
+                                    "Z"=>PadamoAppMessage::HistoryShortcut(true),
+                                    "y"=>PadamoAppMessage::HistoryShortcut(true),
                                     _=>PadamoAppMessage::Noop,
                                 }
                             },
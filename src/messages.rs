@@ -21,15 +21,33 @@ pub enum PadamoAppMessage{
     DetectorUpdate,
     LoadedDetectorsMessage(LoadedDetectorsMessage),
     SetEditLoadedDetectors(bool),
+    /// Show or hide plugins dialog
+    SetShowPlugins(bool),
     PopupMessageClick,
+    PopupMessageAction,
+    /// Select node of editor graph and scroll to it
+    FocusGraphNode(usize),
     Run,
     RerollRun,
+    FullRun,
+    /// Stop graph execution
+    StopRun,
+    /// Graph execution is finished and environment holds its results
+    Executed,
     SetSeed(String),
+    SetCacheRamPart(String),
+    SetDiskCacheLimit(String),
+    ShowCacheStats,
+    ClearDiskCache,
     Open,
     Save,
     Copy,
     Paste,
     SelectAll,
+    Undo,
+    Redo,
+    /// Undo (false) or redo (true) requested from keyboard. Ignored while text field has focus.
+    HistoryShortcut(bool),
     Tick,
     ClearState,
     ResetWorkspace
@@ -5,8 +5,7 @@ use std::{fmt::Display, error::Error};
 pub enum NodeRegistryError{
     NodeDuplicate(String),
     LegacyDuplicate(String),
-    LibraryError(abi_stable::library::LibraryError),
-    PathError,
+    InvalidSubgraph(String),
     // NoName,
 }
 
@@ -15,9 +14,8 @@ impl Display for NodeRegistryError{
         match self {
             Self::NodeDuplicate(x)=>write!(f,"Node {} is defined twice", x),
             Self::LegacyDuplicate(x)=>write!(f,"Legacy name {} is defined twice", x),
-            Self::LibraryError(x)=>write!(f,"Library error: {}", x),
             // Self::NoName=>write!(f,"No file name"),
-            Self::PathError=>write!(f,"Path error ocurred"),
+            Self::InvalidSubgraph(x)=>write!(f,"Invalid subgraph: {}", x),
         }
     }
 }
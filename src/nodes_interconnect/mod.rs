@@ -2,12 +2,14 @@ pub mod errors;
 
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 
 use abi_stable::std_types::RHashMap;
 use padamo_api::calculation_nodes::immediate::{CompiledGraph, CompiledNode, SmallLink};
+use padamo_api::calculation_nodes::subgraph::{SubgraphDefinition, SubgraphNode};
+use padamo_api::calculation_nodes::validation::Diagnostic;
 use padamo_api::prelude::{CalculationNodeBox, CalculationNode, CalculationNode_TO};
-use padamo_api::PadamoModule_Ref;
-use abi_stable::library::lib_header_from_path;
+use padamo_api::plugin::LoadReport;
 
 use errors::NodeRegistryError;
 
@@ -30,7 +32,7 @@ impl NodesRegistry{
     }
 
 
-    fn register_node_box(&mut self, nodebox:CalculationNodeBox)->Result<(),NodeRegistryError>{
+    pub fn register_node_box(&mut self, nodebox:CalculationNodeBox)->Result<(),NodeRegistryError>{
         let key:String = nodebox.identifier().into();
         //self.register_node_by_path(path, nodebox)
         println!("Registering node with key {}", key);
@@ -61,30 +63,21 @@ impl NodesRegistry{
         self.register_node_box(CalculationNode_TO::from_value(node, TD_Opaque))
     }
 
-    pub fn load_lib(&mut self, path:&Path)->Result<(),NodeRegistryError>{
-        //let path = Path::new(path_str);
-        /*if let Some(name_os) = path.file_name(){
-            if let Some(name) = name_os.to_str(){
-                */
-        let path_str = path.to_str().ok_or(NodeRegistryError::PathError)?;
-        let parent_dir = path.parent().ok_or(NodeRegistryError::PathError)?.to_str().ok_or(NodeRegistryError::PathError)?;
-        println!("Loading {}",path_str);
-        //let plugin = PadamoModule_Ref::load_from_file(path).map_err(NodeRegistryError::LibraryError)?;
-
-        let plugin = (|| {
-            let header = lib_header_from_path(&path)?;
-            header.init_root_module::<PadamoModule_Ref>()
-        })().map_err(NodeRegistryError::LibraryError)?;
-
-        let nodes_fn = plugin.nodes();
-        let mut nodes = nodes_fn(parent_dir.into());
-        for node in nodes.iter(){
-            println!("Detected node {}",node.name());
-        }
-        for node in nodes.drain(..){
-            self.register_node_box(node)?;
-        }
-        return Ok(());
+    /// Registers subgraph node. Subgraph with the same name is replaced.
+    pub fn register_subgraph(&mut self, definition:SubgraphDefinition)->Result<(),NodeRegistryError>{
+        let key = definition.identifier();
+        let node = SubgraphNode::new(definition, Arc::new(self.nodes.clone()))
+            .map_err(|e| NodeRegistryError::InvalidSubgraph(e.to_string()))?;
+        self.nodes.remove(&key);
+        self.register_node(node)
+    }
+
+    /// Loads plugins from `seekdir`. Nodes with already registered identifiers are reported as conflicts.
+    pub fn load_plugins(&mut self, seekdir:&Path)->LoadReport{
+        let mut report = LoadReport::new();
+        let mut register = |node:CalculationNodeBox| self.register_node_box(node).map_err(|e| e.to_string());
+        padamo_api::plugin::load_plugins(seekdir, &mut register, &mut report);
+        report
     }
 
     pub fn make_tree(&self)->crate::custom_widgets::treeview::Tree<String>{
@@ -127,7 +120,6 @@ impl NodesRegistry{
                                 target_index:end_i,
                             });
                             //compute_graph.link_fromto(start_i, end_i, &output_port, &input_port);
-                            println!("{}.{}->{}.{}",start_i,input_port,end_i,output_port);
                         }
                     }
                 }
@@ -172,6 +164,10 @@ impl NodesRegistry{
         compiled_graph.make_compute_graph(compute_graph, &self.nodes);
     }
 
+    pub fn validate_graph(&self, template:&GraphNodeStorage)->Vec<Diagnostic>{
+        self.compile_graph(template).validate(&self.nodes)
+    }
+
     pub fn create_calculation_node(&self, identifier:String)->Option<GraphNode>{
         let mut true_id = identifier;
         if !self.nodes.contains_key(&true_id){
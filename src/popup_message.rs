@@ -24,20 +24,28 @@ impl PadamoPopupMessageType{
 #[derive(Clone,Debug)]
 pub struct PadamoPopupMessage{
     message_type:PadamoPopupMessageType,
-    message:String
+    message:String,
+    /// Optional button with message sent after popup is closed
+    action:Option<(String,PadamoAppMessage)>,
 }
 
 impl PadamoPopupMessage{
     pub fn new(message_type: PadamoPopupMessageType, message: String) -> Self {
-        Self { message_type, message }
+        Self { message_type, message, action:None }
     }
 
     pub fn view<'a>(&'a self)->iced::Element<'a, PadamoAppMessage>{
+        let mut buttons = widget::row![
+            widget::button("OK").width(100).on_press(PadamoAppMessage::PopupMessageClick)
+        ].spacing(10);
+        if let Some((label,_)) = &self.action{
+            buttons = buttons.push(widget::button(widget::text(label)).width(100).on_press(PadamoAppMessage::PopupMessageAction));
+        }
 
         iced::widget::container(card::Card::new(widget::text(self.message_type.title()), widget::text(&self.message))
             .foot(
                 widget::container(
-                    widget::button("OK").width(100).on_press(PadamoAppMessage::PopupMessageClick)
+                    buttons
                 ).width(iced::Length::Fill).align_x(iced::alignment::Horizontal::Center)
             )
             .on_close(PadamoAppMessage::PopupMessageClick)
@@ -76,7 +84,18 @@ impl MessageList{
         self.container.push_back(PadamoPopupMessage::new(level, msg));
     }
 
+    pub fn add_message_with_action(&mut self, msg:String, level:PadamoPopupMessageType, label:String, action:PadamoAppMessage){
+        let mut message = PadamoPopupMessage::new(level, msg);
+        message.action = Some((label, action));
+        self.container.push_back(message);
+    }
+
     pub fn pop_oldest_message(&mut self){
         self.container.pop_front();
     }
+
+    /// Removes oldest message and returns its action
+    pub fn take_oldest_action(&mut self)->Option<PadamoAppMessage>{
+        self.container.pop_front().and_then(|x| x.action).map(|x| x.1)
+    }
 }
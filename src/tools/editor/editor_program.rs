@@ -15,18 +15,21 @@ use super::nodes::constants::{NodeConstantBuffer, NodeConstantMessage};
 
 
 
+pub const CANVAS_SCROLLABLE_ID:&'static str = "editor_canvas";
+
 pub struct EditorState{
     pub nodes: super::nodes::GraphNodeStorage,
     //pub copied_data: Option<super::nodes::GraphNodeCloneBufferSerializable>,
     pub pending_paste:RefCell<Option<std::rc::Rc<super::nodes::GraphNodeCloneBuffer>>>,
-    pub scroll_offset: scrollable::AbsoluteOffset
+    pub scroll_offset: scrollable::AbsoluteOffset,
+    pub viewport_size: iced::Size,
 }
 
 
 impl EditorState{
     pub fn new()->Self{
         let nodes = super::nodes::GraphNodeStorage::new();
-        Self { nodes, pending_paste:RefCell::new(None), scroll_offset:scrollable::AbsoluteOffset{x:0.0, y:0.}}
+        Self { nodes, pending_paste:RefCell::new(None), scroll_offset:scrollable::AbsoluteOffset{x:0.0, y:0.}, viewport_size:iced::Size::new(800.0, 600.0)}
     }
 
     pub fn request_paste(&mut self, registry:&NodesRegistry){
@@ -134,6 +137,7 @@ impl EditorState{
                         .scroller_width(20);
 
         let first_part = iced::widget::scrollable(canv)
+                .id(CANVAS_SCROLLABLE_ID)
                 .width(Length::Fill)
                 .height(Length::Fill)
                 .direction(scrollable::Direction::Both{
@@ -154,6 +158,20 @@ impl EditorState{
         self.nodes.draw(frame);
     }
 
+    /// Selects node and returns scroll offset placing it in the middle of canvas view
+    pub fn focus_node(&mut self, index:usize)->Option<scrollable::AbsoluteOffset>{
+        let center = {
+            let node = self.nodes.nodes.get(index)?.borrow();
+            node.position+iced::Vector::new(node.size.width/2.0, node.size.height/2.0)
+        };
+        self.nodes.handle_message(&EditorCanvasMessage::Unselect);
+        self.nodes.handle_message(&EditorCanvasMessage::Select(index));
+        Some(scrollable::AbsoluteOffset{
+            x:(center.x-self.viewport_size.width/2.0).max(0.0),
+            y:(center.y-self.viewport_size.height/2.0).max(0.0),
+        })
+    }
+
     pub fn handle_message(&mut self,msg:&EditorCanvasMessage){
         match msg{
             EditorCanvasMessage::CancelPaste=>{
@@ -180,6 +198,7 @@ impl EditorState{
             },
             EditorCanvasMessage::CanvasScroll(v)=>{
                 self.scroll_offset = v.absolute_offset();
+                self.viewport_size = v.bounds().size();
             }
             _=>{
                 self.nodes.handle_message(msg)
@@ -273,7 +292,18 @@ impl<'a> canvas::Program<EditorCanvasMessage> for EditorProgram<'a>{
         if let Some(curpos) = cursor.position(){
             let curpos = iced::Point::new(curpos.x-bounds.x,curpos.y-bounds.y);
             match state{
-                EditorProgramState::Idle=>(),
+                EditorProgramState::Idle=>{
+                    if let Some(tooltip) = self.editor_state.nodes.diagnostics_at(curpos){
+                        let lines = tooltip.lines().count() as f32;
+                        let width = tooltip.lines().map(|x| x.len()).max().unwrap_or(0) as f32;
+                        let label = canvas::Text{content:tooltip, position:curpos+iced::Vector::new(15.0, 15.0), ..Default::default()};
+                        let size = iced::Size::new(width*label.size.0/2.0+10.0, lines*label.line_height.to_absolute(label.size).0+10.0);
+                        let rect = Path::rectangle(curpos+iced::Vector::new(10.0, 10.0), size);
+                        frame.fill(&rect, iced::Color::from_rgba(1.0, 0.9, 0.9, 1.0));
+                        frame.stroke(&rect, canvas::stroke::Stroke::default().with_color(iced::Color::from_rgb(1.0, 0.0, 0.0)).with_width(1.0));
+                        frame.fill_text(label);
+                    }
+                },
                 EditorProgramState::Dragging { index: _, start_position,cursor_start_position, size, can_delete:_ }=>{
                     let ghost_pos:iced::Point = *start_position+(curpos-*cursor_start_position);
                     let ghost = Path::rectangle(ghost_pos,*size);
@@ -0,0 +1,271 @@
+use hdf5::types::TypeDescriptor;
+use iced::Length;
+use iced::widget::scrollable::{self, Scrollbar};
+
+use super::messages::EditorMessage;
+
+/// Node reading signal and time from HDF5 file
+pub const READER_NODE:&str = "padamohdf5.file_reader_composed";
+/// Node providing file name to reader
+pub const FILENAME_NODE:&str = "padamocore.constant.string";
+
+/// Names of datasets commonly used by PADAMO files, most preferred first
+const SIGNAL_HINTS:[&str;3] = ["pdm", "signal", "data"];
+const TIME_HINTS:[&str;2] = ["unixtime", "time"];
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum EntryKind{
+    Group,
+    Dataset{shape:Vec<usize>, dtype:String, numeric:bool},
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub struct InspectorEntry{
+    /// Full path inside file without leading slash
+    pub path:String,
+    pub depth:usize,
+    pub kind:EntryKind,
+    pub attributes:Vec<(String,String)>,
+    /// Errors met while reading entry. Entry is shown with parts that could be read.
+    pub error:Option<String>,
+}
+
+impl InspectorEntry{
+    pub fn dataset(path:&str, shape:Vec<usize>, dtype:&str, numeric:bool)->Self{
+        Self { path: path.into(), depth: path.matches('/').count(), kind: EntryKind::Dataset { shape, dtype: dtype.into(), numeric }, attributes: Vec::new(), error: None }
+    }
+
+    /// Keeps error of `res` on entry
+    fn record<T>(&mut self, res:hdf5::Result<T>)->Option<T>{
+        match res {
+            Ok(v)=>Some(v),
+            Err(e)=>{
+                self.error = Some(match self.error.take() {
+                    Some(prev)=>format!("{}; {}", prev, e),
+                    None=>e.to_string(),
+                });
+                None
+            }
+        }
+    }
+
+    pub fn numeric_shape(&self)->Option<&[usize]>{
+        if let EntryKind::Dataset { shape, numeric:true, .. } = &self.kind{
+            Some(shape)
+        }
+        else{
+            None
+        }
+    }
+
+    fn name(&self)->&str{
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    fn label(&self)->String{
+        let indent = "  ".repeat(self.depth);
+        let label = match &self.kind {
+            EntryKind::Group=>format!("{}{}/", indent, self.name()),
+            EntryKind::Dataset { shape, dtype, .. }=>format!("{}{} {:?} {}", indent, self.name(), shape, dtype),
+        };
+        match &self.error {
+            Some(e)=>format!("{} <{}>", label, e),
+            None=>label,
+        }
+    }
+}
+
+/// Datasets used for signal (T,H,W) and its time axis
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct FileSchema{
+    pub signal:Option<String>,
+    pub time:Option<String>,
+}
+
+fn hint_rank(path:&str, hints:&[&str])->usize{
+    let name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    hints.iter().position(|x| name.contains(x)).unwrap_or(hints.len())
+}
+
+/// Signal is 3D numeric dataset with name resembling PADAMO signals, largest one otherwise.
+/// Time is 1D numeric dataset with the same length as signal.
+pub fn guess_schema(entries:&[InspectorEntry])->FileSchema{
+    let signal = entries.iter()
+        .filter_map(|x| x.numeric_shape().filter(|s| s.len()==3).map(|s| (x, s)))
+        .min_by_key(|(x,s)| (hint_rank(&x.path, &SIGNAL_HINTS), std::cmp::Reverse(s.iter().product::<usize>())));
+    let length = if let Some((_,shape)) = signal {shape[0]} else {return FileSchema::default()};
+    let time = guess_time(entries, length);
+    FileSchema { signal: signal.map(|(x,_)| x.path.clone()), time }
+}
+
+fn guess_time(entries:&[InspectorEntry], length:usize)->Option<String>{
+    entries.iter()
+        .filter(|x| x.numeric_shape()==Some(&[length]))
+        .min_by_key(|x| hint_rank(&x.path, &TIME_HINTS))
+        .map(|x| x.path.clone())
+}
+
+fn is_numeric(descriptor:&TypeDescriptor)->bool{
+    matches!(descriptor, TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) | TypeDescriptor::Float(_))
+}
+
+fn attribute_value(attr:&hdf5::Attribute)->String{
+    let descriptor = match attr.dtype().and_then(|x| x.to_descriptor()) {
+        Ok(v)=>v,
+        Err(e)=>return format!("<{}>", e),
+    };
+    let shape = attr.shape();
+    if !shape.is_empty(){
+        return format!("{} {:?}", descriptor, shape);
+    }
+    let value = match descriptor {
+        TypeDescriptor::VarLenUnicode=>attr.read_scalar::<hdf5::types::VarLenUnicode>().map(|x| x.to_string()),
+        TypeDescriptor::VarLenAscii=>attr.read_scalar::<hdf5::types::VarLenAscii>().map(|x| x.to_string()),
+        d if is_numeric(&d)=>attr.read_scalar::<f64>().map(|x| x.to_string()),
+        d=>Ok(d.to_string()),
+    };
+    value.unwrap_or_else(|e| format!("<{}>", e))
+}
+
+fn attributes(location:&hdf5::Location)->hdf5::Result<Vec<(String,String)>>{
+    let mut res = Vec::new();
+    for name in location.attr_names()?{
+        let value = location.attr(&name).map(|x| attribute_value(&x)).unwrap_or_else(|e| format!("<{}>", e));
+        res.push((name, value));
+    }
+    Ok(res)
+}
+
+fn strip_path(name:String)->String{
+    name.trim_start_matches('/').into()
+}
+
+/// Unreadable parts of entries are recorded on them, so one broken dataset does not hide the rest of file
+fn visit(group:&hdf5::Group, depth:usize, entries:&mut Vec<InspectorEntry>)->hdf5::Result<()>{
+    for dataset in group.datasets()?{
+        let mut entry = InspectorEntry {
+            path: strip_path(dataset.name()),
+            depth,
+            kind: EntryKind::Dataset { shape: dataset.shape(), dtype: "unknown".into(), numeric: false },
+            attributes: Vec::new(),
+            error: None,
+        };
+        if let Some(descriptor) = entry.record(dataset.dtype().and_then(|x| x.to_descriptor())){
+            entry.kind = EntryKind::Dataset { shape: dataset.shape(), dtype: descriptor.to_string(), numeric: is_numeric(&descriptor) };
+        }
+        entry.attributes = entry.record(attributes(&dataset)).unwrap_or_default();
+        entries.push(entry);
+    }
+    for subgroup in group.groups()?{
+        let mut entry = InspectorEntry { path: strip_path(subgroup.name()), depth, kind: EntryKind::Group, attributes: Vec::new(), error: None };
+        entry.attributes = entry.record(attributes(&subgroup)).unwrap_or_default();
+        let index = entries.len();
+        entries.push(entry);
+        let res = visit(&subgroup, depth+1, entries);
+        entries[index].record(res);
+    }
+    Ok(())
+}
+
+/// Contents of HDF5 file with datasets chosen for reader node
+pub struct HDF5Inspector{
+    pub filename:String,
+    pub entries:Vec<InspectorEntry>,
+    pub file_attributes:Vec<(String,String)>,
+    pub schema:FileSchema,
+}
+
+impl HDF5Inspector{
+    pub fn inspect(filename:&str)->hdf5::Result<Self>{
+        let file = hdf5::File::open(filename)?;
+        let mut entries = Vec::new();
+        visit(&file, 0, &mut entries)?;
+        let schema = guess_schema(&entries);
+        let file_attributes = attributes(&file).unwrap_or_else(|e| vec![("error".into(), format!("<{}>", e))]);
+        Ok(Self { filename: filename.into(), file_attributes, entries, schema })
+    }
+
+    /// Picks 3D dataset as signal and 1D dataset as time
+    pub fn select(&mut self, index:usize){
+        let entry = if let Some(v) = self.entries.get(index) {v} else {return};
+        match entry.numeric_shape().map(|x| x.len()) {
+            Some(3)=>{
+                let length = entry.numeric_shape().unwrap()[0];
+                self.schema.signal = Some(entry.path.clone());
+                let time_fits = self.schema.time.as_ref()
+                    .map(|t| self.entries.iter().any(|x| &x.path==t && x.numeric_shape()==Some(&[length])))
+                    .unwrap_or(false);
+                if !time_fits{
+                    self.schema.time = guess_time(&self.entries, length);
+                }
+            },
+            Some(1)=>self.schema.time = Some(entry.path.clone()),
+            _=>(),
+        }
+    }
+
+    pub fn view(&self)->iced::Element<'_, EditorMessage>{
+        let describe = |x:&Option<String>| x.clone().unwrap_or("not found".into());
+        let mut list = iced::widget::Column::new().spacing(2);
+        for (key, value) in self.file_attributes.iter(){
+            list = list.push(iced::widget::text(format!("@{} = {}", key, value)).size(12));
+        }
+        for (i, entry) in self.entries.iter().enumerate(){
+            let selectable = matches!(entry.numeric_shape().map(|x| x.len()), Some(1) | Some(3));
+            let chosen = Some(&entry.path)==self.schema.signal.as_ref() || Some(&entry.path)==self.schema.time.as_ref();
+            let label = if chosen {format!("{} *", entry.label())} else {entry.label()};
+            let button = iced::widget::button(iced::widget::text(label)).style(iced::widget::button::text).padding(0);
+            list = list.push(if selectable {button.on_press(EditorMessage::InspectorSelect(i))} else {button});
+            let indent = "  ".repeat(entry.depth+1);
+            for (key, value) in entry.attributes.iter(){
+                list = list.push(iced::widget::text(format!("{}@{} = {}", indent, key, value)).size(12));
+            }
+        }
+        let insert = iced::widget::button("Insert reader node").width(Length::Fill);
+        let insert = if self.schema.signal.is_some() && self.schema.time.is_some() {insert.on_press(EditorMessage::InspectorInsert)} else {insert};
+        iced::widget::column![
+            iced::widget::text(&self.filename),
+            iced::widget::text(format!("Signal: {}", describe(&self.schema.signal))),
+            iced::widget::text(format!("Time: {}", describe(&self.schema.time))),
+            insert,
+            iced::widget::button("Close").on_press(EditorMessage::InspectorClose).width(Length::Fill),
+            scrollable::Scrollable::new(list)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .direction(scrollable::Direction::Both { vertical: Scrollbar::new().width(10), horizontal: Scrollbar::new() }),
+        ].spacing(5).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guess_schema() {
+        let entries = vec![
+            InspectorEntry::dataset("raw", vec![100,16,16], "u16", true),
+            InspectorEntry::dataset("pdm_2d_rot_global", vec![50,48,48], "f64", true),
+            InspectorEntry::dataset("gtu", vec![50], "u32", true),
+            InspectorEntry::dataset("unixtime_dbl_global", vec![50], "f64", true),
+            InspectorEntry::dataset("labels", vec![50,48,48], "string", false),
+        ];
+        let schema = guess_schema(&entries);
+        assert_eq!(schema.signal.as_deref(), Some("pdm_2d_rot_global"));
+        assert_eq!(schema.time.as_deref(), Some("unixtime_dbl_global"));
+
+        let schema = guess_schema(&entries[..1]);
+        assert_eq!(schema.signal.as_deref(), Some("raw"));
+        assert_eq!(schema.time, None);
+    }
+
+    #[test]
+    fn test_entry_errors() {
+        let mut entry = InspectorEntry::dataset("group/broken", vec![10], "unknown", false);
+        assert_eq!(entry.record(Ok(1)), Some(1));
+        assert_eq!(entry.record::<()>(Err("bad type".into())), None);
+        assert_eq!(entry.record::<()>(Err("bad attribute".into())), None);
+        assert_eq!(entry.error.as_deref(), Some("bad type; bad attribute"));
+        assert!(entry.label().ends_with("<bad type; bad attribute>"));
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+
+use super::messages::EditorCanvasMessage;
+use super::nodes::constants::NodeConstantMessageContent;
+
+pub const HISTORY_LIMIT:usize = 100;
+
+/// How canvas message changes graph
+#[derive(Clone,Debug,PartialEq)]
+pub enum EditKind{
+    /// Selection, scrolling and other messages not changing graph
+    Unchanged,
+    /// Message is a whole edit
+    Single,
+    /// Message is a part of edit made by several messages with same key. Typing emits edit for every keystroke.
+    Continued(String),
+}
+
+pub fn edit_kind(msg:&EditorCanvasMessage)->EditKind{
+    match msg{
+        EditorCanvasMessage::MoveNode { .. }
+        | EditorCanvasMessage::LinkNode { .. }
+        | EditorCanvasMessage::UnlinkOutput { .. }
+        | EditorCanvasMessage::UnlinkInput { .. }
+        | EditorCanvasMessage::DeleteSelectedNode
+        | EditorCanvasMessage::CommitPaste(_)=>EditKind::Single,
+        EditorCanvasMessage::ConstantEdit(c)=>{
+            if let NodeConstantMessageContent::Text(_) = c.value{
+                EditKind::Continued(format!("constant:{}",c.key))
+            }
+            else{
+                EditKind::Single
+            }
+        }
+        EditorCanvasMessage::Unselect
+        | EditorCanvasMessage::SquareSelect(..)
+        | EditorCanvasMessage::Select(_)
+        | EditorCanvasMessage::SetShift(_)
+        | EditorCanvasMessage::CancelPaste
+        | EditorCanvasMessage::CanvasScroll(_)=>EditKind::Unchanged,
+    }
+}
+
+/// Bounded undo/redo stack of editor states.
+#[derive(Clone,Debug)]
+pub struct EditHistory<T:Clone+PartialEq>{
+    undo_stack:VecDeque<T>,
+    redo_stack:Vec<T>,
+    current:Option<T>,
+    limit:usize,
+}
+
+impl<T:Clone+PartialEq> EditHistory<T>{
+    pub fn new(limit:usize)->Self{
+        Self { undo_stack: VecDeque::new(), redo_stack: Vec::new(), current: None, limit }
+    }
+
+    /// Forgets all history. Used when graph is loaded.
+    pub fn reset(&mut self, state:T){
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.current = Some(state);
+    }
+
+    /// Records state after finished edit. Unchanged states are ignored.
+    pub fn record(&mut self, state:T){
+        if self.current.as_ref()==Some(&state){
+            return;
+        }
+        self.redo_stack.clear();
+        if let Some(prev) = self.current.replace(state){
+            self.undo_stack.push_back(prev);
+            while self.undo_stack.len()>self.limit{
+                self.undo_stack.pop_front();
+            }
+        }
+    }
+
+    pub fn undo(&mut self)->Option<T>{
+        let prev = self.undo_stack.pop_back()?;
+        if let Some(cur) = self.current.replace(prev.clone()){
+            self.redo_stack.push(cur);
+        }
+        Some(prev)
+    }
+
+    pub fn redo(&mut self)->Option<T>{
+        let next = self.redo_stack.pop()?;
+        if let Some(cur) = self.current.replace(next.clone()){
+            self.undo_stack.push_back(cur);
+        }
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn test_undo_redo(){
+        let mut history = EditHistory::new(2);
+        history.reset(0);
+        history.record(1);
+        history.record(2);
+        history.record(2);
+        history.record(3);
+
+        // Limit drops the oldest state
+        assert_eq!(history.undo(), Some(2));
+        assert_eq!(history.undo(), Some(1));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(2));
+
+        // New edit drops redo
+        history.record(5);
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some(2));
+    }
+}
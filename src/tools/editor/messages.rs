@@ -29,5 +29,13 @@ pub enum EditorMessage{
     EditorScroll(scrollable::Viewport),
     CompileGraph,
     Search(String),
+    SubgraphName(String),
+    SubgraphPromote(bool),
+    MakeSubgraph,
+    StopRun,
+    InspectHDF5,
+    InspectorSelect(usize),
+    InspectorInsert,
+    InspectorClose,
 }
 
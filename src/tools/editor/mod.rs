@@ -3,12 +3,14 @@ pub mod nodes;
 use std::collections::HashMap;
 use std::io::Read;
 use std::rc::Rc;
+use std::thread;
 use crate::application::{PadamoState, PadamoStateRef};
 //use crate::custom_widgets::treeview::TreeView;
 use crate::messages::PadamoAppMessage;
 
 use crate::custom_widgets::treeview::Tree;
-use crate::tools::editor::nodes::{GraphNodeCloneBuffer, GraphNodeStorage};
+use crate::tools::editor::nodes::{GraphNode, GraphNodeCloneBuffer, GraphNodeStorage};
+use crate::tools::editor::nodes::constants::{NodeConstantMessage, NodeConstantMessageContent};
 
 use self::messages::EditorMessage;
 
@@ -18,8 +20,13 @@ use iced::{Element, Length};
 use iced::widget::scrollable::{self, Scrollbar};
 use iced::widget::pane_grid;
 use padamo_workspace::PadamoWorkspace;
+use padamo_api::calculation_nodes::subgraph::{PromotedConstant, SubgraphDefinition, SubgraphLibrary};
+use padamo_api::calculation_nodes::graph::CalculationSequenceStorage;
+use padamo_api::prelude::{ExecutionError, ProgressToken};
 pub mod messages;
 pub mod clipboard;
+pub mod history;
+pub mod hdf5_inspector;
 use crate::detector_muxer::{get_mask_var, get_mask_var_by_name, get_signal_var, get_signal_var_by_name, get_transform_var, get_transform_var_by_name, VIEWER_TEST_OBJECT_KEY};
 
 // static SCROLLABLE_ID: Lazy<scrollable::Id> = Lazy::new(scrollable::Id::unique);
@@ -30,6 +37,10 @@ fn make_workspace(workspace:&PadamoWorkspace)->padamo_workspace::PadamoSubWorksp
     .with_action(crate::assets::copy_asset_action("default.json"))
 }
 
+fn subgraph_library_path(workspace:&PadamoWorkspace)->Option<std::path::PathBuf>{
+    workspace.workspace("subgraphs").subdir().map(|x| x.join("library.json"))
+}
+
 pub enum Pane{
     NodeTree,
     CanvasEditor,
@@ -43,6 +54,19 @@ pub struct PadamoEditor{
     panes: pane_grid::State<Pane>,
     current_scroll_offset: scrollable::RelativeOffset,
     search: String,
+    subgraph_name: String,
+    subgraph_promote: bool,
+    history: history::EditHistory<serde_json::Value>,
+    /// Key of edit made by several messages which is not recorded in history yet
+    pending_edit: Option<String>,
+    execution: Option<GraphExecution>,
+    inspector: Option<hdf5_inspector::HDF5Inspector>,
+}
+
+/// Graph being executed in background thread
+struct GraphExecution{
+    handle: thread::JoinHandle<(CalculationSequenceStorage, Result<(),ExecutionError>)>,
+    progress: ProgressToken,
 }
 
 impl PadamoEditor{
@@ -57,12 +81,32 @@ impl PadamoEditor{
         Self{
             state: editor_program::EditorState::new(),
             search:Default::default(),
+            subgraph_name:Default::default(),
+            subgraph_promote:false,
+            history: history::EditHistory::new(history::HISTORY_LIMIT),
+            pending_edit: None,
+            execution: None,
+            inspector: None,
             current_scroll_offset: scrollable::RelativeOffset::START,
             tree, panes,
         }
     }
 
-    fn run(&self,padamo:&mut PadamoState){
+    fn run(&mut self,padamo:&mut PadamoState){
+        if self.execution.is_some(){
+            padamo.show_info("Graph is already running");
+            return;
+        }
+        let diagnostics = padamo.nodes.validate_graph(&self.state.nodes);
+        if !diagnostics.is_empty(){
+            let lines:Vec<String> = diagnostics.iter().map(|x| x.to_string()).collect();
+            let first = diagnostics[0].node_index;
+            self.state.nodes.set_diagnostics(diagnostics);
+            padamo.show_error_with_action(format!("Graph is invalid:\n{}",lines.join("\n")), "Show node", PadamoAppMessage::FocusGraphNode(first));
+            return;
+        }
+        self.state.nodes.set_diagnostics(Vec::new());
+
         let mut x_mut = &mut padamo.compute_graph;
         padamo.nodes.make_compute_graph(&mut x_mut, &self.state.nodes);
         for i in 0..padamo.detectors.len(){
@@ -76,15 +120,71 @@ impl PadamoEditor{
             x_mut.environment.0.remove(get_transform_var_by_name(&nick).as_str());
         }
         x_mut.environment.0.remove(VIEWER_TEST_OBJECT_KEY);
+        // Writers attach graph to output files
+        let graph_json = serde_json::to_string(&self.state.nodes.serialize_to_value()).unwrap_or_default();
+        let seed = padamo.current_seed.parsed_value;
+        x_mut.set_provenance(&graph_json, env!("CARGO_PKG_VERSION"), seed);
+
+        // Graph runs in background. UI keeps its own copy of environment meanwhile.
+        let mut storage = std::mem::replace(x_mut, CalculationSequenceStorage::new());
+        x_mut.environment = storage.environment.clone();
+        storage.progress.reset();
+        let progress = storage.progress.clone();
+        let detectors = padamo.detectors.get_detectors().clone();
+        let handle = thread::spawn(move ||{
+            let res = storage.execute(seed, &detectors);
+            (storage, res)
+        });
+        self.execution = Some(GraphExecution { handle, progress });
+    }
 
-        if let Err(err) = x_mut.execute(padamo.current_seed.parsed_value,padamo.detectors.get_detectors()){
-            padamo.show_error(format!("Execution error: {}",err));
-            //println!("Execution error: {}",err);
+    fn stop_run(&mut self){
+        if let Some(execution) = &self.execution{
+            execution.progress.cancel();
         }
-        else{
-            println!("Execution success");
+    }
+
+    /// Takes results of background execution if it is finished.
+    /// Returns message notifying other tools that new data is available.
+    fn poll_run(&mut self, padamo:&mut PadamoState)->Option<PadamoAppMessage>{
+        let execution = self.execution.take()?;
+        if !execution.handle.is_finished(){
+            self.execution = Some(execution);
+            return None;
+        }
+        let (storage, result) = match execution.handle.join(){
+            Ok(v)=>v,
+            Err(_)=>{
+                padamo.show_error("Graph execution panicked");
+                return None;
+            }
+        };
+        padamo.compute_graph = storage;
+
+        match result{
+            Err(err) if err.is_cancelled()=>{
+                padamo.show_info("Execution is cancelled");
+            }
+            Err(err)=>{
+                if let Some(context) = err.node_context(){
+                    let index = context.node_index;
+                    padamo.show_error_with_action(format!("Execution error: {}",err), "Show node", PadamoAppMessage::FocusGraphNode(index));
+                }
+                else{
+                    padamo.show_error(format!("Execution error: {}",err));
+                }
+                //println!("Execution error: {}",err);
+            }
+            Ok(())=>{
+                println!("Execution success");
+            }
         }
+        self.redirect_detector_vars(padamo);
+        Some(PadamoAppMessage::Executed)
+    }
 
+    /// Copies detector variables to the ones named by detector nickname
+    fn redirect_detector_vars(&self, padamo:&mut PadamoState){
         // Postprocessing
 
         let mut swap_map = Vec::new();
@@ -130,6 +230,7 @@ impl PadamoEditor{
                 self.state.nodes.clear();
             }
         }
+        self.reset_history();
     }
 
     fn try_save_to_string(&self) ->Option<String>{
@@ -157,6 +258,207 @@ impl PadamoEditor{
             padamo.persistent_state.write("last_graph", &s);
         }
     }
+
+    fn graph_state(&self)->serde_json::Value{
+        serde_json::to_value(self.state.nodes.serialize_to_value()).unwrap_or_default()
+    }
+
+    fn reset_history(&mut self){
+        let state = self.graph_state();
+        self.history.reset(state);
+        self.pending_edit = None;
+    }
+
+    fn record_history(&mut self){
+        let state = self.graph_state();
+        self.history.record(state);
+    }
+
+    /// Records edit made by several messages, e.g. typing constant value
+    fn finish_edit(&mut self, padamo: PadamoStateRef){
+        if self.pending_edit.take().is_some(){
+            self.record_history();
+            self.snapshot(padamo);
+        }
+    }
+
+    fn handle_canvas_message(&mut self, padamo: PadamoStateRef, msg:&messages::EditorCanvasMessage){
+        let kind = history::edit_kind(msg);
+        let continues_pending = matches!(&kind, history::EditKind::Continued(key) if self.pending_edit.as_ref()==Some(key));
+        if !continues_pending{
+            self.finish_edit(padamo);
+        }
+        let had_diagnostics = self.state.nodes.has_diagnostics();
+        self.state.handle_message(msg);
+        self.refresh_diagnostics(padamo, had_diagnostics);
+        match kind{
+            history::EditKind::Unchanged=>(),
+            history::EditKind::Single=>{
+                self.record_history();
+                self.snapshot(padamo);
+            }
+            history::EditKind::Continued(key)=>self.pending_edit = Some(key),
+        }
+    }
+
+    fn restore_state(&mut self, padamo: PadamoStateRef, state:serde_json::Value){
+        let had_diagnostics = self.state.nodes.has_diagnostics();
+        match serde_json::from_value(state){
+            Ok(v)=>{
+                if let Err(e) = self.state.nodes.deserialize_from_value(&padamo.nodes, v){
+                    padamo.show_error(format!("{}",e));
+                }
+            }
+            Err(e)=>padamo.show_error(format!("Cannot restore graph: {}",e)),
+        }
+        self.refresh_diagnostics(padamo, had_diagnostics);
+        self.snapshot(padamo);
+    }
+
+    /// Revalidates graph if last run found problems so highlights follow edits
+    fn refresh_diagnostics(&mut self, padamo: PadamoStateRef, had_diagnostics:bool){
+        if had_diagnostics{
+            let diagnostics = padamo.nodes.validate_graph(&self.state.nodes);
+            self.state.nodes.set_diagnostics(diagnostics);
+        }
+    }
+
+    fn load_subgraphs(&mut self, padamo: PadamoStateRef){
+        if let Some(path) = subgraph_library_path(&padamo.workspace){
+            match SubgraphLibrary::load(&path){
+                Ok(library)=>{
+                    for definition in library.0.into_iter(){
+                        if let Err(e) = padamo.nodes.register_subgraph(definition){
+                            padamo.show_error(format!("{}",e));
+                        }
+                    }
+                }
+                Err(e)=>padamo.show_error(format!("Cannot load subgraph library: {}",e)),
+            }
+        }
+        self.tree = padamo.nodes.make_tree();
+    }
+
+    fn open_inspector(&mut self, padamo: PadamoStateRef){
+        if let Some(path) = padamo.workspace.workspace("viewed_hdf5_data").open_dialog(vec![("HDF5 data",vec!["h5"])]){
+            match hdf5_inspector::HDF5Inspector::inspect(&path) {
+                Ok(v)=>self.inspector = Some(v),
+                Err(e)=>padamo.show_error(format!("Cannot inspect {}: {}", path, e)),
+            }
+        }
+    }
+
+    /// Pastes HDF5 reader with datasets chosen in inspector and file name constant connected to it
+    fn insert_reader_node(&mut self, padamo: PadamoStateRef){
+        let inspector = if let Some(v) = &self.inspector {v} else {return};
+        let (signal, time) = if let (Some(s), Some(t)) = (&inspector.schema.signal, &inspector.schema.time) {(s, t)} else {return};
+        let filename_node = padamo.nodes.create_calculation_node(hdf5_inspector::FILENAME_NODE.into());
+        let reader_node = padamo.nodes.create_calculation_node(hdf5_inspector::READER_NODE.into());
+        let (mut filename_node, mut reader_node) = if let (Some(f), Some(r)) = (filename_node, reader_node) {(f, r)}
+        else{
+            padamo.show_error("HDF5 reader nodes are not loaded");
+            return;
+        };
+
+        let set_text = |node:&mut GraphNode, key:&str, value:&str|{
+            node.constants.modify_constant(NodeConstantMessage{key:key.into(), value:NodeConstantMessageContent::Text(value.into())})
+        };
+        let res = set_text(&mut filename_node, "Value", &inspector.filename)
+            .and_then(|_| set_text(&mut reader_node, "Field", signal))
+            .and_then(|_| set_text(&mut reader_node, "Temporal", time));
+        if let Err(e) = res{
+            padamo.show_error(format!("{}",e));
+            return;
+        }
+        filename_node.reestimate_size();
+        reader_node.reestimate_size();
+        reader_node.position = iced::Point::new(filename_node.size.width+40.0, 0.0);
+
+        let offset = iced::Point::new(filename_node.size.width/2.0, filename_node.size.height/2.0);
+        let mut storage = GraphNodeStorage::new();
+        storage.insert_node(filename_node);
+        storage.insert_node(reader_node);
+        let connections = HashMap::from([(1, HashMap::from([("Filename".to_string(), (0, "Value".to_string()))]))]);
+        let buffer = GraphNodeCloneBuffer{storage, offset, connections};
+        *self.state.pending_paste.borrow_mut() = Some(Rc::new(buffer));
+        self.inspector = None;
+    }
+
+    /// Collapses selected nodes into subgraph node and stores it in library
+    fn make_subgraph(&mut self, padamo: PadamoStateRef){
+        let name = self.subgraph_name.trim().to_string();
+        if name.is_empty(){
+            padamo.show_error("Subgraph name is empty");
+            return;
+        }
+        let buffer = if let Some(b) = self.state.nodes.clone_selection() {b}
+        else{
+            padamo.show_error("No nodes selected");
+            return;
+        };
+        // Subgraph ports are defined by environment nodes inside it, so links crossing selection cannot be kept
+        if self.state.nodes.selection_has_external_links(){
+            padamo.show_error("Selected nodes are linked with other nodes. Pass such values through environment nodes instead");
+            return;
+        }
+
+        let mut inner = GraphNodeStorage::new();
+        inner.instantiate(&buffer, buffer.offset);
+        let graph = padamo.nodes.compile_graph(&inner);
+
+        let mut promoted_constants = Vec::new();
+        if self.subgraph_promote{
+            for (i,node) in inner.nodes.iter().enumerate(){
+                let node_ref = node.borrow();
+                // Keys of environment nodes define ports of subgraph
+                if node_ref.represented_node.identifier().starts_with("padamocore.env_"){
+                    continue;
+                }
+                for (key,c) in node_ref.constants.constants.iter(){
+                    if c.use_external{
+                        continue;
+                    }
+                    promoted_constants.push(PromotedConstant{
+                        node_index:i,
+                        key:key.clone(),
+                        name:format!("{}.{}",i,key),
+                        display_name:format!("{}: {}",node_ref.represented_node.title(),c.display_name),
+                        default_value:c.content.clone().into(),
+                    });
+                }
+            }
+        }
+
+        let definition = SubgraphDefinition{name, graph, promoted_constants};
+        let identifier = definition.identifier();
+        if let Err(e) = padamo.nodes.register_subgraph(definition.clone()){
+            padamo.show_error(format!("{}",e));
+            return;
+        }
+        if let Some(path) = subgraph_library_path(&padamo.workspace){
+            let res = SubgraphLibrary::load(&path).and_then(|mut library|{
+                library.insert(definition.clone());
+                library.save(&path)
+            });
+            if let Err(e) = res{
+                padamo.show_error(format!("Cannot save subgraph library: {}",e));
+            }
+        }
+
+        self.finish_edit(padamo);
+        self.state.nodes.delete_selected_nodes();
+        if let Some(mut node) = padamo.nodes.create_calculation_node(identifier){
+            let x = (buffer.offset.x-node.size.width/2.0).max(0.0);
+            let y = (buffer.offset.y-node.size.height/2.0).max(0.0);
+            node.position = iced::Point::new(x, y);
+            self.state.nodes.insert_node(node);
+        }
+        self.tree = padamo.nodes.make_tree();
+        padamo.compute_graph.clear_cache();
+        self.record_history();
+        self.snapshot(padamo);
+        padamo.show_info(format!("Subgraph {} is created", definition.name));
+    }
 }
 
 impl PadamoTool for PadamoEditor{
@@ -190,14 +492,34 @@ impl PadamoTool for PadamoEditor{
                 .height(Length::Fill)
                 .direction(scrollable::Direction::Vertical(Scrollbar::new().width(10).anchor(scrollable::Anchor::Start)))
                 .on_scroll(messages::EditorMessage::EditorScroll),
-
+                iced::widget::button("Inspect HDF5 file").on_press(EditorMessage::InspectHDF5).width(iced::Length::Fill),
             ].into();
+            let first = if let Some(inspector) = &self.inspector {inspector.view()} else {first};
             let (second, third) = self.state.view();
             let second = second.map(messages::EditorMessage::CanvasMessage);
             let third = third.map(messages::EditorMessage::CanvasMessage);
+            let run_status:Element<EditorMessage> = if let Some(execution) = &self.execution{
+                let status:Element<EditorMessage> = if let Some(v) = execution.progress.fraction(){
+                    iced::widget::progress_bar(0.0..=1.0, v).girth(20).into()
+                }
+                else{
+                    iced::widget::text("Running...").into()
+                };
+                iced::widget::row![
+                    status,
+                    iced::widget::button("Stop").on_press(EditorMessage::StopRun),
+                ].spacing(5).into()
+            }
+            else{
+                iced::widget::text("Idle").into()
+            };
             let third:Element<EditorMessage> = iced::widget::column![
+                run_status,
                 third,
-                iced::widget::button("Export compiled graph").on_press(EditorMessage::CompileGraph).width(iced::Length::Fill)
+                iced::widget::button("Export compiled graph").on_press(EditorMessage::CompileGraph).width(iced::Length::Fill),
+                iced::widget::text_input("Subgraph name", &self.subgraph_name).on_input(EditorMessage::SubgraphName),
+                iced::widget::Checkbox::new(self.subgraph_promote).label("Promote constants").on_toggle(EditorMessage::SubgraphPromote),
+                iced::widget::button("Make subgraph from selection").on_press(EditorMessage::MakeSubgraph).width(iced::Length::Fill),
             ].into();
 
             match  pane{
@@ -221,6 +543,7 @@ impl PadamoTool for PadamoEditor{
     }
 
     fn initialize(&mut self, padamo:crate::application::PadamoStateRef) {
+        self.load_subgraphs(padamo);
         if let Some(v) = padamo.persistent_state.read("last_graph"){
             self.try_load_from_string(&v, padamo);
         }
@@ -231,6 +554,7 @@ impl PadamoTool for PadamoEditor{
 
         else{
             self.state = editor_program::EditorState::new();
+            self.reset_history();
         }
     }
 
@@ -239,8 +563,7 @@ impl PadamoTool for PadamoEditor{
             crate::messages::PadamoAppMessage::EditorMessage(emsg) =>{
                 match emsg {
                     messages::EditorMessage::CanvasMessage(msg) => {
-                        self.state.handle_message(msg);
-                        self.snapshot(padamo);
+                        self.handle_canvas_message(padamo, msg);
                     },
 
                     messages::EditorMessage::PaneDrag(pane_grid::DragEvent::Dropped {pane, target})=>{
@@ -284,6 +607,32 @@ impl PadamoTool for PadamoEditor{
                     messages::EditorMessage::Search(s)=>{
                         self.search = s.into();
                     }
+                    messages::EditorMessage::SubgraphName(s)=>{
+                        self.subgraph_name = s.clone();
+                    }
+                    messages::EditorMessage::SubgraphPromote(v)=>{
+                        self.subgraph_promote = *v;
+                    }
+                    messages::EditorMessage::MakeSubgraph=>{
+                        self.make_subgraph(padamo);
+                    }
+                    messages::EditorMessage::StopRun=>{
+                        self.stop_run();
+                    }
+                    messages::EditorMessage::InspectHDF5=>{
+                        self.open_inspector(padamo);
+                    }
+                    messages::EditorMessage::InspectorSelect(index)=>{
+                        if let Some(inspector) = &mut self.inspector{
+                            inspector.select(*index);
+                        }
+                    }
+                    messages::EditorMessage::InspectorInsert=>{
+                        self.insert_reader_node(padamo);
+                    }
+                    messages::EditorMessage::InspectorClose=>{
+                        self.inspector = None;
+                    }
                     _=>()
                 }
             },
@@ -297,10 +646,31 @@ impl PadamoTool for PadamoEditor{
                 padamo.reroll();
                 self.run(padamo);
             },
+            crate::messages::PadamoAppMessage::FullRun=>{
+                padamo.compute_graph.clear_cache();
+                self.run(padamo);
+            },
+            crate::messages::PadamoAppMessage::StopRun=>{
+                self.stop_run();
+            },
+            crate::messages::PadamoAppMessage::FocusGraphNode(index)=>{
+                if let Some(offset) = self.state.focus_node(*index){
+                    padamo.run_task(iced::widget::operation::scroll_to(editor_program::CANVAS_SCROLLABLE_ID, offset));
+                }
+            },
             _=>()
         }
 
     }
+    fn late_update(&mut self, msg: Rc<crate::messages::PadamoAppMessage>, padamo:crate::application::PadamoStateRef)->Option<PadamoAppMessage> {
+        if let crate::messages::PadamoAppMessage::Tick = msg.as_ref(){
+            self.poll_run(padamo)
+        }
+        else{
+            None
+        }
+    }
+
     fn context_update(&mut self, msg: Rc<crate::messages::PadamoAppMessage>, padamo:crate::application::PadamoStateRef) {
         match msg.as_ref() {
             crate::messages::PadamoAppMessage::Save =>{
@@ -343,6 +713,18 @@ impl PadamoTool for PadamoEditor{
             crate::messages::PadamoAppMessage::SelectAll=>{
                 self.state.nodes.select_all();
             }
+            crate::messages::PadamoAppMessage::Undo=>{
+                self.finish_edit(padamo);
+                if let Some(state) = self.history.undo(){
+                    self.restore_state(padamo, state);
+                }
+            }
+            crate::messages::PadamoAppMessage::Redo=>{
+                self.finish_edit(padamo);
+                if let Some(state) = self.history.redo(){
+                    self.restore_state(padamo, state);
+                }
+            }
             _=>(),
         }
 
@@ -1,7 +1,7 @@
 use super::errors::NodeError;
 use ordered_hash_map::OrderedHashMap;
 use padamo_api::calculation_nodes::content::ContentType;
-use serde::{Serialize,Deserialize};
+pub use padamo_api::calculation_nodes::graph_file::NodeConstantContent;
 
 
 #[derive(Clone,Debug,PartialEq)]
@@ -48,62 +48,6 @@ impl NodeConstantMessage{
 
 
 
-#[derive(Clone,Debug,PartialEq,Serialize,Deserialize)]
-pub enum NodeConstantContent{
-    Boolean(bool),
-    Text(String),
-    Integer(i64),
-    Real(f64)
-}
-
-
-
-
-impl NodeConstantContent{
-    pub fn is_compatible(&self, other:&Self)->bool{
-        match (self,other) {
-            (Self::Boolean(_),Self::Boolean(_))=>true,
-            (Self::Text(_),Self::Text(_))=>true,
-            (Self::Integer(_),Self::Integer(_))=>true,
-            (Self::Real(_),Self::Real(_))=>true,
-            _=>false,
-        }
-    }
-
-    pub fn commit_changes(&mut self, other:&Self)->Result<(),NodeError>{
-        if self.is_compatible(other){
-            *self = other.clone();
-            Ok(())
-        }
-        else{
-            Err(NodeError::IncompatibleConstants)
-        }
-    }
-}
-
-impl Into<NodeConstantContent> for padamo_api::prelude::ConstantContent{
-    fn into(self) -> NodeConstantContent {
-        match self {
-            Self::Integer(x)=>NodeConstantContent::Integer(x),
-            Self::Boolean(b)=>NodeConstantContent::Boolean(b),
-            Self::Float(f)=>NodeConstantContent::Real(f),
-            Self::String(s)=>NodeConstantContent::Text(s.into_string()),
-        }
-    }
-}
-
-impl Into<padamo_api::prelude::ConstantContent> for NodeConstantContent{
-    fn into(self) -> padamo_api::prelude::ConstantContent {
-        match self{
-            Self::Boolean(b)=>padamo_api::prelude::ConstantContent::Boolean(b),
-            Self::Real(f)=>padamo_api::prelude::ConstantContent::Float(f),
-            Self::Integer(i)=>padamo_api::prelude::ConstantContent::Integer(i),
-            Self::Text(s)=>padamo_api::prelude::ConstantContent::String(s.into()),
-        }
-    }
-}
-
-
 #[derive(Clone,Debug,PartialEq)]
 pub struct NodeConstant{
     pub buffer:NodeConstantBuffer,
@@ -186,7 +130,7 @@ impl NodeConstant{
             }
         }
         if !self.ok{
-            self.content.commit_changes(&self.default_value).unwrap();
+            self.content = self.default_value.clone();
         }
     }
 }
@@ -244,49 +188,6 @@ impl NodeConstantStorage{
 }
 
 
-impl Into<NodeConstantContent> for &str{
-    fn into(self) -> NodeConstantContent {
-        NodeConstantContent::Text(self.into())
-    }
-}
-
-impl Into<NodeConstantContent> for String{
-    fn into(self) -> NodeConstantContent {
-        NodeConstantContent::Text(self)
-    }
-}
-
-impl Into<NodeConstantContent> for f64{
-    fn into(self) -> NodeConstantContent {
-        NodeConstantContent::Real(self)
-    }
-}
-
-impl Into<NodeConstantContent> for i64{
-    fn into(self) -> NodeConstantContent {
-        NodeConstantContent::Integer(self)
-    }
-}
-
-impl Into<NodeConstantContent> for bool{
-    fn into(self)->NodeConstantContent{
-        NodeConstantContent::Boolean(self)
-    }
-}
-
-
-impl Into<String> for NodeConstantContent{
-    fn into(self) -> String {
-        match self {
-            NodeConstantContent::Boolean(x) => x.to_string(),
-            NodeConstantContent::Text(x) => x,
-            NodeConstantContent::Integer(x) => x.to_string(),
-            NodeConstantContent::Real(x) => x.to_string(),
-        }
-    }
-}
-
-
 impl Into<NodeConstantMessageContent> for NodeConstantContent{
     fn into(self) -> NodeConstantMessageContent {
         match self {
@@ -8,7 +8,6 @@ pub enum NodeError{
     SameNodeLink,
     NodeIndexError(usize),
     IncompatiblePorts(super::PortType,super::PortType),
-    IncompatibleConstants,
     MissingConstant(String)
 }
 
@@ -20,7 +19,6 @@ impl Display for NodeError{
             NodeError::SameNodeLink=>write!(f, "Cannot link to same node"),
             NodeError::NodeIndexError(x)=>write!(f,"No such node with index {}", x),
             NodeError::IncompatiblePorts(x, y)=>write!(f,"Incompatible port types: {:?} and {:?}", x, y),
-            NodeError::IncompatibleConstants=> write!(f,"Incompatible constants"),
             NodeError::MissingConstant(x) => write!(f,"No constant named {}", x),
         }
     }
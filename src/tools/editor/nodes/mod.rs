@@ -7,6 +7,7 @@ use std::{cell::RefCell, collections::HashMap, error::Error, fmt::Display, rc::{
 
 use iced::widget::canvas::{Frame, Path, self,Text};
 use padamo_api::calculation_nodes::node::CalculationNodeBox;
+use padamo_api::calculation_nodes::validation::Diagnostic;
 use serde::{Deserialize, Serialize};
 
 use crate::nodes_interconnect::NodesRegistry;
@@ -21,8 +22,9 @@ use node_proxy::NodeProxy;
 
 const PORT_SIZE:f32 = 20.0;
 const PORT_INTERVAL:f32 = 5.0;
+const ERROR_COLOR:iced::Color = iced::Color::from_rgb(1.0, 0.0, 0.0);
 
-use serialization::{SerdePoint,SerdeConnection};
+use serialization::SerdeConnection;
 
 pub const PORT_CENTER_OFFSET: iced::Vector = iced::Vector::new(PORT_SIZE*0.5,PORT_SIZE*0.5);
 
@@ -120,7 +122,9 @@ pub struct GraphNode{
 
     pub represented_node:NodeProxy,
     pub connections: OrderedHashMap<String,Connection>,
-    pub constants:NodeConstantStorage
+    pub constants:NodeConstantStorage,
+    /// Problems found by last graph validation
+    pub diagnostics:Vec<Diagnostic>,
 }
 
 
@@ -148,6 +152,7 @@ impl GraphNode{
             constants: NodeConstantStorage::new(),
             connections:OrderedHashMap::new(),
             title_offset:0.0,
+            diagnostics:Vec::new(),
             //identifier
         };
         res.reestimate_size();
@@ -168,8 +173,8 @@ impl GraphNode{
             outputs:self.outputs.clone(),
             //inputs,
             //outputs: self.outputs.clone(),
-            constants: self.constants.clone()
-
+            constants: self.constants.clone(),
+            diagnostics:Vec::new(),
         }
     }
 
@@ -388,17 +393,23 @@ impl GraphNode{
         txt.position = self.position;
         let main_rect = Path::rectangle(self.position,self.size);
         let linewidth:f32 = if highlight {6.0} else {2.0};
-        frame.stroke(&main_rect, canvas::Stroke::default().with_width(linewidth).with_color(iced::Color::BLACK));
+        let border_color = if self.diagnostics.is_empty() {iced::Color::BLACK} else {ERROR_COLOR};
+        frame.stroke(&main_rect, canvas::Stroke::default().with_width(linewidth).with_color(border_color));
         frame.fill(&main_rect, iced::Color::WHITE);
         frame.fill_text(txt);
         let port_size = iced::Size::new(PORT_SIZE, PORT_SIZE);
 
         // Drawing inputs
-        for (i,(_, port_data)) in self.inputs.iter().enumerate(){
+        for (i,(key, port_data)) in self.inputs.iter().enumerate(){
             let pos = self.get_input_position(i);
             let port_rect = Path::rectangle(pos, port_size);
 
-            frame.stroke(&port_rect, canvas::Stroke::default().with_width(2.0).with_color(iced::Color::BLACK));
+            if self.diagnostics.iter().any(|x| x.input_port()==Some(key.as_str())){
+                frame.stroke(&port_rect, canvas::Stroke::default().with_width(4.0).with_color(ERROR_COLOR));
+            }
+            else{
+                frame.stroke(&port_rect, canvas::Stroke::default().with_width(2.0).with_color(iced::Color::BLACK));
+            }
             let color:iced::Color = make_iced_color(port_data.port_type.get_color());
             frame.fill(&port_rect, color);
 
@@ -450,6 +461,23 @@ impl GraphNode{
     //     self.reestimate_size();
     // }
 
+    /// Text of diagnostics for given mouse hit. Hovered input shows only its own problems if there are any.
+    pub fn diagnostics_text(&self, hit:&NodeMouseHit)->Option<String>{
+        if self.diagnostics.is_empty(){
+            return None;
+        }
+        let mut lines:Vec<String> = if let NodeMouseHit::Input(port,_) = hit{
+            self.diagnostics.iter().filter(|x| x.input_port()==Some(port.as_str())).map(|x| x.kind.to_string()).collect()
+        }
+        else{
+            Vec::new()
+        };
+        if lines.is_empty(){
+            lines = self.diagnostics.iter().map(|x| x.kind.to_string()).collect();
+        }
+        Some(lines.join("\n"))
+    }
+
     pub fn add_constant(&mut self, key:&str, value: constants::NodeConstantContent, display_name:String){
         self.constants.add_constant(key,value,display_name)
     }
@@ -544,7 +572,7 @@ pub struct GraphNodeCloneBufferSerializable{
 impl From<GraphNodeCloneBuffer> for GraphNodeCloneBufferSerializable{
     fn from(value: GraphNodeCloneBuffer) -> Self {
         GraphNodeCloneBufferSerializable { storage: value.storage.serialize_to_value(),
-            offset: value.offset.into(),
+            offset: serialization::to_serde_point(value.offset),
             connections:value.connections
 
         }
@@ -557,7 +585,7 @@ impl GraphNodeCloneBufferSerializable{
         storage.deserialize_from_value(registry, self.storage)?;
         Ok(GraphNodeCloneBuffer {
             storage,
-            offset: self.offset.into(),
+            offset: serialization::from_serde_point(self.offset),
             connections: self.connections
         })
     }
@@ -663,6 +691,16 @@ impl GraphNodeStorage{
         self.clone_partial(self.selection.selected_nodes.iter().filter_map(|x| x.upgrade()))
     }
 
+    /// Whether some link connects selected node with node outside of selection
+    pub fn selection_has_external_links(&self)->bool{
+        self.nodes.iter().any(|node|{
+            let inside = self.selection.contains_node(node);
+            node.borrow().connections.iter().any(|(_,conn)|{
+                conn.node.upgrade().map(|src| self.selection.contains_node(&src)!=inside).unwrap_or(false)
+            })
+        })
+    }
+
     pub fn clone_whole(&self)->Option<GraphNodeCloneBuffer>{
         self.clone_partial(self.nodes.iter().map(|x|x.clone()))
     }
@@ -737,6 +775,32 @@ impl GraphNodeStorage{
         None
     }
 
+    /// Assigns validation results to nodes. Previous results are dropped.
+    pub fn set_diagnostics(&mut self, diagnostics:Vec<Diagnostic>){
+        for node in self.nodes.iter(){
+            node.borrow_mut().diagnostics.clear();
+        }
+        for diagnostic in diagnostics.into_iter(){
+            if let Some(node) = self.nodes.get(diagnostic.node_index){
+                node.borrow_mut().diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    pub fn has_diagnostics(&self)->bool{
+        self.nodes.iter().any(|x| !x.borrow().diagnostics.is_empty())
+    }
+
+    pub fn diagnostics_at(&self, point:iced::Point)->Option<String>{
+        for node in self.nodes.iter().rev(){
+            let node_in = node.borrow();
+            if let Some(hit) = node_in.mouse_event(point){
+                return node_in.diagnostics_text(&hit);
+            }
+        }
+        None
+    }
+
     pub fn handle_message(&mut self, msg:&EditorCanvasMessage){
         match msg {
             EditorCanvasMessage::MoveNode { index, position }=>{
@@ -1004,7 +1068,7 @@ impl GraphNodeStorage{
         let mut values = Vec::with_capacity(self.nodes.len());
         for node in self.nodes.iter(){
             let node_ref = node.borrow();
-            let pos:SerdePoint = node_ref.position.into();
+            let pos = serialization::to_serde_point(node_ref.position);
 
             // entry.insert("position".into(), serde_json::to_value(pos).unwrap());
             // entry.insert("identifier".into(), node_ref.represented_node.identifier().clone().into());
@@ -1091,7 +1155,7 @@ impl GraphNodeStorage{
                 }
                 // }
             }
-            node.position = obj.position.into();
+            node.position = serialization::from_serde_point(obj.position);
             node.reestimate_size();
 
             self.insert_node(node);
@@ -1,6 +1,7 @@
 use std::thread::{self, JoinHandle};
 
 use padamo_api::lazy_array_operations::{ArrayND, LazyTriSignal};
+use padamo_api::prelude::ExecutionError;
 
 use crate::application::PadamoState;
 use crate::detector_muxer::get_signal_var;
@@ -19,10 +20,10 @@ impl StoredSignal {
         Self { signals, time, detector_id, start_frame }
     }
 
-    pub fn request_data(signal: &LazyTriSignal, start:usize, end:usize, detector_id:usize)->Self{
-        let signals = signal.0.request_range(start,end);
-        let time = signal.1.request_range(start,end).to_vec();
-        Self::new(signals, time, detector_id,start)
+    pub fn request_data(signal: &LazyTriSignal, start:usize, end:usize, detector_id:usize)->Result<Self,ExecutionError>{
+        let signals = signal.0.try_request_range(start,end).into_result()?;
+        let time = signal.1.try_request_range(start,end).into_result()?.to_vec();
+        Ok(Self::new(signals, time, detector_id,start))
     }
 }
 
@@ -31,7 +32,7 @@ pub struct DualSignalsCache{
     pub secondary:Option<StoredSignal>
 }
 
-pub fn spawn_data_loader(padamo:&PadamoState, aux_detector:Option<usize>, start:usize, end:usize)->Option<JoinHandle<DualSignalsCache>>{
+pub fn spawn_data_loader(padamo:&PadamoState, aux_detector:Option<usize>, start:usize, end:usize)->Option<JoinHandle<Result<DualSignalsCache,ExecutionError>>>{
     // let primary_detector = padamo.detectors.get_primary()?;
     let signal = padamo.compute_graph.environment.request_detectorfulldata(get_signal_var(0).as_str()).ok()?;
     let signal_aux = if let Some(aux) = aux_detector{
@@ -42,12 +43,12 @@ pub fn spawn_data_loader(padamo:&PadamoState, aux_detector:Option<usize>, start:
     };
 
     let worker = move || {
-        let primary = StoredSignal::request_data(&signal, start, end, 0);
+        let primary = StoredSignal::request_data(&signal, start, end, 0)?;
         let secondary = if let Some(aux) = signal_aux{
-            let start1 = aux.1.find_unixtime(signal.1.request_range(start,start+1)[0]);
-            let end1 = aux.1.find_unixtime(signal.1.request_range(end-1,end)[0])+1;
+            let start1 = aux.1.try_find_unixtime(signal.1.try_request_range(start,start+1).into_result()?[0])?;
+            let end1 = aux.1.try_find_unixtime(signal.1.try_request_range(end-1,end).into_result()?[0])?+1;
             if end1>start1{
-                Some(StoredSignal::request_data(&aux, start1, end1, aux_detector.unwrap()))
+                Some(StoredSignal::request_data(&aux, start1, end1, aux_detector.unwrap())?)
             }
             else{
                 None
@@ -56,9 +57,9 @@ pub fn spawn_data_loader(padamo:&PadamoState, aux_detector:Option<usize>, start:
         else{
             None
         };
-        DualSignalsCache{
+        Ok(DualSignalsCache{
             primary, secondary
-        }
+        })
     };
 
     Some(thread::spawn(worker))
@@ -67,9 +68,11 @@ pub fn spawn_data_loader(padamo:&PadamoState, aux_detector:Option<usize>, start:
 pub enum CurrentData{
     Idle,
     PendingLoad(super::SyncDataRequest),
-    Loading(JoinHandle<DualSignalsCache>, super::SyncDataRequest),
+    Loading(JoinHandle<Result<DualSignalsCache,ExecutionError>>, super::SyncDataRequest),
     Loaded(DualSignalsCache, super::SyncDataRequest),
     Unloaded,
+    /// Loading failed. Error is reported once and then state becomes Error
+    Failed(ExecutionError),
     Error,
 }
 
@@ -81,7 +84,8 @@ impl CurrentData{
             Self::Loading(loader, request)=>{
                 if loader.is_finished(){
                     match loader.join(){
-                        Ok(r)=>Self::Loaded(r, request),
+                        Ok(Ok(r))=>Self::Loaded(r, request),
+                        Ok(Err(e))=>Self::Failed(e),
                         Err(_)=>Self::Error,
                     }
                 }
@@ -109,6 +113,10 @@ impl CurrentData{
                     Self::Unloaded
                 }
             }
+            Self::Failed(e)=>{
+                padamo.show_error(format!("Could not load plot data: {}",e));
+                Self::Error
+            }
             res => res,
         }
     }
@@ -132,7 +132,7 @@ impl PadamoTool for PlotterNew{
     }
 
     fn update(&mut self, msg: std::rc::Rc<crate::messages::PadamoAppMessage>, padamo:crate::application::PadamoStateRef) {
-        if let PadamoAppMessage::Run = msg.as_ref(){
+        if let PadamoAppMessage::Executed = msg.as_ref(){
             // self.primary_plotter.set_data(None, None);
             // self.secondary_plotter.set_data(None, None);
             self.last_request = None;
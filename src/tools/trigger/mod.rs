@@ -7,6 +7,9 @@ use self::sparse_intervals::{IntervalStorage, Interval};
 use super::PadamoTool;
 use iced::{widget, Font};
 use padamo_api::{lazy_array_operations::ArrayND, trigger_operations::{sparse_event_storage::SparseTag, SparseTagArray}};
+use padamo_api::calculation_nodes::progress::write_file_atomically;
+use padamo_api::prelude::{ExecutionError, ProgressToken};
+use padamo_api::lazy_array_operations::prefetch::DEFAULT_PREFETCH_DEPTH;
 use padamo_detectors::{diagrams::PadamoDetectorDiagram, loaded_detectors_storage::DetectorEntry};
 pub mod messages;
 use messages::TriggerMessage;
@@ -46,12 +49,14 @@ pub enum TriggerProcessMessage{
     Status(String),
     MarkEvent(SparseTag),
     IntervalDone(Interval),
+    Error(String),
     // MarkPositive(sparse_intervals::Interval),
     // MarkNegative(sparse_intervals::Interval),
 }
 
 pub enum ExportProcessMessage{
-    Status(String)
+    Status(String),
+    Error(String),
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -314,7 +319,9 @@ impl PadamoTool for PadamoTrigger{
                     widget::button("Export stop").on_press(TriggerMessage::ExportStop)
                 ],
                 widget::text(&self.trigger_status),
+                super::viewer::worker_progress_bar(&self.trigger_process),
                 widget::text(&self.export_status),
+                super::viewer::worker_progress_bar(&self.export_process),
 
                 widget::column![
                     widget::rule::horizontal(10),
@@ -400,58 +407,39 @@ impl PadamoTool for PadamoTrigger{
                                         padamo.show_error(format!("Interval {} is not available", interval));
                                         return;
                                     }
-                                    let trigger_source = (*trigger).clone();
+                                    let trigger_source = (*trigger).clone().prefetched_until(self.trigger_form_instance.chunksize.max(1), DEFAULT_PREFETCH_DEPTH, interval.end);
                                     let settings = self.trigger_form_instance.clone();
                                     // let settings = trigger_form;
                                     self.stop_worker();
                                     println!("TRIGGER START {}", interval);
 
-                                    let (tx,rx) = mpsc::channel::<bool>();
+                                    let progress = ProgressToken::new();
+                                    let worker_progress = progress.clone();
 
                                     let (tx_status,rx_status) = mpsc::channel::<TriggerProcessMessage>();
 
                                     let handle = thread::spawn(move || {
-                                        let mut start = interval.start;
-                                        let max_step = settings.chunksize;
+                                        let max_step = settings.chunksize.max(1);
                                         let length = interval.length();
                                         tx_status.send(TriggerProcessMessage::Status("Started".into())).unwrap();
-                                        while start<interval.end{
-                                            if let Ok(v) = rx.try_recv(){
-                                                if v{
-                                                    println!("Interrupt requested");
-                                                    break;
-                                                }
-                                            }
-                                            let step = max_step.min(interval.end-start);
-                                            let mut data = trigger_source.request_range(start,start+step);
-                                            println!("Triggering {}-{}",start,start+step);
+                                        let res = trigger_source.try_for_each_chunk(interval.start, interval.end, max_step, &worker_progress, |start, mut data|{
+                                            let end = interval.end.min(start+max_step);
+                                            println!("Triggering {}-{}",start,end);
                                             for x in data.tags.drain(..){
                                                 tx_status.send(TriggerProcessMessage::MarkEvent(x)).unwrap();
                                             }
-                                            tx_status.send(TriggerProcessMessage::IntervalDone(Interval { start, end: start+step })).unwrap();
-                                            // let mut res:Vec<bool> = Vec::with_capacity(data.shape[0]);
-                                            // res.resize(data.shape[0], false);
-                                            // for i in data.enumerate(){
-                                            //     res[i[0]] |= data[&i];
-                                            // }
-                                            // let (positives, negatives) = split_intervals(&res);
-                                            //
-                                            // for pos in positives.iter(){
-                                            //     tx_status.send(TriggerProcessMessage::MarkPositive(pos.offset(start))).unwrap();
-                                            // }
-                                            //
-                                            //
-                                            // for neg in negatives.iter(){
-                                            //     tx_status.send(TriggerProcessMessage::MarkNegative(neg.offset(start))).unwrap();
-                                            // }
-
-                                            start += step;
-                                            tx_status.send(TriggerProcessMessage::Status(format!("{}/{}",start-interval.start,length))).unwrap();
+                                            tx_status.send(TriggerProcessMessage::IntervalDone(Interval { start, end })).unwrap();
+                                            tx_status.send(TriggerProcessMessage::Status(format!("{}/{}",end-interval.start,length))).unwrap();
+                                            Ok(())
+                                        });
+                                        match res{
+                                            Ok(())=>tx_status.send(TriggerProcessMessage::Status("DONE".into())).unwrap(),
+                                            Err(e) if e.is_cancelled()=>println!("Interrupt requested"),
+                                            Err(e)=>tx_status.send(TriggerProcessMessage::Error(format!("Trigger failed on interval {}: {}",interval,e))).unwrap(),
                                         }
-                                        tx_status.send(TriggerProcessMessage::Status("DONE".into())).unwrap();
                                     });
 
-                                    self.trigger_process = Some(Worker::new(handle, tx, rx_status));
+                                    self.trigger_process = Some(Worker::new(handle, progress, rx_status));
 
                                     return;
                                 }
@@ -482,7 +470,8 @@ impl PadamoTool for PadamoTrigger{
                     TriggerMessage::Export=>{
                         if let Some(signal_ref) = &self.signal{
                             if let Some(path) = padamo.workspace.workspace("events_export").choose_dir_dialog(vec![]){
-                                let (tx,rx) = mpsc::channel::<bool>();
+                                let progress = ProgressToken::new();
+                                let worker_progress = progress.clone();
                                 let spatial:padamo_api::lazy_array_operations::LazyDetectorSignal = signal_ref.0.clone();
                                 let temporal:padamo_api::lazy_array_operations::LazyTimeSignal = signal_ref.1.clone();
                                 self.stop_export();
@@ -492,19 +481,25 @@ impl PadamoTool for PadamoTrigger{
 
                                 let handle = thread::spawn(move || {
                                     let total_len = intervals.tags.len();
+                                    worker_progress.set_total(total_len);
                                     for (i,interval) in intervals.tags.iter().enumerate(){
-                                        if let Ok(v) = rx.try_recv(){
-                                            if v{
-                                                println!("Interrupt requested");
-                                                break;
-                                            }
+                                        if worker_progress.is_cancelled(){
+                                            println!("Interrupt requested");
+                                            break;
                                         }
 
                                         let start = interval.position;
                                         let end = interval.position+interval.duration;
 
-                                        let frame = spatial.request_range(start,end).to_ndarray();
-                                        let tim:Vec<f64> = temporal.request_range(start,end).into();
+                                        let data = spatial.try_request_range(start,end).into_result()
+                                            .and_then(|frame| temporal.try_request_range(start,end).into_result().map(|tim| (frame,tim)));
+                                        let (frame, tim) = match data{
+                                            Ok((frame,tim))=>(frame.to_ndarray(), Vec::<f64>::from(tim)),
+                                            Err(e)=>{
+                                                tx_status.send(ExportProcessMessage::Error(format!("Could not export event {}: {}",i,e))).unwrap();
+                                                break;
+                                            }
+                                        };
                                         // let time_of_event = tim[0];
                                         // let time_of_event_secs:i64 = time_of_event as i64;
                                         // let time_of_event_nsecs = ((time_of_event-time_of_event_secs as f64)*1e9) as u32;
@@ -517,31 +512,37 @@ impl PadamoTool for PadamoTrigger{
 
                                         let tgt_path = std::path::Path::new(&path);
                                         let file_path = tgt_path.join(format!("event_{}.h5",i));
-                                        if let Ok(file) = hdf5::File::create(file_path){
+                                        let res = write_file_atomically(&file_path, |file_path|{
+                                            let file = hdf5::File::create(file_path).map_err(ExecutionError::from_error)?;
                                             let space_ds = file.new_dataset::<f64>()
                                                 .deflate(3)
                                                 .shape(frame.shape());
-                                            let space_ds = space_ds.create("pdm_2d_rot_global").unwrap();
+                                            let space_ds = space_ds.create("pdm_2d_rot_global").map_err(ExecutionError::from_error)?;
                                             let time_ds = file.new_dataset::<f64>()
                                                 .deflate(3)
                                                 .shape(vec![tim.len()]);
-                                            let time_ds = time_ds.create("unixtime_dbl_global").unwrap();
+                                            let time_ds = time_ds.create("unixtime_dbl_global").map_err(ExecutionError::from_error)?;
 
-                                            space_ds.write(&frame).unwrap();
-                                            time_ds.write(&tim).unwrap();
+                                            space_ds.write(&frame).map_err(ExecutionError::from_error)?;
+                                            time_ds.write(&tim).map_err(ExecutionError::from_error)?;
 
-                                            let grp = file.create_group("meta").unwrap();
-                                            let tag = grp.new_attr::<hdf5::types::VarLenUnicode>().create("Tag").unwrap();
-                                            let str_to_write: hdf5::types::VarLenUnicode = interval.tag.as_str().parse().unwrap();
-                                            tag.write_scalar(&str_to_write).unwrap();
+                                            let grp = file.create_group("meta").map_err(ExecutionError::from_error)?;
+                                            let tag = grp.new_attr::<hdf5::types::VarLenUnicode>().create("Tag").map_err(ExecutionError::from_error)?;
+                                            let str_to_write: hdf5::types::VarLenUnicode = interval.tag.as_str().parse().map_err(ExecutionError::from_error)?;
+                                            tag.write_scalar(&str_to_write).map_err(ExecutionError::from_error)?;
                                             //tag.write().unwrap();
+                                            Ok(())
+                                        });
+                                        if let Err(e) = res{
+                                            tx_status.send(ExportProcessMessage::Error(format!("Could not export event {}: {}",i,e))).unwrap();
+                                            break;
                                         }
 
-
-                                        tx_status.send(ExportProcessMessage::Status(format!("{}/{}",i,total_len))).unwrap();
+                                        worker_progress.set_done(i+1);
+                                        tx_status.send(ExportProcessMessage::Status(format!("{}/{}",i+1,total_len))).unwrap();
                                     }
                                 });
-                                self.export_process = Some(Worker::new(handle, tx, rx_status));
+                                self.export_process = Some(Worker::new(handle, progress, rx_status));
                             }
                         }
                     }
@@ -558,7 +559,7 @@ impl PadamoTool for PadamoTrigger{
 
     fn late_update(&mut self, msg: std::rc::Rc<crate::messages::PadamoAppMessage>, padamo:crate::application::PadamoStateRef)->Option<crate::messages::PadamoAppMessage>{
         match msg.as_ref() {
-            PadamoAppMessage::Run => {
+            PadamoAppMessage::Executed => {
                 if let Some(padamo_api::prelude::Content::DetectorFullData(signal)) = padamo.compute_graph.environment.0.get(crate::detector_muxer::VIEWER_PRIMARY_SIGNAL_VAR){
                     //let signal_w = signal.clone();
                     let signal = signal.clone();
@@ -593,6 +594,10 @@ impl PadamoTool for PadamoTrigger{
                             TriggerProcessMessage::IntervalDone(i)=>{
                                 self.unmarked_intervals.take_interval(i);
                             }
+                            TriggerProcessMessage::Error(e)=>{
+                                self.trigger_status = "Error".into();
+                                padamo.show_error(e);
+                            }
                         }
                         recv_res = anim.feedback.try_recv();
                     }
@@ -615,6 +620,10 @@ impl PadamoTool for PadamoTrigger{
                     while let Ok(v) = recv_res{
                         match v{
                             ExportProcessMessage::Status(status)=>{self.export_status = status;}
+                            ExportProcessMessage::Error(e)=>{
+                                self.export_status = "Error".into();
+                                padamo.show_error(e);
+                            }
                         }
                         recv_res = exporter.feedback.try_recv();
                     }
@@ -671,11 +680,18 @@ impl PadamoTool for PadamoTrigger{
                 if let Some(path) = padamo.workspace.workspace("marked_up_events_rs").save_dialog(vec![("Marked up tracks",vec!["json"])]){
                     //if let nfd::Response::Okay(path) = v{
                     if let Some(data) = &self.signal{
-                        let events:Vec<(f64, f64, String)> = self.events.tags.iter().map(|x|{
-                            let start: f64 = data.1.request_range(x.position, x.position+1)[0];
-                            let end: f64 = data.1.request_range(x.position+x.duration-1, x.position+x.duration)[0];
-                            (start, end, x.tag.clone().into())
+                        let events:Result<Vec<(f64, f64, String)>,_> = self.events.tags.iter().map(|x|{
+                            let start: f64 = data.1.try_request_range(x.position, x.position+1).into_result()?[0];
+                            let end: f64 = data.1.try_request_range(x.position+x.duration-1, x.position+x.duration).into_result()?[0];
+                            Ok::<_,padamo_api::prelude::ExecutionError>((start, end, x.tag.clone().into()))
                         }).collect();
+                        let events = match events{
+                            Ok(v)=>v,
+                            Err(e)=>{
+                                padamo.show_error(format!("Could not resolve event times: {}", e));
+                                return;
+                            }
+                        };
                         let unmarked = self.unmarked_intervals.to_unixtime_storage(&data.1);
                         let res = SavedData{events, unmarked};
 
@@ -1,6 +1,9 @@
+use std::path::{Path, PathBuf};
 use std::{sync::mpsc, thread};
 
-use super::{AnimationParameters, Worker};
+use super::{AnimationParameters, Worker, WorkerMessage};
+use padamo_api::prelude::{ExecutionError, ProgressToken};
+use padamo_api::lazy_array_operations::prefetch::DEFAULT_PREFETCH_DEPTH;
 use padamo_detectors::diagrams::{ContourMask, PadamoDetectorDiagram};
 use padamo_detectors::loaded_detectors_storage::DetectorEntry;
 use padamo_detectors::Scaling;
@@ -12,20 +15,20 @@ pub fn make_frame<'a,T:plotters_backend::DrawingBackend+Send+Sync+'a>(root:&'a D
                temporal:&'a padamo_api::lazy_array_operations::LazyTimeSignal,
                temporal_primary:&'a padamo_api::lazy_array_operations::LazyTimeSignal,
                remap_frame:bool,
-               current_frame:usize, detector_entry:&DetectorEntry,plot_scale:Scaling){
+               current_frame:usize, detector_entry:&DetectorEntry,plot_scale:Scaling)->Result<(),ExecutionError>{
 
     let remapped_frame = if remap_frame{
-        let unixtime = temporal_primary.request_range(current_frame,current_frame+1)[0];
-        temporal.find_unixtime(unixtime)
+        let unixtime = temporal_primary.try_request_range(current_frame,current_frame+1).into_result()?[0];
+        temporal.try_find_unixtime(unixtime)?
     }
     else{
         current_frame
     };
 
 
-    let mut frame = spatial.request_range(remapped_frame,remapped_frame+1);
+    let mut frame = spatial.try_request_range(remapped_frame,remapped_frame+1).into_result()?;
     frame.shape.drain(0..1);
-    let tim = temporal.request_range(remapped_frame,remapped_frame+1)[0];
+    let tim = temporal.try_request_range(remapped_frame,remapped_frame+1).into_result()?[0];
     root.fill(&WHITE).unwrap();
 
     //TODO provide test object
@@ -37,6 +40,13 @@ pub fn make_frame<'a,T:plotters_backend::DrawingBackend+Send+Sync+'a>(root:&'a D
         .with_title_unixtime(tim)
         .with_rotation(detector_entry.detector_info.rotation);
     chart.build_chart_generic(root, None);
+    Ok(())
+}
+
+fn remove_partial_output(output_path:&Path){
+    if let Err(e) = std::fs::remove_file(output_path){
+        eprintln!("Could not remove partial animation {}: {}", output_path.display(), e);
+    }
 }
 
 pub fn animate<T:plotters_backend::DrawingBackend+Send+Sync+'static>(root:T,spatial:padamo_api::lazy_array_operations::LazyDetectorSignal,
@@ -45,12 +55,14 @@ pub fn animate<T:plotters_backend::DrawingBackend+Send+Sync+'static>(root:T,spat
                remap_frames:bool,
                start:usize,
                end:usize,
-               animation_parameters:AnimationParameters, detector_entry:DetectorEntry, plot_scale:Scaling)->Worker<String>{
+               animation_parameters:AnimationParameters, detector_entry:DetectorEntry, plot_scale:Scaling,
+               output_path:PathBuf)->Worker<WorkerMessage>{
     //let signal = signal_ref.clone();
 
-    let (tx,rx) = mpsc::channel::<bool>();
+    let progress = ProgressToken::new();
+    let worker_progress = progress.clone();
 
-    let (tx_status,rx_status) = mpsc::channel::<String>();
+    let (tx_status,rx_status) = mpsc::channel::<WorkerMessage>();
     //let status = self.animation_status.clone();
     if remap_frames{
         println!("Detector is not primary: remapping frames.");
@@ -58,6 +70,8 @@ pub fn animate<T:plotters_backend::DrawingBackend+Send+Sync+'static>(root:T,spat
     else{
         println!("Detector IS primary: remapping frames is not needed");
     }
+    // Remapped frames are not requested in order, so prefetching would not help
+    let spatial = if remap_frames {spatial} else {spatial.prefetched(1, DEFAULT_PREFETCH_DEPTH)};
 
     let handle = thread::spawn( move || {
         //80 pixels for colormap
@@ -77,7 +91,15 @@ pub fn animate<T:plotters_backend::DrawingBackend+Send+Sync+'static>(root:T,spat
         let lc_pair = if animation_parameters.displaylc.is_enabled(){
             println!("LC animation is enabled");
             //let (a,b) = root.split_vertically(height);
-            let space_out = spatial.request_range(start,end);
+            let space_out = match spatial.try_request_range(start,end).into_result(){
+                Ok(v)=>v,
+                Err(e)=>{
+                    tx_status.send(WorkerMessage::Error(format!("Could not calculate lightcurve: {}",e))).unwrap();
+                    drop(root);
+                    remove_partial_output(&output_path);
+                    return;
+                }
+            };
             let mut lc:Vec<f64> = Vec::with_capacity(end-start);
             lc.resize(end-start, 0.0);
             let mut pixel_count:usize = 0;//(space_out.flat_data.len()/(end-start)) as f64;
@@ -109,10 +131,13 @@ pub fn animate<T:plotters_backend::DrawingBackend+Send+Sync+'static>(root:T,spat
             None
         };
 
+        worker_progress.set_total(end-start);
+        let mut failed = false;
         for i in start..end{
             //let t1 = Instant::now();
             //if (i-start)%10==0{
-            tx_status.send(format!("{}/{}",i-start,end-start)).unwrap();
+            tx_status.send(WorkerMessage::Status(format!("{}/{}",i-start,end-start))).unwrap();
+            worker_progress.set_done(i-start);
 
             //}
             //let report_time = t1.elapsed().as_secs_f64();
@@ -128,7 +153,11 @@ pub fn animate<T:plotters_backend::DrawingBackend+Send+Sync+'static>(root:T,spat
                 let (a,b) = root.split_vertically(animation_parameters.height);
                 //a.fill(&WHITE).unwrap();
                 // chart.build_chart_generic(&a,&Some((&frame,tim)),plot_scale,Default::default(),&None);
-                make_frame(&a, &spatial, &temporal, &temporal_main, remap_frames, i, &detector_entry, plot_scale);
+                if let Err(e) = make_frame(&a, &spatial, &temporal, &temporal_main, remap_frames, i, &detector_entry, plot_scale){
+                    tx_status.send(WorkerMessage::Error(format!("Animation failed at frame {}: {}",i,e))).unwrap();
+                    failed = true;
+                    break;
+                }
 
                 //b.fill(&WHITE).unwrap();
                 let mut chart = ChartBuilder::on(&b)
@@ -145,7 +174,11 @@ pub fn animate<T:plotters_backend::DrawingBackend+Send+Sync+'static>(root:T,spat
             }
             else{
                 //chart.build_chart_generic(&root,&Some((&frame,tim)),plot_scale,Default::default(),&None);
-                make_frame(&root, &spatial, &temporal, &temporal_main, remap_frames, i, &detector_entry, plot_scale);
+                if let Err(e) = make_frame(&root, &spatial, &temporal, &temporal_main, remap_frames, i, &detector_entry, plot_scale){
+                    tx_status.send(WorkerMessage::Error(format!("Animation failed at frame {}: {}",i,e))).unwrap();
+                    failed = true;
+                    break;
+                }
             }
 
 
@@ -157,11 +190,9 @@ pub fn animate<T:plotters_backend::DrawingBackend+Send+Sync+'static>(root:T,spat
                 println!("{:?}",e);
             };
             //let preirq = t1.elapsed().as_secs_f64();
-            if let Ok(v) = rx.try_recv(){
-                if v{
-                    println!("Interrupt requested");
-                    break;
-                }
+            if worker_progress.is_cancelled(){
+                println!("Interrupt requested");
+                break;
             }
 
 
@@ -170,8 +201,14 @@ pub fn animate<T:plotters_backend::DrawingBackend+Send+Sync+'static>(root:T,spat
             //padamo.compute_graph.borrow().
 
         }
-        tx_status.send("END".into()).unwrap();
+        if failed{
+            // Backend must be closed before its partial output is removed. Error stays displayed instead of END.
+            drop(root);
+            remove_partial_output(&output_path);
+            return;
+        }
+        tx_status.send(WorkerMessage::Status("END".into())).unwrap();
     });
 
-    Worker::new(handle, tx, rx_status)
+    Worker::new(handle, progress, rx_status)
 }
@@ -417,6 +417,10 @@ impl CrossProgress{
         }
     }
 
+    pub fn stop(&mut self){
+        self.playstate = PlayState::Stop;
+    }
+
     pub fn run_tick(&mut self)->bool{
         match self.playstate {
             PlayState::Stop=>false,
@@ -1,6 +1,6 @@
 
 use iced::widget::{column, row};
-use padamo_api::{lazy_array_operations::{ArrayND, LazyTriSignal}, prelude::{Content, make_lao_box}};
+use padamo_api::{lazy_array_operations::{ArrayND, LazyTriSignal}, prelude::{Content, ExecutionError, make_lao_box}};
 use padamo_detectors::mesh::Mesh;
 use plotters::style::Color;
 
@@ -55,15 +55,16 @@ impl SingleDetectorDisplay{
         self.detector_id==0
     }
 
-    pub fn set_frame(&mut self, frame:usize, padamo: &PadamoState){
+    pub fn set_frame(&mut self, frame:usize, padamo: &PadamoState)->Result<(),ExecutionError>{
         if let Some(signal_tri) = self.try_get_signal(padamo){
-            let mut signal = signal_tri.0.request_range(frame, frame+1);
+            let mut signal = signal_tri.0.try_request_range(frame, frame+1).into_result()?;
             signal.shape.drain(0..1);
-            let time = signal_tri.1.request_range(frame, frame+1)[0];
+            let time = signal_tri.1.try_request_range(frame, frame+1).into_result()?[0];
             self.buffer = Some((signal,time));
             self.fill_strings(padamo);
             self.scale_state.get_entry_mut(self.detector_id).update_scale();
         }
+        Ok(())
     }
 
     pub fn update_pixels(&self, padamo :&mut PadamoState, save:bool){
@@ -247,11 +248,12 @@ impl SingleDetectorDisplay{
 
     }
 
-    pub fn pump_frame(&mut self, padamo: &PadamoState, timeline:&super::cross_progress::CrossProgress){
+    pub fn pump_frame(&mut self, padamo: &PadamoState, timeline:&super::cross_progress::CrossProgress)->Result<(),ExecutionError>{
         if let Some(frame) = timeline.get_frame(padamo, self.detector_id){
-            self.set_frame(frame, padamo);
+            self.set_frame(frame, padamo)?;
         }
         // self.set_frame(, );
+        Ok(())
     }
 
     pub fn get_id(&self)->usize{
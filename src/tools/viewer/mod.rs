@@ -22,6 +22,9 @@ use padamo_iced_forms::{ActionOrUpdate, IcedFormBuffer};
 
 use std::thread;
 use std::sync::mpsc;
+use padamo_api::calculation_nodes::progress::{ProgressToken, write_file_atomically};
+use padamo_api::prelude::ExecutionError;
+use padamo_api::lazy_array_operations::prefetch::DEFAULT_PREFETCH_DEPTH;
 use sysinfo::{System,RefreshKind,MemoryRefreshKind};
 
 pub use messages::ViewerMessage;
@@ -44,28 +47,26 @@ pub enum PlayState{
 
 
 
+/// Feedback sent by background workers of viewer
+pub enum WorkerMessage{
+    Status(String),
+    Error(String),
+}
+
 pub struct Worker<T>{
     pub worker: Option<thread::JoinHandle<()>>,
-    controller:mpsc::Sender<bool>,
+    progress:ProgressToken,
     pub feedback: mpsc::Receiver<T>,
-    is_stopping:bool
 }
 
 impl<T> Worker<T>{
-    pub fn new(worker: thread::JoinHandle<()>, controller:mpsc::Sender<bool>, feedback: mpsc::Receiver<T>)->Self{
+    pub fn new(worker: thread::JoinHandle<()>, progress:ProgressToken, feedback: mpsc::Receiver<T>)->Self{
         Self{
-            worker:Some(worker),controller,feedback,is_stopping:false
+            worker:Some(worker),progress,feedback
         }
     }
     pub fn request_stop(&mut self){
-        if let Some(worker) = &self.worker{
-            if !worker.is_finished() && !self.is_stopping{
-                if let Err(e) = self.controller.send(true){
-                    println!("{:?}",e);
-                }
-                self.is_stopping = true;
-            }
-        }
+        self.progress.cancel();
     }
     pub fn is_finished(&self)->bool{
         if let Some(worker) = &self.worker{
@@ -76,6 +77,11 @@ impl<T> Worker<T>{
         }
     }
 
+    /// Fraction of work done if worker reports it
+    pub fn progress(&self)->Option<f32>{
+        self.progress.fraction()
+    }
+
     pub fn stop(&mut self){
         if let Some(worker) = self.worker.take(){
             if let Err(e) = worker.join(){
@@ -88,14 +94,20 @@ impl<T> Worker<T>{
     }
 }
 
+/// Progress bar of worker. Empty bar is shown when nothing is running.
+pub fn worker_progress_bar<'a,T,M:'a>(worker:&Option<Worker<T>>)->iced::Element<'a,M>{
+    let value = worker.as_ref().and_then(|x| x.progress()).unwrap_or(0.0);
+    iced::widget::progress_bar(0.0..=1.0, value).girth(10).into()
+}
+
 pub struct PadamoViewer{
 
     form:form::ViewerFormBuffer,
     form_instance:ViewerForm,
     mesh:Option<padamo_detectors::mesh::Mesh>,
 
-    animator:Option<Worker<String>>,
-    exporter:Option<Worker<String>>,
+    animator:Option<Worker<WorkerMessage>>,
+    exporter:Option<Worker<WorkerMessage>>,
     animation_status: String,
     export_status:String,
 
@@ -107,12 +119,9 @@ pub struct PadamoViewer{
 }
 
 fn get_test_object_transform(padamo:&PadamoState)->anyhow::Result<nalgebra::Matrix4<f64>>{
-    if let Ok(o) = padamo.compute_graph.environment.request_detectorsignal(VIEWER_TEST_OBJECT_KEY.into()){
+    if let Ok(o) = padamo.compute_graph.environment.request_matrix(VIEWER_TEST_OBJECT_KEY.into()){
         // println!("Found test object transform matrix");
-        let obj = o.request_range(0,o.length());
-        let res = TryInto::<nalgebra::Matrix4<f64>>::try_into(obj).map_err(|_| anyhow::format_err!("Test object transform must be 4x4 matrix"));
-        //println!("M {:?}", res);
-        res
+        Ok(o.into())
     }
     else{
         Ok(nalgebra::Matrix4::identity())
@@ -120,12 +129,9 @@ fn get_test_object_transform(padamo:&PadamoState)->anyhow::Result<nalgebra::Matr
 }
 
 fn get_detector_transform(padamo:&PadamoState, detector_id:usize)->anyhow::Result<nalgebra::Matrix4<f64>>{
-    if let Ok(o) = padamo.compute_graph.environment.request_detectorsignal(&get_transform_var(detector_id)){
+    if let Ok(o) = padamo.compute_graph.environment.request_matrix(&get_transform_var(detector_id)){
         // println!("Found detector {} object transform matrix", detector_id);
-        let obj = o.request_range(0,o.length());
-        let res = TryInto::<nalgebra::Matrix4<f64>>::try_into(obj).map_err(|_| anyhow::format_err!("Detector {} transform must be 4x4 matrix",detector_id));
-        //println!("D {:?}", res);
-        res
+        Ok(o.into())
     }
     else{
         Ok(nalgebra::Matrix4::identity())
@@ -153,7 +159,14 @@ impl PadamoViewer{
 
                 let start = start;
                 let end = end+1;
-                let mut testframe = spatial.request_range(0,1);
+                let mut testframe = match spatial.try_request_range(0,1).into_result(){
+                    Ok(v)=>v,
+                    Err(e)=>{
+                        padamo.show_error(format!("Could not read test frame: {}",e));
+                        return;
+                    }
+                };
+                let sample = testframe.clone();
                 testframe.shape.drain(0..1); //Remove time axis
                 let frame_shape = testframe.shape;
                 let settings = self.form_instance.export.clone();
@@ -187,7 +200,6 @@ impl PadamoViewer{
                     return;
                 }
 
-                let sample = spatial.request_range(0,1);
                 let sample_size = sample.flat_data.len()*8; // (Flat buffer of f64 (8 bytes each))
 
                 let mut chunk_size:Vec<usize> = sample.shape.clone().into();
@@ -195,17 +207,19 @@ impl PadamoViewer{
 
                 println!("Frame size: {} bytes",sample_size);
 
-                let quota = allowed_memory/sample_size;
+                // Prefetched chunks are kept in memory together with the current one
+                let quota = allowed_memory/sample_size/(DEFAULT_PREFETCH_DEPTH+1);
 
                 println!("Quota: {} samples",quota);
 
 
-                let (tx,rx) = mpsc::channel::<bool>();
+                let progress = ProgressToken::new();
+                let worker_progress = progress.clone();
 
-                let (tx_status,rx_status) = mpsc::channel::<String>();
+                let (tx_status,rx_status) = mpsc::channel::<WorkerMessage>();
 
                 let handle = thread::spawn(move || {
-                        tx_status.send("Estimating frame size".into()).unwrap();
+                        tx_status.send(WorkerMessage::Status("Estimating frame size".into())).unwrap();
 
                         let mut size_up = end-start;
                         let mut size_down = 0;
@@ -224,8 +238,10 @@ impl PadamoViewer{
                         let step = usize::max(size_mid,1);
                         let overhead = spatial.calculate_overhead(start,start+step);
                         println!("Estimated step: {} (overhead {})",step, overhead);
+                        let spatial = spatial.prefetched(step, DEFAULT_PREFETCH_DEPTH);
 
-                        if let Ok(file) = hdf5::File::create(filename){
+                        let res = write_file_atomically(std::path::Path::new(&filename), |path|{
+                            let file = hdf5::File::create(path).map_err(ExecutionError::from_error)?;
                             let mut ds_shape = vec![end-start];
                             ds_shape.extend(frame_shape.clone());
 
@@ -236,7 +252,7 @@ impl PadamoViewer{
                                 space_ds = space_ds.deflate(settings.deflatelevel);
                             }
 
-                            let space_ds = space_ds.create(settings.spatialfield.as_str()).unwrap();
+                            let space_ds = space_ds.create(settings.spatialfield.as_str()).map_err(ExecutionError::from_error)?;
 
                             let mut time_ds = file.new_dataset::<f64>()
                                 .chunk((settings.chunk,))
@@ -247,17 +263,12 @@ impl PadamoViewer{
                                 time_ds = time_ds.deflate(settings.deflatelevel);
                             }
 
-                            let time_ds = time_ds.create(settings.temporalfield.as_str()).unwrap();
+                            let time_ds = time_ds.create(settings.temporalfield.as_str()).map_err(ExecutionError::from_error)?;
 
-                            let mut i = start;
-                            //let step = settings.framesstep;
-                            while i<end{
-                                let size = usize::min(step, end-i);
-
-                                tx_status.send(format!("{}/{}",i-start,end-start)).unwrap();
-
-                                let frame = spatial.request_range(i,i+size).to_ndarray();
-                                let tim = temporal.request_range(i,i+size);
+                            spatial.try_for_each_chunk(start, end, step, &worker_progress, |i, frame|{
+                                let size = frame.shape[0];
+                                tx_status.send(WorkerMessage::Status(format!("{}/{}",i-start,end-start))).unwrap();
+                                let tim = temporal.try_request_range(i,i+size).into_result()?;
 
                                 let mut slabs:Vec<hdf5::SliceOrIndex> = Vec::with_capacity(frame_shape.len());
                                 for j in 0..frame_shape.len()+1{
@@ -270,20 +281,18 @@ impl PadamoViewer{
                                 }
                                 let slicer: hdf5::Hyperslab = slabs.into();
 
-                                space_ds.write_slice(&frame, slicer).unwrap();
-                                time_ds.write_slice(&tim, (i-start..i-start+size, )).unwrap();
-                                if let Ok(v) = rx.try_recv(){
-                                    if v{
-                                        println!("Interrupt requested");
-                                        break;
-                                    }
-                                }
-                                i+=size;
-                            }
+                                space_ds.write_slice(&frame.to_ndarray(), slicer).map_err(ExecutionError::from_error)?;
+                                time_ds.write_slice(&tim, (i-start..i-start+size, )).map_err(ExecutionError::from_error)?;
+                                Ok(())
+                            })
+                        });
+                        match res{
+                            Ok(())=>tx_status.send(WorkerMessage::Status("DONE".into())).unwrap(),
+                            Err(e) if e.is_cancelled()=>println!("Interrupt requested"),
+                            Err(e)=>tx_status.send(WorkerMessage::Error(format!("Export failed: {}",e))).unwrap(),
                         }
-
                 });
-                self.exporter = Some(Worker::new(handle, tx, rx_status));
+                self.exporter = Some(Worker::new(handle, progress, rx_status));
             }
             //}
         }
@@ -336,7 +345,7 @@ impl PadamoViewer{
                             "gif"=>{
                                 let backend = BitMapBackend::gif(filename,(animation_parameters.width+80, height), animation_parameters.framedelay);
                                 match backend{
-                                    Ok(back)=>{Some(animator::animate(back, spatial, temporal, time_primary, !self.window_view.is_primary_selected(), start, end, animation_parameters, detector_entry.clone(), plot_scale))}
+                                    Ok(back)=>{Some(animator::animate(back, spatial, temporal, time_primary, !self.window_view.is_primary_selected(), start, end, animation_parameters, detector_entry.clone(), plot_scale, f.to_path_buf()))}
                                     Err(e)=>{
                                         eprintln!("{}",e);
                                         padamo.show_error(format!("{}",e));
@@ -348,7 +357,7 @@ impl PadamoViewer{
                                 let backend = VideoFrameByFrameWriter::new(&filename, animation_parameters.width+80, height, (1000/animation_parameters.framedelay) as i32);
                                                                // plotters_video::FrameDelay::DelayMS(animation_parameters.framedelay as usize));
                                 match backend{
-                                    Ok(back)=>{Some(animator::animate(back, spatial, temporal, time_primary, !self.window_view.is_primary_selected(), start, end, animation_parameters, detector_entry.clone(), plot_scale))}
+                                    Ok(back)=>{Some(animator::animate(back, spatial, temporal, time_primary, !self.window_view.is_primary_selected(), start, end, animation_parameters, detector_entry.clone(), plot_scale, f.to_path_buf()))}
                                     Err(e)=>{
                                         eprintln!("{}",e);
                                         padamo.show_error(format!("{}",e));
@@ -436,12 +445,16 @@ impl PadamoViewer{
                             "png" | "jpg" => {
                                 let backend = BitMapBackend::new(&path, (width+80,height));
                                 let root = backend.into_drawing_area();
-                                animator::make_frame(&root, &spatial, &temporal, &time_primary, !self.window_view.is_primary_selected(), pointer, &detector_entry, plot_scale);
+                                if let Err(e) = animator::make_frame(&root, &spatial, &temporal, &time_primary, !self.window_view.is_primary_selected(), pointer, &detector_entry, plot_scale){
+                                    padamo.show_error(format!("Could not save frame: {}",e));
+                                }
                             },
                             "svg" => {
                                 let backend = SVGBackend::new(&path, (width+80,height));
                                 let root = backend.into_drawing_area();
-                                animator::make_frame(&root, &spatial, &temporal, &time_primary, !self.window_view.is_primary_selected(), pointer, &detector_entry, plot_scale);
+                                if let Err(e) = animator::make_frame(&root, &spatial, &temporal, &time_primary, !self.window_view.is_primary_selected(), pointer, &detector_entry, plot_scale){
+                                    padamo.show_error(format!("Could not save frame: {}",e));
+                                }
                             },
                             ue=>{
                                 padamo.show_error(format!("Unsupported extension {}",ue));
@@ -512,12 +525,15 @@ impl PadamoViewer{
         }
     }
 
-    fn update_buffer(&mut self, padamo:Option<crate::application::PadamoStateRef>){
+    fn update_buffer(&mut self, mut padamo:Option<crate::application::PadamoStateRef>){
 
         let time_start = Instant::now();
 
-        if let Some(p) = &padamo{
-            self.window_view.pump_frame(p, &self.playbar_state);
+        if let Some(p) = padamo.as_deref_mut(){
+            if let Err(e) = self.window_view.pump_frame(p, &self.playbar_state){
+                self.playbar_state.stop();
+                p.show_error(format!("Could not display frame: {}",e));
+            }
             // self.window_view.set_frame(self.pointer, p);
         }
         let time_stop = time_start.elapsed();
@@ -577,9 +593,11 @@ impl PadamoTool for PadamoViewer{
         let settings_column:iced::Element<'_,ViewerMessage> = column![
 
             row![iced::widget::text("Animation status:"),iced::widget::text(&self.animation_status)],
+            worker_progress_bar(&self.animator),
             iced::widget::rule::horizontal(10),
 
             row![iced::widget::text("Export status:"),iced::widget::text(&self.export_status)],
+            worker_progress_bar(&self.exporter),
             iced::widget::rule::horizontal(10),
 
             self.form.view(None).map(ViewerMessage::EditForm),
@@ -687,10 +705,7 @@ impl PadamoTool for PadamoViewer{
     }
 
     fn update(&mut self, msg: std::rc::Rc<crate::messages::PadamoAppMessage>, padamo:crate::application::PadamoStateRef) {
-        if let crate::messages::PadamoAppMessage::Run = msg.as_ref(){
-            self.window_view.update_pixels(padamo,true);
-        }
-        else if let crate::messages::PadamoAppMessage::ViewerMessage(view) = msg.as_ref(){
+        if let crate::messages::PadamoAppMessage::ViewerMessage(view) = msg.as_ref(){
             // let mut request_buffer_fill = true;
             match view {
                 ViewerMessage::EditForm(v)=>{
@@ -763,8 +778,7 @@ impl PadamoTool for PadamoViewer{
 
     fn late_update(&mut self, msg: std::rc::Rc<crate::messages::PadamoAppMessage>, padamo:crate::application::PadamoStateRef)->Option<PadamoAppMessage> {
         match msg.as_ref() {
-            crate::messages::PadamoAppMessage::Run => self.rerun(padamo),
-            crate::messages::PadamoAppMessage::RerollRun => self.rerun(padamo),
+            crate::messages::PadamoAppMessage::Executed => self.rerun(padamo),
             PadamoAppMessage::Tick => {
                 let mut will_stop = false;
                 if let Some(anim) = &self.animator{
@@ -773,7 +787,13 @@ impl PadamoTool for PadamoViewer{
                             }
                             let pip = &anim.feedback;
                             while let Ok(v) = pip.try_recv(){
-                                self.animation_status = v;
+                                match v{
+                                    WorkerMessage::Status(s)=>self.animation_status = s,
+                                    WorkerMessage::Error(e)=>{
+                                        self.animation_status = "Error".into();
+                                        padamo.show_error(e);
+                                    }
+                                }
                             }
                         }
                 if will_stop{
@@ -786,7 +806,13 @@ impl PadamoTool for PadamoViewer{
                             }
                             let pip = &exp.feedback;
                             while let Ok(v) = pip.try_recv(){
-                                self.export_status = v;
+                                match v{
+                                    WorkerMessage::Status(s)=>self.export_status = s,
+                                    WorkerMessage::Error(e)=>{
+                                        self.export_status = "Error".into();
+                                        padamo.show_error(e);
+                                    }
+                                }
                             }
                         }
                 if will_stop{
@@ -75,3 +75,5 @@ mv -v libpadamostft.so                  plugins/
 mv -v libpadamoeusoroot.so              plugins/
 mv -v libpadamoplaintext.so             plugins/
 mv -v libpadamotransforms.so            plugins/
+mv -v libpadamoscripting.so             plugins/
+mv -v libpadamonpy.so                   plugins/